@@ -0,0 +1,54 @@
+//! Benchmarks reply-thread building on a synthetic 100k-tweet archive
+//!
+//! Run with `cargo bench --bench thread_building`; compare against
+//! `cargo bench --bench thread_building --no-default-features` for the serial path.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tweet_scrolls::processing::data_structures::{Tweet, TweetEntities};
+use tweet_scrolls::processing::process_reply_threads;
+
+/// Builds `count` tweets arranged into chains of up to five replies
+fn synthetic_tweets(count: usize) -> Vec<Tweet> {
+    (0..count)
+        .map(|i| {
+            let reply_to = (i % 5 != 0).then(|| (i - 1).to_string());
+            Tweet {
+                id_str: i.to_string(),
+                id: i.to_string(),
+                full_text: format!("Tweet number {}", i),
+                created_at: format!("2023-01-01 {:08}", i),
+                favorite_count: "0".to_string(),
+                retweet_count: "0".to_string(),
+                retweeted: false,
+                favorited: false,
+                truncated: false,
+                lang: Some("en".to_string()),
+                source: "bench".to_string(),
+                display_text_range: vec!["0".to_string(), "16".to_string()],
+                in_reply_to_status_id: reply_to.clone(),
+                in_reply_to_status_id_str: reply_to,
+                in_reply_to_user_id: None,
+                in_reply_to_user_id_str: None,
+                in_reply_to_screen_name: None,
+                edit_info: None,
+                entities: TweetEntities::default(),
+                possibly_sensitive: None,
+                quoted_status_id: None,
+                conversation_id: None,
+            }
+        })
+        .collect()
+}
+
+fn bench_process_reply_threads(c: &mut Criterion) {
+    let tweets = synthetic_tweets(100_000);
+    let mut group = c.benchmark_group("thread_building");
+    group.sample_size(10);
+    group.bench_function("process_reply_threads_100k", |b| {
+        b.iter(|| process_reply_threads(&tweets, "bench"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_process_reply_threads);
+criterion_main!(benches);
@@ -4,9 +4,10 @@
 //! Particularly useful for splitting large Twitter archive files.
 
 use anyhow::{Context, Result, bail};
+use indicatif::{ProgressBar, ProgressStyle};
 use std::env;
 use std::path::PathBuf;
-use tweet_scrolls::utils::file_splitter::{split_file, parse_size_string, SplitConfig};
+use tweet_scrolls::utils::file_splitter::{split_by_regex_delimiter, split_file_with_progress, parse_size_string, ChunkStrategy, SplitConfig, DEFAULT_MAX_OUTPUT_FILES};
 
 /// Simple argument parsing structure
 #[derive(Debug)]
@@ -14,8 +15,13 @@ struct Args {
     input: PathBuf,
     output_dir: Option<PathBuf>,
     chunk_size: String,
+    lines: Option<u64>,
+    split_on_pattern: Option<String>,
     prefix: Option<String>,
     digits: u8,
+    verify: bool,
+    exclude_empty_lines: bool,
+    pad_last_chunk: bool,
     verbose: bool,
 }
 
@@ -31,8 +37,14 @@ impl Args {
         let mut input = None;
         let mut output_dir = None;
         let mut chunk_size = "1M".to_string();
+        let mut chunk_size_given = false;
+        let mut lines = None;
+        let mut split_on_pattern = None;
         let mut prefix = None;
         let mut digits = 3;
+        let mut verify = false;
+        let mut exclude_empty_lines = false;
+        let mut pad_last_chunk = false;
         let mut verbose = false;
         
         let mut i = 1;
@@ -52,12 +64,28 @@ impl Args {
                     }
                     output_dir = Some(PathBuf::from(&args[i]));
                 }
-                "-s" | "--chunk-size" => {
+                "-s" | "-c" | "--chunk-size" => {
                     i += 1;
                     if i >= args.len() {
                         bail!("Missing value for chunk-size argument");
                     }
                     chunk_size = args[i].clone();
+                    chunk_size_given = true;
+                }
+                "-l" | "--lines" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for lines argument");
+                    }
+                    lines = Some(args[i].parse()
+                        .with_context(|| format!("Invalid lines value: {}", args[i]))?);
+                }
+                "--split-on-pattern" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for split-on-pattern argument");
+                    }
+                    split_on_pattern = Some(args[i].clone());
                 }
                 "-p" | "--prefix" => {
                     i += 1;
@@ -74,6 +102,15 @@ impl Args {
                     digits = args[i].parse()
                         .with_context(|| format!("Invalid digits value: {}", args[i]))?;
                 }
+                "--verify" => {
+                    verify = true;
+                }
+                "--exclude-empty-lines" => {
+                    exclude_empty_lines = true;
+                }
+                "--pad-last-chunk" => {
+                    pad_last_chunk = true;
+                }
                 "-v" | "--verbose" => {
                     verbose = true;
                 }
@@ -97,13 +134,27 @@ impl Args {
         }
         
         let input = input.ok_or_else(|| anyhow::anyhow!("Input file is required"))?;
+        if chunk_size_given && lines.is_some() {
+            bail!("--chunk-size and --lines cannot be used together");
+        }
+        if split_on_pattern.is_some() && (chunk_size_given || lines.is_some()) {
+            bail!("--split-on-pattern cannot be used with --chunk-size or --lines");
+        }
+        if pad_last_chunk && (lines.is_some() || split_on_pattern.is_some()) {
+            bail!("--pad-last-chunk only works with size-based chunks");
+        }
         
         Ok(Args {
             input,
             output_dir,
             chunk_size,
+            lines,
+            split_on_pattern,
             prefix,
             digits,
+            verify,
+            exclude_empty_lines,
+            pad_last_chunk,
             verbose,
         })
     }
@@ -123,9 +174,14 @@ fn print_usage() {
     println!("OPTIONS:");
     println!("    -i, --input <FILE>        Input file to split");
     println!("    -o, --output-dir <DIR>    Output directory for chunks");
-    println!("    -s, --chunk-size <SIZE>   Size of each chunk in MB (e.g., 1M=1MB, 500K, 2G) [default: 1M]");
+    println!("    -s, -c, --chunk-size <SIZE>  Size of each chunk in MB (e.g., 1M=1MB, 500K, 2G) [default: 1M]");
+    println!("    -l, --lines <N>           Put N lines in each chunk instead of splitting by size");
     println!("    -p, --prefix <PREFIX>     Prefix for chunk filenames");
     println!("    -d, --digits <DIGITS>     Number of digits in chunk numbers [default: 3]");
+    println!("        --verify              Re-read each chunk and check its CRC32 after writing");
+    println!("        --exclude-empty-lines With --lines, don't count blank lines toward N");
+    println!("        --split-on-pattern <REGEX>  Start a new chunk at every line matching REGEX");
+    println!("        --pad-last-chunk      Zero-pad the last chunk so every chunk is exactly the chunk size");
     println!("    -v, --verbose             Show verbose output");
     println!("    -h, --help                Show this help message");
     println!();
@@ -133,6 +189,10 @@ fn print_usage() {
     println!("    file-splitter large_file.json");
     println!("    file-splitter -i tweets.js -s 5M -o chunks/");
     println!("    file-splitter --input direct-messages.js --chunk-size 1G --verbose");
+    println!("    file-splitter threads.txt --lines 5000");
+    println!("    file-splitter threads.txt --lines 5000 --exclude-empty-lines");
+    println!("    file-splitter genes.fasta --split-on-pattern '^>'");
+    println!("    file-splitter disk.img --chunk-size 4M --pad-last-chunk");
 }
 
 fn main() -> Result<()> {
@@ -145,13 +205,20 @@ fn main() -> Result<()> {
         if let Some(ref output_dir) = args.output_dir {
             println!("📂 Output directory: {}", output_dir.display());
         }
-        println!("📏 Chunk size: {}", args.chunk_size);
+        match (&args.split_on_pattern, args.lines) {
+            (Some(pattern), _) => println!("📏 New chunk at lines matching: {}", pattern),
+            (None, Some(lines)) => println!("📏 Lines per chunk: {}", lines),
+            (None, None) => println!("📏 Chunk size: {}", args.chunk_size),
+        }
         println!();
     }
     
-    // Parse chunk size
-    let chunk_size = parse_size_string(&args.chunk_size)
-        .with_context(|| format!("Invalid chunk size: {}", args.chunk_size))?;
+    // Parse chunk size, unless splitting by lines
+    let strategy = match args.lines {
+        Some(lines) => ChunkStrategy::Lines(lines),
+        None => ChunkStrategy::Bytes(parse_size_string(&args.chunk_size)
+            .with_context(|| format!("Invalid chunk size: {}", args.chunk_size))?),
+    };
     
     // Validate digits
     if args.digits == 0 || args.digits > 10 {
@@ -162,15 +229,35 @@ fn main() -> Result<()> {
     let config = SplitConfig {
         input_path: args.input,
         output_dir: args.output_dir,
-        chunk_size,
+        strategy,
         prefix: args.prefix,
         digits: args.digits,
+        verify_after_split: args.verify,
+        exclude_empty_lines: args.exclude_empty_lines,
+        pad_last_chunk: args.pad_last_chunk,
+        max_output_files: Some(DEFAULT_MAX_OUTPUT_FILES),
     };
     
     // Perform the split
     println!("🚀 Starting file split operation...");
-    let result = split_file(&config)
-        .context("Failed to split file")?;
+    if let Some(pattern) = &args.split_on_pattern {
+        let result = split_by_regex_delimiter(&config, pattern).context("Failed to split file")?;
+        println!("✅ Split operation completed successfully!\n");
+        println!("{}", result);
+        return Ok(());
+    }
+    let progress_bar = ProgressBar::new(0);
+    progress_bar.set_style(
+        ProgressStyle::with_template("{bar:40} {bytes}/{total_bytes} ({eta})")
+            .context("Invalid progress bar template")?,
+    );
+    let bar = progress_bar.clone();
+    let result = split_file_with_progress(&config, Box::new(move |written, total| {
+        bar.set_length(total);
+        bar.set_position(written);
+    }));
+    progress_bar.finish_and_clear();
+    let result = result.context("Failed to split file")?;
     
     // Display results
     println!("✅ Split operation completed successfully!\n");
@@ -199,8 +286,14 @@ mod tests {
         let mut input = None;
         let mut output_dir = None;
         let mut chunk_size = "1M".to_string();
+        let mut chunk_size_given = false;
+        let mut lines = None;
+        let mut split_on_pattern = None;
         let mut prefix = None;
         let mut digits = 3;
+        let mut verify = false;
+        let mut exclude_empty_lines = false;
+        let mut pad_last_chunk = false;
         let mut verbose = false;
         
         let mut i = 1; // Skip program name
@@ -220,12 +313,28 @@ mod tests {
                     }
                     output_dir = Some(PathBuf::from(args[i]));
                 }
-                "-s" | "--chunk-size" => {
+                "-s" | "-c" | "--chunk-size" => {
                     i += 1;
                     if i >= args.len() {
                         bail!("Missing value for chunk-size argument");
                     }
                     chunk_size = args[i].to_string();
+                    chunk_size_given = true;
+                }
+                "-l" | "--lines" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for lines argument");
+                    }
+                    lines = Some(args[i].parse()
+                        .with_context(|| format!("Invalid lines value: {}", args[i]))?);
+                }
+                "--split-on-pattern" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for split-on-pattern argument");
+                    }
+                    split_on_pattern = Some(args[i].to_string());
                 }
                 "-p" | "--prefix" => {
                     i += 1;
@@ -242,6 +351,15 @@ mod tests {
                     digits = args[i].parse()
                         .with_context(|| format!("Invalid digits value: {}", args[i]))?;
                 }
+                "--verify" => {
+                    verify = true;
+                }
+                "--exclude-empty-lines" => {
+                    exclude_empty_lines = true;
+                }
+                "--pad-last-chunk" => {
+                    pad_last_chunk = true;
+                }
                 "-v" | "--verbose" => {
                     verbose = true;
                 }
@@ -260,13 +378,27 @@ mod tests {
         }
         
         let input = input.ok_or_else(|| anyhow::anyhow!("Input file is required"))?;
+        if chunk_size_given && lines.is_some() {
+            bail!("--chunk-size and --lines cannot be used together");
+        }
+        if split_on_pattern.is_some() && (chunk_size_given || lines.is_some()) {
+            bail!("--split-on-pattern cannot be used with --chunk-size or --lines");
+        }
+        if pad_last_chunk && (lines.is_some() || split_on_pattern.is_some()) {
+            bail!("--pad-last-chunk only works with size-based chunks");
+        }
         
         Ok(Args {
             input,
             output_dir,
             chunk_size,
+            lines,
+            split_on_pattern,
             prefix,
             digits,
+            verify,
+            exclude_empty_lines,
+            pad_last_chunk,
             verbose,
         })
     }
@@ -277,6 +409,7 @@ mod tests {
         assert_eq!(args.input, PathBuf::from("test.txt"));
         assert_eq!(args.chunk_size, "1M");
         assert_eq!(args.digits, 3);
+        assert!(!args.verify);
         assert!(!args.verbose);
         Ok(())
     }
@@ -333,6 +466,45 @@ mod tests {
         Ok(())
     }
     
+    #[test]
+    fn test_args_parsing_lines() -> Result<()> {
+        let args = parse_args_from_vec(vec!["file-splitter", "threads.txt", "-l", "500"])?;
+        assert_eq!(args.lines, Some(500));
+        
+        let args = parse_args_from_vec(vec!["file-splitter", "threads.txt", "-c", "2M"])?;
+        assert_eq!(args.chunk_size, "2M");
+        assert_eq!(args.lines, None);
+        
+        assert!(parse_args_from_vec(vec!["file-splitter", "threads.txt", "--lines", "many"]).is_err());
+        assert!(parse_args_from_vec(vec!["file-splitter", "threads.txt", "-s", "2M", "-l", "10"]).is_err());
+        
+        let args = parse_args_from_vec(vec!["file-splitter", "threads.txt", "-l", "10", "--exclude-empty-lines"])?;
+        assert!(args.exclude_empty_lines);
+        Ok(())
+    }
+    
+    #[test]
+    fn test_args_parsing_split_on_pattern() -> Result<()> {
+        let args = parse_args_from_vec(vec!["file-splitter", "genes.fasta", "--split-on-pattern", "^>"])?;
+        assert_eq!(args.split_on_pattern.as_deref(), Some("^>"));
+        assert_eq!(parse_args_from_vec(vec!["file-splitter", "genes.fasta"])?.split_on_pattern, None);
+        
+        assert!(parse_args_from_vec(vec!["file-splitter", "genes.fasta", "--split-on-pattern"]).is_err());
+        assert!(parse_args_from_vec(vec!["file-splitter", "genes.fasta", "--split-on-pattern", "^>", "-l", "10"]).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_args_parsing_pad_last_chunk() -> Result<()> {
+        let args = parse_args_from_vec(vec!["file-splitter", "disk.img", "-s", "4M", "--pad-last-chunk"])?;
+        assert!(args.pad_last_chunk);
+        assert!(!parse_args_from_vec(vec!["file-splitter", "disk.img"])?.pad_last_chunk);
+        
+        assert!(parse_args_from_vec(vec!["file-splitter", "disk.img", "-l", "10", "--pad-last-chunk"]).is_err());
+        assert!(parse_args_from_vec(vec!["file-splitter", "disk.img", "--split-on-pattern", "^>", "--pad-last-chunk"]).is_err());
+        Ok(())
+    }
+    
     #[test]
     fn test_args_parsing_missing_input() {
         let result = parse_args_from_vec(vec!["file-splitter"]);
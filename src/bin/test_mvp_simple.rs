@@ -28,6 +28,10 @@ async fn main() -> Result<()> {
         tweet_count: 1,
         favorite_count: 0,
         retweet_count: 0,
+        url: String::new(),
+        contains_poll: false,
+        first_tweet_url_entities: Vec::new(),
+        engagement_dropoff_rate: 0.0,
     }).collect();
     
     // Create analyzer and analyze tweets
@@ -1,10 +1,10 @@
 use tweet_scrolls::processing::data_structures::{TweetWrapper, Tweet, Thread};
+use tweet_scrolls::utils::{parse_twitter_date, truncate_to_utf8_boundary};
 use chrono::DateTime;
 use anyhow::{Result, Context};
 use std::collections::{HashMap, HashSet};
 use tokio::fs;
 use std::env;
-use chrono::Utc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -44,7 +44,7 @@ async fn main() -> Result<()> {
     let mut retweet_count = 0;
     let mut total_tweets = 0;
 
-    let threads = create_threads_from_tweets(&all_tweets, "amuldotexe")?.into_iter().map(|thread| {
+    let threads = create_threads_from_tweets(&all_tweets, "amuldotexe")?.into_iter().inspect(|thread| {
         for tweet in &thread.tweets {
             total_tweets += 1;
             if tweet.retweeted {
@@ -58,7 +58,6 @@ async fn main() -> Result<()> {
                 println!("[PROGRESS] Processed {} tweets: {} originals, {} replies, {} retweets", total_tweets, original_tweet_count, reply_count, retweet_count);
             }
         }
-        thread
     }).collect::<Vec<_>>();
 
     println!("\n✅ Completed processing {} threads.", threads.len());
@@ -95,7 +94,7 @@ async fn main() -> Result<()> {
             max_thread_len = len;
         }
     }
-    let avg_thread_len = if threads.len() > 0 { total_thread_len as f64 / threads.len() as f64 } else { 0.0 };
+    let avg_thread_len = if !threads.is_empty() { total_thread_len as f64 / threads.len() as f64 } else { 0.0 };
     println!("Threading quality summary: Total threads: {}, Avg thread length: {:.2}, Max thread length: {}", threads.len(), avg_thread_len, max_thread_len);
     
     // Step 6: Missing tweets investigation
@@ -170,31 +169,15 @@ fn create_threads_from_tweets(all_tweets: &[TweetWrapper], screen_name: &str) ->
     
     // Sort threads by creation date (newest first)
     threads.sort_by(|a, b| {
-        let date_a = DateTime::parse_from_str(&a[0].created_at, "%a %b %d %H:%M:%S %z %Y")
-            .unwrap_or_else(|_| DateTime::parse_from_rfc3339("1970-01-01T00:00:00Z").unwrap());
-        let date_b = DateTime::parse_from_str(&b[0].created_at, "%a %b %d %H:%M:%S %z %Y")
-            .unwrap_or_else(|_| DateTime::parse_from_rfc3339("1970-01-01T00:00:00Z").unwrap());
+        let date_a = parse_twitter_date(&a[0].created_at).unwrap_or(DateTime::UNIX_EPOCH);
+        let date_b = parse_twitter_date(&b[0].created_at).unwrap_or(DateTime::UNIX_EPOCH);
         date_b.cmp(&date_a)
     });
     
     // Convert to Thread structs
-    let result_threads: Vec<Thread> = threads.into_iter().map(|thread| {
-        let tweet_count = thread.len();
-        let favorite_count: u32 = thread.iter()
-            .map(|t| t.favorite_count.parse().unwrap_or(0))
-            .sum();
-        let retweet_count: u32 = thread.iter()
-            .map(|t| t.retweet_count.parse().unwrap_or(0))
-            .sum();
-        
-        Thread {
-            id: thread[0].id_str.clone(),
-            tweets: thread,
-            tweet_count,
-            favorite_count,
-            retweet_count,
-        }
-    }).collect();
+    let result_threads: Vec<Thread> = threads.into_iter()
+        .map(|thread| Thread::from_tweets(thread, screen_name))
+        .collect();
     
     Ok(result_threads)
 }
@@ -241,6 +224,7 @@ fn analyze_tweet_structure(tweets: &[TweetWrapper]) {
              (has_entities as f64 / tweets.len() as f64) * 100.0);
 }
 
+#[allow(dead_code)]
 fn analyze_coverage(all_tweets: &[TweetWrapper], threads: &[tweet_scrolls::processing::data_structures::Thread]) {
     // Count tweets in threads
     let mut tweets_in_threads = 0;
@@ -277,6 +261,7 @@ fn analyze_coverage(all_tweets: &[TweetWrapper], threads: &[tweet_scrolls::proce
     }
 }
 
+#[allow(dead_code)]
 fn analyze_threading_quality(all_tweets: &[TweetWrapper], threads: &[tweet_scrolls::processing::data_structures::Thread]) {
     let mut single_tweet_threads = 0;
     let mut multi_tweet_threads = 0;
@@ -377,12 +362,11 @@ fn investigate_missing_tweets(all_tweets: &[TweetWrapper], threads: &[tweet_scro
     println!("\n   🔍 Sample of missing tweets:");
     for (i, tweet) in missing_tweets.iter().take(5).enumerate() {
         println!("   {}. ID: {} | Created: {}", i + 1, tweet.id_str, tweet.created_at);
-        println!("      Text: {}", &tweet.full_text[..tweet.full_text.len().min(100)]);
+        println!("      Text: {}", truncate_to_utf8_boundary(&tweet.full_text, 100));
         
-        if tweet.in_reply_to_status_id_str.is_some() {
+        if let Some(reply_to) = &tweet.in_reply_to_status_id_str {
             missing_replies += 1;
-            println!("      Type: Reply to {}", 
-                     tweet.in_reply_to_status_id_str.as_ref().unwrap());
+            println!("      Type: Reply to {}", reply_to);
         } else {
             missing_originals += 1;
             println!("      Type: Original tweet");
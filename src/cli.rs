@@ -1,9 +1,18 @@
 //! Command Line Interface module for Tweet-Scrolls
 //! Implements simple folder-based processing as per requirements
 
-use anyhow::{Result, bail};
-use std::path::PathBuf;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local};
+use chrono_tz::Tz;
+use std::path::{Path, PathBuf};
 use std::env;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::processing::{find_dm_files, validate_input_file, CitationMode, DmSortMode, FilterMode, ProcessingProgress};
+use crate::relationship::anonymization::PseudonymRegistry;
+use crate::relationship::Locale;
+use crate::utils::{format_bytes, parse_timezone, sanitize_filename};
 
 /// CLI configuration parsed from command line arguments
 #[derive(Debug)]
@@ -14,6 +23,47 @@ pub struct CliConfig {
     pub output_dir: Option<PathBuf>,
     /// Run in non-interactive mode (no prompts)
     pub non_interactive: bool,
+    /// IANA timezone for run times shown on the console, such as the
+    /// `--diff-against` previous-run date (defaults to UTC)
+    pub timezone: Option<Tz>,
+    /// Which tweets to process based on retweet status
+    pub filter_mode: FilterMode,
+    /// Previous output directory to compare this run's threads against
+    pub diff_against: Option<PathBuf>,
+    /// Citation labels for the cited threads output (`None` skips it)
+    pub citation_mode: CitationMode,
+    /// Language for per-contact relationship profiles (`None` skips them)
+    pub locale: Option<Locale>,
+    /// DM conversations with fewer messages are left out of the DM results
+    pub min_dm_messages: usize,
+    /// Regexes for tweets to drop before threading (repeatable flag)
+    pub exclude_patterns: Vec<String>,
+    /// Hide DM message text in the threads text output
+    pub privacy_mode: bool,
+    /// Also write an Anki flashcard deck of DM contacts
+    pub anki_deck: bool,
+    /// Truncate CSV text cells to this many characters
+    pub max_text_length: Option<usize>,
+    /// Timezone for tweet timestamps in the threads output files (defaults to UTC)
+    pub output_tz: Tz,
+    /// Drop threads containing tweets marked possibly sensitive
+    pub exclude_sensitive: bool,
+    /// CSV file of event dates to compare posting activity around
+    pub events_file: Option<PathBuf>,
+    /// Existing thread summary CSV to append only new threads to
+    pub append_to_existing: Option<PathBuf>,
+    /// Also write archive-wide thread statistics as JSON
+    pub stats_json: bool,
+    /// Order of the DM conversations CSV
+    pub dm_sort_mode: DmSortMode,
+    /// Rebuild the cached DM interaction timeline instead of loading it
+    pub rebuild_cache: bool,
+    /// Log the reason behind every filter and classification decision
+    pub explain: bool,
+    /// Keep only the first N tweets of longer threads
+    pub max_thread_length: Option<usize>,
+    /// Quote every field in the thread CSVs
+    pub always_quote_csv: bool,
 }
 
 impl CliConfig {
@@ -24,6 +74,26 @@ impl CliConfig {
     /// ```bash
     /// tweet-scrolls /path/to/twitter/archive
     /// tweet-scrolls /path/to/twitter/archive /path/to/output
+    /// tweet-scrolls /path/to/twitter/archive --timezone Asia/Tokyo
+    /// tweet-scrolls /path/to/twitter/archive --retweets-only
+    /// tweet-scrolls /path/to/twitter/archive --diff-against /path/to/previous/output
+    /// tweet-scrolls /path/to/twitter/archive --citation-mode numbered
+    /// tweet-scrolls /path/to/twitter/archive --locale fr-FR
+    /// tweet-scrolls /path/to/twitter/archive --min-dm-messages 3
+    /// tweet-scrolls /path/to/twitter/archive --exclude-pattern "🧵" --exclude-pattern "(?i)giveaway"
+    /// tweet-scrolls /path/to/twitter/archive --privacy-mode
+    /// tweet-scrolls /path/to/twitter/archive --anki-deck
+    /// tweet-scrolls /path/to/twitter/archive --max-text-length 32000
+    /// tweet-scrolls /path/to/twitter/archive --output-tz America/New_York
+    /// tweet-scrolls /path/to/twitter/archive --exclude-sensitive
+    /// tweet-scrolls /path/to/twitter/archive --events-file /path/to/events.csv
+    /// tweet-scrolls /path/to/twitter/archive --append-to-existing /path/to/threads.csv
+    /// tweet-scrolls /path/to/twitter/archive --stats-json
+    /// tweet-scrolls /path/to/twitter/archive --dm-output-sort recency
+    /// tweet-scrolls /path/to/twitter/archive --rebuild-cache
+    /// tweet-scrolls /path/to/twitter/archive --explain
+    /// tweet-scrolls /path/to/twitter/archive --max-thread-length 20
+    /// tweet-scrolls /path/to/twitter/archive --always-quote-csv
     /// ```
     pub fn from_args() -> Result<Self> {
         let args: Vec<String> = env::args().collect();
@@ -33,33 +103,215 @@ impl CliConfig {
             bail!("Missing required argument: archive folder path");
         }
         
-        let archive_folder = PathBuf::from(&args[1]);
+        let config = Self::parse_args(&args[1..])?;
         
         // Validate the folder exists
-        if !archive_folder.exists() {
-            bail!("Archive folder does not exist: {}", archive_folder.display());
+        if !config.archive_folder.exists() {
+            bail!("Archive folder does not exist: {}", config.archive_folder.display());
         }
         
-        if !archive_folder.is_dir() {
-            bail!("Path is not a directory: {}", archive_folder.display());
+        if !config.archive_folder.is_dir() {
+            bail!("Path is not a directory: {}", config.archive_folder.display());
         }
         
         // Check for required files
-        let tweets_file = archive_folder.join("tweets.js");
-        if !tweets_file.exists() {
+        if !config.tweets_file().exists() {
             bail!("tweets.js not found in archive folder");
         }
         
-        let output_dir = if args.len() > 2 {
-            Some(PathBuf::from(&args[2]))
-        } else {
-            None
+        if let Some(prev_dir) = &config.diff_against {
+            if !prev_dir.is_dir() {
+                bail!("Previous output directory does not exist: {}", prev_dir.display());
+            }
+        }
+        
+        if let Some(events_file) = &config.events_file {
+            if !events_file.is_file() {
+                bail!("Events file does not exist: {}", events_file.display());
+            }
+        }
+        
+        Ok(config)
+    }
+    
+    /// Parse arguments (excluding the program name) without touching the filesystem
+    pub fn parse_args(args: &[String]) -> Result<Self> {
+        let mut positional = Vec::new();
+        let mut timezone = None;
+        let mut filter_mode = FilterMode::default();
+        let mut diff_against = None;
+        let mut citation_mode = CitationMode::default();
+        let mut locale = None;
+        let mut min_dm_messages = 1;
+        let mut exclude_patterns = Vec::new();
+        let mut privacy_mode = false;
+        let mut anki_deck = false;
+        let mut max_text_length = None;
+        let mut output_tz = chrono_tz::UTC;
+        let mut exclude_sensitive = false;
+        let mut events_file = None;
+        let mut append_to_existing = None;
+        let mut stats_json = false;
+        let mut dm_sort_mode = DmSortMode::default();
+        let mut rebuild_cache = false;
+        let mut explain = false;
+        let mut max_thread_length = None;
+        let mut always_quote_csv = false;
+        
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--timezone" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --timezone argument");
+                    }
+                    timezone = Some(parse_timezone(&args[i])?);
+                }
+                "--diff-against" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --diff-against argument");
+                    }
+                    diff_against = Some(PathBuf::from(&args[i]));
+                }
+                "--citation-mode" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --citation-mode argument");
+                    }
+                    citation_mode = args[i].parse()?;
+                }
+                "--dm-output-sort" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --dm-output-sort argument");
+                    }
+                    dm_sort_mode = args[i].parse()?;
+                }
+                "--locale" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --locale argument");
+                    }
+                    locale = Some(args[i].parse()?);
+                }
+                "--min-dm-messages" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --min-dm-messages argument");
+                    }
+                    min_dm_messages = args[i].parse()
+                        .with_context(|| format!("Invalid --min-dm-messages value: {}", args[i]))?;
+                }
+                "--exclude-pattern" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --exclude-pattern argument");
+                    }
+                    exclude_patterns.push(args[i].clone());
+                }
+                "--privacy-mode" => privacy_mode = true,
+                "--anki-deck" => anki_deck = true,
+                "--exclude-sensitive" => exclude_sensitive = true,
+                "--always-quote-csv" => always_quote_csv = true,
+                "--stats-json" => stats_json = true,
+                "--rebuild-cache" => rebuild_cache = true,
+                "--explain" => explain = true,
+                "--events-file" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --events-file argument");
+                    }
+                    events_file = Some(PathBuf::from(&args[i]));
+                }
+                "--append-to-existing" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --append-to-existing argument");
+                    }
+                    append_to_existing = Some(PathBuf::from(&args[i]));
+                }
+                "--output-tz" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --output-tz argument");
+                    }
+                    output_tz = parse_timezone(&args[i])?;
+                }
+                "--max-thread-length" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --max-thread-length argument");
+                    }
+                    let max: usize = args[i].parse()
+                        .with_context(|| format!("Invalid --max-thread-length value: {}", args[i]))?;
+                    if max == 0 {
+                        bail!("--max-thread-length must be at least 1");
+                    }
+                    max_thread_length = Some(max);
+                }
+                "--max-text-length" => {
+                    i += 1;
+                    if i >= args.len() {
+                        bail!("Missing value for --max-text-length argument");
+                    }
+                    max_text_length = Some(args[i].parse()
+                        .with_context(|| format!("Invalid --max-text-length value: {}", args[i]))?);
+                }
+                "--include-retweets" | "--retweets-only" => {
+                    let mode = if args[i] == "--include-retweets" {
+                        FilterMode::All
+                    } else {
+                        FilterMode::RetweetsOnly
+                    };
+                    if filter_mode != FilterMode::default() && filter_mode != mode {
+                        bail!("--include-retweets and --retweets-only cannot be used together");
+                    }
+                    filter_mode = mode;
+                }
+                arg if arg.starts_with("--") => {
+                    bail!("Unknown argument: {}", arg);
+                }
+                arg => positional.push(PathBuf::from(arg)),
+            }
+            i += 1;
+        }
+        
+        let mut positional = positional.into_iter();
+        let archive_folder = match positional.next() {
+            Some(folder) => folder,
+            None => bail!("Missing required argument: archive folder path"),
         };
+        let output_dir = positional.next();
+        if let Some(extra) = positional.next() {
+            bail!("Unexpected argument: {}", extra.display());
+        }
         
         Ok(CliConfig {
             archive_folder,
             output_dir,
             non_interactive: true, // Always non-interactive when using CLI args
+            timezone,
+            filter_mode,
+            diff_against,
+            citation_mode,
+            locale,
+            min_dm_messages,
+            exclude_patterns,
+            privacy_mode,
+            anki_deck,
+            max_text_length,
+            output_tz,
+            exclude_sensitive,
+            events_file,
+            append_to_existing,
+            stats_json,
+            dm_sort_mode,
+            rebuild_cache,
+            explain,
+            max_thread_length,
+            always_quote_csv,
         })
     }
     
@@ -68,14 +320,14 @@ impl CliConfig {
         self.archive_folder.join("tweets.js")
     }
     
-    /// Get the path to direct-messages.js file (if it exists)
-    pub fn dms_file(&self) -> Option<PathBuf> {
-        let path = self.archive_folder.join("direct-messages.js");
-        if path.exists() {
-            Some(path)
-        } else {
-            None
-        }
+    /// Get the paths of the direct-messages*.js part files, excluding group DMs
+    pub fn dms_files(&self) -> Vec<PathBuf> {
+        find_dm_files(&self.archive_folder)
+            .unwrap_or_default()
+            .into_iter()
+            .map(PathBuf::from)
+            .filter(|path| !path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("direct-messages-group")))
+            .collect()
     }
     
     /// Get the path to direct-message-headers.js file (if it exists)
@@ -88,6 +340,17 @@ impl CliConfig {
         }
     }
     
+    /// Path of the cached DM interaction timeline, kept in the archive folder across runs
+    pub fn timeline_cache_file(&self, screen_name: &str) -> PathBuf {
+        self.archive_folder.join(format!("timeline_cache_{}.json", screen_name))
+    }
+    
+    /// Path of the pseudonym registry, kept in the archive folder so labels
+    /// stay the same across runs
+    pub fn pseudonym_registry_file(&self, screen_name: &str) -> PathBuf {
+        PseudonymRegistry::path_for(&self.archive_folder, screen_name)
+    }
+    
     /// Get or create the output directory
     pub fn get_output_dir(&self, screen_name: &str, timestamp: i64) -> PathBuf {
         match &self.output_dir {
@@ -97,32 +360,195 @@ impl CliConfig {
     }
 }
 
+/// Inputs for `--non-interactive` runs, read from flags instead of prompts
+///
+/// ```bash
+/// tweet-scrolls --non-interactive --tweets-file tweets.js --screen-name jack
+/// tweet-scrolls --non-interactive --tweets-file tweets.js --screen-name jack --dm-file direct-messages.js --analyze-relationships true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NonInteractiveArgs {
+    /// Value of `--tweets-file` (required)
+    pub tweets_file: Option<String>,
+    /// Value of `--screen-name` (required)
+    pub screen_name: Option<String>,
+    /// Value of `--dm-file`
+    pub dm_file: Option<String>,
+    /// `--analyze-relationships [true|false]`; a bare flag means `true` [default: false]
+    pub analyze_relationships: bool,
+}
+
+impl NonInteractiveArgs {
+    /// Returns true if `args` ask for non-interactive mode
+    pub fn requested(args: &[String]) -> bool {
+        args.iter().any(|arg| arg == "--non-interactive")
+    }
+
+    /// Parse arguments (excluding the program name) without touching the filesystem
+    pub fn parse_args(args: &[String]) -> Result<Self> {
+        let mut parsed = Self::default();
+        let value = |i: usize, flag: &str| -> Result<String> {
+            match args.get(i) {
+                Some(value) if !value.starts_with("--") => Ok(value.clone()),
+                _ => bail!("Missing value for {} argument", flag),
+            }
+        };
+
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--non-interactive" => {}
+                "--tweets-file" => {
+                    i += 1;
+                    parsed.tweets_file = Some(value(i, "--tweets-file")?);
+                }
+                "--screen-name" => {
+                    i += 1;
+                    parsed.screen_name = Some(value(i, "--screen-name")?);
+                }
+                "--dm-file" => {
+                    i += 1;
+                    parsed.dm_file = Some(value(i, "--dm-file")?);
+                }
+                "--analyze-relationships" => {
+                    parsed.analyze_relationships = match args.get(i + 1).map(String::as_str) {
+                        Some("true") => { i += 1; true }
+                        Some("false") => { i += 1; false }
+                        _ => true,
+                    };
+                }
+                arg => bail!("Unknown argument in --non-interactive mode: {}", arg),
+            }
+            i += 1;
+        }
+
+        Ok(parsed)
+    }
+
+    /// The tweets file, which must be given and readable
+    pub fn input_file(&self) -> Result<String> {
+        let Some(path) = &self.tweets_file else {
+            bail!("--tweets-file is required in --non-interactive mode");
+        };
+        validate_input_file(path).map_err(|message| anyhow::anyhow!(message))?;
+        Ok(path.trim().to_string())
+    }
+
+    /// The screen name used in output file names, which must be given
+    pub fn screen_name(&self) -> Result<String> {
+        match self.screen_name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => Ok(sanitize_filename(name)),
+            _ => bail!("--screen-name is required in --non-interactive mode"),
+        }
+    }
+
+    /// The optional DM file, which must be readable when given
+    pub fn dm_file(&self) -> Result<Option<String>> {
+        match &self.dm_file {
+            Some(path) => {
+                validate_input_file(path).map_err(|message| anyhow::anyhow!(message))?;
+                Ok(Some(path.trim().to_string()))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Process a tweets file with every input taken from `--non-interactive` flags
+///
+/// Fails before any processing if a required flag is missing or a file is unreadable.
+pub async fn process_non_interactive(args: NonInteractiveArgs) -> Result<()> {
+    use crate::main_integration::{run_full_pipeline, PipelineConfig};
+    use chrono::Utc;
+
+    let input_file = args.input_file()?;
+    let screen_name = args.screen_name()?;
+    let dm_file = args.dm_file()?;
+    let timestamp = Utc::now().timestamp();
+
+    let output_dir = std::path::Path::new(&input_file).parent()
+        .unwrap_or_else(|| std::path::Path::new("."))
+        .join(format!("output_{}_{}", screen_name, timestamp));
+    println!("🚀 Processing {} (non-interactive)", input_file);
+    println!("📁 Output directory: {}", output_dir.display());
+
+    let mut pipeline = PipelineConfig::new(&input_file, &screen_name, &output_dir, timestamp);
+    pipeline.dms_files = dm_file.into_iter().map(PathBuf::from).collect();
+    pipeline.relationship_analysis = args.analyze_relationships;
+    let report = run_full_pipeline(pipeline).await?;
+    for warning in &report.warnings {
+        eprintln!("⚠️ {}", warning);
+    }
+    println!("📊 {} output files written in {:.2}s", report.output_files.len(), report.elapsed.as_secs_f64());
+    println!("✅ Processing complete!");
+    Ok(())
+}
+
 fn print_usage() {
     eprintln!("Tweet-Scrolls - Twitter Archive Processor");
     eprintln!();
     eprintln!("Usage:");
-    eprintln!("  tweet-scrolls <archive-folder> [output-folder]");
+    eprintln!("  tweet-scrolls <archive-folder> [output-folder] [options]");
+    eprintln!("  tweet-scrolls --non-interactive --tweets-file <FILE> --screen-name <NAME> [--dm-file <FILE>] [--analyze-relationships [true|false]]");
     eprintln!();
     eprintln!("Arguments:");
     eprintln!("  <archive-folder>  Path to Twitter archive folder containing tweets.js");
     eprintln!("  [output-folder]   Optional output directory (defaults to archive folder)");
     eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --timezone <TZ>   IANA timezone for run times shown on the console, e.g. the --diff-against date (e.g. Asia/Tokyo) [default: UTC]");
+    eprintln!("  --include-retweets  Process retweets alongside your own tweets");
+    eprintln!("  --retweets-only   Process only retweets, each as a standalone thread");
+    eprintln!("  --diff-against <DIR>  Report threads new, removed or changed since a previous output folder");
+    eprintln!("  --citation-mode <MODE>  Also write threads with none|numbered|lettered citation labels [default: none]");
+    eprintln!("  --locale <LOCALE>  Also write DM contact profiles in en-US|es-ES|fr-FR|de-DE (message text is not translated)");
+    eprintln!("  --min-dm-messages <N>  Skip DM conversations with fewer than N messages [default: 1]");
+    eprintln!("  --exclude-pattern <REGEX>  Drop tweets whose text matches REGEX before threading (repeatable)");
+    eprintln!("  --privacy-mode    Replace DM message text with [message hidden] in the DM threads text file");
+    eprintln!("  --anki-deck       Also write an Anki flashcard deck of DM contacts (anki_deck_<name>_<ts>.tsv)");
+    eprintln!("  --max-text-length <N>  Truncate CSV text cells to N characters, adding \"…\" (spreadsheets cap cells at 32767)");
+    eprintln!("  --max-thread-length <N>  Keep only the first N tweets of longer threads, noting the cut in the output");
+    eprintln!("  --always-quote-csv  Quote every field of the thread CSVs, for tools that split on commas naively");
+    eprintln!("  --exclude-sensitive  Drop threads containing tweets marked possibly sensitive");
+    eprintln!("  --output-tz <TZ>  Write tweet timestamps in threads output files in this IANA timezone [default: UTC]");
+    eprintln!("  --events-file <PATH>  CSV of event dates (YYYY-MM-DD, first column) to compare thread counts 30 days before and after");
+    eprintln!("  --append-to-existing <PATH>  Append threads not already in this thread summary CSV (created if missing; other CSV layouts are rejected)");
+    eprintln!("  --list-output-files <DIR>  List the files in a previous output folder with sizes and creation times, then exit");
+    eprintln!("  --dm-output-sort <ORDER>  Order the DM conversations CSV by recency|count [default: count]");
+    eprintln!("  --rebuild-cache   Rebuild the DM timeline cache (timeline_cache_<name>.json in the archive folder)");
+    eprintln!("  --explain         Log why each tweet, conversation and contact was kept, dropped or classified (explain_<name>_<ts>.log)");
+    eprintln!("  --stats-json      Also write thread statistics (lengths, engagement percentiles, date range, languages) as JSON");
+    eprintln!();
     eprintln!("Example:");
     eprintln!("  tweet-scrolls /home/user/twitter-archive");
     eprintln!("  tweet-scrolls /home/user/twitter-archive /home/user/output");
 }
 
+/// Creates a progress channel whose updates are printed to stderr
+///
+/// Drop the sender and await the handle to make sure the last update is printed.
+pub fn spawn_progress_printer() -> (watch::Sender<ProcessingProgress>, JoinHandle<()>) {
+    let (sender, mut receiver) = watch::channel(ProcessingProgress::default());
+    let handle = tokio::spawn(async move {
+        while receiver.changed().await.is_ok() {
+            let progress = receiver.borrow_and_update().clone();
+            eprintln!("{}", progress.message);
+        }
+    });
+    (sender, handle)
+}
+
 /// Process Twitter archive with CLI configuration
 pub async fn process_with_cli(config: CliConfig) -> Result<()> {
-    use crate::main_process::main_process_twitter_archive;
+    use crate::main_integration::{run_full_pipeline, PipelineConfig};
+    use crate::processing::{diff_against_previous, read_event_dates};
     use chrono::Utc;
-    use crate::utils::file_splitter::{split_file, SplitConfig};
+    use crate::utils::file_splitter::{split_file, ChunkStrategy, SplitConfig, DEFAULT_MAX_OUTPUT_FILES};
 
     println!("🚀 Processing Twitter archive from: {}", config.archive_folder.display());
 
     let tweets_file = config.tweets_file();
-    let dms_file = config.dms_file();
-    let dm_headers_file = config.dm_headers_file();
+    let dms_files = config.dms_files();
 
     // Input file splitting removed: Only output TXT files will be split after processing
 
@@ -132,16 +558,53 @@ pub async fn process_with_cli(config: CliConfig) -> Result<()> {
     let output_dir = config.get_output_dir(screen_name, timestamp);
 
     println!("📁 Output directory: {}", output_dir.display());
+    if let Some(tz) = config.timezone {
+        println!("🕒 Displaying times in: {}", tz);
+    }
+    match config.filter_mode {
+        FilterMode::All => println!("🔁 Including retweets"),
+        FilterMode::RetweetsOnly => println!("🔁 Processing retweets only"),
+        FilterMode::ExcludeRetweets => {}
+    }
 
     // Process the archive
-    main_process_twitter_archive(
-        tweets_file.to_str().unwrap(),
-        dms_file.as_ref().map(|p| p.to_str().unwrap()),
-        dm_headers_file.as_ref().map(|p| p.to_str().unwrap()),
-        output_dir.to_str().unwrap(),
-        screen_name,
-        timestamp,
-    ).await?;
+    let mut pipeline = PipelineConfig::new(&tweets_file, screen_name, &output_dir, timestamp);
+    pipeline.dms_files = dms_files;
+    pipeline.filter_mode = config.filter_mode;
+    pipeline.citation_mode = config.citation_mode;
+    pipeline.profile_locale = config.locale;
+    pipeline.min_dm_messages = config.min_dm_messages;
+    pipeline.exclude_patterns = config.exclude_patterns.clone();
+    pipeline.privacy_mode = config.privacy_mode;
+    pipeline.anki_deck = config.anki_deck;
+    pipeline.csv_options.max_text_length = config.max_text_length;
+    pipeline.csv_options.always_quote = config.always_quote_csv;
+    pipeline.output_locale = config.output_tz;
+    pipeline.exclude_sensitive = config.exclude_sensitive;
+    if let Some(events_file) = &config.events_file {
+        pipeline.event_dates = read_event_dates(events_file)?;
+        println!("📅 Comparing posting activity around {} events", pipeline.event_dates.len());
+    }
+    pipeline.append_to_existing = config.append_to_existing.clone();
+    pipeline.stats_json = config.stats_json;
+    pipeline.dm_sort_mode = config.dm_sort_mode;
+    pipeline.timeline_cache = Some(config.timeline_cache_file(screen_name));
+    pipeline.rebuild_cache = config.rebuild_cache;
+    pipeline.pseudonym_registry = Some(config.pseudonym_registry_file(screen_name));
+    pipeline.explain = config.explain;
+    pipeline.max_thread_length = config.max_thread_length;
+    pipeline.show_progress = true;
+    let report = run_full_pipeline(pipeline).await?;
+    for warning in &report.warnings {
+        eprintln!("⚠️ {}", warning);
+    }
+    println!("📊 {} output files written in {:.2}s", report.output_files.len(), report.elapsed.as_secs_f64());
+
+    if let Some(prev_dir) = &config.diff_against {
+        println!("🔍 Comparing against previous run in: {}", prev_dir.display());
+        let diff = diff_against_previous(&output_dir, prev_dir, screen_name, timestamp, config.timezone)?;
+        println!("📈 {}", diff);
+    }
 
     println!("✅ Processing complete!");
 
@@ -167,13 +630,17 @@ pub async fn process_with_cli(config: CliConfig) -> Result<()> {
         .collect::<Vec<_>>();
 
     for (path, size) in txt_files {
-        println!("[FileSplitter] Splitting large TXT file: {} ({} bytes)", path.display(), size);
+        println!("[FileSplitter] Splitting large TXT file: {} ({})", path.display(), format_bytes(size));
         let split_config = SplitConfig {
             input_path: path.clone(),
             output_dir: Some(path.parent().unwrap().to_path_buf()),
-            chunk_size: 1024 * 1024, // 1MB
+            strategy: ChunkStrategy::Bytes(1024 * 1024), // 1MB
             prefix: None,
             digits: 3,
+            verify_after_split: false,
+            exclude_empty_lines: false,
+            max_output_files: Some(DEFAULT_MAX_OUTPUT_FILES),
+            pad_last_chunk: false,
         };
         match split_file(&split_config) {
             Ok(result) => println!("[FileSplitter] {}", result),
@@ -184,6 +651,103 @@ pub async fn process_with_cli(config: CliConfig) -> Result<()> {
     Ok(())
 }
 
+/// One file found by [`list_output_files`]
+#[derive(Debug, Clone, PartialEq)]
+struct OutputFileEntry {
+    name: String,
+    file_type: &'static str,
+    size: u64,
+    created: Option<DateTime<Local>>,
+}
+
+impl OutputFileEntry {
+    /// Problems worth pointing out next to the file
+    fn warnings(&self) -> Vec<&'static str> {
+        let mut warnings = Vec::new();
+        if self.size == 0 {
+            warnings.push("empty");
+        }
+        if looks_temporary(&self.name) {
+            warnings.push("temporary");
+        }
+        warnings
+    }
+}
+
+/// Output type label from a file extension
+fn output_file_type(name: &str) -> &'static str {
+    match Path::new(name).extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("csv") => "CSV",
+        Some("txt") => "TXT",
+        Some("json") => "JSON",
+        Some("md") => "Markdown",
+        Some("log") => "Log",
+        _ => "Other",
+    }
+}
+
+/// Whether a file name looks like an editor backup or an unfinished write
+fn looks_temporary(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.starts_with('.') || lower.starts_with('~') || lower.ends_with('~')
+        || lower.ends_with(".tmp") || lower.ends_with(".temp") || lower.ends_with(".part") || lower.ends_with(".bak")
+}
+
+/// Files directly inside `output_dir`, grouped by type then sorted by name
+async fn collect_output_files(output_dir: &Path) -> Result<Vec<OutputFileEntry>> {
+    let mut entries = tokio::fs::read_dir(output_dir).await
+        .with_context(|| format!("Failed to read output directory: {}", output_dir.display()))?;
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = tokio::fs::metadata(entry.path()).await
+            .with_context(|| format!("Failed to read metadata: {}", entry.path().display()))?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        files.push(OutputFileEntry {
+            file_type: output_file_type(&name),
+            size: metadata.len(),
+            created: metadata.created().or_else(|_| metadata.modified()).ok().map(DateTime::<Local>::from),
+            name,
+        });
+    }
+    let type_order = |file_type: &str| ["CSV", "TXT", "JSON", "Markdown"].iter().position(|t| *t == file_type).unwrap_or(4);
+    files.sort_by(|a, b| type_order(a.file_type).cmp(&type_order(b.file_type)).then_with(|| a.name.cmp(&b.name)));
+    Ok(files)
+}
+
+/// Prints a table of the files in a previous output directory
+///
+/// Files are grouped by type (CSV, TXT, JSON, Markdown) with their size and
+/// creation time (modification time where the filesystem records no creation
+/// time). Empty files and names that look temporary are flagged. Only file
+/// metadata is read.
+pub async fn list_output_files(output_dir: &Path) -> Result<()> {
+    let files = collect_output_files(output_dir).await?;
+    if files.is_empty() {
+        println!("📭 No files found in {}", output_dir.display());
+        return Ok(());
+    }
+
+    let name_width = files.iter().map(|f| f.name.chars().count()).max().unwrap_or(0).max("File".len());
+    println!("📂 {} files in {}", files.len(), output_dir.display());
+    println!("{:<name_width$}  {:<8}  {:>10}  {:<19}", "File", "Type", "Size", "Created");
+    println!("{}", "-".repeat(name_width + 2 + 8 + 2 + 10 + 2 + 19));
+    for file in &files {
+        let created = file.created.map(|c| c.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_else(|| "unknown".to_string());
+        let warnings = file.warnings();
+        let flag = if warnings.is_empty() { String::new() } else { format!("  ⚠️ {}", warnings.join(", ")) };
+        println!("{:<name_width$}  {:<8}  {:>10}  {:<19}{}", file.name, file.file_type, format_bytes(file.size), created, flag);
+    }
+
+    let flagged = files.iter().filter(|f| !f.warnings().is_empty()).count();
+    if flagged > 0 {
+        println!("⚠️ {} files look empty or temporary; they may come from an interrupted run", flagged);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,6 +764,26 @@ mod tests {
             archive_folder: PathBuf::from("/tmp"),
             output_dir: None,
             non_interactive: true,
+            timezone: None,
+            filter_mode: FilterMode::default(),
+            diff_against: None,
+            citation_mode: CitationMode::default(),
+            locale: None,
+            min_dm_messages: 1,
+            exclude_patterns: Vec::new(),
+            privacy_mode: false,
+            anki_deck: false,
+            max_text_length: None,
+            output_tz: chrono_tz::UTC,
+            exclude_sensitive: false,
+            events_file: None,
+            append_to_existing: None,
+            stats_json: false,
+            dm_sort_mode: DmSortMode::default(),
+            rebuild_cache: false,
+            explain: false,
+            max_thread_length: None,
+            always_quote_csv: false,
         };
         
         assert_eq!(config.archive_folder, PathBuf::from("/tmp"));
@@ -214,17 +798,349 @@ mod tests {
         // Create test files
         fs::write(archive_path.join("tweets.js"), "test").await?;
         fs::write(archive_path.join("direct-messages.js"), "test").await?;
+        fs::write(archive_path.join("direct-messages-part1.js"), "test").await?;
+        fs::write(archive_path.join("direct-messages-group.js"), "test").await?;
         
         let config = CliConfig {
             archive_folder: archive_path.to_path_buf(),
             output_dir: None,
             non_interactive: true,
+            timezone: None,
+            filter_mode: FilterMode::default(),
+            diff_against: None,
+            citation_mode: CitationMode::default(),
+            locale: None,
+            min_dm_messages: 1,
+            exclude_patterns: Vec::new(),
+            privacy_mode: false,
+            anki_deck: false,
+            max_text_length: None,
+            output_tz: chrono_tz::UTC,
+            exclude_sensitive: false,
+            events_file: None,
+            append_to_existing: None,
+            stats_json: false,
+            dm_sort_mode: DmSortMode::default(),
+            rebuild_cache: false,
+            explain: false,
+            max_thread_length: None,
+            always_quote_csv: false,
         };
         
         assert!(config.tweets_file().exists());
-        assert!(config.dms_file().is_some());
+        assert_eq!(config.dms_files(), vec![
+            archive_path.join("direct-messages-part1.js"),
+            archive_path.join("direct-messages.js"),
+        ]);
         assert!(config.dm_headers_file().is_none());
         
         Ok(())
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_parse_args_with_timezone() -> Result<()> {
+        let args: Vec<String> = ["/tmp/archive", "/tmp/out", "--timezone", "Asia/Tokyo"]
+            .iter().map(|s| s.to_string()).collect();
+        let config = CliConfig::parse_args(&args)?;
+        
+        assert_eq!(config.archive_folder, PathBuf::from("/tmp/archive"));
+        assert_eq!(config.output_dir, Some(PathBuf::from("/tmp/out")));
+        assert_eq!(config.timezone, Some(chrono_tz::Asia::Tokyo));
+        
+        let bad: Vec<String> = ["/tmp/archive", "--timezone", "Not/AZone"]
+            .iter().map(|s| s.to_string()).collect();
+        assert!(CliConfig::parse_args(&bad).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_retweet_flags() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive"]))?;
+        assert_eq!(config.filter_mode, FilterMode::ExcludeRetweets);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--include-retweets"]))?;
+        assert_eq!(config.filter_mode, FilterMode::All);
+        
+        let config = CliConfig::parse_args(&to_args(&["--retweets-only", "/tmp/archive"]))?;
+        assert_eq!(config.filter_mode, FilterMode::RetweetsOnly);
+        assert_eq!(config.archive_folder, PathBuf::from("/tmp/archive"));
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--include-retweets", "--retweets-only"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_diff_against() -> Result<()> {
+        let args: Vec<String> = ["/tmp/archive", "--diff-against", "/tmp/previous"]
+            .iter().map(|s| s.to_string()).collect();
+        let config = CliConfig::parse_args(&args)?;
+        
+        assert_eq!(config.diff_against, Some(PathBuf::from("/tmp/previous")));
+        assert!(config.output_dir.is_none());
+        
+        let missing: Vec<String> = ["/tmp/archive", "--diff-against"]
+            .iter().map(|s| s.to_string()).collect();
+        assert!(CliConfig::parse_args(&missing).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_citation_mode() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive"]))?;
+        assert_eq!(config.citation_mode, CitationMode::None);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--citation-mode", "lettered"]))?;
+        assert_eq!(config.citation_mode, CitationMode::Lettered);
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--citation-mode", "roman"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--citation-mode"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_locale() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.locale, None);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--locale", "fr-FR"]))?;
+        assert_eq!(config.locale, Some(Locale::FrFr));
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--locale", "xx-XX"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--locale"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_min_dm_messages() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.min_dm_messages, 1);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--min-dm-messages", "3"]))?;
+        assert_eq!(config.min_dm_messages, 3);
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--min-dm-messages", "-1"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--min-dm-messages"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_exclude_pattern() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.exclude_patterns.is_empty());
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--exclude-pattern", "🧵", "--exclude-pattern", "(?i)promo"]))?;
+        assert_eq!(config.exclude_patterns, vec!["🧵", "(?i)promo"]);
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--exclude-pattern"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_privacy_mode() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(!CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.privacy_mode);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--privacy-mode"]))?.privacy_mode);
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_anki_deck() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(!CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.anki_deck);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--anki-deck"]))?.anki_deck);
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_max_text_length() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.max_text_length, None);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-text-length", "32000"]))?;
+        assert_eq!(config.max_text_length, Some(32000));
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-text-length", "lots"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-text-length"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_output_tz() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.output_tz, chrono_tz::UTC);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--output-tz", "America/New_York"]))?;
+        assert_eq!(config.output_tz, chrono_tz::America::New_York);
+        
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--output-tz", "Nowhere/Special"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_exclude_sensitive() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(!CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.exclude_sensitive);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--exclude-sensitive"]))?.exclude_sensitive);
+        Ok(())
+    }
+    
+    #[test]
+    fn test_non_interactive_args() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        let temp_dir = tempdir()?;
+        let tweets = temp_dir.path().join("tweets.js");
+        std::fs::write(&tweets, "[]")?;
+        let tweets = tweets.to_str().unwrap();
+        
+        assert!(NonInteractiveArgs::requested(&to_args(&["--screen-name", "jack", "--non-interactive"])));
+        assert!(!NonInteractiveArgs::requested(&to_args(&["/tmp/archive"])));
+        
+        let args = NonInteractiveArgs::parse_args(&to_args(&[
+            "--non-interactive", "--tweets-file", tweets, "--screen-name", "jack", "--analyze-relationships",
+        ]))?;
+        assert_eq!(args.input_file()?, tweets);
+        assert_eq!(args.screen_name()?, "jack");
+        let evil = NonInteractiveArgs::parse_args(&to_args(&["--non-interactive", "--screen-name", "evil:name*/test"]))?;
+        assert_eq!(evil.screen_name()?, "evil_name__test");
+        assert_eq!(args.dm_file()?, None);
+        assert!(args.analyze_relationships);
+        
+        let args = NonInteractiveArgs::parse_args(&to_args(&["--non-interactive", "--analyze-relationships", "false"]))?;
+        assert!(!args.analyze_relationships);
+        assert!(args.input_file().unwrap_err().to_string().contains("--tweets-file is required"));
+        assert!(args.screen_name().unwrap_err().to_string().contains("--screen-name is required"));
+        
+        let args = NonInteractiveArgs::parse_args(&to_args(&["--non-interactive", "--dm-file", "/nonexistent/dms.js"]))?;
+        assert!(args.dm_file().is_err());
+        assert!(NonInteractiveArgs::parse_args(&to_args(&["--non-interactive", "--tweets-file"])).is_err());
+        assert!(NonInteractiveArgs::parse_args(&to_args(&["--non-interactive", "--screen-name", "--dm-file"])).is_err());
+        assert!(NonInteractiveArgs::parse_args(&to_args(&["--non-interactive", "/tmp/archive"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_events_file() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.events_file, None);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--events-file", "/tmp/events.csv"]))?;
+        assert_eq!(config.events_file, Some(PathBuf::from("/tmp/events.csv")));
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--events-file"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_append_to_existing() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.append_to_existing, None);
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--append-to-existing", "/tmp/threads.csv"]))?;
+        assert_eq!(config.append_to_existing, Some(PathBuf::from("/tmp/threads.csv")));
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--append-to-existing"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_stats_json() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(!CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.stats_json);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--stats-json"]))?.stats_json);
+        Ok(())
+    }
+    
+    #[tokio::test]
+    async fn test_collect_output_files_groups_and_flags() -> Result<()> {
+        let temp_dir = tempdir()?;
+        fs::write(temp_dir.path().join("threads_jack_1.txt"), "text").await?;
+        fs::write(temp_dir.path().join("threads_jack_1.csv"), "a,b").await?;
+        fs::write(temp_dir.path().join("dm_results_jack_1.csv"), "").await?;
+        fs::write(temp_dir.path().join("thread_stats_jack_1.json"), "{}").await?;
+        fs::write(temp_dir.path().join("notes.md.tmp"), "draft").await?;
+        fs::create_dir(temp_dir.path().join("obsidian")).await?;
+        
+        let files = collect_output_files(temp_dir.path()).await?;
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        
+        assert_eq!(names, vec!["dm_results_jack_1.csv", "threads_jack_1.csv", "threads_jack_1.txt", "thread_stats_jack_1.json", "notes.md.tmp"]);
+        assert_eq!(files[3].file_type, "JSON");
+        assert_eq!(files[4].file_type, "Other");
+        assert_eq!(files[0].warnings(), vec!["empty"]);
+        assert!(files[1].warnings().is_empty());
+        assert_eq!(files[4].warnings(), vec!["temporary"]);
+        assert!(files.iter().all(|f| f.created.is_some()));
+        
+        assert!(list_output_files(temp_dir.path()).await.is_ok());
+        assert!(list_output_files(&temp_dir.path().join("missing")).await.is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_dm_output_sort() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.dm_sort_mode, DmSortMode::MessageCountFirst);
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--dm-output-sort", "recency"]))?;
+        assert_eq!(config.dm_sort_mode, DmSortMode::RecencyFirst);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--dm-output-sort", "alphabetical"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--dm-output-sort"])).is_err());
+        Ok(())
+    }
+    
+    #[test]
+    fn test_parse_args_with_rebuild_cache() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive"]))?;
+        assert!(!config.rebuild_cache);
+        assert_eq!(config.timeline_cache_file("jack"), PathBuf::from("/tmp/archive/timeline_cache_jack.json"));
+        assert_eq!(config.pseudonym_registry_file("jack"), PathBuf::from("/tmp/archive/pseudonym_registry_jack.json"));
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--rebuild-cache"]))?.rebuild_cache);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_with_explain() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(!CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.explain);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--explain"]))?.explain);
+        assert_eq!(output_file_type("explain_jack_1.log"), "Log");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_with_max_thread_length() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert_eq!(CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.max_thread_length, None);
+        let config = CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-thread-length", "20"]))?;
+        assert_eq!(config.max_thread_length, Some(20));
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-thread-length", "0"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-thread-length", "many"])).is_err());
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--max-thread-length"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_args_with_always_quote_csv() -> Result<()> {
+        let to_args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        
+        assert!(!CliConfig::parse_args(&to_args(&["/tmp/archive"]))?.always_quote_csv);
+        assert!(CliConfig::parse_args(&to_args(&["/tmp/archive", "--always-quote-csv"]))?.always_quote_csv);
+        Ok(())
+    }
+}
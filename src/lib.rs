@@ -11,7 +11,6 @@ pub mod utils;
 pub mod processing;
 pub mod relationship;
 pub mod main_integration;
-pub mod main_process;
 pub mod cli;
 
 // Re-exports for common types
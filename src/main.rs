@@ -3,21 +3,15 @@
 //! A Rust CLI tool that processes Twitter JSON archive files to extract and organize 
 //! tweet threads and direct message conversations with relationship intelligence analysis.
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::Utc;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs as async_fs;
-use tweet_scrolls::processing::data_structures::Thread;
 
 // Import our modular components
-use tweet_scrolls::processing::{
-    MvpAnalyzer,
-    file_io::{get_input_file, get_dm_file},
-    tweets::process_tweets,
-    direct_messages::process_dm_file,
-    data_structures::TweetWrapper,
-};
-use tweet_scrolls::models::direct_message::DmWrapper;
+use tweet_scrolls::main_integration::{run_full_pipeline, PipelineConfig};
+use tweet_scrolls::processing::file_io::{get_input_file, get_dm_files, confirm_relationship_analysis, select_output_format};
+use tweet_scrolls::utils::archive_validation::{detect_archive_version, ArchiveVersion, ARCHIVE_HELP_URL};
 
 // Global allocator for performance optimization
 #[global_allocator]
@@ -25,20 +19,34 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 /// Main entry point for the Tweet-Scrolls application
 /// 
-/// This function orchestrates the entire processing pipeline:
-/// 1. Gets user input for file paths and screen name
-/// 2. Creates output directory structure
-/// 3. Processes tweets and/or DMs based on user input
-/// 4. Optionally performs relationship intelligence analysis
+/// This function gathers the user's choices and hands them to the library pipeline:
+/// 1. Gets user input for file paths, output format and relationship analysis
+/// 2. Builds a `PipelineConfig` for the output directory next to the input file
+/// 3. Runs `run_full_pipeline` and reports the outcome
 #[tokio::main]
 async fn main() -> Result<()> {
     use std::env;
-    use tweet_scrolls::cli::{CliConfig, process_with_cli};
+    use tweet_scrolls::cli::{CliConfig, NonInteractiveArgs, list_output_files, process_non_interactive, process_with_cli};
     
     // Check if CLI arguments were provided
     let args: Vec<String> = env::args().collect();
+    if NonInteractiveArgs::requested(&args[1..]) {
+        // Every input comes from flags; a missing one exits with an error instead of prompting
+        let non_interactive = NonInteractiveArgs::parse_args(&args[1..])?;
+        return process_non_interactive(non_interactive).await;
+    }
+    if args.get(1).map(String::as_str) == Some("--list-output-files") {
+        // Metadata-only listing of a previous run's output folder
+        let Some(output_dir) = args.get(2) else {
+            anyhow::bail!("Missing value for --list-output-files argument");
+        };
+        return list_output_files(Path::new(output_dir)).await;
+    }
     if args.len() > 1 {
         // CLI mode - process with provided folder path
+        if let Some(archive_dir) = args.get(1).filter(|arg| !arg.starts_with("--")) {
+            warn_if_old_archive(Path::new(archive_dir));
+        }
         let config = CliConfig::from_args()?;
         return process_with_cli(config).await;
     }
@@ -54,6 +62,7 @@ async fn main() -> Result<()> {
     println!();
     
     let input_file = get_input_file()?;
+    let output_format = select_output_format()?;
     let screen_name = "user".to_string(); // Generic name, we'll extract real handle from data if needed
     let timestamp = Utc::now().timestamp();
 
@@ -61,175 +70,50 @@ async fn main() -> Result<()> {
 
     // Validate input file exists
     if async_fs::metadata(&input_file).await.is_err() {
+        if let Some(archive_dir) = Path::new(&input_file).parent() {
+            warn_if_old_archive(archive_dir);
+        }
         anyhow::bail!("❌ File does not exist: {}", input_file);
     }
 
-    // Create output directory
+    let dm_files = get_dm_files()?;
+    println!();
+    let relationship_analysis = confirm_relationship_analysis()?;
+
     let input_path = Path::new(&input_file);
     let output_dir = input_path.parent().unwrap().join(format!("output_{}_{}", screen_name, timestamp));
-    async_fs::create_dir_all(&output_dir).await.context("Failed to create output directory")?;
 
-    // Process tweets with enhanced CSV output
+    let mut config = PipelineConfig::new(&input_file, &screen_name, &output_dir, timestamp);
+    config.dms_files = dm_files.into_iter().map(PathBuf::from).collect();
+    config.output_format = output_format;
+    config.relationship_analysis = relationship_analysis;
+    config.show_progress = true;
+
     println!("🌟 Avengers, assemble! Initiating Operation: Tweet Processing...");
-    if let Err(e) = process_tweets(&input_file, &screen_name, &output_dir, timestamp).await {
-        eprintln!("🚨 Mission Failed: {}", e);
-    } else {
-        println!("🎉 Victory! Tweets have been successfully processed and organized.");
+    let report = run_full_pipeline(config).await?;
+    println!("🎉 Victory! Tweets have been successfully processed and organized.");
+    if report.dms_processed {
+        println!("💬 DM processing completed successfully!");
     }
-
-    // Process DM file if provided
-    if let Some(dm_file) = get_dm_file()? {
-        println!("📱 Initiating DM Processing Operation...");
-        if let Err(e) = process_dm_file(&dm_file, &screen_name, &output_dir, timestamp).await {
-            eprintln!("🚨 DM Mission Failed: {}", e);
-        } else {
-            println!("💬 DM processing completed successfully!");
-        }
+    if report.relationship_analysis_run {
+        println!("🎯 Relationship intelligence analysis completed successfully!");
     }
-
-    // Ask user if they want to run relationship intelligence analysis
-    println!("\nWould you like to generate relationship intelligence profiles? (y/n)");
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
-    if input.trim().to_lowercase() == "y" {
-        println!("\nInitiating Relationship Intelligence Analysis...");
-        if let Err(e) = perform_relationship_analysis(&screen_name, &output_dir, timestamp).await {
-            eprintln!("🚨 Relationship Analysis Failed: {}", e);
-        } else {
-            println!("🎯 Relationship intelligence analysis completed successfully!");
-        }
+    for warning in &report.warnings {
+        eprintln!("🚨 {}", warning);
+    }
+    if report.files_removed > 0 {
+        println!("🧹 Removed {} files not matching the chosen output format.", report.files_removed);
     }
 
     println!("✨ All operations completed successfully! Check the output directory for results.");
     Ok(())
 }
 
-/// Performs MVP relationship intelligence analysis
-/// 
-/// This function provides immediate value by analyzing:
-/// - Who you interact with most
-/// - When you're most active
-/// - Clean, readable insights
-async fn perform_relationship_analysis(
-    screen_name: &str, 
-    output_dir: &Path, 
-    timestamp: i64
-) -> Result<()> {
-    println!("🔬 Analyzing your Twitter relationships and activity patterns...");
-    
-    // Create MVP analyzer
-    let mut analyzer = MvpAnalyzer::new();
-    
-    // Try to load and analyze tweet data
-    let tweets_file = output_dir.parent().unwrap().join("tweets.js");
-    if async_fs::metadata(&tweets_file).await.is_ok() {
-        println!("📊 Analyzing tweet interactions...");
-        
-        let tweets_content = async_fs::read_to_string(&tweets_file).await
-            .context("Failed to read tweets file")?;
-        
-        // Parse tweets (remove JavaScript prefix)
-        if let Some(json_start) = tweets_content.find('[') {
-            if let Some(json_end) = tweets_content.rfind(']') {
-                let json_content = &tweets_content[json_start..=json_end];
-                
-                match serde_json::from_str::<Vec<TweetWrapper>>(json_content) {
-                    Ok(tweet_wrappers) => {
-                        // Convert to threads for analysis
-                        let threads: Vec<Thread> = tweet_wrappers.into_iter().map(|tw| Thread {
-                            id: tw.tweet.id_str.clone(),
-                            tweets: vec![tw.tweet],
-                            tweet_count: 1,
-                            favorite_count: 0,
-                            retweet_count: 0,
-                        }).collect();
-                        
-                        analyzer.analyze_tweets(&threads)?;
-                        println!("✅ Tweet analysis complete - found {} relationships", 
-                            analyzer.relationships.len());
-                    }
-                    Err(e) => {
-                        println!("⚠️ Could not parse tweets file: {}", e);
-                    }
-                }
-            }
-        }
+/// Warns that pre-2018 archives (monthly `data/js/tweets/` files) cannot be processed
+fn warn_if_old_archive(archive_dir: &Path) {
+    if detect_archive_version(archive_dir) == ArchiveVersion::Old {
+        eprintln!("⚠️ This looks like an old-format Twitter archive (data/js/tweets/ with one file per month).");
+        eprintln!("   Archives from before 2018 are not supported; please request a new archive from Twitter:");
+        eprintln!("   {}", ARCHIVE_HELP_URL);
     }
-    
-    // Try to load and analyze DM data
-    let dm_file = output_dir.parent().unwrap().join("direct-messages.js");
-    if async_fs::metadata(&dm_file).await.is_ok() {
-        println!("💬 Analyzing direct message conversations...");
-        
-        let dm_content = async_fs::read_to_string(&dm_file).await
-            .context("Failed to read DM file")?;
-        
-        // Parse DMs (remove JavaScript prefix)
-        if let Some(json_start) = dm_content.find('[') {
-            if let Some(json_end) = dm_content.rfind(']') {
-                let json_content = &dm_content[json_start..=json_end];
-                
-                match serde_json::from_str::<Vec<DmWrapper>>(json_content) {
-                    Ok(dm_wrappers) => {
-                        analyzer.analyze_dms(&dm_wrappers)?;
-                        println!("✅ DM analysis complete");
-                    }
-                    Err(e) => {
-                        println!("⚠️ Could not parse DM file: {}", e);
-                    }
-                }
-            }
-        }
-    }
-    
-    // Generate the intelligence report
-    println!("📈 Generating relationship intelligence report...");
-    analyzer.generate_report(output_dir, screen_name, timestamp).await?;
-    
-    // Show quick preview of insights
-    let top_relationships = analyzer.get_top_relationships(3);
-    if !top_relationships.is_empty() {
-        println!("\n🎯 QUICK INSIGHTS:");
-        println!("Your top connections:");
-        for (i, rel) in top_relationships.iter().enumerate() {
-            println!("  {}. @{} ({} interactions)", i + 1, rel.username, rel.interaction_count);
-        }
-    }
-    
-    let peak_hours = analyzer.get_peak_activity_hours(2);
-    if !peak_hours.is_empty() {
-        println!("Most active times:");
-        for (hour, count) in peak_hours {
-            let time_str = if hour == 0 {
-                "12:00 AM".to_string()
-            } else if hour < 12 {
-                format!("{}:00 AM", hour)
-            } else if hour == 12 {
-                "12:00 PM".to_string()
-            } else {
-                format!("{}:00 PM", hour - 12)
-            };
-            println!("  {} ({} activities)", time_str, count);
-        }
-    }
-    
-    println!("\n📄 Full report saved to output directory!");
-    Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[tokio::test]
-    async fn test_relationship_analysis_function() {
-        use tempfile::tempdir;
-        
-        let temp_dir = tempdir().unwrap();
-        let output_dir = temp_dir.path();
-        
-        // Test that the relationship analysis function can be called
-        let result = perform_relationship_analysis("testuser", output_dir, 1234567890).await;
-        assert!(result.is_ok());
-    }
-}
\ No newline at end of file
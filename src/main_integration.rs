@@ -1,9 +1,352 @@
-//! Main function integration for relationship analysis
+//! Main function integration for relationship analysis and the full
+//! library-callable processing pipeline
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::fs as async_fs;
+
+use crate::cli::spawn_progress_printer;
+use crate::models::direct_message::DmWrapper;
 use crate::models::profile::UserProfile;
 use crate::models::interaction::InteractionEvent;
+use crate::processing::data_structures::{Thread, TweetWrapper};
+use crate::processing::file_io::{retain_output_format, snapshot_paths, write_ghost_contacts_csv, OutputFormat};
+use crate::processing::{merge_dm_files, parse_dm_wrappers, parse_tweet_wrappers, process_all_dm_files, process_dm_file, process_tweets, CitationMode, SampleConfig, CsvWriteOptions, DmOptions, DmSortMode, FilterMode, MvpAnalyzer, TweetOptions};
+use crate::relationship::anonymization::{build_pseudonym_registry, hash_user_id, update_shared_registry, PseudonymRegistry, SharedPseudonymRegistry};
 use crate::relationship::file_generation::LLMFileGenerator;
+use crate::relationship::file_writer::FileWriter;
+use crate::utils::explain::ExplainLog;
+use crate::utils::sanitize_filename;
+use crate::relationship::{find_ghost_contacts, find_tweet_only_contacts, tweet_user_hashes, Locale, RelationshipAnalyzer};
+
+/// Inputs and feature switches for [`run_full_pipeline`]
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    /// Path to `tweets.js`
+    pub tweets_file: PathBuf,
+    /// DM files to process as one archive: `direct-messages.js` and any
+    /// `direct-messages*.js` part files (empty skips DM processing)
+    pub dms_files: Vec<PathBuf>,
+    /// Twitter handle used in output file names
+    pub screen_name: String,
+    /// Directory to write results to (created if missing)
+    pub output_dir: PathBuf,
+    /// Timestamp used in output file names
+    pub timestamp: i64,
+    /// Which tweets to process based on retweet status
+    pub filter_mode: FilterMode,
+    /// Citation labels for the cited threads output
+    pub citation_mode: CitationMode,
+    /// Regexes; tweets whose text matches any of them are dropped before threading
+    pub exclude_patterns: Vec<String>,
+    /// Text column options for the threads CSV
+    pub csv_options: CsvWriteOptions,
+    /// Timezone for tweet timestamps in the threads text and CSV outputs
+    pub output_locale: Tz,
+    /// Drop threads containing any tweet marked `possibly_sensitive`
+    pub exclude_sensitive: bool,
+    /// Dates to compare posting activity around (empty skips the event activity CSV)
+    pub event_dates: Vec<DateTime<Utc>>,
+    /// Existing thread summary CSV to append new threads to
+    pub append_to_existing: Option<PathBuf>,
+    /// Thread only a seeded random subset of the tweets (`None` processes all of them)
+    pub sample_mode: Option<SampleConfig>,
+    /// Also write archive-wide thread statistics as JSON
+    pub stats_json: bool,
+    /// Keep only the first N tweets of longer threads (`None` keeps them all)
+    pub max_thread_length: Option<usize>,
+    /// Which kinds of output files to keep
+    pub output_format: OutputFormat,
+    /// Process `dms_files` when there are any
+    pub process_dms: bool,
+    /// Leave DM conversations with fewer messages out of the DM results
+    pub min_dm_messages: usize,
+    /// Hide message text in the DM threads text file
+    pub privacy_mode: bool,
+    /// Also write an Anki flashcard deck of DM contacts
+    pub anki_deck: bool,
+    /// Order of the DM conversations CSV
+    pub dm_sort_mode: DmSortMode,
+    /// JSON file caching the DM interaction timeline between runs (`None` disables caching)
+    pub timeline_cache: Option<PathBuf>,
+    /// Delete `timeline_cache` first so the timeline is rebuilt
+    pub rebuild_cache: bool,
+    /// JSON file the pseudonym registry is kept in across runs (`None` uses
+    /// `pseudonym_registry_{screen_name}.json` next to `tweets_file`, since
+    /// `output_dir` usually changes every run)
+    pub pseudonym_registry: Option<PathBuf>,
+    /// Log why each tweet, conversation and contact was kept, dropped or
+    /// classified to `explain_{screen_name}_{timestamp}.log`
+    pub explain: bool,
+    /// Run relationship intelligence analysis after processing
+    pub relationship_analysis: bool,
+    /// Write per-contact relationship profiles for the DMs in this language
+    /// (`None` skips them)
+    pub profile_locale: Option<Locale>,
+    /// Print progress updates to stderr
+    pub show_progress: bool,
+}
+
+impl PipelineConfig {
+    /// Creates a config with DM processing on, relationship analysis off and default options
+    ///
+    /// `screen_name` is passed through [`sanitize_filename`] since it ends up in file names.
+    pub fn new(tweets_file: impl Into<PathBuf>, screen_name: impl Into<String>, output_dir: impl Into<PathBuf>, timestamp: i64) -> Self {
+        Self {
+            tweets_file: tweets_file.into(),
+            dms_files: Vec::new(),
+            screen_name: sanitize_filename(&screen_name.into()),
+            output_dir: output_dir.into(),
+            timestamp,
+            filter_mode: FilterMode::default(),
+            citation_mode: CitationMode::default(),
+            exclude_patterns: Vec::new(),
+            csv_options: CsvWriteOptions::default(),
+            output_locale: chrono_tz::UTC,
+            exclude_sensitive: false,
+            event_dates: Vec::new(),
+            append_to_existing: None,
+            sample_mode: None,
+            stats_json: false,
+            max_thread_length: None,
+            output_format: OutputFormat::default(),
+            process_dms: true,
+            min_dm_messages: 1,
+            privacy_mode: false,
+            anki_deck: false,
+            dm_sort_mode: DmSortMode::default(),
+            timeline_cache: None,
+            rebuild_cache: false,
+            pseudonym_registry: None,
+            explain: false,
+            relationship_analysis: false,
+            profile_locale: None,
+            show_progress: false,
+        }
+    }
+
+    /// Options for the tweet stage of the run
+    pub fn tweet_options(&self) -> TweetOptions {
+        TweetOptions {
+            filter_mode: self.filter_mode,
+            citation_mode: self.citation_mode,
+            exclude_patterns: self.exclude_patterns.clone(),
+            csv_options: self.csv_options,
+            output_locale: self.output_locale,
+            exclude_sensitive: self.exclude_sensitive,
+            event_dates: self.event_dates.clone(),
+            append_to: self.append_to_existing.clone(),
+            sample_mode: self.sample_mode,
+            stats_json: self.stats_json,
+            max_thread_length: self.max_thread_length,
+            ..TweetOptions::new(self.screen_name.clone(), self.output_dir.clone(), self.timestamp)
+        }
+    }
+
+    /// Options for the DM stage of the run
+    pub fn dm_options(&self) -> DmOptions {
+        DmOptions {
+            min_messages: self.min_dm_messages,
+            privacy_mode: self.privacy_mode,
+            anki_deck: self.anki_deck,
+            sort_mode: self.dm_sort_mode,
+            timeline_cache: self.timeline_cache.clone(),
+            ..DmOptions::new(self.screen_name.clone(), self.output_dir.clone(), self.timestamp)
+        }
+    }
+}
+
+/// Outcome of [`run_full_pipeline`]
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReport {
+    /// Directory the results were written to
+    pub output_dir: PathBuf,
+    /// Files present in the output directory after the run, sorted by path
+    pub output_files: Vec<PathBuf>,
+    /// Whether DMs were processed successfully
+    pub dms_processed: bool,
+    /// Whether relationship analysis ran successfully
+    pub relationship_analysis_run: bool,
+    /// Files removed because they did not match the output format
+    pub files_removed: usize,
+    /// Non-fatal failures from the optional DM and relationship stages
+    pub warnings: Vec<String>,
+    /// Wall-clock time of the whole run
+    pub elapsed: Duration,
+}
+
+/// Runs tweet processing, DM processing and relationship analysis as configured
+///
+/// Tweet processing errors are returned; failures in the optional DM and
+/// relationship stages are recorded in [`PipelineReport::warnings`] instead.
+/// Every stage shares one pseudonym registry, seeded up front by
+/// [`seed_pseudonym_registry`].
+pub async fn run_full_pipeline(config: PipelineConfig) -> Result<PipelineReport> {
+    let start = Instant::now();
+    let mut report = PipelineReport {
+        output_dir: config.output_dir.clone(),
+        ..Default::default()
+    };
+
+    // Anything already in the output folder is left alone by the output format filter
+    let preexisting = snapshot_paths(&config.output_dir)?;
+    async_fs::create_dir_all(&config.output_dir).await
+        .with_context(|| format!("Failed to create output directory: {}", config.output_dir.display()))?;
+
+    let tweets_file = config.tweets_file.to_str()
+        .with_context(|| format!("Tweets file path is not valid UTF-8: {}", config.tweets_file.display()))?;
+    let registry_path = config.pseudonym_registry.clone()
+        .unwrap_or_else(|| {
+            let archive_dir = config.tweets_file.parent().unwrap_or_else(|| Path::new("."));
+            PseudonymRegistry::path_for(archive_dir, &config.screen_name)
+        });
+    let pseudonyms = seed_pseudonym_registry(&config.tweets_file, &config.dms_files, &registry_path).await?;
+    let explain = if config.explain {
+        Some(ExplainLog::create(&config.output_dir, &config.screen_name, config.timestamp)?)
+    } else {
+        None
+    };
+    if config.show_progress {
+        let (progress, printer) = spawn_progress_printer();
+        let result = process_tweets(tweets_file, &config.tweet_options(), Some(&pseudonyms), explain.as_ref(), Some(&progress)).await;
+        drop(progress);
+        printer.await?;
+        result?;
+    } else {
+        process_tweets(tweets_file, &config.tweet_options(), Some(&pseudonyms), explain.as_ref(), None).await?;
+    }
+
+    if config.process_dms && !config.dms_files.is_empty() {
+        println!("📱 Initiating DM Processing Operation...");
+        if let Some(cache_path) = config.timeline_cache.as_ref().filter(|path| config.rebuild_cache && path.exists()) {
+            println!("🔄 Rebuilding timeline cache {}", cache_path.display());
+            async_fs::remove_file(cache_path).await
+                .with_context(|| format!("Failed to remove timeline cache: {}", cache_path.display()))?;
+        }
+        let result = match config.dms_files.as_slice() {
+            [dm_file] => process_dm_file(&dm_file.to_string_lossy(), &config.dm_options(), Some(&pseudonyms), explain.as_ref()).await,
+            dm_files => {
+                let paths: Vec<String> = dm_files.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+                process_all_dm_files(&paths, &config.dm_options(), Some(&pseudonyms), explain.as_ref()).await
+            }
+        };
+        match result {
+            Ok(()) => report.dms_processed = true,
+            Err(e) => report.warnings.push(format!("DM processing failed: {}", e)),
+        }
+
+        if let Some(locale) = config.profile_locale.filter(|_| report.dms_processed) {
+            if let Err(e) = write_localized_profiles(&config.dms_files, &config.screen_name, &config.output_dir, config.timestamp, locale).await {
+                report.warnings.push(format!("Writing relationship profiles failed: {}", e));
+            }
+        }
+    }
+
+    if let Some(log) = &explain {
+        log.flush()?;
+        println!("🔎 Decision log written to {}", log.path().display());
+    }
+
+    if config.relationship_analysis {
+        println!("\nInitiating Relationship Intelligence Analysis...");
+        match perform_relationship_analysis(&config.screen_name, &config.tweets_file, &config.dms_files,
+                                            &config.output_dir, config.timestamp, Some(&pseudonyms)).await {
+            Ok(()) => report.relationship_analysis_run = true,
+            Err(e) => report.warnings.push(format!("Relationship analysis failed: {}", e)),
+        }
+    }
+
+    report.files_removed = retain_output_format(&config.output_dir, config.output_format, &preexisting)?;
+
+    let mut output_files = Vec::new();
+    for entry in std::fs::read_dir(&config.output_dir)
+        .with_context(|| format!("Failed to read output directory: {}", config.output_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() {
+            output_files.push(path);
+        }
+    }
+    output_files.sort();
+    report.output_files = output_files;
+    report.elapsed = start.elapsed();
+
+    Ok(report)
+}
+
+/// Loads the pseudonym registry at `registry_path` and assigns pseudonyms to
+/// everyone the tweets reply to and everyone in the DMs, before any output is written
+///
+/// Tweet reply targets are registered by user ID, the same IDs DM conversations
+/// use, so each person gets one pseudonym across all output files. A missing
+/// or unparseable file is skipped here; the processing stages report it. The
+/// registry file is only written when new users were found.
+pub async fn seed_pseudonym_registry(tweets_file: &Path, dms_files: &[PathBuf], registry_path: &Path) -> Result<SharedPseudonymRegistry> {
+    let mut user_ids: Vec<String> = Vec::new();
+    if let Ok(content) = async_fs::read_to_string(tweets_file).await {
+        if let Ok(wrappers) = parse_tweet_wrappers(&content) {
+            user_ids.extend(wrappers.into_iter().filter_map(|wrapper| {
+                wrapper.tweet.in_reply_to_user_id_str.or(wrapper.tweet.in_reply_to_user_id)
+            }));
+        }
+    }
+    if !dms_files.is_empty() {
+        if let Ok(wrappers) = load_dm_wrappers(dms_files).await {
+            user_ids.extend(RelationshipAnalyzer::new().extract_users_from_dms(&wrappers));
+        }
+    }
+
+    let registry = SharedPseudonymRegistry::load(registry_path)?;
+    update_shared_registry(&registry, user_ids.iter().map(String::as_str))?;
+    Ok(registry)
+}
+
+/// Reads the DM files of a run as one list of conversations
+///
+/// A single file is parsed as is; several part files are combined with
+/// [`merge_dm_files`] so a conversation split across parts is analysed whole.
+async fn load_dm_wrappers(dms_files: &[PathBuf]) -> Result<Vec<DmWrapper>> {
+    match dms_files {
+        [dm_file] => {
+            let content = async_fs::read_to_string(dm_file).await
+                .with_context(|| format!("Failed to read DM file: {}", dm_file.display()))?;
+            parse_dm_wrappers(&content)
+        }
+        _ => {
+            let paths: Vec<String> = dms_files.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+            let path_refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+            merge_dm_files(&path_refs).await
+        }
+    }
+}
+
+/// Writes one profile per DM contact into `relationship_profiles_{screen_name}_{timestamp}/`
+///
+/// Profile headers and labels are written in `locale`; message content is not translated.
+pub async fn write_localized_profiles(
+    dms_files: &[PathBuf],
+    screen_name: &str,
+    output_dir: &Path,
+    timestamp: i64,
+    locale: Locale,
+) -> Result<()> {
+    let dm_wrappers = load_dm_wrappers(dms_files).await?;
+
+    let mut analyzer = RelationshipAnalyzer::new();
+    let timeline = analyzer.build_timeline(&dm_wrappers, &[]);
+    analyzer.build_profiles(&dm_wrappers, &[]);
+    let profiles = analyzer.profiles.into_values()
+        .map(|profile| (hash_user_id(&profile.user_id), profile))
+        .collect();
+
+    FileWriter::new(output_dir.to_string_lossy(), screen_name, timestamp)
+        .with_locale(locale)
+        .write_all_files(&profiles, &timeline)
+        .await
+}
 
 /// Main orchestration function for relationship analysis
 pub async fn main_analyze_relationships(
@@ -46,4 +389,266 @@ pub fn generate_relationship_analysis_prompt() -> String {
 pub fn should_run_relationship_analysis(input: &str) -> bool {
     let trimmed = input.trim().to_lowercase();
     matches!(trimmed.as_str(), "y" | "yes" | "1" | "true")
-}
\ No newline at end of file
+}
+
+/// Performs MVP relationship intelligence analysis
+/// 
+/// This function provides immediate value by analyzing:
+/// - Who you interact with most
+/// - When you're most active
+/// - Clean, readable insights
+///
+/// `tweets_file` and `dms_files` are read directly; missing files are skipped.
+/// Ghost contacts are named with pseudonyms from `pseudonyms`, the run's shared
+/// registry, or from the registry file in `output_dir` when it is `None`.
+pub async fn perform_relationship_analysis(
+    screen_name: &str, 
+    tweets_file: &Path,
+    dms_files: &[PathBuf],
+    output_dir: &Path, 
+    timestamp: i64,
+    pseudonyms: Option<&SharedPseudonymRegistry>,
+) -> Result<()> {
+    println!("🔬 Analyzing your Twitter relationships and activity patterns...");
+    
+    // Create MVP analyzer
+    let mut analyzer = MvpAnalyzer::new();
+    let mut tweets = None;
+    let mut dms = None;
+    
+    // Try to load and analyze tweet data
+    if async_fs::metadata(tweets_file).await.is_ok() {
+        println!("📊 Analyzing tweet interactions...");
+        
+        let tweets_content = async_fs::read_to_string(tweets_file).await
+            .context("Failed to read tweets file")?;
+        
+        // Parse tweets (remove JavaScript prefix)
+        if let Some(json_start) = tweets_content.find('[') {
+            if let Some(json_end) = tweets_content.rfind(']') {
+                let json_content = &tweets_content[json_start..=json_end];
+                
+                match serde_json::from_str::<Vec<TweetWrapper>>(json_content) {
+                    Ok(tweet_wrappers) => {
+                        // Convert to threads for analysis
+                        let threads: Vec<Thread> = tweet_wrappers.into_iter().map(|tw| Thread {
+                            id: tw.tweet.id_str.clone(),
+                            tweets: vec![tw.tweet.clone()],
+                            tweet_count: 1,
+                            favorite_count: 0,
+                            retweet_count: 0,
+                            url: String::new(),
+                            contains_poll: false,
+                            first_tweet_url_entities: tw.tweet.entities.urls.clone(),
+                            engagement_dropoff_rate: 0.0,
+                        }).collect();
+                        
+                        analyzer.analyze_tweets(&threads)?;
+                        println!("✅ Tweet analysis complete - found {} relationships", 
+                            analyzer.relationships.len());
+                        tweets = Some(threads.into_iter().flat_map(|thread| thread.tweets).collect::<Vec<_>>());
+                    }
+                    Err(e) => {
+                        println!("⚠️ Could not parse tweets file: {}", e);
+                    }
+                }
+            }
+        }
+    }
+    
+    // Try to load and analyze DM data
+    let dms_files: Vec<PathBuf> = dms_files.iter().filter(|path| path.exists()).cloned().collect();
+    if !dms_files.is_empty() {
+        println!("💬 Analyzing direct message conversations...");
+        
+        match load_dm_wrappers(&dms_files).await {
+            Ok(dm_wrappers) => {
+                analyzer.analyze_dms(&dm_wrappers)?;
+                println!("✅ DM analysis complete");
+                dms = Some(dm_wrappers);
+            }
+            Err(e) => {
+                println!("⚠️ Could not parse DM file: {}", e);
+            }
+        }
+    }
+    
+    if let (Some(tweets), Some(dms)) = (&tweets, &dms) {
+        let mut relationships = RelationshipAnalyzer::new();
+        relationships.build_profiles(dms, tweets);
+        let tweet_users = tweet_user_hashes(tweets);
+        let ghosts = find_ghost_contacts(&relationships, &tweet_users);
+        analyzer.ghost_contacts = ghosts.len();
+        analyzer.tweet_only_contacts = find_tweet_only_contacts(&relationships, &tweet_users).len();
+        let dm_users = relationships.extract_users_from_dms(dms);
+        let dm_users = dm_users.iter().map(String::as_str);
+        let registry = match pseudonyms {
+            Some(shared) => update_shared_registry(shared, dm_users)?,
+            None => build_pseudonym_registry(output_dir, screen_name, dm_users)?,
+        };
+        write_ghost_contacts_csv(&ghosts, &registry, screen_name, timestamp, output_dir).await?;
+        println!("👻 {} DM-only contacts, {} tweet-only contacts", analyzer.ghost_contacts, analyzer.tweet_only_contacts);
+    }
+    
+    // Generate the intelligence report
+    println!("📈 Generating relationship intelligence report...");
+    analyzer.generate_report(output_dir, screen_name, timestamp).await?;
+    
+    // Show quick preview of insights
+    let top_relationships = analyzer.get_top_relationships(3);
+    if !top_relationships.is_empty() {
+        println!("\n🎯 QUICK INSIGHTS:");
+        println!("Your top connections:");
+        for (i, rel) in top_relationships.iter().enumerate() {
+            println!("  {}. @{} ({} interactions)", i + 1, rel.username, rel.interaction_count);
+        }
+    }
+    
+    let peak_hours = analyzer.get_peak_activity_hours(2);
+    if !peak_hours.is_empty() {
+        println!("Most active times:");
+        for (hour, count) in peak_hours {
+            let time_str = if hour == 0 {
+                "12:00 AM".to_string()
+            } else if hour < 12 {
+                format!("{}:00 AM", hour)
+            } else if hour == 12 {
+                "12:00 PM".to_string()
+            } else {
+                format!("{}:00 PM", hour - 12)
+            };
+            println!("  {} ({} activities)", time_str, count);
+        }
+    }
+    
+    println!("\n📄 Full report saved to output directory!");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_relationship_analysis_function() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+        
+        // Missing inputs are skipped rather than treated as errors
+        let tweets_file = output_dir.join("tweets.js");
+        let result = perform_relationship_analysis("testuser", &tweets_file, &[], output_dir, 1234567890, None).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_full_pipeline() {
+        let temp_dir = tempdir().unwrap();
+        let tweets_file = temp_dir.path().join("tweets.js");
+        std::fs::write(&tweets_file, r#"window.YTD.tweets.part0 = [{"tweet": {
+            "id_str": "1", "id": "1", "full_text": "Hello world",
+            "created_at": "Mon Jan 02 12:00:00 +0000 2023",
+            "favorite_count": "3", "retweet_count": "1",
+            "retweeted": false, "favorited": false, "truncated": false,
+            "lang": "en", "source": "web", "display_text_range": ["0", "11"],
+            "in_reply_to_status_id": null, "in_reply_to_status_id_str": null,
+            "in_reply_to_user_id": "222", "in_reply_to_user_id_str": "222",
+            "in_reply_to_screen_name": null,
+            "entities": {"hashtags": [], "symbols": [], "user_mentions": [], "urls": []}
+        }}]"#).unwrap();
+
+        let output_dir = temp_dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        std::fs::write(output_dir.join("notes.txt"), "keep me").unwrap();
+        let mut config = PipelineConfig::new(&tweets_file, "tester", &output_dir, 0);
+        config.output_format = OutputFormat::CsvOnly;
+        config.dms_files = vec![temp_dir.path().join("missing-direct-messages.js")];
+
+        let report = run_full_pipeline(config).await.unwrap();
+
+        assert_eq!(report.output_dir, output_dir);
+        assert!(!report.output_files.is_empty());
+        assert!(report.output_files.iter().all(|p| p.extension().is_some_and(|e| e == "csv") || p.ends_with("notes.txt")));
+        assert!(output_dir.join("notes.txt").exists());
+        // Tweet outputs carry the run's timestamp like every other output
+        assert!(output_dir.join("threads_tester_0.csv").exists());
+        assert!(report.files_removed > 0);
+        assert!(!report.dms_processed);
+        assert_eq!(report.warnings.len(), 1);
+        // The pseudonym registry outlives the per-run output folder
+        assert!(temp_dir.path().join("pseudonym_registry_tester.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_write_localized_profiles() {
+        let temp_dir = tempdir().unwrap();
+        let dm_file = temp_dir.path().join("direct-messages.js");
+        std::fs::write(&dm_file, r#"window.YTD.direct_messages.part0 = [{"dmConversation": {
+            "conversationId": "111-222",
+            "messages": [{"messageCreate": {"id": "1", "text": "Bonjour",
+                "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "111", "recipientId": "222"}}]
+        }}]"#).unwrap();
+
+        crate::relationship::anonymization::set_test_mode(true);
+        write_localized_profiles(&[dm_file], "tester", temp_dir.path(), 7, Locale::FrFr).await.unwrap();
+
+        let profiles_dir = temp_dir.path().join("relationship_profiles_tester_7");
+        let profile_file = profiles_dir.join("user_222_profile.txt");
+        let profile_text = std::fs::read_to_string(profile_file).unwrap();
+        assert!(profile_text.contains("Nombre total d'interactions: 1"));
+    }
+
+    #[tokio::test]
+    async fn test_relationship_analysis_writes_ghost_contacts() {
+        let temp_dir = tempdir().unwrap();
+        let tweets_file = temp_dir.path().join("tweets.js");
+        std::fs::write(&tweets_file, r#"window.YTD.tweets.part0 = [{"tweet": {
+            "id_str": "1", "id": "1", "full_text": "@bob hi",
+            "created_at": "Mon Jan 02 12:00:00 +0000 2023",
+            "favorite_count": "0", "retweet_count": "0",
+            "retweeted": false, "favorited": false, "truncated": false,
+            "lang": "en", "source": "web", "display_text_range": ["0", "7"],
+            "in_reply_to_status_id": null, "in_reply_to_status_id_str": null,
+            "in_reply_to_user_id": "222", "in_reply_to_user_id_str": "222",
+            "in_reply_to_screen_name": "bob",
+            "entities": {"hashtags": [], "symbols": [], "urls": [], "user_mentions": [
+                {"name": "Dave", "screen_name": "dave", "indices": ["0", "5"], "id_str": "444", "id": "444"}
+            ]}
+        }}]"#).unwrap();
+        let dm_file = temp_dir.path().join("direct-messages.js");
+        std::fs::write(&dm_file, r#"window.YTD.direct_messages.part0 = [{"dmConversation": {
+            "conversationId": "111-222",
+            "messages": [{"messageCreate": {"id": "1", "text": "Hey",
+                "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "111", "recipientId": "222"}}]
+        }}, {"dmConversation": {
+            "conversationId": "111-333",
+            "messages": [{"messageCreate": {"id": "2", "text": "Psst",
+                "createdAt": "2023-01-01T11:00:00.000Z", "senderId": "333", "recipientId": "111"}}]
+        }}]"#).unwrap();
+
+        crate::relationship::anonymization::set_test_mode(true);
+        perform_relationship_analysis("tester", &tweets_file, std::slice::from_ref(&dm_file), temp_dir.path(), 9, None).await.unwrap();
+
+        let ghosts = std::fs::read_to_string(temp_dir.path().join("ghost_contacts_tester_9.csv")).unwrap();
+        assert!(ghosts.lines().any(|line| line.starts_with("333,")));
+        assert!(ghosts.lines().any(|line| line.starts_with("111,")));
+        assert!(!ghosts.lines().any(|line| line.starts_with("222,")));
+        let report = std::fs::read_to_string(temp_dir.path().join("relationship_intelligence_tester_9.txt")).unwrap();
+        assert!(report.contains("Ghost contacts (DMs only): 2\nTweet-only contacts: 1\n"));
+
+        let seeded_dir = temp_dir.path().join("seeded");
+        std::fs::create_dir(&seeded_dir).unwrap();
+        let pseudonyms = seed_pseudonym_registry(&tweets_file, std::slice::from_ref(&dm_file), &PseudonymRegistry::path_for(&seeded_dir, "tester")).await.unwrap();
+        let seeded = pseudonyms.snapshot().unwrap();
+        assert_eq!(seeded.len(), 3);
+        let ghost_pseudonym = seeded.get("333").unwrap().to_string();
+
+        perform_relationship_analysis("tester", &tweets_file, std::slice::from_ref(&dm_file), &seeded_dir, 9, Some(&pseudonyms)).await.unwrap();
+
+        let ghosts = std::fs::read_to_string(seeded_dir.join("ghost_contacts_tester_9.csv")).unwrap();
+        assert!(ghosts.lines().next().unwrap().ends_with(",Pseudonym"));
+        assert!(ghosts.contains("333,1,"));
+        assert!(ghosts.lines().any(|line| line.starts_with("333,") && line.ends_with(&ghost_pseudonym)));
+        assert_eq!(pseudonyms.snapshot().unwrap(), seeded);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::utils::parse_twitter_date;
 use serde::Deserialize;
 
+use super::dm_headers::{DmParticipantEvent, DmParticipantEventType};
+
 /// Represents a direct message in a conversation
 #[derive(Debug, Clone, Deserialize)]
 pub struct DmMessage {
@@ -87,10 +90,163 @@ pub struct DmWrapper {
 
 /// Represents a DM conversation
 #[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawDmConversation")]
 pub struct DmConversation {
     /// The conversation ID (format: "user1-user2")
-    #[serde(rename = "conversationId")]
     pub conversation_id: String,
     /// The messages in the conversation
     pub messages: Vec<DmMessage>,
+    /// Name of a group conversation, from its latest `conversationNameUpdate`
+    ///
+    /// `None` for two-party conversations and unnamed groups.
+    pub group_name: Option<String>,
+    /// Users added to the group, oldest first
+    pub join_events: Vec<DmParticipantEvent>,
+    /// Users who left the group, oldest first
+    pub leave_events: Vec<DmParticipantEvent>,
+}
+
+/// A group rename event in the conversation's `messages` array
+#[derive(Deserialize)]
+struct RawConversationNameUpdate {
+    name: Option<String>,
+    #[serde(rename = "createdAt", default)]
+    created_at: Option<String>,
+}
+
+/// A `participantsJoin` or `participantsLeave` entry in the `messages` array
+#[derive(Deserialize)]
+struct RawParticipantsChange {
+    #[serde(rename = "userIds", default)]
+    user_ids: Vec<String>,
+    #[serde(rename = "createdAt", default)]
+    created_at: Option<String>,
+}
+
+impl RawParticipantsChange {
+    /// Entries without a valid timestamp are dropped
+    fn into_event(self, event_type: DmParticipantEventType) -> Option<DmParticipantEvent> {
+        let created_at = parse_twitter_date(self.created_at.as_deref()?).ok()?;
+        Some(DmParticipantEvent { event_type, user_ids: self.user_ids, created_at })
+    }
+}
+
+/// An entry in the wire-format `messages` array
+#[derive(Deserialize)]
+struct RawDmEvent {
+    #[serde(rename = "messageCreate")]
+    message_create: Option<DmMessageCreate>,
+    #[serde(rename = "conversationNameUpdate")]
+    conversation_name_update: Option<RawConversationNameUpdate>,
+    #[serde(rename = "participantsJoin")]
+    participants_join: Option<RawParticipantsChange>,
+    #[serde(rename = "participantsLeave")]
+    participants_leave: Option<RawParticipantsChange>,
+}
+
+/// Wire format of a DM conversation
+#[derive(Deserialize)]
+struct RawDmConversation {
+    #[serde(rename = "conversationId")]
+    conversation_id: String,
+    messages: Vec<RawDmEvent>,
+}
+
+impl From<RawDmConversation> for DmConversation {
+    fn from(raw: RawDmConversation) -> Self {
+        let mut name_updates = Vec::new();
+        let mut join_events = Vec::new();
+        let mut leave_events = Vec::new();
+        let mut messages = Vec::with_capacity(raw.messages.len());
+        for event in raw.messages {
+            if let Some(update) = event.conversation_name_update {
+                name_updates.push(update);
+            }
+            join_events.extend(event.participants_join.and_then(|change| change.into_event(DmParticipantEventType::Join)));
+            leave_events.extend(event.participants_leave.and_then(|change| change.into_event(DmParticipantEventType::Leave)));
+            messages.push(DmMessage { message_create: event.message_create });
+        }
+
+        // ISO 8601 timestamps sort lexically; ties keep the later entry
+        let group_name = name_updates.into_iter()
+            .filter(|update| update.name.as_deref().is_some_and(|n| !n.trim().is_empty()))
+            .max_by(|a, b| a.created_at.cmp(&b.created_at))
+            .and_then(|update| update.name);
+        join_events.sort_by_key(|event: &DmParticipantEvent| event.created_at);
+        leave_events.sort_by_key(|event: &DmParticipantEvent| event.created_at);
+
+        Self {
+            conversation_id: raw.conversation_id,
+            messages,
+            group_name,
+            join_events,
+            leave_events,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_name_from_latest_name_update() {
+        let json = r#"{
+            "dmConversation": {
+                "conversationId": "123-456",
+                "messages": [
+                    { "conversationNameUpdate": { "initiatingUserId": "123", "name": "Team Rust", "createdAt": "2023-01-03T10:00:00.000Z" } },
+                    { "messageCreate": { "id": "1", "text": "hi", "createdAt": "2023-01-02T10:00:00.000Z", "senderId": "123", "recipientId": "456" } },
+                    { "conversationNameUpdate": { "initiatingUserId": "456", "name": "Rustaceans", "createdAt": "2023-01-01T10:00:00.000Z" } }
+                ]
+            }
+        }"#;
+
+        let wrapper: DmWrapper = serde_json::from_str(json).unwrap();
+        let conversation = wrapper.dm_conversation;
+
+        assert_eq!(conversation.group_name.as_deref(), Some("Team Rust"));
+        assert_eq!(conversation.messages.len(), 3);
+        assert_eq!(conversation.messages.iter().filter(|m| m.message_create.is_some()).count(), 1);
+    }
+
+    #[test]
+    fn test_two_party_conversation_has_no_group_name() {
+        let json = r#"{
+            "conversationId": "123-456",
+            "messages": [
+                { "messageCreate": { "id": "1", "text": "hi", "createdAt": "2023-01-02T10:00:00.000Z", "senderId": "123", "recipientId": "456" } }
+            ]
+        }"#;
+
+        let conversation: DmConversation = serde_json::from_str(json).unwrap();
+
+        assert_eq!(conversation.group_name, None);
+        assert_eq!(conversation.conversation_id, "123-456");
+        assert!(conversation.join_events.is_empty() && conversation.leave_events.is_empty());
+    }
+
+    #[test]
+    fn test_participant_join_and_leave_events() {
+        let json = r#"{
+            "conversationId": "group-42",
+            "messages": [
+                { "participantsLeave": { "userIds": ["333"], "createdAt": "2023-01-05T10:00:00.000Z" } },
+                { "messageCreate": { "id": "1", "text": "hi", "createdAt": "2023-01-03T10:00:00.000Z", "senderId": "111", "recipientId": "group-42" } },
+                { "participantsJoin": { "initiatingUserId": "111", "userIds": ["333", "444"], "createdAt": "2023-01-02T10:00:00.000Z" } },
+                { "participantsJoin": { "initiatingUserId": "111", "userIds": ["555"], "createdAt": "2023-01-01T10:00:00.000Z" } }
+            ]
+        }"#;
+
+        let conversation: DmConversation = serde_json::from_str(json).unwrap();
+
+        assert_eq!(conversation.join_events.len(), 2);
+        assert_eq!(conversation.join_events[0].user_ids, vec!["555"]);
+        assert_eq!(conversation.join_events[1].user_ids, vec!["333", "444"]);
+        assert_eq!(conversation.join_events[1].event_type, DmParticipantEventType::Join);
+        assert_eq!(conversation.leave_events.len(), 1);
+        assert_eq!(conversation.leave_events[0].event_type, DmParticipantEventType::Leave);
+        assert_eq!(conversation.leave_events[0].created_at.to_rfc3339(), "2023-01-05T10:00:00+00:00");
+        assert_eq!(conversation.messages.len(), 4);
+    }
 }
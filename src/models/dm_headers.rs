@@ -1,5 +1,11 @@
+//! Data structures for `direct-message-headers.js` (metadata-only DM archive)
+
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
+use crate::processing::data_structures::ProcessedConversation;
+use crate::utils::parse_twitter_date;
+
 /// Represents a direct message header (metadata only)
 #[derive(Debug, Deserialize, Clone)]
 pub struct DmHeaderMessage {
@@ -24,6 +30,29 @@ pub struct DmHeaderMessageCreate {
     pub recipient_id: String,
 }
 
+/// Whether participants joined or left a group conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmParticipantEventType {
+    /// A `participantsJoin` entry
+    Join,
+    /// A `participantsLeave` entry
+    Leave,
+}
+
+/// Participants joining or leaving a group conversation
+///
+/// Read from `participantsJoin` / `participantsLeave` entries in a
+/// conversation's `messages` array (see [`DmConversation`](super::direct_message::DmConversation)).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DmParticipantEvent {
+    /// Whether the users joined or left
+    pub event_type: DmParticipantEventType,
+    /// IDs of the users who joined or left
+    pub user_ids: Vec<String>,
+    /// When it happened
+    pub created_at: DateTime<Utc>,
+}
+
 /// Represents a DM conversation wrapper from the Twitter archive (headers only)
 #[derive(Debug, Deserialize, Clone)]
 pub struct DmHeaderWrapper {
@@ -32,12 +61,155 @@ pub struct DmHeaderWrapper {
     pub dm_conversation: DmHeaderConversation,
 }
 
+/// A participant entry in a headers-file conversation
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct DmHeaderParticipant {
+    /// The participant's user ID
+    #[serde(rename = "participantId")]
+    pub participant_id: String,
+    /// When the participant joined the conversation
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
 /// Represents a DM conversation (headers only)
 #[derive(Debug, Deserialize, Clone)]
+#[serde(from = "RawDmHeaderConversation")]
 pub struct DmHeaderConversation {
     /// The conversation ID (format: "user1-user2")
-    #[serde(rename = "conversationId")]
     pub conversation_id: String,
+    /// IDs of everyone in the conversation
+    ///
+    /// Taken from the `participants` array when present, otherwise from the
+    /// two IDs in a one-to-one `conversation_id`.
+    pub participant_ids: Vec<String>,
+    /// Participant metadata including join timestamps (empty if absent in the archive)
+    pub participants: Vec<DmHeaderParticipant>,
+    /// When the conversation was created (or earliest known join/message time)
+    pub created_at: Option<DateTime<Utc>>,
     /// The message headers in the conversation
     pub messages: Vec<DmHeaderMessage>,
-}
\ No newline at end of file
+}
+
+/// Wire format of a headers-file conversation
+#[derive(Deserialize)]
+struct RawDmHeaderConversation {
+    #[serde(rename = "conversationId")]
+    conversation_id: String,
+    #[serde(default)]
+    participants: Vec<DmHeaderParticipant>,
+    #[serde(rename = "createdAt", default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    messages: Vec<DmHeaderMessage>,
+}
+
+impl From<RawDmHeaderConversation> for DmHeaderConversation {
+    fn from(raw: RawDmHeaderConversation) -> Self {
+        let participant_ids = if raw.participants.is_empty() {
+            raw.conversation_id
+                .split('-')
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string())
+                .collect()
+        } else {
+            let mut ids: Vec<String> = Vec::new();
+            for participant in &raw.participants {
+                if !ids.contains(&participant.participant_id) {
+                    ids.push(participant.participant_id.clone());
+                }
+            }
+            ids
+        };
+
+        let created_at = raw.created_at
+            .or_else(|| raw.participants.iter().filter_map(|p| p.created_at).min())
+            .or_else(|| {
+                raw.messages.iter()
+                    .filter_map(|m| parse_twitter_date(&m.message_create.created_at).ok())
+                    .min()
+            });
+
+        Self {
+            conversation_id: raw.conversation_id,
+            participant_ids,
+            participants: raw.participants,
+            created_at,
+            messages: raw.messages,
+        }
+    }
+}
+
+impl From<DmHeaderConversation> for ProcessedConversation {
+    fn from(conversation: DmHeaderConversation) -> Self {
+        let first_message_date = conversation.messages.iter()
+            .map(|m| &m.message_create.created_at)
+            .min()
+            .cloned();
+        let last_message_date = conversation.messages.iter()
+            .map(|m| &m.message_create.created_at)
+            .max()
+            .cloned();
+
+        ProcessedConversation {
+            conversation_id: conversation.conversation_id,
+            message_count: conversation.messages.len() as u32,
+            participants: conversation.participant_ids,
+            first_message_date,
+            last_message_date,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_header_conversation_with_participants() {
+        let json = r#"{
+            "dmConversation": {
+                "conversationId": "group-42",
+                "createdAt": "2023-01-01T09:00:00.000Z",
+                "participants": [
+                    { "participantId": "111", "createdAt": "2023-01-01T09:00:00.000Z" },
+                    { "participantId": "222", "createdAt": "2023-01-02T09:00:00.000Z" },
+                    { "participantId": "333" }
+                ],
+                "messages": [
+                    { "messageCreate": { "id": "2", "createdAt": "2023-01-03T10:00:00.000Z", "senderId": "222", "recipientId": "111" } },
+                    { "messageCreate": { "id": "1", "createdAt": "2023-01-02T10:00:00.000Z", "senderId": "111", "recipientId": "222" } }
+                ]
+            }
+        }"#;
+
+        let wrapper: DmHeaderWrapper = serde_json::from_str(json).unwrap();
+        let conversation = wrapper.dm_conversation;
+
+        assert_eq!(conversation.participant_ids, vec!["111", "222", "333"]);
+        assert_eq!(conversation.participants[2].created_at, None);
+        assert_eq!(conversation.created_at.unwrap().to_rfc3339(), "2023-01-01T09:00:00+00:00");
+
+        let processed = ProcessedConversation::from(conversation);
+        assert_eq!(processed.message_count, 2);
+        assert_eq!(processed.participants.len(), 3);
+        assert_eq!(processed.first_message_date.as_deref(), Some("2023-01-02T10:00:00.000Z"));
+        assert_eq!(processed.last_message_date.as_deref(), Some("2023-01-03T10:00:00.000Z"));
+    }
+
+    #[test]
+    fn test_deserialize_header_conversation_without_participants() {
+        let json = r#"{
+            "conversationId": "111-222",
+            "messages": [
+                { "messageCreate": { "id": "1", "createdAt": "2023-01-02T10:00:00.000Z", "senderId": "111", "recipientId": "222" } }
+            ]
+        }"#;
+
+        let conversation: DmHeaderConversation = serde_json::from_str(json).unwrap();
+
+        assert_eq!(conversation.participant_ids, vec!["111", "222"]);
+        assert!(conversation.participants.is_empty());
+        assert_eq!(conversation.created_at.unwrap().to_rfc3339(), "2023-01-02T10:00:00+00:00");
+    }
+}
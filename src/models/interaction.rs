@@ -1,11 +1,18 @@
 //! Interaction models for tweet and DM events
 
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::utils::parse_twitter_date;
+use crate::utils::sentiment::score_text;
 
 /// Represents different types of interactions in the system
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum InteractionType {
     /// Direct message sent by the user
     DmSent,
@@ -27,10 +34,36 @@ pub enum InteractionType {
     Retweet,
     /// Quote tweet of another tweet
     Quote,
+    /// Quote tweet by the user of another user's tweet
+    TweetQuote,
+    /// Tweet liked by the user (from `like.js`)
+    TweetLiked,
+    /// Reaction added to a direct message
+    DmReacted {
+        /// Emoji for the reaction (falls back to the archive's reaction key)
+        reaction_emoji: String,
+    },
     /// Other type of interaction
     Other,
 }
 
+impl InteractionType {
+    /// Maps an archive `reactionKey` (e.g. "like", "funny") to its emoji
+    pub fn reaction_emoji(reaction_key: &str) -> String {
+        match reaction_key {
+            "like" => "❤️",
+            "funny" => "😂",
+            "surprised" => "😲",
+            "sad" => "😢",
+            "excited" => "🔥",
+            "agree" => "👍",
+            "disagree" => "👎",
+            other => other,
+        }
+        .to_string()
+    }
+}
+
 impl fmt::Display for InteractionType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -44,6 +77,9 @@ impl fmt::Display for InteractionType {
             Self::Like => write!(f, "Like"),
             Self::Retweet => write!(f, "Retweet"),
             Self::Quote => write!(f, "Quote"),
+            Self::TweetQuote => write!(f, "Tweet Quote"),
+            Self::TweetLiked => write!(f, "Tweet Liked"),
+            Self::DmReacted { reaction_emoji } => write!(f, "DM Reacted {}", reaction_emoji),
             Self::Other => write!(f, "Other"),
         }
     }
@@ -64,6 +100,20 @@ pub struct InteractionEvent {
     pub content: String,
     /// Additional metadata as key-value pairs
     pub metadata: std::collections::HashMap<String, String>,
+    /// Sentiment of the content: 0.0 is neutral, negative is negative, positive is positive
+    #[serde(default)]
+    pub sentiment_score: f32,
+    /// Time since the chronologically previous event in the timeline
+    /// (`None` for the earliest event or outside a built timeline)
+    #[serde(default)]
+    pub duration_since_prev: Option<std::time::Duration>,
+    /// Most distinctive words of the content, set by
+    /// [`enrich_timeline_with_topics`](crate::relationship::topics::enrich_timeline_with_topics)
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Number of whitespace-separated words in the content
+    #[serde(default)]
+    pub word_count: usize,
 }
 
 impl InteractionEvent {
@@ -75,13 +125,18 @@ impl InteractionEvent {
         user_id: impl Into<String>,
         content: impl Into<String>,
     ) -> Self {
+        let content = content.into();
         Self {
             id: id.into(),
             timestamp,
             interaction_type,
             user_id: user_id.into(),
-            content: content.into(),
+            sentiment_score: score_text(&content),
+            word_count: content.split_whitespace().count(),
+            content,
             metadata: std::collections::HashMap::new(),
+            duration_since_prev: None,
+            topics: Vec::new(),
         }
     }
 
@@ -93,7 +148,6 @@ impl InteractionEvent {
     
     /// Creates an InteractionEvent from a DM message
     pub fn from_dm_message(message: &crate::models::direct_message::DmMessage, conversation_id: &str) -> Option<Self> {
-        use chrono::DateTime;
         use std::collections::HashMap;
         
         let message_create = message.message_create.as_ref()?;
@@ -101,10 +155,7 @@ impl InteractionEvent {
         let created_at = message_create.created_at.as_ref()?;
         
         // Parse the timestamp
-        let timestamp = match DateTime::parse_from_rfc3339(created_at) {
-            Ok(dt) => dt.with_timezone(&chrono::Utc),
-            Err(_) => return None,
-        };
+        let timestamp = parse_twitter_date(created_at).ok()?;
         
         // Extract participants from conversation ID (format: "user1-user2")
         let participants: Vec<String> = conversation_id
@@ -123,14 +174,105 @@ impl InteractionEvent {
         if let Some(text) = &message_create.text {
             metadata.insert("text_length".to_string(), text.len().to_string());
         }
+        if !message_create.media_urls.is_empty() {
+            metadata.insert("media_count".to_string(), message_create.media_urls.len().to_string());
+        }
         
+        let content = message_create.text.as_deref().unwrap_or("").to_string();
         Some(Self {
             id: id.clone(),
             timestamp,
             interaction_type: InteractionType::DmSent, // Default to sent, adjust if needed
-            user_id: participants[0].clone(), // Assuming first participant is the sender
-            content: message_create.text.as_deref().unwrap_or("").to_string(),
+            // Older exports can lack senderId; fall back to the first participant
+            user_id: message_create.sender_id.clone().unwrap_or_else(|| participants[0].clone()),
+            sentiment_score: score_text(&content),
+            word_count: content.split_whitespace().count(),
+            content,
+            metadata,
+            duration_since_prev: None,
+            topics: Vec::new(),
+        })
+    }
+
+    /// Creates one `DmReacted` event per reaction on a DM message
+    ///
+    /// These sit next to the message's own event from [`Self::from_dm_message`],
+    /// so reacted-to messages still count as sent DMs. Each event is attributed
+    /// to the reacting user and timed at the reaction (falling back to the
+    /// message time when the reaction has no parseable `created_at`).
+    pub fn reactions_from_dm_message(message: &crate::models::direct_message::DmMessage, conversation_id: &str) -> Vec<Self> {
+        let Some(message_event) = Self::from_dm_message(message, conversation_id) else {
+            return Vec::new();
+        };
+        let Some(message_create) = message.message_create.as_ref() else {
+            return Vec::new();
+        };
+
+        message_create.reactions.iter().enumerate()
+            .filter_map(|(index, reaction)| {
+                let key = reaction.reaction_key.as_deref()?;
+                let timestamp = reaction.created_at.as_deref()
+                    .and_then(|created_at| parse_twitter_date(created_at).ok())
+                    .unwrap_or(message_event.timestamp);
+                let id = reaction.event_id.clone()
+                    .unwrap_or_else(|| format!("{}-reaction-{}", message_event.id, index));
+                let mut metadata = std::collections::HashMap::new();
+                metadata.insert("message_id".to_string(), message_event.id.clone());
+                metadata.insert("conversation_id".to_string(), conversation_id.to_string());
+                metadata.insert("reaction_key".to_string(), key.to_string());
+                Some(Self {
+                    id,
+                    timestamp,
+                    interaction_type: InteractionType::DmReacted { reaction_emoji: InteractionType::reaction_emoji(key) },
+                    user_id: reaction.sender_id.clone().unwrap_or_else(|| message_event.user_id.clone()),
+                    content: String::new(),
+                    sentiment_score: 0.0,
+                    word_count: 0,
+                    metadata,
+                    duration_since_prev: None,
+                    topics: Vec::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Creates an InteractionEvent from a reply or quote tweet
+    ///
+    /// Quote-tweets (with `quoted_status_id`) become `TweetQuote`, other replies
+    /// `TweetReply`. Returns `None` for standalone tweets (no other user
+    /// involved) and for tweets whose `created_at` cannot be parsed.
+    pub fn from_tweet(tweet: &crate::processing::data_structures::Tweet) -> Option<Self> {
+        use std::collections::HashMap;
+
+        let user_id = tweet.in_reply_to_user_id_str.as_ref()
+            .or(tweet.in_reply_to_user_id.as_ref())?;
+        let timestamp = parse_twitter_date(&tweet.created_at).ok()?;
+
+        let mut metadata = HashMap::new();
+        metadata.insert("tweet_id".to_string(), tweet.id_str.clone());
+        if let Some(screen_name) = &tweet.in_reply_to_screen_name {
+            metadata.insert("screen_name".to_string(), screen_name.clone());
+        }
+
+        let interaction_type = match &tweet.quoted_status_id {
+            Some(quoted_id) => {
+                metadata.insert("quoted_tweet_id".to_string(), quoted_id.clone());
+                InteractionType::TweetQuote
+            }
+            None => InteractionType::TweetReply,
+        };
+
+        Some(Self {
+            id: tweet.id_str.clone(),
+            timestamp,
+            interaction_type,
+            user_id: user_id.clone(),
+            content: tweet.full_text.clone(),
             metadata,
+            sentiment_score: score_text(&tweet.full_text),
+            duration_since_prev: None,
+            topics: Vec::new(),
+            word_count: tweet.full_text.split_whitespace().count(),
         })
     }
 }
@@ -179,3 +321,177 @@ impl ConversationThread {
         self.events.push(event);
     }
 }
+
+/// Reads a timeline previously written by [`save_events_to_cache`]
+pub fn load_events_from_cache(path: &Path) -> Result<Vec<InteractionEvent>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open timeline cache: {}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse timeline cache: {}", path.display()))
+}
+
+/// Writes a timeline as JSON so later runs can skip rebuilding it
+pub fn save_events_to_cache(path: &Path, events: &[InteractionEvent]) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create timeline cache: {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, events)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Identifies the inputs a cached timeline was built from
+///
+/// Combines the crate version with each source file's path, size and
+/// modification time, so replacing or editing an input (or upgrading) gives a
+/// different key and the cache is rebuilt.
+pub fn timeline_cache_key<P: AsRef<Path>>(sources: &[P]) -> Result<String> {
+    let mut key = format!("tweet-scrolls {}", env!("CARGO_PKG_VERSION"));
+    for source in sources {
+        let path = source.as_ref();
+        let metadata = std::fs::metadata(path)
+            .with_context(|| format!("Failed to read timeline source: {}", path.display()))?;
+        let modified = metadata.modified().ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |since_epoch| since_epoch.as_nanos());
+        key.push_str(&format!("\n{} {} {}", path.display(), metadata.len(), modified));
+    }
+    Ok(key)
+}
+
+/// File next to a timeline cache holding the [`timeline_cache_key`] it was built for
+pub fn timeline_cache_key_path(cache_path: &Path) -> PathBuf {
+    cache_path.with_extension("key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::direct_message::DmMessage;
+    use crate::processing::data_structures::{Tweet, TweetEntities};
+
+    fn reply_tweet(quoted_status_id: Option<&str>) -> Tweet {
+        Tweet {
+            id_str: "10".to_string(),
+            id: "10".to_string(),
+            full_text: "@friend great point".to_string(),
+            created_at: "Mon Jan 02 12:00:00 +0000 2023".to_string(),
+            favorite_count: "0".to_string(),
+            retweet_count: "0".to_string(),
+            retweeted: false,
+            favorited: false,
+            truncated: false,
+            lang: Some("en".to_string()),
+            source: "Twitter Web App".to_string(),
+            display_text_range: vec!["0".to_string(), "19".to_string()],
+            in_reply_to_status_id: Some("9".to_string()),
+            in_reply_to_status_id_str: Some("9".to_string()),
+            in_reply_to_user_id: Some("42".to_string()),
+            in_reply_to_user_id_str: Some("42".to_string()),
+            in_reply_to_screen_name: Some("friend".to_string()),
+            edit_info: None,
+            entities: TweetEntities::default(),
+            possibly_sensitive: None,
+            quoted_status_id: quoted_status_id.map(str::to_string),
+            conversation_id: None,
+        }
+    }
+
+    #[test]
+    fn test_from_tweet_detects_quotes() {
+        let reply = InteractionEvent::from_tweet(&reply_tweet(None)).unwrap();
+        assert_eq!(reply.interaction_type, InteractionType::TweetReply);
+        assert_eq!(reply.word_count, 3);
+
+        let quote = InteractionEvent::from_tweet(&reply_tweet(Some("7"))).unwrap();
+        assert_eq!(quote.interaction_type, InteractionType::TweetQuote);
+        assert_eq!(quote.metadata.get("quoted_tweet_id").map(String::as_str), Some("7"));
+    }
+
+    #[test]
+    fn test_from_dm_message_detects_reactions() {
+        let message: DmMessage = serde_json::from_str(r#"{
+            "messageCreate": {
+                "id": "1",
+                "text": "See you soon",
+                "createdAt": "2023-01-02T10:00:00.000Z",
+                "senderId": "111",
+                "recipientId": "222",
+                "reactions": [{ "senderId": "222", "reactionKey": "like", "eventId": "5", "createdAt": "2023-01-02T10:01:00.000Z" }]
+            }
+        }"#).unwrap();
+
+        let event = InteractionEvent::from_dm_message(&message, "111-222").unwrap();
+        let reactions = InteractionEvent::reactions_from_dm_message(&message, "111-222");
+
+        assert_eq!(event.interaction_type, InteractionType::DmSent);
+        assert_eq!(event.word_count, 3);
+        assert_eq!(reactions.len(), 1);
+        assert_eq!(reactions[0].interaction_type, InteractionType::DmReacted { reaction_emoji: "❤️".to_string() });
+        assert_eq!(reactions[0].interaction_type.to_string(), "DM Reacted ❤️");
+        assert_eq!(reactions[0].user_id, "222");
+        assert_eq!(reactions[0].id, "5");
+        assert_eq!(reactions[0].timestamp, parse_twitter_date("2023-01-02T10:01:00.000Z").unwrap());
+        assert_eq!(reactions[0].metadata.get("message_id").map(String::as_str), Some("1"));
+        assert_eq!(InteractionType::reaction_emoji("unheard-of"), "unheard-of");
+    }
+
+    #[test]
+    fn test_events_cache_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("timeline_cache_jack.json");
+        let mut event = InteractionEvent::new("1", Utc::now(), InteractionType::DmReacted { reaction_emoji: "🔥".to_string() }, "111", "great news");
+        event.metadata.insert("conversation_id".to_string(), "111-222".to_string());
+        event.duration_since_prev = Some(std::time::Duration::from_secs(90));
+        event.topics = vec!["news".to_string()];
+
+        save_events_to_cache(&path, &[event.clone()]).unwrap();
+        let loaded = load_events_from_cache(&path).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].timestamp, event.timestamp);
+        assert_eq!(loaded[0].interaction_type, event.interaction_type);
+        assert_eq!(loaded[0].metadata, event.metadata);
+        assert_eq!(loaded[0].sentiment_score, event.sentiment_score);
+        assert_eq!(loaded[0].duration_since_prev, event.duration_since_prev);
+        assert_eq!(loaded[0].topics, event.topics);
+        assert_eq!(loaded[0].word_count, 2);
+        assert!(load_events_from_cache(&temp_dir.path().join("missing.json")).is_err());
+    }
+
+    #[test]
+    fn test_timeline_cache_key_tracks_sources() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("direct-messages.js");
+        std::fs::write(&source, "[]").unwrap();
+
+        let key = timeline_cache_key(&[&source]).unwrap();
+        assert_eq!(timeline_cache_key(&[&source]).unwrap(), key);
+
+        std::fs::write(&source, "[ ]").unwrap();
+        assert_ne!(timeline_cache_key(&[&source]).unwrap(), key);
+        assert!(timeline_cache_key(&[temp_dir.path().join("missing.js")]).is_err());
+        assert_eq!(timeline_cache_key_path(Path::new("/a/timeline_cache_jack.json")), PathBuf::from("/a/timeline_cache_jack.key"));
+    }
+
+    #[test]
+    fn test_from_dm_message_uses_sender_id() {
+        let message: DmMessage = serde_json::from_str(r#"{
+            "messageCreate": {
+                "id": "2",
+                "text": "Hello back",
+                "createdAt": "2023-01-02T10:05:00.000Z",
+                "senderId": "222",
+                "recipientId": "111"
+            }
+        }"#).unwrap();
+        let event = InteractionEvent::from_dm_message(&message, "111-222").unwrap();
+        assert_eq!(event.user_id, "222");
+
+        let anonymous: DmMessage = serde_json::from_str(r#"{
+            "messageCreate": { "id": "3", "text": "Hi", "createdAt": "2023-01-02T10:06:00.000Z" }
+        }"#).unwrap();
+        let event = InteractionEvent::from_dm_message(&anonymous, "111-222").unwrap();
+        assert_eq!(event.user_id, "111");
+    }
+}
@@ -4,6 +4,19 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// `metadata` key for the mean DM response time with a contact, in whole seconds
+pub const AVG_RESPONSE_TIME_SECS_KEY: &str = "avg_response_time_secs";
+/// `metadata` key for the UTC hour (0-23) with the most DMs exchanged with a contact
+pub const PEAK_ACTIVITY_HOUR_KEY: &str = "peak_activity_hour";
+/// `metadata` key for the number of DMs in the earlier half of a contact's timespan
+pub const FIRST_HALF_INTERACTIONS_KEY: &str = "first_half_interactions";
+/// `metadata` key for the number of DMs in the later half of a contact's timespan
+pub const SECOND_HALF_INTERACTIONS_KEY: &str = "second_half_interactions";
+/// `metadata` key for the number of DM conversations a contact takes part in
+pub const CONVERSATION_COUNT_KEY: &str = "conversation_count";
+/// `metadata` key for the TF-IDF topic word that comes up most often with a contact
+pub const TOP_TOPIC_KEY: &str = "top_topic";
+
 /// Represents a user's profile with interaction statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfile {
@@ -19,6 +32,173 @@ pub struct UserProfile {
     pub interaction_counts: HashMap<String, u32>,
     /// Additional profile metadata
     pub metadata: HashMap<String, String>,
+    /// Average sentiment score across the user's interactions
+    #[serde(default)]
+    pub avg_sentiment: f64,
+    /// Direction sentiment has moved over the course of the relationship
+    #[serde(default)]
+    pub sentiment_trend: SentimentTrend,
+    /// Whether the relationship plays out mostly in DMs, tweets, or both
+    #[serde(default)]
+    pub contact_type: ContactType,
+    /// How often the archive owner replied to this user's tweets
+    #[serde(default)]
+    pub tweet_reply_stats: TweetReplyStats,
+    /// Number of other profiles that share at least one conversation with this user
+    #[serde(default)]
+    pub mutual_contact_count: usize,
+}
+
+/// Replies the archive owner tweeted at one contact
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TweetReplyStats {
+    /// Number of tweets replying to the contact
+    pub total_replies: usize,
+    /// Mean length of those replies in characters
+    pub avg_reply_length_chars: f64,
+    /// When the earliest reply was posted
+    pub first_reply_date: Option<DateTime<Utc>>,
+    /// When the latest reply was posted
+    pub last_reply_date: Option<DateTime<Utc>>,
+}
+
+/// High-level classification of a contact by where interactions happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ContactType {
+    /// More than 80% of interactions are direct messages
+    DmHeavy,
+    /// More than 80% of interactions are tweets (replies, mentions, quotes)
+    TweetHeavy,
+    /// Between 20% and 80% of interactions are direct messages
+    Mixed,
+    /// Fewer than 5 interactions in total
+    #[default]
+    Minimal,
+}
+
+impl std::fmt::Display for ContactType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DmHeavy => write!(f, "DM-heavy"),
+            Self::TweetHeavy => write!(f, "Tweet-heavy"),
+            Self::Mixed => write!(f, "Mixed"),
+            Self::Minimal => write!(f, "Minimal"),
+        }
+    }
+}
+
+/// Classifies a contact by the share of their interactions that are DMs
+///
+/// `profile.total_interactions` is taken as the DM count, since profiles are
+/// built from DM data; `tweet_interaction_count` supplies the tweet side.
+pub fn classify_contact(profile: &UserProfile, tweet_interaction_count: usize) -> ContactType {
+    let dm_count = profile.total_interactions as usize;
+    let total = dm_count + tweet_interaction_count;
+    if total < 5 {
+        return ContactType::Minimal;
+    }
+
+    let dm_share = dm_count as f64 / total as f64;
+    if dm_share > 0.8 {
+        ContactType::DmHeavy
+    } else if dm_share < 0.2 {
+        ContactType::TweetHeavy
+    } else {
+        ContactType::Mixed
+    }
+}
+
+/// One-line reason for the contact type [`classify_contact`] gives, e.g. `DmHeavy (92% of interactions are DMs)`
+pub fn explain_contact_type(profile: &UserProfile, tweet_interaction_count: usize) -> String {
+    let dm_count = profile.total_interactions as usize;
+    let total = dm_count + tweet_interaction_count;
+    let contact_type = classify_contact(profile, tweet_interaction_count);
+    if contact_type == ContactType::Minimal {
+        return format!("{:?} ({} interactions in total, fewer than 5)", contact_type, total);
+    }
+    let dm_percent = (dm_count as f64 / total as f64 * 100.0).round();
+    format!("{:?} ({}% of interactions are DMs)", contact_type, dm_percent)
+}
+
+/// Where a relationship stands, judged by how its activity has changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelationshipStage {
+    /// The later half of the timespan has over 20% more interactions than the earlier half
+    Growing,
+    /// Both halves are within 20% of each other
+    Stable,
+    /// The later half has over 20% fewer interactions than the earlier half
+    Declining,
+    /// No interaction in the 90 days before the archive's newest one
+    Dormant,
+}
+
+impl std::fmt::Display for RelationshipStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Growing => write!(f, "Growing"),
+            Self::Stable => write!(f, "Stable"),
+            Self::Declining => write!(f, "Declining"),
+            Self::Dormant => write!(f, "Dormant"),
+        }
+    }
+}
+
+/// A contact's [`RelationshipStage`] and the change in activity behind it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleStage {
+    /// Lifecycle stage of the relationship
+    pub stage: RelationshipStage,
+    /// Change in interactions from the earlier to the later half, in percent
+    pub trend_pct: f64,
+}
+
+/// Direction of sentiment change over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SentimentTrend {
+    /// Later interactions are noticeably more positive than earlier ones
+    Improving,
+    /// Later interactions are noticeably more negative than earlier ones
+    Worsening,
+    /// No meaningful change (or too few interactions to tell)
+    #[default]
+    Stable,
+}
+
+impl SentimentTrend {
+    /// Minimum change in average score between halves to count as a trend
+    const THRESHOLD: f64 = 0.1;
+
+    /// Determines the trend from chronologically ordered sentiment scores
+    ///
+    /// Compares the average of the earlier half with the average of the later half.
+    pub fn from_scores(scores: &[f32]) -> Self {
+        if scores.len() < 2 {
+            return Self::Stable;
+        }
+
+        let (earlier, later) = scores.split_at(scores.len() / 2);
+        let mean = |s: &[f32]| s.iter().map(|&v| v as f64).sum::<f64>() / s.len() as f64;
+        let change = mean(later) - mean(earlier);
+
+        if change > Self::THRESHOLD {
+            Self::Improving
+        } else if change < -Self::THRESHOLD {
+            Self::Worsening
+        } else {
+            Self::Stable
+        }
+    }
+}
+
+impl std::fmt::Display for SentimentTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Improving => write!(f, "Improving"),
+            Self::Worsening => write!(f, "Worsening"),
+            Self::Stable => write!(f, "Stable"),
+        }
+    }
 }
 
 impl UserProfile {
@@ -31,6 +211,11 @@ impl UserProfile {
             last_interaction: None,
             interaction_counts: HashMap::new(),
             metadata: HashMap::new(),
+            avg_sentiment: 0.0,
+            sentiment_trend: SentimentTrend::Stable,
+            contact_type: ContactType::Minimal,
+            tweet_reply_stats: TweetReplyStats::default(),
+            mutual_contact_count: 0,
         }
     }
 
@@ -43,13 +228,53 @@ impl UserProfile {
         *self.interaction_counts.entry(type_str).or_insert(0) += 1;
         
         // Update timestamps
-        if self.first_interaction.map_or(true, |t| timestamp < t) {
+        if self.first_interaction.is_none_or(|t| timestamp < t) {
             self.first_interaction = Some(timestamp);
         }
-        if self.last_interaction.map_or(true, |t| timestamp > t) {
+        if self.last_interaction.is_none_or(|t| timestamp > t) {
             self.last_interaction = Some(timestamp);
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentiment_trend_from_scores() {
+        assert_eq!(SentimentTrend::from_scores(&[]), SentimentTrend::Stable);
+        assert_eq!(SentimentTrend::from_scores(&[1.0]), SentimentTrend::Stable);
+        assert_eq!(SentimentTrend::from_scores(&[-1.0, -0.5, 0.5, 1.0]), SentimentTrend::Improving);
+        assert_eq!(SentimentTrend::from_scores(&[1.0, 0.5, -0.5]), SentimentTrend::Worsening);
+        assert_eq!(SentimentTrend::from_scores(&[0.5, 0.5, 0.55, 0.5]), SentimentTrend::Stable);
+    }
+
+    #[test]
+    fn test_classify_contact() {
+        let mut profile = UserProfile::new("user");
+        profile.total_interactions = 2;
+        assert_eq!(classify_contact(&profile, 2), ContactType::Minimal);
 
+        profile.total_interactions = 9;
+        assert_eq!(classify_contact(&profile, 1), ContactType::DmHeavy);
+        assert_eq!(classify_contact(&profile, 9), ContactType::Mixed);
+        // Exactly 80% DMs is still mixed
+        profile.total_interactions = 8;
+        assert_eq!(classify_contact(&profile, 2), ContactType::Mixed);
+
+        profile.total_interactions = 1;
+        assert_eq!(classify_contact(&profile, 9), ContactType::TweetHeavy);
+        profile.total_interactions = 0;
+        assert_eq!(classify_contact(&profile, 5), ContactType::TweetHeavy);
+    }
+
+    #[test]
+    fn test_explain_contact_type() {
+        let mut profile = UserProfile::new("user");
+        profile.total_interactions = 23;
+        assert_eq!(explain_contact_type(&profile, 2), "DmHeavy (92% of interactions are DMs)");
+        profile.total_interactions = 2;
+        assert_eq!(explain_contact_type(&profile, 1), "Minimal (3 interactions in total, fewer than 5)");
+    }
+}
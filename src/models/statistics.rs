@@ -2,7 +2,10 @@
 
 use chrono::{self, DateTime, Duration, Timelike, Datelike, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::processing::data_structures::Thread;
+use crate::utils::parse_twitter_date;
 
 /// Statistical metrics for a set of interactions
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -135,6 +138,197 @@ pub fn calculate_percentiles(data: &[f64]) -> std::collections::HashMap<String,
     percentiles
 }
 
+/// Tweet and engagement totals for a single ISO week
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyStats {
+    /// ISO week number (1-53)
+    pub iso_week: u32,
+    /// ISO week-based year the week belongs to
+    pub year: i32,
+    /// Number of tweets in threads started during this week
+    pub tweet_count: usize,
+    /// Total likes across those tweets
+    pub total_likes: u64,
+    /// Total retweets across those tweets
+    pub total_retweets: u64,
+    /// Number of distinct screen names replied to during this week
+    pub unique_reply_targets: usize,
+}
+
+/// Aggregates threads into per-week statistics sorted by `(year, iso_week)`
+///
+/// A thread is attributed to the week of its first tweet. Threads whose first
+/// tweet has an unparseable `created_at` are skipped.
+pub fn compute_weekly_stats(threads: &[Thread]) -> Vec<WeeklyStats> {
+    let mut weeks: BTreeMap<(i32, u32), (WeeklyStats, HashSet<String>)> = BTreeMap::new();
+
+    for thread in threads {
+        let Some(first_tweet) = thread.tweets.first() else {
+            continue;
+        };
+        let Ok(created_at) = parse_twitter_date(&first_tweet.created_at) else {
+            continue;
+        };
+        let iso = created_at.iso_week();
+
+        let (stats, reply_targets) = weeks.entry((iso.year(), iso.week())).or_insert_with(|| {
+            (
+                WeeklyStats {
+                    iso_week: iso.week(),
+                    year: iso.year(),
+                    tweet_count: 0,
+                    total_likes: 0,
+                    total_retweets: 0,
+                    unique_reply_targets: 0,
+                },
+                HashSet::new(),
+            )
+        });
+
+        stats.tweet_count += thread.tweets.len();
+        stats.total_likes += thread.favorite_count as u64;
+        stats.total_retweets += thread.retweet_count as u64;
+        for tweet in &thread.tweets {
+            if let Some(target) = &tweet.in_reply_to_screen_name {
+                reply_targets.insert(target.clone());
+            }
+        }
+    }
+
+    weeks
+        .into_values()
+        .map(|(mut stats, reply_targets)| {
+            stats.unique_reply_targets = reply_targets.len();
+            stats
+        })
+        .collect()
+}
+
+/// Trailing moving average of weekly likes over (up to) the last `window` weeks
+pub fn likes_moving_average(stats: &[WeeklyStats], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    (0..stats.len())
+        .map(|i| {
+            let start = (i + 1).saturating_sub(window);
+            let slice = &stats[start..=i];
+            slice.iter().map(|s| s.total_likes as f64).sum::<f64>() / slice.len() as f64
+        })
+        .collect()
+}
+
+/// Language code to report for a tweet, mapping undetermined (`"und"`),
+/// empty and missing codes to `"unknown"`
+pub fn language_label(lang: Option<&str>) -> &str {
+    match lang {
+        Some(code) if !code.is_empty() && code != "und" => code,
+        _ => "unknown",
+    }
+}
+
+/// Counts threads per language code of their first tweet
+///
+/// Codes are reported as by [`language_label`].
+pub fn language_breakdown(threads: &[Thread]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for thread in threads {
+        let lang = language_label(thread.tweets.first().and_then(|t| t.lang.as_deref()));
+        *counts.entry(lang.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Quartile and p95 cut-offs of per-thread engagement (likes + retweets)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EngagementDistribution {
+    /// 25th percentile
+    pub p25: f64,
+    /// Median
+    pub p50: f64,
+    /// 75th percentile
+    pub p75: f64,
+    /// 95th percentile
+    pub p95: f64,
+}
+
+/// Earliest and latest tweet timestamps across all threads
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DateRange {
+    /// Earliest tweet, `None` when no timestamp parses
+    pub first: Option<DateTime<Utc>>,
+    /// Latest tweet, `None` when no timestamp parses
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// Archive-wide thread statistics written to `thread_stats_*.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreadStatistics {
+    /// Number of threads
+    pub total_threads: usize,
+    /// Number of tweets across all threads
+    pub total_tweets: usize,
+    /// Mean tweets per thread (0 when there are no threads)
+    pub avg_tweets_per_thread: f64,
+    /// Tweet count of the longest thread
+    pub max_thread_length: usize,
+    /// Per-thread engagement percentiles
+    pub engagement_distribution: EngagementDistribution,
+    /// Span of tweet timestamps
+    pub date_range: DateRange,
+    /// Threads per language code, as in [`language_breakdown`]
+    pub language_distribution: BTreeMap<String, usize>,
+    /// Mean `engagement_dropoff_rate` of threads with two or more tweets
+    /// (0 when there are none); negative means later tweets get fewer likes
+    #[serde(default)]
+    pub avg_engagement_dropoff_rate: f64,
+}
+
+/// Summarises threads into a [`ThreadStatistics`]
+///
+/// Tweets with an unparseable `created_at` are left out of the date range.
+pub fn compute_thread_statistics(threads: &[Thread]) -> ThreadStatistics {
+    let total_tweets: usize = threads.iter().map(|t| t.tweets.len()).sum();
+
+    let mut engagement: Vec<f64> = threads
+        .iter()
+        .map(|t| t.favorite_count as f64 + t.retweet_count as f64)
+        .collect();
+    engagement.sort_by(|a, b| a.total_cmp(b));
+
+    let dates: Vec<DateTime<Utc>> = threads
+        .iter()
+        .flat_map(|t| &t.tweets)
+        .filter_map(|tweet| parse_twitter_date(&tweet.created_at).ok())
+        .collect();
+
+    let multi_tweet_rates: Vec<f64> = threads.iter()
+        .filter(|t| t.tweets.len() > 1)
+        .map(|t| t.engagement_dropoff_rate)
+        .collect();
+
+    ThreadStatistics {
+        total_threads: threads.len(),
+        total_tweets,
+        avg_tweets_per_thread: if threads.is_empty() { 0.0 } else { total_tweets as f64 / threads.len() as f64 },
+        max_thread_length: threads.iter().map(|t| t.tweets.len()).max().unwrap_or(0),
+        engagement_distribution: EngagementDistribution {
+            p25: percentile(&engagement, 0.25),
+            p50: percentile(&engagement, 0.5),
+            p75: percentile(&engagement, 0.75),
+            p95: percentile(&engagement, 0.95),
+        },
+        date_range: DateRange {
+            first: dates.iter().min().copied(),
+            last: dates.iter().max().copied(),
+        },
+        language_distribution: language_breakdown(threads).into_iter().collect(),
+        avg_engagement_dropoff_rate: if multi_tweet_rates.is_empty() {
+            0.0
+        } else {
+            multi_tweet_rates.iter().sum::<f64>() / multi_tweet_rates.len() as f64
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,4 +388,144 @@ mod tests {
         println!("p90: {:.2} (expected 4.0)", p90);
         println!("p95: {:.2} (expected 4.0)", p95);
     }
+
+    fn create_thread(id: &str, created_at: &str, likes: u32, retweets: u32, reply_to: Option<&str>) -> Thread {
+        use crate::processing::data_structures::{Tweet, TweetEntities};
+
+        let tweet = Tweet {
+            id_str: id.to_string(),
+            id: id.to_string(),
+            full_text: "Test tweet".to_string(),
+            created_at: created_at.to_string(),
+            favorite_count: likes.to_string(),
+            retweet_count: retweets.to_string(),
+            retweeted: false,
+            favorited: false,
+            truncated: false,
+            lang: Some("en".to_string()),
+            source: "Twitter Web App".to_string(),
+            display_text_range: vec!["0".to_string(), "10".to_string()],
+            in_reply_to_status_id: reply_to.map(|_| "0".to_string()),
+            in_reply_to_status_id_str: reply_to.map(|_| "0".to_string()),
+            in_reply_to_user_id: None,
+            in_reply_to_user_id_str: None,
+            in_reply_to_screen_name: reply_to.map(|s| s.to_string()),
+            edit_info: None,
+            entities: TweetEntities::default(),
+            possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
+        };
+
+        Thread {
+            id: id.to_string(),
+            tweets: vec![tweet],
+            tweet_count: 1,
+            favorite_count: likes,
+            retweet_count: retweets,
+            url: String::new(),
+            contains_poll: false,
+            first_tweet_url_entities: Vec::new(),
+            engagement_dropoff_rate: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_compute_weekly_stats() {
+        let threads = vec![
+            // ISO week 2 of 2023
+            create_thread("3", "Wed Jan 11 12:00:00 +0000 2023", 7, 0, None),
+            // ISO week 1 of 2023 (Jan 2 is a Monday)
+            create_thread("1", "Mon Jan 02 12:00:00 +0000 2023", 5, 2, Some("alice")),
+            create_thread("2", "Sun Jan 08 12:00:00 +0000 2023", 3, 1, Some("bob")),
+            // Jan 1 2023 belongs to ISO week 52 of 2022
+            create_thread("0", "Sun Jan 01 12:00:00 +0000 2023", 1, 0, Some("alice")),
+            create_thread("x", "not a date", 100, 100, None),
+        ];
+
+        let stats = compute_weekly_stats(&threads);
+
+        assert_eq!(stats.len(), 3);
+        assert_eq!((stats[0].year, stats[0].iso_week), (2022, 52));
+        assert_eq!((stats[1].year, stats[1].iso_week), (2023, 1));
+        assert_eq!(stats[1].tweet_count, 2);
+        assert_eq!(stats[1].total_likes, 8);
+        assert_eq!(stats[1].total_retweets, 3);
+        assert_eq!(stats[1].unique_reply_targets, 2);
+        assert_eq!((stats[2].year, stats[2].iso_week), (2023, 2));
+        assert_eq!(stats[2].unique_reply_targets, 0);
+    }
+
+    #[test]
+    fn test_likes_moving_average() {
+        let threads: Vec<Thread> = [
+            "Mon Jan 02 12:00:00 +0000 2023",
+            "Mon Jan 09 12:00:00 +0000 2023",
+            "Mon Jan 16 12:00:00 +0000 2023",
+            "Mon Jan 23 12:00:00 +0000 2023",
+            "Mon Jan 30 12:00:00 +0000 2023",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, date)| create_thread(&i.to_string(), date, (i as u32 + 1) * 4, 0, None))
+        .collect();
+
+        let stats = compute_weekly_stats(&threads);
+        let averages = likes_moving_average(&stats, 4);
+
+        // Likes per week: 4, 8, 12, 16, 20
+        assert_eq!(averages, vec![4.0, 6.0, 8.0, 10.0, 14.0]);
+    }
+
+    #[test]
+    fn test_language_breakdown() {
+        let mut threads = vec![
+            create_thread("1", "Mon Jan 02 12:00:00 +0000 2023", 0, 0, None),
+            create_thread("2", "Mon Jan 02 12:00:00 +0000 2023", 0, 0, None),
+            create_thread("3", "Mon Jan 02 12:00:00 +0000 2023", 0, 0, None),
+            create_thread("4", "Mon Jan 02 12:00:00 +0000 2023", 0, 0, None),
+            create_thread("5", "Mon Jan 02 12:00:00 +0000 2023", 0, 0, None),
+        ];
+        threads[1].tweets[0].lang = Some("ja".to_string());
+        threads[2].tweets[0].lang = Some("und".to_string());
+        threads[3].tweets[0].lang = None;
+
+        let breakdown = language_breakdown(&threads);
+
+        assert_eq!(breakdown.len(), 3);
+        assert_eq!(breakdown["en"], 2);
+        assert_eq!(breakdown["ja"], 1);
+        assert_eq!(breakdown["unknown"], 2);
+    }
+
+    #[test]
+    fn test_compute_thread_statistics() {
+        let mut threads = vec![
+            create_thread("1", "Mon Jan 02 12:00:00 +0000 2023", 10, 0, None),
+            create_thread("2", "Wed Jan 11 12:00:00 +0000 2023", 20, 10, None),
+            create_thread("3", "Sun Jan 01 08:00:00 +0000 2023", 0, 0, None),
+        ];
+        let mut second_tweet = threads[0].tweets[0].clone();
+        second_tweet.created_at = "Fri Jan 20 09:30:00 +0000 2023".to_string();
+        threads[0].tweets.push(second_tweet);
+        threads[0].engagement_dropoff_rate = -1.0;
+        threads[2].tweets[0].lang = Some("ja".to_string());
+
+        let stats = compute_thread_statistics(&threads);
+
+        assert_eq!(stats.total_threads, 3);
+        assert_eq!(stats.total_tweets, 4);
+        assert!((stats.avg_tweets_per_thread - 4.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.max_thread_length, 2);
+        assert_eq!(stats.engagement_distribution.p50, 10.0);
+        let engagement = &stats.engagement_distribution;
+        assert!(engagement.p25 <= engagement.p50 && engagement.p75 <= engagement.p95);
+        assert_eq!(stats.date_range.first, Some(Utc.with_ymd_and_hms(2023, 1, 1, 8, 0, 0).unwrap()));
+        assert_eq!(stats.date_range.last, Some(Utc.with_ymd_and_hms(2023, 1, 20, 9, 30, 0).unwrap()));
+        assert_eq!(stats.language_distribution.get("ja"), Some(&1));
+        assert_eq!(stats.language_distribution.get("en"), Some(&2));
+        assert_eq!(stats.avg_engagement_dropoff_rate, -1.0);
+
+        assert_eq!(compute_thread_statistics(&[]), ThreadStatistics::default());
+    }
 }
@@ -25,11 +25,51 @@ pub enum TimelinePattern {
     NoPattern,
 }
 
+/// Overall communication pattern of a relationship
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConversationRhythm {
+    /// At least 0.8 interactions per day on average
+    DailyChat,
+    /// At least 0.14 interactions per day (about one a week)
+    WeeklyCheckin,
+    /// At least 0.033 interactions per day (about one a month)
+    MonthlyUpdate,
+    /// Less frequent than monthly
+    #[default]
+    Sporadic,
+    /// More than 50 interactions with an average response time under an hour
+    Intensive,
+}
+
+impl ConversationRhythm {
+    /// Classifies a relationship from its average interactions per day,
+    /// average response time in seconds and total interaction count
+    ///
+    /// `Intensive` takes precedence over the frequency-based labels; a zero
+    /// response time means no responses were measured and never counts as fast.
+    pub fn classify(interactions_per_day: f64, average_response_secs: f64, interaction_count: usize) -> Self {
+        if interaction_count > 50 && average_response_secs > 0.0 && average_response_secs < 3600.0 {
+            ConversationRhythm::Intensive
+        } else if interactions_per_day >= 0.8 {
+            ConversationRhythm::DailyChat
+        } else if interactions_per_day >= 0.14 {
+            ConversationRhythm::WeeklyCheckin
+        } else if interactions_per_day >= 0.033 {
+            ConversationRhythm::MonthlyUpdate
+        } else {
+            ConversationRhythm::Sporadic
+        }
+    }
+}
+
 /// Metrics about the timeline density
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimelineDensity {
     /// Average number of interactions per day
     pub avg_interactions_per_day: f64,
+    /// Variance of the per-day interaction count across the analysis period
+    #[serde(default)]
+    pub daily_variance: f64,
     /// Peak interaction times
     pub peak_hours: Vec<u32>,
     /// Days with highest activity
@@ -72,6 +112,16 @@ pub struct TimelineAnalysis {
     pub total_interactions: usize,
     /// Number of unique participants
     pub unique_participants: usize,
+    /// Number of time windows whose interaction count exceeds the window
+    /// mean by more than two standard deviations
+    #[serde(default)]
+    pub bursty_periods: usize,
+    /// Narrative label for the relationship's communication pattern
+    #[serde(default)]
+    pub conversation_rhythm: ConversationRhythm,
+    /// Mean number of words per interaction
+    #[serde(default)]
+    pub avg_words_per_event: f64,
 }
 
 impl TimelineAnalysis {
@@ -81,6 +131,7 @@ impl TimelineAnalysis {
             patterns: Vec::new(),
             density: TimelineDensity {
                 avg_interactions_per_day: 0.0,
+                daily_variance: 0.0,
                 peak_hours: Vec::new(),
                 peak_days: Vec::new(),
                 peak_hour: 0,
@@ -97,6 +148,9 @@ impl TimelineAnalysis {
             end_time,
             total_interactions: 0,
             unique_participants: 0,
+            bursty_periods: 0,
+            conversation_rhythm: ConversationRhythm::Sporadic,
+            avg_words_per_event: 0.0,
         }
     }
 }
@@ -116,5 +170,17 @@ mod tests {
         assert_eq!(analysis.density.avg_interactions_per_day, 0.0);
         assert_eq!(analysis.total_interactions, 0);
         assert_eq!(analysis.unique_participants, 0);
+        assert_eq!(analysis.conversation_rhythm, ConversationRhythm::Sporadic);
+    }
+
+    #[test]
+    fn test_conversation_rhythm_classification() {
+        assert_eq!(ConversationRhythm::classify(1.0, 7200.0, 30), ConversationRhythm::DailyChat);
+        assert_eq!(ConversationRhythm::classify(0.2, 7200.0, 30), ConversationRhythm::WeeklyCheckin);
+        assert_eq!(ConversationRhythm::classify(0.05, 7200.0, 30), ConversationRhythm::MonthlyUpdate);
+        assert_eq!(ConversationRhythm::classify(0.01, 7200.0, 30), ConversationRhythm::Sporadic);
+        assert_eq!(ConversationRhythm::classify(2.0, 600.0, 51), ConversationRhythm::Intensive);
+        assert_eq!(ConversationRhythm::classify(2.0, 600.0, 50), ConversationRhythm::DailyChat);
+        assert_eq!(ConversationRhythm::classify(2.0, 0.0, 100), ConversationRhythm::DailyChat);
     }
 }
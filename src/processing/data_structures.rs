@@ -1,7 +1,10 @@
 //! Core data structures for tweet and DM processing
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc as async_mpsc;
+use tokio::sync::watch;
 
 /// Represents a tweet from the Twitter archive
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -24,8 +27,9 @@ pub struct Tweet {
     pub favorited: bool,
     /// Whether the tweet was truncated in the original response
     pub truncated: bool,
-    /// Language code of the tweet
-    pub lang: String,
+    /// Language code of the tweet (e.g. "en", "ja", "und")
+    #[serde(default)]
+    pub lang: Option<String>,
     /// Source application used to post the tweet
     pub source: String,
     /// Display range indices for the tweet text
@@ -50,8 +54,16 @@ pub struct Tweet {
     pub entities: TweetEntities,
     
     /// Whether the tweet contains sensitive content
-    #[serde(default)]
+    #[serde(rename = "possibly_sensitive", default)]
     pub possibly_sensitive: Option<bool>,
+
+    /// ID of the tweet being quoted (if this is a quote-tweet)
+    #[serde(rename = "quoted_status_id_str", default)]
+    pub quoted_status_id: Option<String>,
+
+    /// ID of the tweet that started the reply chain this tweet belongs to
+    #[serde(rename = "conversation_id_str", default)]
+    pub conversation_id: Option<String>,
 }
 
 /// Edit information for tweets
@@ -90,6 +102,9 @@ pub struct TweetEntities {
     pub user_mentions: Vec<UserMention>,
     /// URLs included in the tweet
     pub urls: Vec<TweetUrl>,
+    /// Option labels when the tweet is a poll
+    #[serde(rename = "poll", default)]
+    pub poll_option_labels: Option<Vec<String>>,
 }
 
 /// Hashtag in tweet
@@ -158,6 +173,50 @@ pub struct Thread {
     pub favorite_count: u32,
     /// Total number of retweets across all tweets in the thread
     pub retweet_count: u32,
+    /// Canonical Twitter URL of the thread (see [`thread_url`](super::tweets::thread_url))
+    pub url: String,
+    /// Whether any tweet in the thread is a poll (see [`tweet_is_poll`](super::tweets::tweet_is_poll))
+    pub contains_poll: bool,
+    /// Links in the first tweet, with their expanded targets
+    pub first_tweet_url_entities: Vec<TweetUrl>,
+    /// How engagement moves along the thread (see
+    /// [`compute_engagement_dropoff_rate`](super::tweets::compute_engagement_dropoff_rate))
+    pub engagement_dropoff_rate: f64,
+}
+
+impl Thread {
+    /// Builds a thread from its tweets in order, summing engagement and
+    /// computing the URL for `screen_name`
+    pub fn from_tweets(tweets: Vec<Tweet>, screen_name: &str) -> Self {
+        let mut thread = Thread {
+            id: tweets.first().map(|t| t.id_str.clone()).unwrap_or_default(),
+            tweet_count: tweets.len(),
+            favorite_count: tweets.iter().map(|t| t.favorite_count.parse::<u32>().unwrap_or(0)).sum(),
+            retweet_count: tweets.iter().map(|t| t.retweet_count.parse::<u32>().unwrap_or(0)).sum(),
+            contains_poll: tweets.iter().any(super::tweets::tweet_is_poll),
+            first_tweet_url_entities: tweets.first().map(|t| t.entities.urls.clone()).unwrap_or_default(),
+            tweets,
+            url: String::new(),
+            engagement_dropoff_rate: 0.0,
+        };
+        thread.url = super::tweets::thread_url(&thread, screen_name);
+        thread.engagement_dropoff_rate = super::tweets::compute_engagement_dropoff_rate(&thread);
+        thread
+    }
+
+    /// Keeps only the first `max_tweets` tweets; `tweet_count` stays at the original count
+    ///
+    /// Returns whether any tweets were dropped.
+    pub fn truncate(&mut self, max_tweets: usize) -> bool {
+        let longer = self.tweets.len() > max_tweets;
+        self.tweets.truncate(max_tweets);
+        longer
+    }
+
+    /// Whether [`Thread::truncate`] dropped tweets from this thread
+    pub fn is_truncated(&self) -> bool {
+        self.tweets.len() < self.tweet_count
+    }
 }
 
 /// Represents a processed DM conversation
@@ -175,6 +234,78 @@ pub struct ProcessedConversation {
     pub last_message_date: Option<String>,
 }
 
+/// A transform applied to each CSV record before it is written
+pub type RecordTransform = Box<dyn Fn(Vec<String>) -> Vec<String> + Send>;
+
+/// Header row written by [`CsvWriter::new`]
+pub const THREAD_CSV_HEADERS: [&str; 11] = [
+    "Thread ID",
+    "Date time of first tweet",
+    "Number of Tweets in Thread",
+    "Likes in first tweet",
+    "Retweets in first tweet",
+    "Total likes for all tweets",
+    "Total retweets for all tweets",
+    "Language",
+    "Thread Text",
+    "First Tweet URLs",
+    "Truncated",
+];
+
+/// Value type of a CSV column, for tools that read the schema metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Free text
+    Text,
+    /// Whole number
+    Integer,
+    /// Decimal number
+    Float,
+    /// Date and time
+    DateTime,
+    /// `true` or `false`
+    Boolean,
+}
+
+impl ColumnType {
+    /// Lowercase name used in the `#types` row
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Text => "text",
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "float",
+            ColumnType::DateTime => "datetime",
+            ColumnType::Boolean => "boolean",
+        }
+    }
+
+    /// JSON Schema (Draft-7) description of a value of this type
+    pub fn json_schema(&self) -> serde_json::Value {
+        match self {
+            ColumnType::Text => serde_json::json!({ "type": "string" }),
+            ColumnType::Integer => serde_json::json!({ "type": "integer" }),
+            ColumnType::Float => serde_json::json!({ "type": "number" }),
+            ColumnType::DateTime => serde_json::json!({ "type": "string", "format": "date-time" }),
+            ColumnType::Boolean => serde_json::json!({ "type": "boolean" }),
+        }
+    }
+}
+
+/// Column types of [`THREAD_CSV_HEADERS`], in the same order
+pub const THREAD_CSV_COLUMN_TYPES: [ColumnType; 11] = [
+    ColumnType::Text,
+    ColumnType::DateTime,
+    ColumnType::Integer,
+    ColumnType::Integer,
+    ColumnType::Integer,
+    ColumnType::Integer,
+    ColumnType::Integer,
+    ColumnType::Text,
+    ColumnType::Text,
+    ColumnType::Text,
+    ColumnType::Boolean,
+];
+
 /// CSV writer for async processing
 pub struct CsvWriter {
     /// Path where the CSV file will be written
@@ -183,6 +314,31 @@ pub struct CsvWriter {
     pub receiver: async_mpsc::Receiver<Vec<String>>,
     /// Size of the buffer for batching writes
     pub buffer_size: usize,
+    /// Column index used to skip records whose value has already been written
+    pub dedup_on_field: Option<usize>,
+    /// Type of each column; when set, `schema.json` is written next to the CSV
+    pub column_types: Vec<ColumnType>,
+    /// Write a `#types,…` row after the header (requires `column_types`)
+    pub(crate) types_row: bool,
+    /// Comment written as a single `# `-prefixed field before the header row
+    pub header_comment: Option<String>,
+    /// Quote every field, not only those containing a delimiter, quote or newline
+    pub auto_quoting: bool,
+    /// Values seen in the dedup column so far
+    pub(crate) seen_values: HashSet<String>,
+    /// Number of records written
+    pub(crate) written_count: usize,
+    /// Number of duplicate records skipped
+    pub(crate) skipped_count: usize,
+    /// Number of buffer flushes, including the final one after the channel closes
+    #[cfg(test)]
+    pub(crate) flush_count: usize,
+    /// Transforms applied in order to each record before it is written
+    pub(crate) transformation: Vec<RecordTransform>,
+    /// Header row written before any records
+    pub(crate) headers: Vec<String>,
+    /// In-memory destination used instead of `output_path` when set
+    pub(crate) memory_sink: Option<Arc<Mutex<Vec<u8>>>>,
 }
 
 impl CsvWriter {
@@ -192,7 +348,160 @@ impl CsvWriter {
             output_path,
             receiver,
             buffer_size,
+            dedup_on_field: None,
+            column_types: Vec::new(),
+            types_row: false,
+            header_comment: None,
+            auto_quoting: false,
+            seen_values: HashSet::new(),
+            written_count: 0,
+            skipped_count: 0,
+            #[cfg(test)]
+            flush_count: 0,
+            transformation: Vec::new(),
+            headers: THREAD_CSV_HEADERS.iter().map(|h| h.to_string()).collect(),
+            memory_sink: None,
         }
     }
+
+    /// Creates a CsvWriter that writes to memory instead of a file
+    ///
+    /// Once [`run`](Self::run) returns, the returned buffer holds the
+    /// complete CSV output, header row included.
+    pub fn to_memory(receiver: async_mpsc::Receiver<Vec<String>>, buffer_size: usize, headers: Vec<String>) -> (Self, Arc<Mutex<Vec<u8>>>) {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let mut writer = Self::new(String::new(), receiver, buffer_size);
+        writer.headers = headers;
+        writer.memory_sink = Some(Arc::clone(&buffer));
+        (writer, buffer)
+    }
+
+    /// Adds a transform to apply to every record before it is written
+    ///
+    /// Transforms run in the order they were added, before deduplication.
+    /// See `processing::transforms` for built-in ones.
+    pub fn with_transform<F: Fn(Vec<String>) -> Vec<String> + Send + 'static>(mut self, f: F) -> Self {
+        self.transformation.push(Box::new(f));
+        self
+    }
+
+    /// Skips records whose value at `column_index` has already been written
+    pub fn with_dedup_on_field(mut self, column_index: usize) -> Self {
+        self.dedup_on_field = Some(column_index);
+        self
+    }
+
+    /// Records the type of each column
+    ///
+    /// File-backed writers also write a JSON Schema (Draft-7) `schema.json` to
+    /// the CSV's directory when they run. Types must match the headers one to one.
+    pub fn with_column_types(mut self, column_types: Vec<ColumnType>) -> Self {
+        self.column_types = column_types;
+        self
+    }
+
+    /// Writes a `#types,text,integer,…` comment row after the header
+    ///
+    /// The row has one more field than the header, so tools that skip `#`
+    /// comment lines read the file unchanged.
+    pub fn with_types_row(mut self) -> Self {
+        self.types_row = true;
+        self
+    }
+
+    /// Writes `comment` as a `# `-prefixed row before the header
+    ///
+    /// Tools configured to skip `#` comment lines (`comment = '#'`) read the
+    /// file unchanged. See [`provenance_comment`](Self::provenance_comment).
+    pub fn with_header_comment(mut self, comment: impl Into<String>) -> Self {
+        self.header_comment = Some(comment.into());
+        self
+    }
+
+    /// Quotes every field, for downstream tools that split lines on commas naively
+    ///
+    /// The header comment and `#types` rows stay unquoted so comment-skipping
+    /// tools still recognise them.
+    pub fn with_auto_quoting(mut self, auto_quoting: bool) -> Self {
+        self.auto_quoting = auto_quoting;
+        self
+    }
+
+    /// Provenance line for [`with_header_comment`](Self::with_header_comment):
+    /// tool version, screen name and generation time
+    pub fn provenance_comment(screen_name: &str) -> String {
+        format!("Generated by tweet-scrolls v{} for @{} on {}", env!("CARGO_PKG_VERSION"), screen_name, chrono::Utc::now())
+    }
+
+    /// JSON Schema (Draft-7) for one row, built from the headers and `column_types`
+    pub fn json_schema(&self) -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = self.headers.iter()
+            .zip(&self.column_types)
+            .map(|(header, column_type)| (header.clone(), column_type.json_schema()))
+            .collect();
+        serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": properties,
+            "required": self.headers,
+        })
+    }
+
+    /// Returns `(written, skipped)` record counts
+    pub fn deduplication_stats(&self) -> (usize, usize) {
+        (self.written_count, self.skipped_count)
+    }
 }
 
+/// Stage of the tweet processing pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stage {
+    /// Processing has not started yet
+    #[default]
+    Starting,
+    /// Reading the archive file from disk
+    Reading,
+    /// Parsing the archive JSON
+    Parsing,
+    /// Applying the retweet filter
+    Filtering,
+    /// Grouping tweets into threads
+    Threading,
+    /// Sorting threads by date
+    Sorting,
+    /// Writing output files
+    Writing,
+    /// All output has been written
+    Complete,
+}
+
+/// Snapshot of processing progress published to subscribers
+#[derive(Debug, Clone, Default)]
+pub struct ProcessingProgress {
+    /// Current pipeline stage
+    pub stage: Stage,
+    /// Items processed so far in this stage
+    pub current: usize,
+    /// Total items in this stage (0 if unknown)
+    pub total: usize,
+    /// Human-readable status message
+    pub message: String,
+}
+
+/// Publishes a progress update if a sender was provided; a no-op otherwise
+pub fn report_progress(
+    progress: Option<&watch::Sender<ProcessingProgress>>,
+    stage: Stage,
+    current: usize,
+    total: usize,
+    message: impl Into<String>,
+) {
+    if let Some(sender) = progress {
+        sender.send_replace(ProcessingProgress {
+            stage,
+            current,
+            total,
+            message: message.into(),
+        });
+    }
+}
@@ -0,0 +1,245 @@
+//! Comparison of thread output between two processing runs
+
+use anyhow::{bail, Context, Result};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use csv::{Reader as CsvReaderLib, StringRecord, Writer as CsvWriterLib};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Summary of differences between the current and a previous run
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadDiff {
+    /// Threads present now but not in the previous run
+    pub new: usize,
+    /// Threads present in the previous run but not now
+    pub removed: usize,
+    /// Threads present in both runs with a different tweet count
+    pub changed: usize,
+    /// Date of the previous run, taken from its output file name
+    pub prev_run_date: String,
+}
+
+impl fmt::Display for ThreadDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{} new threads, -{} removed, ~{} changed since {}",
+               self.new, self.removed, self.changed, self.prev_run_date)
+    }
+}
+
+/// Rows of a threads CSV grouped by thread ID
+struct ThreadsCsv {
+    headers: StringRecord,
+    /// Thread IDs in first-seen order
+    order: Vec<String>,
+    /// Rows and tweet count for each thread
+    threads: HashMap<String, (Vec<StringRecord>, String)>,
+}
+
+/// Finds the most recent `threads_{screen_name}_{timestamp}.csv` in a directory
+///
+/// Returns the path and the timestamp parsed from its name.
+pub fn find_latest_threads_csv(dir: &Path, screen_name: &str) -> Result<Option<(PathBuf, i64)>> {
+    let prefix = format!("threads_{}_", screen_name);
+    let mut latest: Option<(PathBuf, i64)> = None;
+
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(timestamp) = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".csv"))
+            .and_then(|ts| ts.parse::<i64>().ok())
+        else {
+            continue;
+        };
+        if latest.as_ref().is_none_or(|(_, ts)| timestamp > *ts) {
+            latest = Some((path, timestamp));
+        }
+    }
+
+    Ok(latest)
+}
+
+fn read_threads_csv(path: &Path) -> Result<ThreadsCsv> {
+    let mut reader = CsvReaderLib::from_path(path)
+        .with_context(|| format!("Failed to open threads CSV: {}", path.display()))?;
+    let headers = reader.headers()?.clone();
+
+    let column = |name: &str| -> Result<usize> {
+        headers.iter().position(|h| h == name)
+            .with_context(|| format!("Missing '{}' column in {}", name, path.display()))
+    };
+    let id_column = column("thread_id")?;
+    let count_column = column("thread_tweet_count")?;
+
+    let mut order = Vec::new();
+    let mut threads: HashMap<String, (Vec<StringRecord>, String)> = HashMap::new();
+    for record in reader.records() {
+        let record = record.with_context(|| format!("Failed to read row in {}", path.display()))?;
+        let thread_id = record.get(id_column).unwrap_or_default().to_string();
+        let tweet_count = record.get(count_column).unwrap_or_default().to_string();
+        let entry = threads.entry(thread_id.clone()).or_insert_with(|| {
+            order.push(thread_id);
+            (Vec::new(), tweet_count)
+        });
+        entry.0.push(record);
+    }
+
+    Ok(ThreadsCsv { headers, order, threads })
+}
+
+fn write_threads_subset<'a>(
+    path: &Path,
+    headers: &StringRecord,
+    rows: impl Iterator<Item = &'a StringRecord>,
+) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create file: {}", path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+    writer.write_record(headers)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Compares this run's threads CSV in `output_dir` against the latest one in `prev_dir`
+///
+/// Writes `new_threads_`, `removed_threads_` and `changed_threads_{screen_name}_{timestamp}.csv`
+/// to `output_dir`. New and changed threads use the current rows; removed threads
+/// use the previous rows. The previous run's date is shown in `timezone`
+/// (UTC when `None`).
+pub fn diff_against_previous(output_dir: &Path, prev_dir: &Path, screen_name: &str, timestamp: i64, timezone: Option<Tz>) -> Result<ThreadDiff> {
+    let Some((current_path, _)) = find_latest_threads_csv(output_dir, screen_name)? else {
+        bail!("No threads CSV found in {}", output_dir.display());
+    };
+    let Some((prev_path, prev_timestamp)) = find_latest_threads_csv(prev_dir, screen_name)? else {
+        bail!("No previous threads CSV for '{}' found in {}", screen_name, prev_dir.display());
+    };
+
+    let current = read_threads_csv(&current_path)?;
+    let previous = read_threads_csv(&prev_path)?;
+
+    let new_ids: Vec<&String> = current.order.iter()
+        .filter(|id| !previous.threads.contains_key(*id))
+        .collect();
+    let removed_ids: Vec<&String> = previous.order.iter()
+        .filter(|id| !current.threads.contains_key(*id))
+        .collect();
+    let changed_ids: Vec<&String> = current.order.iter()
+        .filter(|id| matches!(
+            (current.threads.get(*id), previous.threads.get(*id)),
+            (Some((_, now)), Some((_, before))) if now != before
+        ))
+        .collect();
+
+    let output_path = |kind: &str| output_dir.join(format!("{}_threads_{}_{}.csv", kind, screen_name, timestamp));
+    write_threads_subset(
+        &output_path("new"),
+        &current.headers,
+        new_ids.iter().flat_map(|id| current.threads[*id].0.iter()),
+    )?;
+    write_threads_subset(
+        &output_path("removed"),
+        &previous.headers,
+        removed_ids.iter().flat_map(|id| previous.threads[*id].0.iter()),
+    )?;
+    write_threads_subset(
+        &output_path("changed"),
+        &current.headers,
+        changed_ids.iter().flat_map(|id| current.threads[*id].0.iter()),
+    )?;
+
+    let prev_run_date = DateTime::from_timestamp(prev_timestamp, 0)
+        .map(|dt| match timezone {
+            Some(tz) => dt.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S %Z").to_string(),
+            None => dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        })
+        .unwrap_or_else(|| prev_timestamp.to_string());
+
+    Ok(ThreadDiff {
+        new: new_ids.len(),
+        removed: removed_ids.len(),
+        changed: changed_ids.len(),
+        prev_run_date,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_csv(path: &Path, rows: &[(&str, &str, &str)]) {
+        let mut content = String::from("tweet_id,thread_id,thread_tweet_count\n");
+        for (tweet_id, thread_id, count) in rows {
+            content.push_str(&format!("{},{},{}\n", tweet_id, thread_id, count));
+        }
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_find_latest_threads_csv() {
+        let dir = tempdir().unwrap();
+        write_csv(&dir.path().join("threads_me_100.csv"), &[]);
+        write_csv(&dir.path().join("threads_me_200.csv"), &[]);
+        write_csv(&dir.path().join("threads_other_300.csv"), &[]);
+        std::fs::write(dir.path().join("threads_me_400.txt"), "").unwrap();
+
+        let (path, timestamp) = find_latest_threads_csv(dir.path(), "me").unwrap().unwrap();
+
+        assert_eq!(timestamp, 200);
+        assert!(path.ends_with("threads_me_200.csv"));
+        assert!(find_latest_threads_csv(dir.path(), "nobody").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_diff_against_previous() {
+        let prev_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        write_csv(&prev_dir.path().join("threads_me_0.csv"), &[
+            ("1", "a", "1"),
+            ("2", "b", "1"),
+            ("3", "c", "1"),
+        ]);
+        write_csv(&output_dir.path().join("threads_me_86400.csv"), &[
+            ("1", "a", "1"),
+            ("2", "b", "2"),
+            ("4", "b", "2"),
+            ("5", "d", "1"),
+        ]);
+
+        let diff = diff_against_previous(output_dir.path(), prev_dir.path(), "me", 42, None).unwrap();
+
+        assert_eq!((diff.new, diff.removed, diff.changed), (1, 1, 1));
+        assert_eq!(diff.to_string(), "+1 new threads, -1 removed, ~1 changed since 1970-01-01 00:00:00 UTC");
+
+        let changed = std::fs::read_to_string(output_dir.path().join("changed_threads_me_42.csv")).unwrap();
+        assert_eq!(changed, "tweet_id,thread_id,thread_tweet_count\n2,b,2\n4,b,2\n");
+        let removed = std::fs::read_to_string(output_dir.path().join("removed_threads_me_42.csv")).unwrap();
+        assert!(removed.contains("3,c,1"));
+        let new = std::fs::read_to_string(output_dir.path().join("new_threads_me_42.csv")).unwrap();
+        assert!(new.contains("5,d,1"));
+
+        let local = diff_against_previous(output_dir.path(), prev_dir.path(), "me", 43, Some(chrono_tz::Asia::Tokyo)).unwrap();
+        assert_eq!(local.prev_run_date, "1970-01-01 09:00:00 JST");
+    }
+
+    #[test]
+    fn test_diff_against_missing_previous() {
+        let prev_dir = tempdir().unwrap();
+        let output_dir = tempdir().unwrap();
+        write_csv(&output_dir.path().join("threads_me_1.csv"), &[]);
+
+        assert!(diff_against_previous(output_dir.path(), prev_dir.path(), "me", 1, None).is_err());
+    }
+}
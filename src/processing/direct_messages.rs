@@ -1,32 +1,132 @@
 //! Direct message processing pipeline
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use csv::Writer as CsvWriterLib;
 use serde_json::from_str;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::BufWriter;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tokio::fs as async_fs;
 
 use crate::models::direct_message::DmWrapper;
-use crate::relationship::RelationshipAnalyzer;
+use crate::models::interaction::timeline_cache_key;
+use crate::relationship::{apply_mutual_contact_counts, compute_mutual_contacts, export_anki_deck_with, quarterly_sentiment, RelationshipAnalyzer};
+use crate::models::profile::{explain_contact_type, UserProfile};
+use crate::relationship::anonymization::{build_pseudonym_registry, hash_user_id, update_shared_registry, PseudonymRegistry, SharedPseudonymRegistry};
+use crate::utils::explain::ExplainLog;
+use crate::utils::{format_bytes, parse_twitter_date};
 use super::data_structures::ProcessedConversation;
-use super::dm_threads::{convert_dms_to_threads, format_dm_thread_as_text};
+use super::file_io::write_sentiment_timeline_csv;
+use super::dm_threads::{convert_dms_to_threads, merge_dm_files, default_content_warning_keywords, detect_content_warnings, format_dm_thread_as_text, PRIVACY_MODE_BANNER};
+
+/// Order of conversations in the DM conversations CSV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DmSortMode {
+    /// Most messages first (default)
+    #[default]
+    MessageCountFirst,
+    /// Most recent last message first; conversations without a parseable date go last
+    RecencyFirst,
+}
+
+impl std::str::FromStr for DmSortMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "count" => Ok(DmSortMode::MessageCountFirst),
+            "recency" => Ok(DmSortMode::RecencyFirst),
+            other => anyhow::bail!("Unknown DM sort order: {} (expected recency or count)", other),
+        }
+    }
+}
+
+/// Parsed `last_message_date` of a conversation
+fn last_message_time(conversation: &ProcessedConversation) -> Option<DateTime<Utc>> {
+    conversation.last_message_date.as_deref()
+        .and_then(|date| parse_twitter_date(date).ok())
+}
+
+/// Sorts conversations by `mode`; ties keep their current order
+pub fn sort_conversations(conversations: &mut [ProcessedConversation], mode: DmSortMode) {
+    match mode {
+        DmSortMode::MessageCountFirst => conversations.sort_by_key(|c| std::cmp::Reverse(c.message_count)),
+        // `None` sorts below every date, so reversing puts undated conversations last
+        DmSortMode::RecencyFirst => conversations.sort_by_key(|c| std::cmp::Reverse(last_message_time(c))),
+    }
+}
+
+/// 1-based position of each conversation (by ID) when sorted most recent first
+pub fn recency_ranks(conversations: &[ProcessedConversation]) -> HashMap<String, usize> {
+    let mut by_recency: Vec<&ProcessedConversation> = conversations.iter().collect();
+    by_recency.sort_by_key(|c| std::cmp::Reverse(last_message_time(c)));
+    by_recency.into_iter()
+        .enumerate()
+        .map(|(i, c)| (c.conversation_id.clone(), i + 1))
+        .collect()
+}
+
+/// Options for [`process_dm_file`] and [`process_all_dm_files`]
+#[derive(Debug, Clone)]
+pub struct DmOptions {
+    /// Twitter handle for output file naming
+    pub screen_name: String,
+    /// Directory to write output files
+    pub output_dir: PathBuf,
+    /// Timestamp for file naming
+    pub timestamp: i64,
+    /// Conversations with fewer messages are left out of every DM output: CSVs,
+    /// thread text, timeline, profiles, the Anki deck and summary totals
+    /// (`1` keeps every non-empty conversation)
+    pub min_messages: usize,
+    /// Hide message text in the DM threads text file, keeping its structure,
+    /// senders and timestamps
+    pub privacy_mode: bool,
+    /// Also write `anki_deck_{screen_name}_{timestamp}.tsv`, one flashcard per contact
+    pub anki_deck: bool,
+    /// Order of the conversations CSV
+    pub sort_mode: DmSortMode,
+    /// JSON file to load the interaction timeline from when it was built from
+    /// the same, unchanged DM file(s); otherwise the timeline is rebuilt and
+    /// saved there (`None` always rebuilds it without saving)
+    pub timeline_cache: Option<PathBuf>,
+}
+
+impl DmOptions {
+    /// Creates options that keep every non-empty conversation, sorted busiest
+    /// first, without privacy mode, an Anki deck or a timeline cache
+    pub fn new(screen_name: impl Into<String>, output_dir: impl Into<PathBuf>, timestamp: i64) -> Self {
+        Self {
+            screen_name: screen_name.into(),
+            output_dir: output_dir.into(),
+            timestamp,
+            min_messages: 1,
+            privacy_mode: false,
+            anki_deck: false,
+            sort_mode: DmSortMode::default(),
+            timeline_cache: None,
+        }
+    }
+}
 
 /// Processes direct messages from a JSON file and generates analysis
 /// 
 /// # Arguments
 /// 
 /// * `dm_file` - Path to the DM JSON file
-/// * `screen_name` - Twitter handle for output file naming
-/// * `output_dir` - Directory to write output files
-/// * `timestamp` - Timestamp for file naming
+/// * `options` - Output naming, filtering and caching options
+/// * `pseudonyms` - The run's shared pseudonym registry; `None` loads the
+///   registry file in the output directory instead
+/// * `explain` - Decision log to record dropped conversations and contact
+///   classifications in (`None` records nothing)
 /// 
 /// # Returns
 /// 
 /// Result indicating success or failure of the processing
-pub async fn process_dm_file(dm_file: &str, screen_name: &str, output_dir: &Path, timestamp: i64) -> Result<()> {
+pub async fn process_dm_file(dm_file: &str, options: &DmOptions, pseudonyms: Option<&SharedPseudonymRegistry>, explain: Option<&ExplainLog>) -> Result<()> {
     let start_time = Instant::now();
     
     println!("📱 Reading DM file...");
@@ -34,37 +134,55 @@ pub async fn process_dm_file(dm_file: &str, screen_name: &str, output_dir: &Path
         .with_context(|| format!("Failed to read DM file: {}", dm_file))?;
     
     println!("🔍 Parsing DM data...");
-    // Remove JavaScript assignment prefix if present (handle both formats)
-    let json_content = if let Some(stripped) = dm_content.strip_prefix("window.YTD.direct_messages.part0 = ") {
-        stripped
-    } else if let Some(stripped) = dm_content.strip_prefix("window.YTD.direct_message_headers.part0 = ") {
-        stripped
-    } else {
-        &dm_content
+    let dm_wrappers = parse_dm_wrappers(&dm_content)?;
+    let cache_key = match options.timeline_cache {
+        Some(_) => Some(timeline_cache_key(&[dm_file])?),
+        None => None,
     };
     
-    let dm_wrappers: Vec<DmWrapper> = from_str(json_content)
-        .context("Failed to parse DM JSON")?;
-        
-    // Create relationship analyzer for timeline analysis
-    let analyzer = RelationshipAnalyzer::new();
-    
-    // Build interaction timeline from DM data
-    let timeline = analyzer.build_timeline(&dm_wrappers, &[]);
+    process_dm_wrappers(dm_wrappers, dm_content.len() as u64, options, cache_key, pseudonyms, explain, start_time).await
+}
+
+/// Processes several DM part files (e.g. `direct-messages.js`,
+/// `direct-messages-part1.js`) as one archive
+/// 
+/// The files are merged with [`merge_dm_files`] first, so a conversation split
+/// across parts is analysed as a whole. Arguments are the same as
+/// [`process_dm_file`].
+pub async fn process_all_dm_files(paths: &[String], options: &DmOptions, pseudonyms: Option<&SharedPseudonymRegistry>, explain: Option<&ExplainLog>) -> Result<()> {
+    let start_time = Instant::now();
     
-    // Perform timeline analysis
-    let timeline_analysis = analyzer.analyze_timeline(&timeline);
+    println!("📱 Reading {} DM files...", paths.len());
+    let path_refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+    let dm_wrappers = merge_dm_files(&path_refs).await?;
     
-    // Print timeline analysis summary
-    println!("\n📊 Timeline Analysis Results:");
-    println!("  • Total interactions: {}", timeline_analysis.total_interactions);
-    println!("  • Unique participants: {}", timeline_analysis.unique_participants);
-    println!("  • Analysis patterns: {} detected", timeline_analysis.patterns.len());
-    println!("  • Average response time: {:.2} minutes", 
-             timeline_analysis.response_times.average / 60.0);
-    println!("  • Interactions per day: {:.2}", 
-             timeline_analysis.density.avg_interactions_per_day);
+    let mut input_bytes = 0;
+    for path in paths {
+        input_bytes += async_fs::metadata(path).await
+            .with_context(|| format!("Failed to read DM file: {}", path))?
+            .len();
+    }
+    let cache_key = match options.timeline_cache {
+        Some(_) => Some(timeline_cache_key(paths)?),
+        None => None,
+    };
     
+    process_dm_wrappers(dm_wrappers, input_bytes, options, cache_key, pseudonyms, explain, start_time).await
+}
+
+/// Shared pipeline once DM conversations are parsed; `input_bytes` is the size
+/// of the source file(s) reported in the summary, and `cache_key` their
+/// [`timeline_cache_key`] when `options.timeline_cache` is set
+async fn process_dm_wrappers(dm_wrappers: Vec<DmWrapper>, input_bytes: u64, options: &DmOptions, cache_key: Option<String>, pseudonyms: Option<&SharedPseudonymRegistry>, explain: Option<&ExplainLog>, start_time: Instant) -> Result<()> {
+    let screen_name = options.screen_name.as_str();
+    let output_dir = options.output_dir.as_path();
+    let timestamp = options.timestamp;
+    let min_messages = options.min_messages;
+    let (dm_wrappers, duplicate_conversations) = deduplicate_dm_conversations(dm_wrappers);
+    if duplicate_conversations > 0 {
+        println!("🧹 Deduplicated {} duplicate conversations", duplicate_conversations);
+    }
+        
     println!("💬 Processing {} conversations...", dm_wrappers.len());
     
     let mut conversations: Vec<ProcessedConversation> = dm_wrappers
@@ -95,8 +213,64 @@ pub async fn process_dm_file(dm_file: &str, screen_name: &str, output_dir: &Path
         .filter(|conv| conv.message_count > 0)
         .collect();
     
-    // Sort by message count (descending)
-    conversations.sort_by(|a, b| b.message_count.cmp(&a.message_count));
+    // Drop very short conversations (spam, notifications, dead starts) before
+    // anything is built from them, so every DM output sees the same set
+    let messages_before_filter: u32 = conversations.iter().map(|c| c.message_count).sum();
+    let conversations_before_filter = conversations.len();
+    if let Some(log) = explain {
+        for conversation in conversations.iter().filter(|c| (c.message_count as usize) < min_messages) {
+            log.record(format_args!("conversation {}", conversation.conversation_id),
+                       format_args!("dropped ({} messages, fewer than {})", conversation.message_count, min_messages))?;
+        }
+    }
+    conversations.retain(|c| c.message_count as usize >= min_messages);
+    let filter_stats = DmFilterStats {
+        duplicate_conversations,
+        min_messages,
+        filtered_conversations: conversations_before_filter - conversations.len(),
+        messages_before_filter,
+    };
+    if filter_stats.filtered_conversations > 0 {
+        println!("🧹 Filtered out {} conversations with fewer than {} messages",
+                 filter_stats.filtered_conversations, min_messages);
+    }
+    let kept: HashSet<&str> = conversations.iter().map(|c| c.conversation_id.as_str()).collect();
+    let dm_wrappers: Vec<DmWrapper> = dm_wrappers.into_iter()
+        .filter(|wrapper| kept.contains(wrapper.dm_conversation.conversation_id.as_str()))
+        .collect();
+    sort_conversations(&mut conversations, options.sort_mode);
+    
+    // Create relationship analyzer for timeline analysis
+    let mut analyzer = RelationshipAnalyzer::new();
+    
+    // Build interaction timeline from DM data, or reuse a cached one; the key
+    // covers `min_messages` since it decides which conversations are in it
+    let cache_key = cache_key.map(|key| format!("{}\nmin_messages {}", key, min_messages));
+    let timeline = match options.timeline_cache.as_deref().zip(cache_key) {
+        Some((cache_path, source_key)) => {
+            let (timeline, loaded) = analyzer.build_timeline_cached(&dm_wrappers, &[], cache_path, &source_key)?;
+            if loaded {
+                println!("♻️ Loaded {} timeline events from {}", timeline.len(), cache_path.display());
+            } else {
+                println!("💾 Cached {} timeline events in {}", timeline.len(), cache_path.display());
+            }
+            timeline
+        }
+        None => analyzer.build_timeline(&dm_wrappers, &[]),
+    };
+    
+    // Perform timeline analysis
+    let timeline_analysis = analyzer.analyze_timeline(&timeline);
+    
+    // Print timeline analysis summary
+    println!("\n📊 Timeline Analysis Results:");
+    println!("  • Total interactions: {}", timeline_analysis.total_interactions);
+    println!("  • Unique participants: {}", timeline_analysis.unique_participants);
+    println!("  • Analysis patterns: {} detected", timeline_analysis.patterns.len());
+    println!("  • Average response time: {:.2} minutes", 
+             timeline_analysis.response_times.average / 60.0);
+    println!("  • Interactions per day: {:.2}", 
+             timeline_analysis.density.avg_interactions_per_day);
     
     println!("📊 Writing DM results...");
     
@@ -104,7 +278,7 @@ pub async fn process_dm_file(dm_file: &str, screen_name: &str, output_dir: &Path
     write_dm_csv(&conversations, screen_name, timestamp, output_dir).await?;
     
     // Convert DMs to threads and write thread files
-    write_dm_threads(&dm_wrappers, screen_name, timestamp, output_dir).await?;
+    write_dm_threads(&dm_wrappers, screen_name, timestamp, output_dir, options.privacy_mode).await?;
     
     // Write timeline analysis to a separate CSV
     write_timeline_analysis_csv(&timeline_analysis, screen_name, timestamp, output_dir).await?;
@@ -112,13 +286,133 @@ pub async fn process_dm_file(dm_file: &str, screen_name: &str, output_dir: &Path
     // Write timeline analysis to TXT file
     write_timeline_analysis_txt(&timeline_analysis, screen_name, timestamp, output_dir).await?;
     
+    // Write how sentiment moved quarter by quarter
+    write_sentiment_timeline_csv(&quarterly_sentiment(&timeline), screen_name, timestamp, output_dir).await?;
+    
+    // Keep stable, readable pseudonyms for everyone in the DMs
+    let users = analyzer.extract_users_from_dms(&dm_wrappers);
+    let registry = match pseudonyms {
+        Some(shared) => update_shared_registry(shared, users.iter().map(String::as_str))?,
+        None => build_pseudonym_registry(output_dir, screen_name, users.iter().map(String::as_str))?,
+    };
+    
+    // Rank contacts by relationship strength
+    analyzer.build_profiles(&dm_wrappers, &[]);
+    let mutual_contacts = compute_mutual_contacts(&analyzer.profiles, &dm_wrappers);
+    apply_mutual_contact_counts(&mut analyzer.profiles, &mutual_contacts);
+    if let Some(log) = explain {
+        let mut profiles: Vec<&UserProfile> = analyzer.profiles.values().collect();
+        profiles.sort_by(|a, b| a.user_id.cmp(&b.user_id));
+        for profile in profiles {
+            log.record(format_args!("contact {}", hash_user_id(&profile.user_id)),
+                       explain_contact_type(profile, profile.tweet_reply_stats.total_replies))?;
+        }
+    }
+    write_mutual_contacts_csv(&mutual_contacts, &registry, screen_name, timestamp, output_dir)?;
+    write_relationships_csv(&analyzer, &dm_wrappers, &registry, screen_name, timestamp, output_dir).await?;
+    if options.anki_deck {
+        export_anki_deck_with(&analyzer, &timeline, &registry, &output_dir.join(format!("anki_deck_{}_{}.tsv", screen_name, timestamp)))?;
+    }
+    
     // Write summary file
-    write_dm_summary(&conversations, &timeline_analysis, screen_name, timestamp, output_dir, start_time).await?;
+    write_dm_summary(&conversations, &timeline_analysis, input_bytes, &filter_stats, options, start_time).await?;
     
     println!("✅ DM processing completed successfully!");
     Ok(())
 }
 
+/// Writes one row per DM contact with its strength score, strongest first
+async fn write_relationships_csv(
+    analyzer: &RelationshipAnalyzer,
+    dm_wrappers: &[DmWrapper],
+    registry: &PseudonymRegistry,
+    screen_name: &str,
+    timestamp: i64,
+    output_dir: &Path,
+) -> Result<()> {
+    let mut rows: Vec<(&UserProfile, f64)> = analyzer.profiles.values()
+        .filter(|profile| profile.total_interactions > 0)
+        .map(|profile| {
+            // Only the contact's own conversations count towards their frequency
+            let own_conversations: Vec<DmWrapper> = dm_wrappers.iter()
+                .filter(|w| w.dm_conversation.conversation_id.split('-').any(|id| id == profile.user_id))
+                .cloned()
+                .collect();
+            let freq = analyzer.calculate_communication_frequency(&profile.user_id, &own_conversations);
+            (profile, analyzer.relationship_strength_score(profile, &freq))
+        })
+        .collect();
+    rows.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.user_id.cmp(&b.0.user_id)));
+    // Dormancy is judged as of this run
+    let run_time = DateTime::from_timestamp(timestamp, 0).unwrap_or_else(Utc::now);
+    let lifecycle = analyzer.detect_relationship_lifecycle_as_of(run_time);
+
+    let csv_path = output_dir.join(format!("relationships_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&csv_path)
+        .with_context(|| format!("Failed to create file: {}", csv_path.display()))?;
+    let mut csv_writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    csv_writer.write_record([
+        "Contact",
+        "User Hash",
+        "Total Interactions",
+        "Last Interaction",
+        "Strength Score",
+        "Contact Type",
+        "Stage",
+        "Trend %",
+    ])?;
+
+    for (profile, score) in rows {
+        let hash = hash_user_id(&profile.user_id);
+        csv_writer.write_record([
+            registry.get(&hash).unwrap_or("Unknown").to_string(),
+            hash,
+            profile.total_interactions.to_string(),
+            profile.last_interaction.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            format!("{:.3}", score),
+            profile.contact_type.to_string(),
+            lifecycle.get(&profile.user_id).map(|l| l.stage.to_string()).unwrap_or_default(),
+            lifecycle.get(&profile.user_id).map(|l| format!("{:.1}", l.trend_pct)).unwrap_or_default(),
+        ])?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
+/// Number of pairs listed in the mutual contacts CSV
+const MUTUAL_CONTACTS_TOP_PAIRS: usize = 10;
+
+/// Writes the pairs of contacts sharing the most conversations, most first
+fn write_mutual_contacts_csv(
+    mutual_contacts: &HashMap<(String, String), usize>,
+    registry: &PseudonymRegistry,
+    screen_name: &str,
+    timestamp: i64,
+    output_dir: &Path,
+) -> Result<()> {
+    let mut pairs: Vec<(&(String, String), &usize)> = mutual_contacts.iter().collect();
+    pairs.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+    let csv_path = output_dir.join(format!("mutual_contacts_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&csv_path)
+        .with_context(|| format!("Failed to create file: {}", csv_path.display()))?;
+    let mut csv_writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    csv_writer.write_record(["Contact A", "Contact B", "Shared Conversations"])?;
+    let contact = |user_id: &str| {
+        let hash = hash_user_id(user_id);
+        registry.get(&hash).map(str::to_string).unwrap_or(hash)
+    };
+    for ((a, b), shared) in pairs.into_iter().take(MUTUAL_CONTACTS_TOP_PAIRS) {
+        csv_writer.write_record([contact(a), contact(b), shared.to_string()])?;
+    }
+
+    csv_writer.flush()?;
+    Ok(())
+}
+
 /// Writes DM conversations to CSV file
 async fn write_dm_csv(
     conversations: &[ProcessedConversation], 
@@ -136,14 +430,17 @@ async fn write_dm_csv(
         "Message Count", 
         "First Message Date",
         "Last Message Date",
+        "Recency Rank",
     ])?;
     
+    let ranks = recency_ranks(conversations);
     for conv in conversations {
         csv_writer.write_record([
             &conv.conversation_id,
             &conv.message_count.to_string(),
             conv.first_message_date.as_deref().unwrap_or("N/A"),
             conv.last_message_date.as_deref().unwrap_or("N/A"),
+            &ranks[&conv.conversation_id].to_string(),
         ])?;
     }
     csv_writer.flush()?;
@@ -156,10 +453,15 @@ async fn write_dm_threads(
     dm_wrappers: &[DmWrapper],
     screen_name: &str,
     timestamp: i64,
-    output_dir: &Path
+    output_dir: &Path,
+    privacy_mode: bool,
 ) -> Result<()> {
     // Convert DMs to threads
-    let dm_threads = convert_dms_to_threads(dm_wrappers);
+    let mut dm_threads = convert_dms_to_threads(dm_wrappers);
+    let warning_keywords = default_content_warning_keywords();
+    for thread in &mut dm_threads {
+        thread.content_warnings = detect_content_warnings(thread, &warning_keywords);
+    }
     
     if dm_threads.is_empty() {
         println!("⚠️  No DM threads to write");
@@ -180,20 +482,32 @@ async fn write_dm_threads(
         "Avg Response Time (seconds)",
         "Start Time",
         "End Time",
-        "Participants"
+        "Participants",
+        "Content Warnings",
+        "Avg Msg Length",
+        "Longest Message (chars)",
+        "Shortest Message (chars)",
+        "Group Name",
+        "Media Messages"
     ])?;
     
     // Write thread data
     for thread in &dm_threads {
         csv_writer.write_record([
             &thread.thread_id,
-            &thread.participant_count.to_string(),
+            &thread.participant_count().to_string(),
             &thread.metadata.message_count.to_string(),
             &thread.metadata.duration_seconds.map_or("N/A".to_string(), |d| d.to_string()),
             &thread.metadata.avg_response_time.map_or("N/A".to_string(), |t| format!("{:.2}", t)),
             &thread.metadata.start_time.map_or("N/A".to_string(), |t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
             &thread.metadata.end_time.map_or("N/A".to_string(), |t| t.format("%Y-%m-%d %H:%M:%S").to_string()),
-            &thread.participants.join(";")
+            &thread.participants.join(";"),
+            &thread.content_warnings.join(";"),
+            &format!("{:.1}", thread.average_message_length),
+            &thread.longest_message_chars.to_string(),
+            &thread.shortest_message_chars.to_string(),
+            thread.group_name.as_deref().unwrap_or_default(),
+            &thread.media_message_count.to_string()
         ])?;
     }
     csv_writer.flush()?;
@@ -202,6 +516,9 @@ async fn write_dm_threads(
     let txt_path = output_dir.join(format!("dm_threads_{}_{}.txt", screen_name, timestamp));
     let mut txt_content = String::new();
     
+    if privacy_mode {
+        txt_content.push_str(&format!("{}\n\n", PRIVACY_MODE_BANNER));
+    }
     txt_content.push_str("📱 DIRECT MESSAGE THREADS\n");
     txt_content.push_str(&format!("{}\n\n", "=".repeat(50)));
     txt_content.push_str(&format!("Total threads: {}\n", dm_threads.len()));
@@ -209,7 +526,7 @@ async fn write_dm_threads(
     txt_content.push_str(&format!("{}\n\n", "=".repeat(50)));
     
     for thread in &dm_threads {
-        txt_content.push_str(&format_dm_thread_as_text(thread));
+        txt_content.push_str(&format_dm_thread_as_text(thread, privacy_mode));
         txt_content.push('\n');
     }
     
@@ -286,7 +603,10 @@ async fn write_timeline_analysis_txt(
     writeln!(timeline_txt_writer, "| {:<36} | {:>35} |", "Patterns Detected", timeline_analysis.patterns.len())?;
     writeln!(timeline_txt_writer, "| {:<36} | {:>35.2} min |", "Avg Response Time", timeline_analysis.response_times.average / 60.0)?;
     writeln!(timeline_txt_writer, "| {:<36} | {:>35.2} min |", "Median Response Time", timeline_analysis.response_times.median / 60.0)?;
+    writeln!(timeline_txt_writer, "| {:<36} | {:>35.2} min |", "P90 Response Time", timeline_analysis.response_times.percentiles.get("p90").copied().unwrap_or(0.0) / 60.0)?;
     writeln!(timeline_txt_writer, "| {:<36} | {:>35.2} |", "Interactions Per Day", timeline_analysis.density.avg_interactions_per_day)?;
+    writeln!(timeline_txt_writer, "| {:<36} | {:>35.2} |", "Daily Variance", timeline_analysis.density.daily_variance)?;
+    writeln!(timeline_txt_writer, "| {:<36} | {:>35} |", "Bursty Hours", timeline_analysis.bursty_periods)?;
     
     // Peak Activity
     writeln!(timeline_txt_writer, "\n{:-<40}", " Peak Activity ")?;
@@ -297,15 +617,28 @@ async fn write_timeline_analysis_txt(
     Ok(())
 }
 
+/// Conversations dropped before writing DM results
+struct DmFilterStats {
+    /// Repeated conversation IDs that were merged
+    duplicate_conversations: usize,
+    /// Minimum message count a conversation needed to be kept
+    min_messages: usize,
+    /// Conversations dropped for having fewer than `min_messages` messages
+    filtered_conversations: usize,
+    /// Total messages across all conversations before the `min_messages` filter
+    messages_before_filter: u32,
+}
+
 /// Writes DM processing summary
 async fn write_dm_summary(
     conversations: &[ProcessedConversation],
     timeline_analysis: &crate::models::timeline::TimelineAnalysis,
-    screen_name: &str, 
-    timestamp: i64, 
-    output_dir: &Path,
+    input_size: u64,
+    filter_stats: &DmFilterStats,
+    options: &DmOptions,
     start_time: Instant
 ) -> Result<()> {
+    let screen_name = options.screen_name.as_str();
     let total_messages: u32 = conversations.iter().map(|c| c.message_count).sum();
     let duration = start_time.elapsed();
     
@@ -313,8 +646,12 @@ async fn write_dm_summary(
         "DM Processing Summary\n\
          ====================\n\
          Screen Name: {}\n\
+         Input Size: {}\n\
          Total Conversations: {}\n\
+         Deduplicated {} duplicate conversations\n\
+         Filtered out {} conversations with fewer than {} messages\n\
          Total Messages: {}\n\
+         Total Messages (before filtering): {}\n\
          Total Interactions: {}\n\
          Unique Participants: {}\n\
          Processing Duration: {:.2} seconds\n\
@@ -327,8 +664,13 @@ async fn write_dm_summary(
          ====================\n\
          Status: Processing Completed Successfully",
         screen_name,
+        format_bytes(input_size),
         conversations.len(),
+        filter_stats.duplicate_conversations,
+        filter_stats.filtered_conversations,
+        filter_stats.min_messages,
         total_messages,
+        filter_stats.messages_before_filter,
         timeline_analysis.total_interactions,
         timeline_analysis.unique_participants,
         duration.as_secs_f64(),
@@ -338,17 +680,74 @@ async fn write_dm_summary(
         timeline_analysis.patterns.len()
     );
 
-    let summary_path = output_dir.join(format!("dm_results_{}_{}.txt", screen_name, timestamp));
+    let summary_path = options.output_dir.join(format!("dm_results_{}_{}.txt", screen_name, options.timestamp));
     async_fs::write(&summary_path, summary_content).await.context("Failed to write DM summary file")?;
     
     Ok(())
 }
 
+/// Parses the contents of a DM archive file into conversations
+///
+/// Accepts `direct-messages.js` and `direct-message-headers.js` contents, and
+/// their `-part1`, `-part2`, ... files, with their JavaScript assignment prefix
+/// (`window.YTD.<name>.partN = `), or plain JSON.
+pub fn parse_dm_wrappers(dm_content: &str) -> Result<Vec<DmWrapper>> {
+    // Remove JavaScript assignment prefix if present (any export name and part number)
+    let json_content = match dm_content.strip_prefix("window.YTD.").and_then(|rest| rest.split_once('=')) {
+        Some((_, stripped)) => stripped.trim_start(),
+        None => dm_content,
+    };
+    
+    from_str(json_content).context("Failed to parse DM JSON")
+}
+
+/// Returns true if any message in the conversation carries text
+///
+/// Conversations loaded from `direct-message-headers.js` have no text, so this
+/// tells a full `direct-messages.js` conversation apart from its headers-only copy.
+fn has_message_text(wrapper: &DmWrapper) -> bool {
+    wrapper.dm_conversation.messages.iter()
+        .any(|m| m.message_create.as_ref().and_then(|mc| mc.text.as_ref()).is_some())
+}
+
+/// Drops conversations that appear more than once, e.g. when both
+/// `direct-messages.js` and `direct-message-headers.js` were loaded
+///
+/// The version with message text is kept; otherwise the first one wins.
+/// Returns the remaining conversations in first-seen order and the number dropped.
+pub fn deduplicate_dm_conversations(dm_data: Vec<DmWrapper>) -> (Vec<DmWrapper>, usize) {
+    let mut seen_conversation_ids: HashMap<String, usize> = HashMap::new();
+    let mut unique: Vec<DmWrapper> = Vec::new();
+    let mut duplicates = 0;
+
+    for wrapper in dm_data {
+        match seen_conversation_ids.get(&wrapper.dm_conversation.conversation_id) {
+            Some(&index) => {
+                duplicates += 1;
+                if !has_message_text(&unique[index]) && has_message_text(&wrapper) {
+                    unique[index] = wrapper;
+                }
+            }
+            None => {
+                seen_conversation_ids.insert(wrapper.dm_conversation.conversation_id.clone(), unique.len());
+                unique.push(wrapper);
+            }
+        }
+    }
+
+    (unique, duplicates)
+}
+
 /// Simple DM processing function for testing
-pub async fn process_dm_conversations(dm_data: &[DmWrapper], _screen_name: &str) -> Result<Vec<ProcessedConversation>> {
+///
+/// Conversations that appear more than once are processed once, preferring
+/// the copy with message text (see `deduplicate_dm_conversations`). The result
+/// is ordered by `sort_mode`.
+pub async fn process_dm_conversations(dm_data: &[DmWrapper], _screen_name: &str, sort_mode: DmSortMode) -> Result<Vec<ProcessedConversation>> {
+    let (unique, _) = deduplicate_dm_conversations(dm_data.to_vec());
     let mut conversations = Vec::new();
     
-    for dm_wrapper in dm_data {
+    for dm_wrapper in &unique {
         let conversation = &dm_wrapper.dm_conversation;
         
         // Skip empty conversations
@@ -377,8 +776,7 @@ pub async fn process_dm_conversations(dm_data: &[DmWrapper], _screen_name: &str)
         conversations.push(processed);
     }
     
-    // Sort by message count (descending)
-    conversations.sort_by(|a, b| b.message_count.cmp(&a.message_count));
+    sort_conversations(&mut conversations, sort_mode);
     
     Ok(conversations)
 }
@@ -399,9 +797,9 @@ mod tests {
         // but tests the function signature and basic structure
         let result = process_dm_file(
             "nonexistent_file.js",
-            "testuser",
-            output_dir,
-            1234567890
+            &DmOptions::new("testuser", output_dir, 1234567890),
+            None,
+            None
         ).await;
         
         // Should fail due to missing file, but not due to compilation issues
@@ -438,9 +836,9 @@ mod tests {
         
         let result = process_dm_file(
             dm_file_path.to_str().unwrap(),
-            "testuser",
-            output_dir,
-            1234567890
+            &DmOptions::new("testuser", output_dir, 1234567890),
+            None,
+            None
         ).await;
         
         // Should succeed with proper JavaScript prefix handling
@@ -452,6 +850,14 @@ mod tests {
         
         assert!(csv_file.exists());
         assert!(summary_file.exists());
+        let conversations_csv = std::fs::read_to_string(&csv_file).unwrap();
+        assert!(conversations_csv.starts_with("Conversation ID,Message Count,First Message Date,Last Message Date,Recency Rank\n"));
+        assert!(conversations_csv.contains("test-123,1,2023-01-01T10:00:00.000Z,2023-01-01T10:00:00.000Z,1\n"));
+        
+        let relationships = std::fs::read_to_string(output_dir.join("relationships_testuser_1234567890.csv")).unwrap();
+        assert!(relationships.starts_with("Contact,User Hash,Total Interactions,Last Interaction,Strength Score,Contact Type,Stage,Trend %\n"));
+        assert!(relationships.lines().skip(1).all(|row| row.ends_with(",Stable,0.0")));
+        assert!(relationships.lines().count() > 1);
     }
 
     #[test]
@@ -484,12 +890,162 @@ mod tests {
         
         let result = process_dm_file(
             dm_file_path.to_str().unwrap(),
-            "testuser",
-            output_dir,
-            1234567890
+            &DmOptions::new("testuser", output_dir, 1234567890),
+            None,
+            None
         ).await;
         
         // Should handle empty files gracefully
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_duplicate_conversations_prefer_full_text() {
+        let json = r#"[
+  { "dmConversation": { "conversationId": "1-2", "messages": [
+    { "messageCreate": { "id": "h1", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" } }
+  ] } },
+  { "dmConversation": { "conversationId": "1-2", "messages": [
+    { "messageCreate": { "id": "m1", "text": "Hi", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" } },
+    { "messageCreate": { "id": "m2", "text": "Hey", "createdAt": "2023-01-01T10:05:00.000Z", "senderId": "2", "recipientId": "1" } }
+  ] } },
+  { "dmConversation": { "conversationId": "1-3", "messages": [
+    { "messageCreate": { "id": "m3", "text": "Yo", "createdAt": "2023-01-02T10:00:00.000Z", "senderId": "1", "recipientId": "3" } }
+  ] } }
+]"#;
+        let dm_data: Vec<DmWrapper> = from_str(json).unwrap();
+
+        let conversations = process_dm_conversations(&dm_data, "testuser", DmSortMode::default()).await.unwrap();
+        assert_eq!(conversations.len(), 2);
+        assert_eq!(conversations[0].conversation_id, "1-2");
+        assert_eq!(conversations[0].message_count, 2);
+        assert_eq!(conversations[1].conversation_id, "1-3");
+
+        let (unique, duplicates) = deduplicate_dm_conversations(dm_data);
+        assert_eq!(duplicates, 1);
+        assert_eq!(unique.len(), 2);
+        assert_eq!(unique[0].dm_conversation.messages.len(), 2);
+        assert_eq!(unique[1].dm_conversation.conversation_id, "1-3");
+    }
+
+    #[tokio::test]
+    async fn test_summary_reports_deduplicated_conversations() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+        let conversation = r#"{ "dmConversation": { "conversationId": "1-2", "messages": [
+    { "messageCreate": { "id": "m1", "text": "Hi", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" } }
+  ] } }"#;
+        let dm_file_path = output_dir.join("dup_dm.js");
+        fs::write(&dm_file_path, format!("[{},{}]", conversation, conversation)).unwrap();
+
+        process_dm_file(dm_file_path.to_str().unwrap(), &DmOptions::new("testuser", output_dir, 42), None, None).await.unwrap();
+
+        let summary = fs::read_to_string(output_dir.join("dm_results_testuser_42.txt")).unwrap();
+        assert!(summary.contains("Deduplicated 1 duplicate conversations"));
+        assert!(!output_dir.join("anki_deck_testuser_42.tsv").exists());
+    }
+
+    #[tokio::test]
+    async fn test_min_messages_filters_short_conversations() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+        let message = |id: &str| format!(r#"{{ "messageCreate": {{ "id": "{}", "text": "Hi", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" }} }}"#, id);
+        let content = format!(
+            r#"[{{ "dmConversation": {{ "conversationId": "1-2", "messages": [{}, {}, {}] }} }},
+                {{ "dmConversation": {{ "conversationId": "1-3", "messages": [{}] }} }}]"#,
+            message("a"), message("b"), message("c"), message("d"));
+        let dm_file_path = output_dir.join("short_dm.js");
+        fs::write(&dm_file_path, content).unwrap();
+
+        process_dm_file(dm_file_path.to_str().unwrap(), &DmOptions { min_messages: 2, anki_deck: true, ..DmOptions::new("testuser", output_dir, 42) }, None, None).await.unwrap();
+
+        let summary = fs::read_to_string(output_dir.join("dm_results_testuser_42.txt")).unwrap();
+        assert!(summary.contains("Total Conversations: 1\n"));
+        assert!(summary.contains("Filtered out 1 conversations with fewer than 2 messages"));
+        assert!(summary.contains("Total Messages: 3\n"));
+        assert!(summary.contains("Total Messages (before filtering): 4\n"));
+        let csv = fs::read_to_string(output_dir.join("dm_conversations_testuser_42.csv")).unwrap();
+        assert!(!csv.contains("1-3"));
+        // The dropped conversation is left out of every other output too
+        let threads = fs::read_to_string(output_dir.join("dm_threads_testuser_42.txt")).unwrap();
+        assert_eq!(threads.matches("💬 Conversation").count(), 1);
+        let relationships = fs::read_to_string(output_dir.join("relationships_testuser_42.csv")).unwrap();
+        assert!(relationships.contains(&hash_user_id("2")));
+        assert!(!relationships.contains(&hash_user_id("3")));
+        let anki = fs::read_to_string(output_dir.join("anki_deck_testuser_42.tsv")).unwrap();
+        assert_eq!(anki.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_privacy_mode_hides_thread_text() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+        let content = r#"[{ "dmConversation": { "conversationId": "1-2", "messages": [
+    { "messageCreate": { "id": "m1", "text": "my secret plans", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" } }
+  ] } }]"#;
+        let dm_file_path = output_dir.join("private_dm.js");
+        fs::write(&dm_file_path, content).unwrap();
+
+        process_dm_file(dm_file_path.to_str().unwrap(), &DmOptions { privacy_mode: true, ..DmOptions::new("testuser", output_dir, 42) }, None, None).await.unwrap();
+
+        let threads = fs::read_to_string(output_dir.join("dm_threads_testuser_42.txt")).unwrap();
+        assert!(threads.starts_with(PRIVACY_MODE_BANNER));
+        assert!(threads.contains("User 1: [2023-01-01 10:00:00 UTC] [message hidden]"));
+        assert!(!threads.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_process_all_dm_files_merges_parts() {
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+        let conversation = |part: u32, id: &str, message_id: &str| format!(
+            r#"window.YTD.direct_messages.part{} = [{{ "dmConversation": {{ "conversationId": "{}", "messages": [
+    {{ "messageCreate": {{ "id": "{}", "text": "Hi", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" }} }}
+  ] }} }}]"#, part, id, message_id);
+        let first = output_dir.join("direct-messages.js");
+        let second = output_dir.join("direct-messages-part1.js");
+        fs::write(&first, conversation(0, "1-2", "m1")).unwrap();
+        fs::write(&second, conversation(1, "1-3", "m2")).unwrap();
+        let paths = vec![first.to_string_lossy().into_owned(), second.to_string_lossy().into_owned()];
+
+        process_all_dm_files(&paths, &DmOptions::new("testuser", output_dir, 42), None, None).await.unwrap();
+
+        let summary = fs::read_to_string(output_dir.join("dm_results_testuser_42.txt")).unwrap();
+        assert!(summary.contains("Total Conversations: 2\n"));
+        assert!(process_all_dm_files(&["/nonexistent/direct-messages.js".to_string()], &DmOptions::new("testuser", output_dir, 43), None, None).await.is_err());
+    }
+
+    #[test]
+    fn test_sort_conversations_by_recency() {
+        let conversation = |id: &str, count: u32, last: Option<&str>| ProcessedConversation {
+            conversation_id: id.to_string(),
+            message_count: count,
+            participants: vec![],
+            first_message_date: None,
+            last_message_date: last.map(str::to_string),
+        };
+        let mut conversations = vec![
+            conversation("old-busy", 500, Some("2019-05-01T10:00:00.000Z")),
+            conversation("undated", 50, Some("not a date")),
+            conversation("recent", 3, Some("2024-02-01T10:00:00.000Z")),
+            conversation("middle", 20, Some("2022-07-01T10:00:00.000Z")),
+        ];
+        let ids = |conversations: &[ProcessedConversation]| -> Vec<String> {
+            conversations.iter().map(|c| c.conversation_id.clone()).collect()
+        };
+
+        sort_conversations(&mut conversations, DmSortMode::RecencyFirst);
+        assert_eq!(ids(&conversations), vec!["recent", "middle", "old-busy", "undated"]);
+
+        sort_conversations(&mut conversations, DmSortMode::MessageCountFirst);
+        assert_eq!(ids(&conversations), vec!["old-busy", "undated", "middle", "recent"]);
+
+        let ranks = recency_ranks(&conversations);
+        assert_eq!(ranks["recent"], 1);
+        assert_eq!(ranks["old-busy"], 3);
+        assert_eq!(ranks["undated"], 4);
+
+        assert_eq!("Recency".parse::<DmSortMode>().unwrap(), DmSortMode::RecencyFirst);
+        assert!("newest".parse::<DmSortMode>().is_err());
+    }
+}
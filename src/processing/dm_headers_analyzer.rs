@@ -1,7 +1,9 @@
 use crate::models::dm_headers::{DmHeaderWrapper, DmHeaderMessage};
+use crate::processing::data_structures::ProcessedConversation;
 use crate::processing::mvp_analyzer::SimpleRelationship;
+use crate::utils::{parse_twitter_date, truncate_to_utf8_boundary};
 use anyhow::{Result, Context};
-use chrono::{DateTime, Utc, Timelike, Weekday, Datelike};
+use chrono::{Timelike, Weekday, Datelike};
 use std::collections::HashMap;
 use tokio::fs;
 
@@ -44,7 +46,7 @@ impl DmHeadersAnalyzer {
         // Try parsing with better error handling
         let dm_data: Vec<DmHeaderWrapper> = serde_json::from_str(&json_content)
             .with_context(|| {
-                let sample = &json_content[..json_content.len().min(200)];
+                let sample = truncate_to_utf8_boundary(&json_content, 200);
                 format!("Failed to parse DM headers JSON. Sample: {}", sample)
             })?;
 
@@ -103,8 +105,7 @@ impl DmHeadersAnalyzer {
         self.total_messages += 1;
 
         // Parse timestamp for activity analysis
-        if let Ok(timestamp) = DateTime::parse_from_rfc3339(&msg_create.created_at) {
-            let utc_time: DateTime<Utc> = timestamp.into();
+        if let Ok(utc_time) = parse_twitter_date(&msg_create.created_at) {
             
             // Track hourly activity
             let hour = utc_time.hour();
@@ -166,6 +167,21 @@ impl DmHeadersAnalyzer {
     }
 }
 
+/// Summarize header conversations for the standard conversation output pipeline
+///
+/// Conversations without messages are dropped; the rest are sorted by message
+/// count (descending), matching the full DM processing output.
+pub fn summarize_headers(headers: Vec<DmHeaderWrapper>) -> Vec<ProcessedConversation> {
+    let mut conversations: Vec<ProcessedConversation> = headers
+        .into_iter()
+        .map(|wrapper| ProcessedConversation::from(wrapper.dm_conversation))
+        .filter(|conv| conv.message_count > 0)
+        .collect();
+    
+    conversations.sort_by_key(|c| std::cmp::Reverse(c.message_count));
+    conversations
+}
+
 /// Analysis results from DM headers processing
 #[derive(Debug, Clone)]
 pub struct DmHeadersAnalysisResult {
@@ -196,4 +212,32 @@ impl Default for DmHeadersAnalyzer {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_headers() {
+        let json = r#"[
+            { "dmConversation": { "conversationId": "1-2", "messages": [
+                { "messageCreate": { "id": "a", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "2" } }
+            ] } },
+            { "dmConversation": { "conversationId": "1-3", "messages": [] } },
+            { "dmConversation": { "conversationId": "1-4", "messages": [
+                { "messageCreate": { "id": "b", "createdAt": "2023-01-01T10:00:00.000Z", "senderId": "1", "recipientId": "4" } },
+                { "messageCreate": { "id": "c", "createdAt": "2023-01-01T11:00:00.000Z", "senderId": "4", "recipientId": "1" } }
+            ] } }
+        ]"#;
+        let headers: Vec<DmHeaderWrapper> = serde_json::from_str(json).unwrap();
+
+        let summary = summarize_headers(headers);
+
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].conversation_id, "1-4");
+        assert_eq!(summary[0].message_count, 2);
+        assert_eq!(summary[0].participants, vec!["1", "4"]);
+        assert_eq!(summary[1].conversation_id, "1-2");
+    }
+}
@@ -1,24 +1,52 @@
 //! Direct Message thread conversion module
 //! Converts DM conversations to thread-like structures similar to tweet threads
 
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tokio::fs as async_fs;
 
-use crate::models::direct_message::{DmWrapper, DmConversation};
+use crate::models::direct_message::{DmMessage, DmWrapper, DmConversation};
+use crate::models::dm_headers::{DmParticipantEvent, DmParticipantEventType};
+use crate::relationship::anonymization::hash_user_id;
+use crate::relationship::topics::{cosine_similarity, tfidf_vectors};
+use crate::utils::parse_twitter_date;
+use super::direct_messages::parse_dm_wrappers;
 
 /// Represents a DM thread with structured conversation flow
 #[derive(Debug, Clone)]
 pub struct DmThread {
     /// Unique thread identifier
     pub thread_id: String,
-    /// Number of participants in the conversation
-    pub participant_count: usize,
-    /// Participant IDs
+    /// Name of the group conversation, if it has one
+    pub group_name: Option<String>,
+    /// Hashed IDs of everyone who sent a message, deduplicated in order of first message
     pub participants: Vec<String>,
     /// Messages in chronological order
     pub messages: Vec<DmThreadMessage>,
     /// Thread metadata
     pub metadata: ThreadMetadata,
+    /// Content warning categories triggered by the messages (see `detect_content_warnings`)
+    pub content_warnings: Vec<String>,
+    /// Mean message length in characters (see [`compute_avg_message_length`])
+    pub average_message_length: f64,
+    /// Length of the longest message in characters
+    pub longest_message_chars: usize,
+    /// Length of the shortest message in characters
+    pub shortest_message_chars: usize,
+    /// Number of messages with media attached (see [`has_media_attachment`])
+    pub media_message_count: usize,
+    /// Users joining or leaving the group, oldest first
+    pub participant_events: Vec<DmParticipantEvent>,
+    /// Indices of messages that start a new topic (see [`detect_topic_shifts`])
+    pub topic_shift_points: Vec<usize>,
+}
+
+impl DmThread {
+    /// Number of distinct senders in the conversation
+    pub fn participant_count(&self) -> usize {
+        self.participants.len()
+    }
 }
 
 /// Individual message in a DM thread
@@ -73,20 +101,16 @@ fn convert_single_dm_to_thread(conversation: DmConversation) -> Option<DmThread>
         return None;
     }
     
-                // Extract participants
-            let mut participants = HashMap::new();
-            for msg in &valid_messages {
-                if let Some(mc) = &msg.message_create {
-                    if let Some(sender_id) = &mc.sender_id {
-                        participants.insert(sender_id.clone(), true);
-                    }
-                    if let Some(recipient) = &mc.recipient_id {
-                        participants.insert(recipient.clone(), true);
-                    }
-                }
+    // Extract distinct senders (hashed), keeping first-appearance order
+    let mut participant_list: Vec<String> = Vec::new();
+    for msg in &valid_messages {
+        if let Some(sender_id) = msg.message_create.as_ref().and_then(|mc| mc.sender_id.as_ref()) {
+            let hashed = hash_user_id(sender_id);
+            if !participant_list.contains(&hashed) {
+                participant_list.push(hashed);
             }
-    
-    let participant_list: Vec<String> = participants.keys().cloned().collect();
+        }
+    }
     
     // Convert messages to thread messages
     let mut thread_messages = Vec::new();
@@ -94,13 +118,7 @@ fn convert_single_dm_to_thread(conversation: DmConversation) -> Option<DmThread>
     
     for (idx, msg) in valid_messages.iter().enumerate() {
         if let Some(mc) = &msg.message_create {
-            let timestamp = mc.created_at.as_ref()
-                .and_then(|ts| {
-                    // Try ISO 8601 format first (real data format)
-                    DateTime::parse_from_rfc3339(ts).ok()
-                        .or_else(|| DateTime::parse_from_str(ts, "%a %b %d %H:%M:%S %z %Y").ok())
-                })
-                .map(|dt| dt.with_timezone(&Utc));
+            let timestamp = mc.created_at.as_deref().and_then(parse_message_timestamp);
             
             if let Some(ts) = &timestamp {
                 timestamps.push(*ts);
@@ -127,13 +145,212 @@ fn convert_single_dm_to_thread(conversation: DmConversation) -> Option<DmThread>
     // Calculate metadata
     let metadata = calculate_thread_metadata(&thread_messages, &timestamps);
     
-    Some(DmThread {
+    let media_message_count = valid_messages.iter().filter(|msg| has_media_attachment(msg)).count();
+    let lengths = thread_messages.iter().map(|m| m.text.chars().count());
+    let longest_message_chars = lengths.clone().max().unwrap_or(0);
+    let shortest_message_chars = lengths.min().unwrap_or(0);
+    let mut participant_events: Vec<DmParticipantEvent> = conversation.join_events.into_iter()
+        .chain(conversation.leave_events)
+        .collect();
+    participant_events.sort_by_key(|event| event.created_at);
+    let mut thread = DmThread {
         thread_id: format!("dm_{}", conversation.conversation_id),
-        participant_count: participant_list.len(),
+        group_name: conversation.group_name,
         participants: participant_list,
         messages: thread_messages,
         metadata,
-    })
+        content_warnings: Vec::new(),
+        average_message_length: 0.0,
+        longest_message_chars,
+        shortest_message_chars,
+        media_message_count,
+        participant_events,
+        topic_shift_points: Vec::new(),
+    };
+    thread.average_message_length = compute_avg_message_length(&thread);
+    thread.topic_shift_points = detect_topic_shifts(&thread, TOPIC_SHIFT_WINDOW);
+    Some(thread)
+}
+
+/// Whether a DM has any media (images, video, GIFs) attached
+pub fn has_media_attachment(message: &DmMessage) -> bool {
+    message.message_create.as_ref()
+        .is_some_and(|mc| !mc.media_urls.is_empty())
+}
+
+/// Parses a DM `createdAt` value: ISO 8601 (real data format) or Twitter's tweet date format
+fn parse_message_timestamp(created_at: &str) -> Option<DateTime<Utc>> {
+    parse_twitter_date(created_at).ok()
+}
+
+/// Timestamp of a message, if it has a parseable one
+fn message_timestamp(message: &DmMessage) -> Option<DateTime<Utc>> {
+    message.message_create.as_ref()
+        .and_then(|mc| mc.created_at.as_deref())
+        .and_then(parse_message_timestamp)
+}
+
+/// Reads several DM archive files and merges them into one set of conversations
+///
+/// Meant for archives requested on different dates whose DM files overlap.
+/// Conversations with the same `conversation_id` are combined: their messages
+/// are deduplicated by message ID (messages without an ID are always kept) and
+/// sorted chronologically, with undated messages last. A group name from a later
+/// file replaces an earlier one. Conversations are returned oldest first by their
+/// earliest message, ties and undated conversations ordered by ID.
+pub async fn merge_dm_files(paths: &[&str]) -> Result<Vec<DmWrapper>> {
+    let mut order: Vec<String> = Vec::new();
+    let mut merged: HashMap<String, (DmWrapper, HashSet<String>)> = HashMap::new();
+
+    for path in paths {
+        let content = async_fs::read_to_string(path).await
+            .with_context(|| format!("Failed to read DM file: {}", path))?;
+        let wrappers = parse_dm_wrappers(&content)
+            .with_context(|| format!("Failed to parse DM file: {}", path))?;
+
+        for wrapper in wrappers {
+            let conversation = wrapper.dm_conversation;
+            let (existing, seen_ids) = merged.entry(conversation.conversation_id.clone())
+                .or_insert_with(|| {
+                    order.push(conversation.conversation_id.clone());
+                    let empty = DmConversation {
+                        conversation_id: conversation.conversation_id.clone(),
+                        messages: Vec::new(),
+                        group_name: None,
+                        join_events: Vec::new(),
+                        leave_events: Vec::new(),
+                    };
+                    (DmWrapper { dm_conversation: empty }, HashSet::new())
+                });
+            let target = &mut existing.dm_conversation;
+            if conversation.group_name.is_some() {
+                target.group_name = conversation.group_name;
+            }
+            for (events, new_events) in [(&mut target.join_events, conversation.join_events), (&mut target.leave_events, conversation.leave_events)] {
+                for event in new_events {
+                    if !events.contains(&event) {
+                        events.push(event);
+                    }
+                }
+                events.sort_by_key(|event| event.created_at);
+            }
+            for message in conversation.messages {
+                let id = message.message_create.as_ref().and_then(|mc| mc.id.clone());
+                if id.is_none_or(|id| seen_ids.insert(id)) {
+                    target.messages.push(message);
+                }
+            }
+        }
+    }
+
+    let mut conversations: Vec<DmWrapper> = order.into_iter()
+        .filter_map(|id| merged.remove(&id))
+        .map(|(mut wrapper, _)| {
+            wrapper.dm_conversation.messages.sort_by_key(|m| {
+                let timestamp = message_timestamp(m);
+                (timestamp.is_none(), timestamp)
+            });
+            wrapper
+        })
+        .collect();
+    conversations.sort_by(|a, b| {
+        let created_at = |w: &DmWrapper| w.dm_conversation.messages.iter().filter_map(message_timestamp).min();
+        let (a_created, b_created) = (created_at(a), created_at(b));
+        (a_created.is_none(), a_created, &a.dm_conversation.conversation_id)
+            .cmp(&(b_created.is_none(), b_created, &b.dm_conversation.conversation_id))
+    });
+
+    Ok(conversations)
+}
+
+/// Mean length of the thread's messages in characters
+///
+/// Messages without text count as length 0. Returns 0.0 for a thread with no messages.
+pub fn compute_avg_message_length(thread: &DmThread) -> f64 {
+    if thread.messages.is_empty() {
+        return 0.0;
+    }
+    let total: usize = thread.messages.iter().map(|m| m.text.chars().count()).sum();
+    total as f64 / thread.messages.len() as f64
+}
+
+/// Number of messages per window compared by [`detect_topic_shifts`] when building threads
+pub const TOPIC_SHIFT_WINDOW: usize = 5;
+
+/// Adjacent windows less similar than this mark a topic shift
+pub const TOPIC_SHIFT_SIMILARITY_THRESHOLD: f64 = 0.3;
+
+/// Indices of messages where the conversation moves to a new topic
+///
+/// Slides over the thread comparing the `window` messages before each index
+/// with the `window` messages from it onwards, using the cosine similarity of
+/// their TF-IDF vectors. An index is marked when the similarity drops below
+/// [`TOPIC_SHIFT_SIMILARITY_THRESHOLD`]; the search then resumes `window`
+/// messages later so one change of topic is marked once. Windows without any
+/// topic words are never compared. Threads shorter than two windows have no
+/// shift points.
+pub fn detect_topic_shifts(thread: &DmThread, window: usize) -> Vec<usize> {
+    if window == 0 || thread.messages.len() < 2 * window {
+        return Vec::new();
+    }
+    let texts: Vec<&str> = thread.messages.iter().map(|m| m.text.as_str()).collect();
+    let windows: Vec<String> = texts.windows(window).map(|messages| messages.join(" ")).collect();
+    let windows: Vec<&str> = windows.iter().map(String::as_str).collect();
+    let vectors = tfidf_vectors(&windows);
+
+    let mut shifts = Vec::new();
+    let mut index = window;
+    while index + window <= texts.len() {
+        let (before, after) = (&vectors[index - window], &vectors[index]);
+        if !before.is_empty() && !after.is_empty() && cosine_similarity(before, after) < TOPIC_SHIFT_SIMILARITY_THRESHOLD {
+            shifts.push(index);
+            index += window;
+        } else {
+            index += 1;
+        }
+    }
+    shifts
+}
+
+/// Default content warning keywords: empty, so screening is opt-in
+pub fn default_content_warning_keywords() -> HashMap<String, Vec<String>> {
+    HashMap::new()
+}
+
+/// Returns the warning categories whose keywords appear in any message of the thread
+///
+/// `keyword_lists` maps a category name (e.g. `"violence"`) to its keywords.
+/// Matching is case-insensitive; single-word keywords must match a whole word,
+/// multi-word keywords match as a phrase. Categories are returned sorted and
+/// deduplicated.
+pub fn detect_content_warnings(thread: &DmThread, keyword_lists: &HashMap<String, Vec<String>>) -> Vec<String> {
+    if keyword_lists.is_empty() {
+        return Vec::new();
+    }
+
+    let texts: Vec<String> = thread.messages.iter().map(|m| m.text.to_lowercase()).collect();
+    let words: Vec<Vec<&str>> = texts.iter()
+        .map(|text| {
+            text.split_whitespace()
+                .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric() && c != '\''))
+                .collect()
+        })
+        .collect();
+
+    let mut triggered = BTreeSet::new();
+    for (category, keywords) in keyword_lists {
+        let hit = keywords.iter().map(|k| k.trim().to_lowercase()).filter(|k| !k.is_empty()).any(|keyword| {
+            if keyword.contains(char::is_whitespace) {
+                texts.iter().any(|text| text.contains(&keyword))
+            } else {
+                words.iter().any(|message_words| message_words.contains(&keyword.as_str()))
+            }
+        });
+        if hit {
+            triggered.insert(category.clone());
+        }
+    }
+    triggered.into_iter().collect()
 }
 
 /// Calculate thread metadata from messages
@@ -178,10 +395,27 @@ fn calculate_thread_metadata(messages: &[DmThreadMessage], timestamps: &[DateTim
     }
 }
 
+/// Placeholder shown instead of message text in privacy mode
+pub const HIDDEN_MESSAGE_TEXT: &str = "[message hidden]";
+
+/// Banner written at the top of DM text files produced in privacy mode
+pub const PRIVACY_MODE_BANNER: &str = "=== PRIVACY MODE: Message content hidden ===";
+
 /// Format DM thread as human-readable text
-pub fn format_dm_thread_as_text(thread: &DmThread) -> String {
+///
+/// Named group conversations start with a `--- Group: "Name" (conversation_id) ---` line.
+/// Users joining or leaving the group are listed under `Participant changes:`.
+/// A `--- Topic Shift ---` line comes before each message in `topic_shift_points`.
+/// With `privacy_mode`, every message body is replaced by
+/// [`HIDDEN_MESSAGE_TEXT`]; headers, senders and timestamps are unchanged.
+pub fn format_dm_thread_as_text(thread: &DmThread, privacy_mode: bool) -> String {
     let mut output = String::new();
     
+    if let Some(name) = &thread.group_name {
+        let conversation_id = thread.thread_id.strip_prefix("dm_").unwrap_or(&thread.thread_id);
+        output.push_str(&format!("--- Group: \"{}\" ({}) ---\n", name, conversation_id));
+    }
+    
     // Simplified header with just essential info
     output.push_str(&format!("💬 Conversation ({} messages", thread.messages.len()));
     
@@ -195,11 +429,30 @@ pub fn format_dm_thread_as_text(thread: &DmThread) -> String {
         }
     }
     output.push_str(")\n");
+    output.push_str(&format!("Participants: {}\n", thread.participant_count()));
+    if !thread.content_warnings.is_empty() {
+        output.push_str(&format!("Content Warnings: {}\n", thread.content_warnings.join(", ")));
+    }
+    if !thread.participant_events.is_empty() {
+        output.push_str("Participant changes:\n");
+        for event in &thread.participant_events {
+            let action = match event.event_type {
+                DmParticipantEventType::Join => "joined",
+                DmParticipantEventType::Leave => "left",
+            };
+            output.push_str(&format!("  [{} UTC] User {} {}\n",
+                event.created_at.format("%Y-%m-%d %H:%M:%S"), event.user_ids.join(", User "), action));
+        }
+    }
     output.push_str(&format!("{}\n", "─".repeat(40)));
     
     let mut previous_timestamp: Option<chrono::DateTime<chrono::Utc>> = None;
     
-    for (i, msg) in thread.messages.iter().enumerate() {
+    for (index, msg) in thread.messages.iter().enumerate() {
+        if thread.topic_shift_points.contains(&index) {
+            output.push_str("--- Topic Shift ---\n");
+        }
+
         // Calculate relative timing
         let timing_info = if let (Some(current_ts), Some(prev_ts)) = (msg.timestamp, previous_timestamp) {
             let duration = current_ts.signed_duration_since(prev_ts);
@@ -226,7 +479,8 @@ pub fn format_dm_thread_as_text(thread: &DmThread) -> String {
         };
 
         // Output format: user_id: [timestamp][relative] message
-        output.push_str(&format!("{}{} {}\n", sender_label, timestamp_str, msg.text));
+        let text = if privacy_mode { HIDDEN_MESSAGE_TEXT } else { msg.text.as_str() };
+        output.push_str(&format!("{}{} {}\n", sender_label, timestamp_str, text));
 
         previous_timestamp = msg.timestamp;
     }
@@ -243,8 +497,12 @@ mod tests {
     fn create_test_dm_conversation() -> DmConversation {
         use crate::models::direct_message::{DmMessage, DmMessageCreate};
         
+        crate::relationship::anonymization::set_test_mode(true);
         DmConversation {
             conversation_id: "123-456".to_string(),
+            group_name: None,
+            join_events: Vec::new(),
+            leave_events: Vec::new(),
             messages: vec![
                 DmMessage {
                     message_create: Some(DmMessageCreate {
@@ -295,7 +553,8 @@ mod tests {
         let thread = convert_single_dm_to_thread(conversation).unwrap();
         
         assert_eq!(thread.thread_id, "dm_123-456");
-        assert_eq!(thread.participant_count, 2);
+        assert_eq!(thread.participant_count(), 2);
+        assert_eq!(thread.participants, vec!["123", "456"]);
         assert_eq!(thread.messages.len(), 3);
         
         // Check message order and content
@@ -314,6 +573,35 @@ mod tests {
         assert!(thread.messages[2].reply_context.is_some());
     }
     
+    #[test]
+    fn test_message_length_stats() {
+        let mut conversation = create_test_dm_conversation();
+        conversation.messages[1].message_create.as_mut().unwrap().text = None;
+        let thread = convert_single_dm_to_thread(conversation).unwrap();
+        
+        // "Hello!" (6), no text (0), "How are you?" (12)
+        assert_eq!(thread.average_message_length, 6.0);
+        assert_eq!(compute_avg_message_length(&thread), 6.0);
+        assert_eq!(thread.longest_message_chars, 12);
+        assert_eq!(thread.shortest_message_chars, 0);
+    }
+    
+    #[test]
+    fn test_media_message_count() {
+        let mut conversation = create_test_dm_conversation();
+        conversation.messages[0].message_create.as_mut().unwrap().media_urls = vec![
+            "https://ton.twitter.com/1.jpg".to_string(),
+            "https://ton.twitter.com/2.jpg".to_string(),
+        ];
+        conversation.messages[2].message_create.as_mut().unwrap().media_urls = vec!["https://ton.twitter.com/3.jpg".to_string()];
+        assert!(has_media_attachment(&conversation.messages[0]));
+        assert!(!has_media_attachment(&conversation.messages[1]));
+        
+        let thread = convert_single_dm_to_thread(conversation).unwrap();
+        assert_eq!(thread.media_message_count, 2);
+        assert_eq!(convert_single_dm_to_thread(create_test_dm_conversation()).unwrap().media_message_count, 0);
+    }
+    
     #[test]
     fn test_thread_metadata_calculation() {
         let conversation = create_test_dm_conversation();
@@ -328,6 +616,9 @@ mod tests {
     fn test_empty_conversation_handling() {
         let empty_conversation = DmConversation {
             conversation_id: "empty".to_string(),
+            group_name: None,
+            join_events: Vec::new(),
+            leave_events: Vec::new(),
             messages: vec![],
         };
         
@@ -339,13 +630,171 @@ mod tests {
     fn test_dm_thread_formatting() {
         let conversation = create_test_dm_conversation();
         let thread = convert_single_dm_to_thread(conversation).unwrap();
-        let formatted = format_dm_thread_as_text(&thread);
+        let formatted = format_dm_thread_as_text(&thread, false);
         
         assert!(formatted.contains("💬 Conversation"));
         assert!(formatted.contains("messages"));
         assert!(formatted.contains("Hello!"));
         assert!(formatted.contains("Hi there!"));
         assert!(formatted.contains("How are you?"));
-        assert!(formatted.contains("A:") || formatted.contains("B:")); // Should have sender labels
+        assert!(formatted.contains("User 123:") && formatted.contains("User 456:")); // Should have sender labels
+        assert!(formatted.contains("Participants: 2\n"));
+        assert!(!formatted.contains("--- Group:"));
+    }
+
+    #[test]
+    fn test_group_thread_header_and_name() {
+        let mut conversation = create_test_dm_conversation();
+        conversation.group_name = Some("Team Rust".to_string());
+        let thread = convert_single_dm_to_thread(conversation).unwrap();
+        let formatted = format_dm_thread_as_text(&thread, false);
+        
+        assert_eq!(thread.group_name.as_deref(), Some("Team Rust"));
+        assert_eq!(formatted.lines().next(), Some("--- Group: \"Team Rust\" (123-456) ---"));
+        assert!(!formatted.contains("Participant changes:"));
+    }
+
+    #[test]
+    fn test_group_thread_lists_participant_changes() {
+        let mut conversation = create_test_dm_conversation();
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        conversation.join_events.push(DmParticipantEvent {
+            event_type: DmParticipantEventType::Join,
+            user_ids: vec!["789".to_string(), "999".to_string()],
+            created_at: at("2023-01-01T09:00:00Z"),
+        });
+        conversation.leave_events.push(DmParticipantEvent {
+            event_type: DmParticipantEventType::Leave,
+            user_ids: vec!["456".to_string()],
+            created_at: at("2022-12-31T09:00:00Z"),
+        });
+        let thread = convert_single_dm_to_thread(conversation).unwrap();
+        let formatted = format_dm_thread_as_text(&thread, false);
+
+        assert!(formatted.contains("Participant changes:\n  [2022-12-31 09:00:00 UTC] User 456 left\n  [2023-01-01 09:00:00 UTC] User 789, User 999 joined\n"));
+    }
+
+    #[test]
+    fn test_dm_thread_formatting_privacy_mode() {
+        let conversation = create_test_dm_conversation();
+        let thread = convert_single_dm_to_thread(conversation).unwrap();
+        let visible = format_dm_thread_as_text(&thread, false);
+        let hidden = format_dm_thread_as_text(&thread, true);
+        
+        assert!(!hidden.contains("Hello!") && !hidden.contains("How are you?"));
+        assert_eq!(hidden.matches(HIDDEN_MESSAGE_TEXT).count(), thread.messages.len());
+        assert!(hidden.contains("User 123:") && hidden.contains("User 456:"));
+        assert_eq!(hidden.lines().count(), visible.lines().count());
+        assert_eq!(hidden.lines().next(), visible.lines().next());
+    }
+    
+    #[test]
+    fn test_group_conversation_participants() {
+        use crate::models::direct_message::{DmMessage, DmMessageCreate};
+
+        let message = |id: &str, sender: Option<&str>| DmMessage {
+            message_create: Some(DmMessageCreate {
+                id: Some(id.to_string()),
+                created_at: Some("2023-01-01T12:00:00.000Z".to_string()),
+                sender_id: sender.map(|s| s.to_string()),
+                recipient_id: None,
+                text: Some("Hi all".to_string()),
+                reactions: vec![],
+                urls: vec![],
+                media_urls: vec![],
+                edit_history: vec![],
+            }),
+        };
+        let conversation = DmConversation {
+            conversation_id: "group-1".to_string(),
+            group_name: None,
+            join_events: Vec::new(),
+            leave_events: Vec::new(),
+            messages: vec![
+                message("1", Some("111")),
+                message("2", Some("222")),
+                message("3", Some("111")),
+                message("4", Some("333")),
+                message("5", None),
+            ],
+        };
+
+        let thread = convert_single_dm_to_thread(conversation).unwrap();
+
+        assert_eq!(thread.participant_count(), 3);
+        assert!(!thread.participants.contains(&"111".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_merge_dm_files_with_overlap() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message = |id: u32, day: u32| format!(
+            r#"{{ "messageCreate": {{ "id": "{}", "text": "m{}", "createdAt": "2023-01-{:02}T10:00:00.000Z", "senderId": "111", "recipientId": "222" }} }}"#,
+            id, id, day
+        );
+        let conversation = |id: &str, messages: Vec<String>| format!(
+            r#"{{ "dmConversation": {{ "conversationId": "{}", "messages": [{}] }} }}"#,
+            id, messages.join(",")
+        );
+        // Archives list messages newest first
+        let first = format!("window.YTD.direct_messages.part0 = [{}, {}]",
+            conversation("111-222", (1..=10).rev().map(|i| message(i, i + 10)).collect()),
+            conversation("111-333", vec![message(100, 2)]));
+        let second = format!("[{}, {}]",
+            conversation("111-222", (8..=15).rev().map(|i| message(i, i + 10)).collect()),
+            conversation("111-444", vec![message(200, 25)]));
+        let first_path = temp_dir.path().join("dms_2023.js");
+        let second_path = temp_dir.path().join("dms_2024.js");
+        std::fs::write(&first_path, first).unwrap();
+        std::fs::write(&second_path, second).unwrap();
+
+        let merged = merge_dm_files(&[first_path.to_str().unwrap(), second_path.to_str().unwrap()]).await.unwrap();
+
+        let ids: Vec<&str> = merged.iter().map(|w| w.dm_conversation.conversation_id.as_str()).collect();
+        assert_eq!(ids, vec!["111-333", "111-222", "111-444"]);
+        let message_ids: Vec<String> = merged[1].dm_conversation.messages.iter()
+            .map(|m| m.message_create.as_ref().unwrap().id.clone().unwrap())
+            .collect();
+        assert_eq!(message_ids, (1..=15).map(|i| i.to_string()).collect::<Vec<_>>());
+        assert!(merge_dm_files(&["/nonexistent/dms.js"]).await.is_err());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_detect_content_warnings() {
+        let mut thread = convert_single_dm_to_thread(create_test_dm_conversation()).unwrap();
+        let keywords: HashMap<String, Vec<String>> = [
+            ("greeting".to_string(), vec!["HELLO".to_string()]),
+            ("question".to_string(), vec!["how are you".to_string()]),
+            ("partial".to_string(), vec!["hell".to_string()]),
+        ].into_iter().collect();
+        
+        assert!(detect_content_warnings(&thread, &default_content_warning_keywords()).is_empty());
+        
+        let warnings = detect_content_warnings(&thread, &keywords);
+        assert_eq!(warnings, vec!["greeting".to_string(), "question".to_string()]);
+        
+        thread.content_warnings = warnings;
+        assert!(format_dm_thread_as_text(&thread, false).contains("Content Warnings: greeting, question\n"));
+    }
+
+    #[test]
+    fn test_detect_topic_shifts() {
+        let mut thread = convert_single_dm_to_thread(create_test_dm_conversation()).unwrap();
+        assert!(thread.topic_shift_points.is_empty());
+        let template = thread.messages[0].clone();
+        let texts = [
+            "pizza dinner tonight?", "pizza sounds great", "dinner pizza place downtown", "pizza dinner booked",
+            "guitar concert tickets", "concert starts late", "guitar solo concert", "tickets sold concert",
+        ];
+        thread.messages = texts.iter().map(|text| DmThreadMessage { text: text.to_string(), ..template.clone() }).collect();
+
+        assert_eq!(detect_topic_shifts(&thread, 3), vec![4]);
+        assert!(detect_topic_shifts(&thread, 0).is_empty());
+        assert!(detect_topic_shifts(&thread, 5).is_empty());
+
+        thread.topic_shift_points = detect_topic_shifts(&thread, 3);
+        let formatted = format_dm_thread_as_text(&thread, false);
+        assert_eq!(formatted.matches("--- Topic Shift ---\n").count(), 1);
+        assert!(formatted.contains("pizza dinner booked\n--- Topic Shift ---\nUser 123: guitar concert tickets\n"));
+    }
+}
@@ -1,33 +1,90 @@
 //! File I/O operations for CSV writing and file management
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono_tz::Tz;
 use csv::Writer as CsvWriterLib;
-use std::fs::File;
-use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Cursor, Write};
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc as async_mpsc;
 
-use super::data_structures::{CsvWriter, Thread};
+use super::data_structures::{CsvWriter, Thread, THREAD_CSV_HEADERS};
+use super::tweets::{tweet_is_poll, EventActivityReport};
+use crate::models::profile::UserProfile;
+use crate::relationship::anonymization::{hash_user_id, PseudonymRegistry};
+use crate::utils::{format_twitter_date_in_tz, parse_twitter_date};
+use crate::models::statistics::{compute_thread_statistics, compute_weekly_stats, language_breakdown, language_label, likes_moving_average};
+
+/// Writes a `#`-prefixed row with the csv crate's default quoting, so `#`
+/// stays the first character of the line even when [`CsvWriter::auto_quoting`] is on
+fn write_comment_row<W: Write, I>(sink: &mut W, fields: I) -> Result<()>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(sink);
+    writer.write_record(fields)?;
+    writer.flush()?;
+    Ok(())
+}
 
 impl CsvWriter {
     /// Runs the CSV writer, consuming records from the channel
-    pub async fn run(mut self) -> Result<()> {
+    ///
+    /// Returns once all senders are dropped; `deduplication_stats` can then be queried.
+    pub async fn run(&mut self) -> Result<()> {
+        if !self.column_types.is_empty() && self.column_types.len() != self.headers.len() {
+            return Err(anyhow!("{} column types given for {} CSV columns", self.column_types.len(), self.headers.len()));
+        }
+
+        if let Some(sink) = self.memory_sink.clone() {
+            let mut buffer = Cursor::new(Vec::new());
+            self.write_records(&mut buffer).await?;
+            *sink.lock().map_err(|_| anyhow!("In-memory CSV buffer lock poisoned"))? = buffer.into_inner();
+            return Ok(());
+        }
+
+        if !self.column_types.is_empty() {
+            let schema_path = Path::new(&self.output_path).with_file_name("schema.json");
+            let schema = serde_json::to_string_pretty(&self.json_schema())?;
+            std::fs::write(&schema_path, schema)
+                .with_context(|| format!("Failed to write schema: {}", schema_path.display()))?;
+        }
+
         let file = File::create(&self.output_path)
             .with_context(|| format!("Failed to create file: {}", self.output_path))?;
-        let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+        self.write_records(&mut BufWriter::new(file)).await
+    }
 
-        // Write headers
-        writer.write_record([
-            "Thread ID",
-            "Date time of first tweet",
-            "Number of Tweets in Thread",
-            "Likes in first tweet",
-            "Retweets in first tweet",
-            "Total likes for all tweets",
-            "Total retweets for all tweets",
-            "Thread Text",
-        ])?;
+    /// CSV builder for this writer: flexible rows, and every field quoted with `auto_quoting`
+    fn writer_builder(&self) -> csv::WriterBuilder {
+        let mut builder = csv::WriterBuilder::new();
+        builder.flexible(true);
+        if self.auto_quoting {
+            builder.quote_style(csv::QuoteStyle::Always);
+        }
+        builder
+    }
 
+    /// Writes the header row and every record received on the channel to `sink`
+    ///
+    /// Full buffers are flushed as they fill. When every sender has been
+    /// dropped (including early, after an error upstream) `recv` returns
+    /// `None` and whatever is left in the buffer is flushed before returning.
+    async fn write_records<W: Write>(&mut self, sink: &mut W) -> Result<()> {
+        if let Some(comment) = &self.header_comment {
+            write_comment_row(sink, [format!("# {}", comment)])?;
+        }
+        let mut header_writer = self.writer_builder().from_writer(&mut *sink);
+        header_writer.write_record(&self.headers)?;
+        header_writer.flush()?;
+        drop(header_writer);
+        if self.types_row && !self.column_types.is_empty() {
+            write_comment_row(sink, std::iter::once("#types").chain(self.column_types.iter().map(|t| t.name())))?;
+        }
+
+        let mut writer = self.writer_builder().from_writer(sink);
         let mut buffer = Vec::with_capacity(self.buffer_size);
 
         while let Some(record) = self.receiver.recv().await {
@@ -45,10 +102,24 @@ impl CsvWriter {
         Ok(())
     }
 
-    /// Flushes the buffer to the CSV writer
-    fn flush_buffer(&self, writer: &mut CsvWriterLib<BufWriter<File>>, buffer: &mut Vec<Vec<String>>) -> Result<()> {
+    /// Flushes the buffer to the CSV writer, applying transforms and skipping
+    /// duplicates when dedup is enabled
+    fn flush_buffer<W: Write>(&mut self, writer: &mut CsvWriterLib<W>, buffer: &mut Vec<Vec<String>>) -> Result<()> {
+        #[cfg(test)]
+        {
+            self.flush_count += 1;
+        }
         for record in buffer.drain(..) {
+            let record = self.transformation.iter().fold(record, |record, transform| transform(record));
+            if let Some(column) = self.dedup_on_field {
+                let value = record.get(column).cloned().unwrap_or_default();
+                if !self.seen_values.insert(value) {
+                    self.skipped_count += 1;
+                    continue;
+                }
+            }
             writer.write_record(&record)?;
+            self.written_count += 1;
         }
         Ok(())
     }
@@ -56,14 +127,41 @@ impl CsvWriter {
 
 /// Writes threads to a text file
 pub async fn write_threads_to_file(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    write_threads_to_file_in_tz(threads, screen_name, timestamp, output_dir, chrono_tz::UTC).await
+}
+
+/// Writes threads to a text file with each thread's `Timestamp:` converted to `output_tz`
+pub async fn write_threads_to_file_in_tz(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path, output_tz: Tz) -> Result<()> {
+    write_threads_to_file_with_options(threads, screen_name, timestamp, output_dir, output_tz, TxtWriteOptions::default()).await
+}
+
+/// Options for the threads text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TxtWriteOptions {
+    /// Start the file with a table of every thread's date and engagement,
+    /// followed by `--- End of Summary ---`
+    pub include_toc: bool,
+}
+
+/// Marks the end of the summary table at the top of a threads text file
+pub const SUMMARY_END_MARKER: &str = "--- End of Summary ---";
+
+/// Writes threads to a text file in `output_tz`, optionally starting with a summary table
+pub async fn write_threads_to_file_with_options(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path, output_tz: Tz, options: TxtWriteOptions) -> Result<()> {
     let file_path = output_dir.join(format!("threads_{}_{}.txt", screen_name, timestamp));
     let file = File::create(&file_path)?;
     let mut writer = BufWriter::new(file);
 
+    if options.include_toc {
+        write!(writer, "{}", threads_summary_table(threads, output_tz))?;
+        writeln!(writer, "{}\n", SUMMARY_END_MARKER)?;
+    }
+
     for thread in threads {
         writeln!(writer, "--- Start of Thread ---")?;
         writeln!(writer, "Thread ID: {}", thread.id)?;
-        writeln!(writer, "Timestamp: {}", thread.tweets[0].created_at)?;
+        writeln!(writer, "URL: {}", thread.url)?;
+        writeln!(writer, "Timestamp: {}", format_twitter_date_in_tz(&thread.tweets[0].created_at, output_tz))?;
         writeln!(writer, "Public Support: {} retweets, {} likes",
                  thread.tweets[0].retweet_count, thread.tweets[0].favorite_count)?;
         writeln!(writer, "Thread text:")?;
@@ -71,6 +169,21 @@ pub async fn write_threads_to_file(threads: &[Thread], screen_name: &str, timest
         for (i, tweet) in thread.tweets.iter().enumerate() {
             writeln!(writer, "- Tweet {}:", i + 1)?;
             writeln!(writer, "{}", tweet.full_text)?;
+            if tweet_is_poll(tweet) {
+                writeln!(writer, "[This tweet was a poll]")?;
+            }
+            writeln!(writer)?;
+        }
+        if thread.is_truncated() {
+            writeln!(writer, "[Thread truncated at {} of {} tweets]", thread.tweets.len(), thread.tweet_count)?;
+            writeln!(writer)?;
+        }
+
+        if !thread.first_tweet_url_entities.is_empty() {
+            writeln!(writer, "Links:")?;
+            for url in &thread.first_tweet_url_entities {
+                writeln!(writer, "  {} → {}", url.display_url, url.expanded_url)?;
+            }
             writeln!(writer)?;
         }
 
@@ -81,34 +194,404 @@ pub async fn write_threads_to_file(threads: &[Thread], screen_name: &str, timest
     Ok(())
 }
 
+/// Markdown table with one row per thread, columns padded to their widest cell
+fn threads_summary_table(threads: &[Thread], output_tz: Tz) -> String {
+    let header = ["#", "Thread ID", "Date", "Tweets", "Likes", "Retweets"].map(String::from);
+    let rows: Vec<[String; 6]> = threads.iter().enumerate()
+        .map(|(i, thread)| {
+            let created_at = thread.tweets.first().map(|tweet| tweet.created_at.as_str()).unwrap_or_default();
+            let date = parse_twitter_date(created_at)
+                .map(|date| date.with_timezone(&output_tz).format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|_| created_at.to_string());
+            [
+                (i + 1).to_string(),
+                thread.id.clone(),
+                date,
+                thread.tweet_count.to_string(),
+                thread.favorite_count.to_string(),
+                thread.retweet_count.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.clone().map(|cell| cell.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[String; 6]| {
+        let padded: Vec<String> = cells.iter().zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("| {} |\n", padded.join(" | "))
+    };
+    let separator: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+
+    let mut table = format_row(&header);
+    table.push_str(&format!("|-{}-|\n", separator.join("-|-")));
+    for row in &rows {
+        table.push_str(&format_row(row));
+    }
+    table
+}
+
+/// Writes per-week tweet and engagement statistics with a 4-week moving average of likes
+pub async fn write_weekly_stats_csv(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("weekly_stats_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record([
+        "Year",
+        "ISO Week",
+        "Tweet Count",
+        "Total Likes",
+        "Total Retweets",
+        "Unique Reply Targets",
+        "Likes 4-Week Moving Avg",
+    ])?;
+
+    let stats = compute_weekly_stats(threads);
+    let moving_average = likes_moving_average(&stats, 4);
+    for (week, avg) in stats.iter().zip(moving_average) {
+        writer.write_record([
+            week.year.to_string(),
+            week.iso_week.to_string(),
+            week.tweet_count.to_string(),
+            week.total_likes.to_string(),
+            week.total_retweets.to_string(),
+            week.unique_reply_targets.to_string(),
+            format!("{:.2}", avg),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes the number of threads per language code, most common first
+pub async fn write_language_breakdown_csv(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("language_breakdown_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["Language", "Thread Count"])?;
+
+    let mut breakdown: Vec<(String, usize)> = language_breakdown(threads).into_iter().collect();
+    breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (lang, count) in breakdown {
+        writer.write_record([lang, count.to_string()])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes archive-wide thread statistics as pretty-printed JSON
+pub async fn write_thread_stats_json(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("thread_stats_{}_{}.json", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, &compute_thread_statistics(threads))?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes quote-tweet edges (quoting tweet → quoted tweet), ordered by quoting tweet ID
+pub async fn write_quote_graph_csv(quote_graph: &HashMap<String, String>, screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("quote_graph_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["quoting_tweet_id", "quoted_tweet_id"])?;
+
+    let mut edges: Vec<(&String, &String)> = quote_graph.iter().collect();
+    edges.sort();
+    for (quoting, quoted) in edges {
+        writer.write_record([quoting, quoted])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes every media link found in the threads to `thread_media_{screen_name}_{timestamp}.csv`
+///
+/// See [`extract_thread_images`](super::tweets::extract_thread_images) for
+/// which links are included.
+pub async fn write_thread_media_csv(threads: &[Thread], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("thread_media_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["Thread ID", "Tweet ID", "Media URL"])?;
+    for thread in threads {
+        for (tweet_id, url) in super::tweets::extract_thread_images(thread) {
+            writer.write_record([thread.id.as_str(), &tweet_id, &url])?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes `(handle, count)` pairs to `frequent_mentions_{screen_name}_{timestamp}.csv`
+pub async fn write_frequent_mentions_csv(mentions: &[(String, usize)], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("frequent_mentions_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["Handle", "Mentions"])?;
+    for (handle, count) in mentions {
+        writer.write_record([format!("@{}", handle), count.to_string()])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes DM-only contacts to `ghost_contacts_{screen_name}_{timestamp}.csv`
+///
+/// Users are identified by their hashed ID, as in the other relationship outputs.
+pub async fn write_ghost_contacts_csv(ghosts: &[&UserProfile], registry: &PseudonymRegistry, screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("ghost_contacts_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["User Hash", "DM Interactions", "First Interaction", "Last Interaction", "Pseudonym"])?;
+    let format_date = |date: Option<chrono::DateTime<chrono::Utc>>| date.map(|d| d.to_rfc3339()).unwrap_or_default();
+    for profile in ghosts {
+        let hash = hash_user_id(&profile.user_id);
+        let pseudonym = registry.get(&hash).unwrap_or_default().to_string();
+        writer.write_record([
+            hash,
+            profile.total_interactions.to_string(),
+            format_date(profile.first_interaction),
+            format_date(profile.last_interaction),
+            pseudonym,
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads event dates from the first column of a CSV file
+///
+/// Dates may be `YYYY-MM-DD` (midnight UTC) or RFC 3339. A first row that isn't a
+/// date is treated as a header; any other unparseable row is an error.
+pub fn read_event_dates(path: &Path) -> Result<Vec<chrono::DateTime<chrono::Utc>>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(path)
+        .with_context(|| format!("Failed to open events file: {}", path.display()))?;
+
+    let parse_date = |value: &str| -> Option<chrono::DateTime<chrono::Utc>> {
+        let value = value.trim();
+        parse_twitter_date(value).ok()
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+                    .map(|date| date.and_utc())
+            })
+    };
+
+    let mut dates = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("Failed to read events file: {}", path.display()))?;
+        let value = record.get(0).unwrap_or_default();
+        if value.trim().is_empty() {
+            continue;
+        }
+        match parse_date(value) {
+            Some(date) => dates.push(date),
+            None if row == 0 => {}
+            None => return Err(anyhow!("Invalid event date on line {} of {}: {}", row + 1, path.display(), value)),
+        }
+    }
+    Ok(dates)
+}
+
+/// Writes posting activity around each event to `event_activity_{screen_name}_{timestamp}.csv`
+pub async fn write_event_activity_csv(reports: &[EventActivityReport], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("event_activity_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["Event Date", "Threads Before", "Threads After", "Change (%)"])?;
+    for report in reports {
+        writer.write_record([
+            report.event_date.format("%Y-%m-%d").to_string(),
+            report.pre_window_count.to_string(),
+            report.post_window_count.to_string(),
+            format!("{:.1}", report.change_pct),
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes per-quarter sentiment (see [`quarterly_sentiment`](crate::relationship::quarterly_sentiment))
+/// to `sentiment_timeline_{screen_name}_{timestamp}.csv`
+pub async fn write_sentiment_timeline_csv(periods: &[(String, f64, usize)], screen_name: &str, timestamp: i64, output_dir: &Path) -> Result<()> {
+    let file_path = output_dir.join(format!("sentiment_timeline_{}_{}.csv", screen_name, timestamp));
+    let file = File::create(&file_path)
+        .with_context(|| format!("Failed to create file: {}", file_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+
+    writer.write_record(["Period", "Avg Sentiment", "Event Count"])?;
+    for (period, avg_sentiment, count) in periods {
+        writer.write_record([period.clone(), format!("{:.3}", avg_sentiment), count.to_string()])?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Options for writing thread CSVs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CsvWriteOptions {
+    /// Keep at most this many characters of each text cell and append "…"
+    ///
+    /// Spreadsheet tools cap cells at 32,767 characters. `None` keeps the full text.
+    pub max_text_length: Option<usize>,
+    /// Quote every field (see [`CsvWriter::with_auto_quoting`])
+    pub always_quote: bool,
+}
+
+impl CsvWriteOptions {
+    /// Applies `max_text_length` to `text`, returning the text and whether it was cut
+    pub fn truncate_text(&self, text: String) -> (String, bool) {
+        match self.max_text_length {
+            Some(max) if text.chars().count() > max => {
+                let mut truncated: String = text.chars().take(max).collect();
+                truncated.push('…');
+                (truncated, true)
+            }
+            _ => (text, false),
+        }
+    }
+
+    /// `QuoteStyle::Always` with `always_quote`, otherwise the csv crate's default
+    pub fn quote_style(&self) -> csv::QuoteStyle {
+        if self.always_quote {
+            csv::QuoteStyle::Always
+        } else {
+            csv::QuoteStyle::Necessary
+        }
+    }
+}
+
 /// Writes CSV data for threads
+///
+/// Returns the number of `thread_text` cells cut short by `options.max_text_length`.
 pub async fn write_csv(
     threads: &[Thread],
     _screen_name: &str,
     _timestamp: i64,
     csv_tx: async_mpsc::Sender<Vec<String>>,
-) -> Result<()> {
+    options: CsvWriteOptions,
+) -> Result<usize> {
+    let mut truncated_count = 0;
     for thread in threads {
-        let first_tweet = &thread.tweets[0];
-        let total_likes: u32 = thread.tweets.iter().filter_map(|t| t.favorite_count.parse::<u32>().ok()).sum();
-        let total_retweets: u32 = thread.tweets.iter().filter_map(|t| t.retweet_count.parse::<u32>().ok()).sum();
-        let thread_text: String = thread.tweets.iter().map(|t| t.full_text.replace('\n', " ")).collect::<Vec<_>>().join(" ");
-
-        let record = vec![
-            thread.id.clone(),
-            first_tweet.created_at.clone(),
-            thread.tweets.len().to_string(),
-            first_tweet.favorite_count.clone(),
-            first_tweet.retweet_count.clone(),
-            total_likes.to_string(),
-            total_retweets.to_string(),
-            thread_text,
-        ];
-
+        let (record, truncated) = thread_csv_record(thread, options);
+        truncated_count += usize::from(truncated);
         csv_tx.send(record).await?;
     }
 
-    Ok(())
+    Ok(truncated_count)
+}
+
+/// Builds a [`THREAD_CSV_HEADERS`] row for a thread, returning it and whether its text was cut
+fn thread_csv_record(thread: &Thread, options: CsvWriteOptions) -> (Vec<String>, bool) {
+    let first_tweet = &thread.tweets[0];
+    let total_likes: u32 = thread.tweets.iter().filter_map(|t| t.favorite_count.parse::<u32>().ok()).sum();
+    let total_retweets: u32 = thread.tweets.iter().filter_map(|t| t.retweet_count.parse::<u32>().ok()).sum();
+    let thread_text: String = thread.tweets.iter().map(|t| t.full_text.replace('\n', " ")).collect::<Vec<_>>().join(" ");
+    let (thread_text, truncated) = options.truncate_text(thread_text);
+
+    let record = vec![
+        thread.id.clone(),
+        first_tweet.created_at.clone(),
+        thread.tweet_count.to_string(),
+        first_tweet.favorite_count.clone(),
+        first_tweet.retweet_count.clone(),
+        total_likes.to_string(),
+        total_retweets.to_string(),
+        language_label(first_tweet.lang.as_deref()).to_string(),
+        thread_text,
+        thread.first_tweet_url_entities.iter().map(|url| url.expanded_url.as_str()).collect::<Vec<_>>().join("|"),
+        thread.is_truncated().to_string(),
+    ];
+    (record, truncated)
+}
+
+/// Appends threads not already present to an existing thread summary CSV
+///
+/// Thread IDs are read from the first column of `existing_path` (after the
+/// header) and only threads with other IDs are written, in the
+/// [`THREAD_CSV_HEADERS`] layout. A missing or empty file is created with the
+/// header; a file with any other header is rejected rather than mixing layouts
+/// (e.g. the per-tweet `threads_*.csv`). Returns the number of rows appended.
+pub async fn append_to_csv(threads: &[Thread], _screen_name: &str, existing_path: &Path) -> Result<usize> {
+    let mut existing_ids = HashSet::new();
+    let file_exists = existing_path.metadata().map(|m| m.len() > 0).unwrap_or(false);
+    if file_exists {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_path(existing_path)
+            .with_context(|| format!("Failed to open existing CSV: {}", existing_path.display()))?;
+        let headers = reader.headers()
+            .with_context(|| format!("Failed to read header of {}", existing_path.display()))?;
+        if !headers.iter().eq(THREAD_CSV_HEADERS.iter().copied()) {
+            return Err(anyhow!(
+                "{} is not a thread summary CSV (expected columns: {})",
+                existing_path.display(),
+                THREAD_CSV_HEADERS.join(","),
+            ));
+        }
+        for record in reader.records() {
+            let record = record.with_context(|| format!("Failed to read row in {}", existing_path.display()))?;
+            if let Some(id) = record.get(0) {
+                existing_ids.insert(id.to_string());
+            }
+        }
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(existing_path)
+        .with_context(|| format!("Failed to open file for appending: {}", existing_path.display()))?;
+    let mut writer = CsvWriterLib::from_writer(BufWriter::new(file));
+    if !file_exists {
+        writer.write_record(THREAD_CSV_HEADERS)?;
+    }
+
+    let mut appended = 0;
+    for thread in threads {
+        if !existing_ids.insert(thread.id.clone()) {
+            continue;
+        }
+        let (record, _) = thread_csv_record(thread, CsvWriteOptions::default());
+        writer.write_record(&record)?;
+        appended += 1;
+    }
+    writer.flush()?;
+
+    Ok(appended)
 }
 
 /// Reads user input from any `BufRead` source.
@@ -139,9 +622,49 @@ pub fn prompt_input(prompt: &str) -> Result<String> {
     prompt_input_from_reader(&mut handle, prompt)
 }
 
-/// Gets input file path from user
+/// Checks that a user-supplied input path exists, is a file, and is readable
+///
+/// Returns a user-facing message describing the problem otherwise.
+pub fn validate_input_file(path: &str) -> std::result::Result<(), String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("Please enter a path".to_string());
+    }
+
+    let file_path = Path::new(path);
+    if !file_path.exists() {
+        return Err(format!("File does not exist: {}", path));
+    }
+    if !file_path.is_file() {
+        return Err(format!("Not a file: {}", path));
+    }
+    if let Err(e) = File::open(file_path) {
+        return Err(format!("File is not readable: {} ({})", path, e));
+    }
+    Ok(())
+}
+
+/// Gets input file path from user, re-prompting until a readable file is given
+#[cfg(feature = "tui")]
+pub fn get_input_file() -> Result<String> {
+    let path = dialoguer::Input::<String>::new()
+        .with_prompt("📁 Enter path to your tweets.js file")
+        .validate_with(|input: &String| validate_input_file(input))
+        .interact_text()
+        .context("Failed to read input file path")?;
+    Ok(path.trim().to_string())
+}
+
+/// Gets input file path from user, re-prompting until a readable file is given
+#[cfg(not(feature = "tui"))]
 pub fn get_input_file() -> Result<String> {
-    prompt_input("📁 Enter path to your tweets.js file: ")
+    loop {
+        let path = prompt_input("📁 Enter path to your tweets.js file: ")?;
+        match validate_input_file(&path) {
+            Ok(()) => return Ok(path),
+            Err(message) => println!("❌ {}", message),
+        }
+    }
 }
 
 /// Gets optional DM file path from user
@@ -154,25 +677,450 @@ pub fn get_dm_file() -> Result<Option<String>> {
     }
 }
 
+/// Gets every DM part file in a directory the user names
+///
+/// Returns an empty Vec when the prompt is skipped or no files match.
+pub fn get_dm_files() -> Result<Vec<String>> {
+    let input = prompt_input("💬 Enter directory containing direct-messages*.js files (or press Enter to skip): ")?;
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+    let files = find_dm_files(Path::new(&input))?;
+    println!("📂 Found {} DM file(s)", files.len());
+    Ok(files)
+}
+
+/// Paths of the `direct-messages*.js` files in `dir`, sorted by filename
+pub fn find_dm_files(dir: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))? {
+        let path = entry?.path();
+        let is_dm_file = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("direct-messages") && name.ends_with(".js"));
+        if is_dm_file && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(files.into_iter().map(|path| path.to_string_lossy().into_owned()).collect())
+}
+
+/// Asks whether to generate relationship intelligence profiles
+#[cfg(feature = "tui")]
+pub fn confirm_relationship_analysis() -> Result<bool> {
+    dialoguer::Confirm::new()
+        .with_prompt("Would you like to generate relationship intelligence profiles?")
+        .default(false)
+        .interact()
+        .context("Failed to read confirmation")
+}
+
+/// Asks whether to generate relationship intelligence profiles
+#[cfg(not(feature = "tui"))]
+pub fn confirm_relationship_analysis() -> Result<bool> {
+    let input = prompt_input("Would you like to generate relationship intelligence profiles? (y/n): ")?;
+    Ok(crate::main_integration::should_run_relationship_analysis(&input))
+}
+
+/// Which kinds of output files to keep after processing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Keep both CSV and TXT files
+    #[default]
+    All,
+    /// Keep only CSV files
+    CsvOnly,
+    /// Keep only TXT files
+    TxtOnly,
+}
+
+impl OutputFormat {
+    /// All formats in menu order
+    pub const ALL: [OutputFormat; 3] = [OutputFormat::All, OutputFormat::CsvOnly, OutputFormat::TxtOnly];
+
+    /// Human-readable label for menus
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputFormat::All => "CSV + TXT (spreadsheets and readable text)",
+            OutputFormat::CsvOnly => "CSV only (for spreadsheets and data tools)",
+            OutputFormat::TxtOnly => "TXT only (for reading and LLM prompts)",
+        }
+    }
+
+    /// Returns true if a file with this path should be kept
+    pub fn keeps(&self, path: &Path) -> bool {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match self {
+            OutputFormat::All => true,
+            OutputFormat::CsvOnly => extension != "txt",
+            OutputFormat::TxtOnly => extension != "csv",
+        }
+    }
+}
+
+/// Asks which output format to keep
+#[cfg(feature = "tui")]
+pub fn select_output_format() -> Result<OutputFormat> {
+    let labels: Vec<&str> = OutputFormat::ALL.iter().map(|f| f.label()).collect();
+    let selection = dialoguer::Select::new()
+        .with_prompt("📄 Choose output format")
+        .items(&labels)
+        .default(0)
+        .interact()
+        .context("Failed to read output format selection")?;
+    Ok(OutputFormat::ALL[selection])
+}
+
+/// Asks which output format to keep
+#[cfg(not(feature = "tui"))]
+pub fn select_output_format() -> Result<OutputFormat> {
+    println!("📄 Choose output format:");
+    for (i, format) in OutputFormat::ALL.iter().enumerate() {
+        println!("   {}. {}", i + 1, format.label());
+    }
+    let input = prompt_input("Enter choice [1]: ")?;
+    let format = input
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| OutputFormat::ALL.get(choice.wrapping_sub(1)).copied())
+        .unwrap_or_default();
+    Ok(format)
+}
+
+/// Every file and directory under `dir`, recursively (empty if `dir` doesn't exist)
+///
+/// Taken before a run so [`retain_output_format`] only touches what the run created.
+pub fn snapshot_paths(dir: &Path) -> Result<HashSet<PathBuf>> {
+    let mut paths = HashSet::new();
+    if !dir.is_dir() {
+        return Ok(paths);
+    }
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            paths.extend(snapshot_paths(&path)?);
+        }
+        paths.insert(path);
+    }
+    Ok(paths)
+}
+
+/// Removes files under `output_dir` that the chosen format does not keep
+///
+/// Only files missing from `preexisting` (see [`snapshot_paths`]) are removed,
+/// so files that were already in the folder are left alone. Subdirectories
+/// created by the run are searched too and removed if that leaves them empty.
+/// Returns the number of files removed.
+pub fn retain_output_format(output_dir: &Path, format: OutputFormat, preexisting: &HashSet<PathBuf>) -> Result<usize> {
+    let mut removed = 0;
+    for entry in std::fs::read_dir(output_dir)
+        .with_context(|| format!("Failed to read output directory: {}", output_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            removed += retain_output_format(&path, format, preexisting)?;
+            if !preexisting.contains(&path) && std::fs::read_dir(&path)?.next().is_none() {
+                std::fs::remove_dir(&path)
+                    .with_context(|| format!("Failed to remove directory: {}", path.display()))?;
+            }
+        } else if path.is_file() && !format.keeps(&path) && !preexisting.contains(&path) {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove file: {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::tempdir;
     use tokio::sync::mpsc as async_mpsc;
 
+    #[test]
+    fn test_read_event_dates() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("events.csv");
+        std::fs::write(&path, "date,label\n2023-05-01,New job\n\n2023-06-10T09:30:00Z,RustConf\n").unwrap();
+
+        let dates = read_event_dates(&path).unwrap();
+
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[0].to_rfc3339(), "2023-05-01T00:00:00+00:00");
+        assert_eq!(dates[1].to_rfc3339(), "2023-06-10T09:30:00+00:00");
+
+        std::fs::write(&path, "2023-05-01\nnext spring\n").unwrap();
+        assert!(read_event_dates(&path).is_err());
+    }
+
     #[tokio::test]
     async fn test_csv_writer_creation() {
-        let temp_dir = tempdir().unwrap();
-        let csv_path = temp_dir.path().join("test.csv");
         let (_, rx) = async_mpsc::channel::<Vec<String>>(10);
         
-        let writer = CsvWriter::new(csv_path.to_string_lossy().to_string(), rx, 100);
+        let writer = CsvWriter::new("test.csv".to_string(), rx, 100);
         assert_eq!(writer.buffer_size, 100);
     }
 
+    fn thread_headers() -> Vec<String> {
+        crate::processing::data_structures::THREAD_CSV_HEADERS.iter().map(|h| h.to_string()).collect()
+    }
+
+    fn test_tweet(id: &str, text: &str) -> super::super::data_structures::Tweet {
+        use super::super::data_structures::{Tweet, TweetEntities};
+
+        Tweet {
+            id_str: id.to_string(),
+            id: id.to_string(),
+            full_text: text.to_string(),
+            created_at: "Mon Jan 02 12:00:00 +0000 2023".to_string(),
+            favorite_count: "1".to_string(),
+            retweet_count: "0".to_string(),
+            retweeted: false,
+            favorited: false,
+            truncated: false,
+            lang: Some("en".to_string()),
+            source: "Twitter Web App".to_string(),
+            display_text_range: vec![],
+            in_reply_to_status_id: None,
+            in_reply_to_status_id_str: None,
+            in_reply_to_user_id: None,
+            in_reply_to_user_id_str: None,
+            in_reply_to_screen_name: None,
+            edit_info: None,
+            entities: TweetEntities::default(),
+            possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_to_memory() {
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let (mut writer, output) = CsvWriter::to_memory(rx, 1, vec!["id".to_string(), "text".to_string()]);
+
+        tx.send(vec!["1".to_string(), "hello, world".to_string()]).await.unwrap();
+        drop(tx);
+        writer.run().await.unwrap();
+
+        let content = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert_eq!(content, "id,text\n1,\"hello, world\"\n");
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_header_comment() {
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let (writer, output) = CsvWriter::to_memory(rx, 1, vec!["id".to_string(), "text".to_string()]);
+        let comment = CsvWriter::provenance_comment("testuser");
+        let mut writer = writer.with_header_comment(comment.clone());
+
+        tx.send(vec!["1".to_string(), "hello".to_string()]).await.unwrap();
+        drop(tx);
+        writer.run().await.unwrap();
+
+        let content = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(comment.starts_with(&format!("Generated by tweet-scrolls v{} for @testuser on ", env!("CARGO_PKG_VERSION"))));
+        assert_eq!(content, format!("# {}\nid,text\n1,hello\n", comment));
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_auto_quoting() {
+        use crate::processing::data_structures::ColumnType;
+
+        for (auto_quoting, expected) in [
+            (false, "# note\nid,text\n#types,integer,text\n1,plain words\n2,\"hello, world\"\n"),
+            (true, "# note\n\"id\",\"text\"\n#types,integer,text\n\"1\",\"plain words\"\n\"2\",\"hello, world\"\n"),
+        ] {
+            let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+            let (writer, output) = CsvWriter::to_memory(rx, 1, vec!["id".to_string(), "text".to_string()]);
+            let mut writer = writer
+                .with_header_comment("note")
+                .with_column_types(vec![ColumnType::Integer, ColumnType::Text])
+                .with_types_row()
+                .with_auto_quoting(auto_quoting);
+
+            tx.send(vec!["1".to_string(), "plain words".to_string()]).await.unwrap();
+            tx.send(vec!["2".to_string(), "hello, world".to_string()]).await.unwrap();
+            drop(tx);
+            writer.run().await.unwrap();
+
+            assert_eq!(String::from_utf8(output.lock().unwrap().clone()).unwrap(), expected);
+        }
+        assert!(matches!(CsvWriteOptions::default().quote_style(), csv::QuoteStyle::Necessary));
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_column_types() {
+        use crate::processing::data_structures::{ColumnType, THREAD_CSV_COLUMN_TYPES};
+
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let (writer, output) = CsvWriter::to_memory(rx, 10, thread_headers());
+        let mut writer = writer
+            .with_column_types(THREAD_CSV_COLUMN_TYPES.to_vec())
+            .with_types_row();
+
+        let mut record = vec![String::new(); 8];
+        record.push("text".to_string());
+        record.push(String::new());
+        tx.send(record).await.unwrap();
+        drop(tx);
+        writer.run().await.unwrap();
+
+        let content = output.lock().unwrap().clone();
+        assert_eq!(String::from_utf8(content.clone()).unwrap().lines().nth(1).unwrap(), "#types,text,datetime,integer,integer,integer,integer,integer,text,text,text,boolean");
+        let mut reader = csv::ReaderBuilder::new().comment(Some(b'#')).from_reader(content.as_slice());
+        assert_eq!(reader.records().count(), 1);
+
+        let (_, rx) = async_mpsc::channel::<Vec<String>>(1);
+        let (writer, _) = CsvWriter::to_memory(rx, 1, vec!["id".to_string()]);
+        let mut mismatched = writer.with_column_types(vec![ColumnType::Integer, ColumnType::Float]);
+        assert!(mismatched.run().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_writes_schema_file() {
+        use crate::processing::data_structures::THREAD_CSV_COLUMN_TYPES;
+
+        // The schema file sits next to the CSV, so this one needs a real directory
+        let temp_dir = tempdir().unwrap();
+        let csv_path = temp_dir.path().join("typed.csv");
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let mut writer = CsvWriter::new(csv_path.to_string_lossy().to_string(), rx, 10)
+            .with_column_types(THREAD_CSV_COLUMN_TYPES.to_vec());
+        drop(tx);
+        writer.run().await.unwrap();
+
+        let schema: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(temp_dir.path().join("schema.json")).unwrap()).unwrap();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["properties"]["Thread ID"]["type"], "string");
+        assert_eq!(schema["properties"]["Date time of first tweet"]["format"], "date-time");
+        assert_eq!(schema["properties"]["Likes in first tweet"]["type"], "integer");
+        assert_eq!(schema["required"][8], "Thread Text");
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_flushes_partial_buffer_when_sender_dropped() {
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(100);
+        let (mut writer, output) = CsvWriter::to_memory(rx, 100, thread_headers());
+
+        for i in 0..50 {
+            let mut record = vec![i.to_string()];
+            record.extend(std::iter::repeat_n(String::new(), 8));
+            tx.send(record).await.unwrap();
+        }
+        drop(tx);
+        writer.run().await.unwrap();
+
+        // Only the final flush after the channel closed ran
+        assert_eq!(writer.flush_count, 1);
+        assert_eq!(writer.deduplication_stats(), (50, 0));
+        let content = output.lock().unwrap().clone();
+        assert_eq!(csv::Reader::from_reader(content.as_slice()).records().count(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_dedup_on_field() {
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let (writer, output) = CsvWriter::to_memory(rx, 2, thread_headers());
+        let mut writer = writer.with_dedup_on_field(0);
+
+        for id in ["a", "b", "a", "c", "b"] {
+            let mut record = vec![id.to_string()];
+            record.extend(std::iter::repeat_n(String::new(), 8));
+            tx.send(record).await.unwrap();
+        }
+        drop(tx);
+        writer.run().await.unwrap();
+
+        assert_eq!(writer.deduplication_stats(), (3, 2));
+        let content = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert_eq!(content.lines().count(), 4); // header + 3 unique rows
+    }
+
+    #[tokio::test]
+    async fn test_csv_writer_transforms_run_in_order() {
+        use crate::processing::transforms::{escape_html, redact_emails, truncate_column};
+
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let (writer, output) = CsvWriter::to_memory(rx, 10, thread_headers());
+        let mut writer = writer
+            .with_transform(redact_emails(8))
+            .with_transform(escape_html(8))
+            .with_transform(truncate_column(8, 30));
+
+        let mut record = vec![String::new(); 8];
+        record.push("Ask <me> at me@example.com & friends".to_string());
+        tx.send(record).await.unwrap();
+        drop(tx);
+        writer.run().await.unwrap();
+
+        let content = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(content.lines().nth(1).unwrap().ends_with(",Ask &lt;me&gt; at [email redac"));
+    }
+
+    #[test]
+    fn test_csv_write_options_truncate_text() {
+        let options = CsvWriteOptions { max_text_length: Some(5), ..Default::default() };
+
+        assert_eq!(options.truncate_text("héllo wörld".to_string()), ("héllo…".to_string(), true));
+        assert_eq!(options.truncate_text("short".to_string()), ("short".to_string(), false));
+        assert!(!CsvWriteOptions::default().truncate_text("héllo wörld".to_string()).1);
+    }
+
+    #[tokio::test]
+    async fn test_write_csv_truncates_thread_text() {
+        let threads = vec![
+            Thread::from_tweets(vec![test_tweet("1", "A long thread"), test_tweet("2", "that keeps going")], "testuser"),
+            Thread::from_tweets(vec![test_tweet("3", "Short")], "testuser"),
+        ];
+
+        let (tx, rx) = async_mpsc::channel::<Vec<String>>(10);
+        let (mut writer, output) = CsvWriter::to_memory(rx, 10, thread_headers());
+        let options = CsvWriteOptions { max_text_length: Some(10), ..Default::default() };
+        let truncated = write_csv(&threads, "testuser", 0, tx, options).await.unwrap();
+        writer.run().await.unwrap();
+
+        assert_eq!(truncated, 1);
+        let content = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        assert!(content.lines().nth(1).unwrap().ends_with(",A long thr…,,false"));
+        assert!(content.lines().nth(2).unwrap().ends_with(",Short,,false"));
+    }
+
+    #[tokio::test]
+    async fn test_append_to_csv_skips_existing_threads() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("threads.csv");
+
+        let first_run = vec![Thread::from_tweets(vec![test_tweet("1", "Tweet 1")], "testuser")];
+        assert_eq!(append_to_csv(&first_run, "testuser", &path).await.unwrap(), 1);
+
+        let second_run = vec![
+            Thread::from_tweets(vec![test_tweet("1", "Tweet 1")], "testuser"),
+            Thread::from_tweets(vec![test_tweet("2", "Tweet 2")], "testuser"),
+        ];
+        assert_eq!(append_to_csv(&second_run, "testuser", &path).await.unwrap(), 1);
+        assert_eq!(append_to_csv(&second_run, "testuser", &path).await.unwrap(), 0);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Thread ID,"));
+        assert!(lines[1].starts_with("1,") && lines[1].ends_with(",Tweet 1,,false"));
+        assert!(lines[2].starts_with("2,") && lines[2].ends_with(",Tweet 2,,false"));
+
+        let per_tweet = temp_dir.path().join("threads_testuser_1.csv");
+        std::fs::write(&per_tweet, "ID,Text,Created At\n1,Tweet 1,2023-01-02\n").unwrap();
+        assert!(append_to_csv(&second_run, "testuser", &per_tweet).await.is_err());
+        assert_eq!(std::fs::read_to_string(&per_tweet).unwrap(), "ID,Text,Created At\n1,Tweet 1,2023-01-02\n");
+    }
+
     #[tokio::test]
     async fn test_write_threads_to_file() {
-        use super::super::data_structures::{Tweet, Thread, TweetEntities};
+        use super::super::data_structures::{Tweet, Thread, TweetEntities, TweetUrl};
         
         let temp_dir = tempdir().unwrap();
         let output_dir = temp_dir.path();
@@ -187,7 +1135,7 @@ mod tests {
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
             display_text_range: vec!["0".to_string(), "10".to_string()],
             in_reply_to_status_id: None,
@@ -197,12 +1145,15 @@ mod tests {
             in_reply_to_screen_name: None,
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         };
 
         let thread = Thread {
@@ -211,13 +1162,88 @@ mod tests {
             favorite_count: 5,
             retweet_count: 2,
             tweet_count: 1,
+            url: "https://twitter.com/testuser/status/thread_123".to_string(),
+            contains_poll: false,
+            first_tweet_url_entities: Vec::new(),
+            engagement_dropoff_rate: 0.0,
         };
 
-        let result = write_threads_to_file(&[thread], "testuser", 1234567890, output_dir).await;
+        let mut threads = [thread];
+        let result = write_threads_to_file(&threads, "testuser", 1234567890, output_dir).await;
         assert!(result.is_ok());
 
         let file_path = output_dir.join("threads_testuser_1234567890.txt");
         assert!(file_path.exists());
+        let content = std::fs::read_to_string(file_path).unwrap();
+        assert!(content.contains("Thread ID: thread_123\nURL: https://twitter.com/testuser/status/thread_123\n"));
+        assert!(!content.contains(SUMMARY_END_MARKER));
+
+        threads[0].tweets[0].created_at = "Sun Jan 01 12:00:00 +0000 2023".to_string();
+        let options = TxtWriteOptions { include_toc: true };
+        write_threads_to_file_with_options(&threads, "testuser", 1234567891, output_dir, chrono_tz::UTC, options).await.unwrap();
+
+        let content = std::fs::read_to_string(output_dir.join("threads_testuser_1234567891.txt")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "| # | Thread ID  | Date       | Tweets | Likes | Retweets |");
+        assert_eq!(lines[1], "|---|------------|------------|--------|-------|----------|");
+        assert_eq!(lines[2], "| 1 | thread_123 | 2023-01-01 | 1      | 5     | 2        |");
+        assert_eq!(lines[3], SUMMARY_END_MARKER);
+        assert_eq!(lines[5], "--- Start of Thread ---");
+        assert!(!content.contains("Links:"));
+
+        threads[0].tweets[0].entities.urls = ["example.com/a", "example.org/b"].iter()
+            .map(|display| TweetUrl {
+                url: "https://t.co/x".to_string(),
+                expanded_url: format!("https://{}", display),
+                display_url: display.to_string(),
+                indices: vec![],
+            })
+            .collect();
+        let threads = [Thread::from_tweets(threads[0].tweets.clone(), "testuser")];
+        write_threads_to_file(&threads, "testuser", 1234567892, output_dir).await.unwrap();
+
+        let content = std::fs::read_to_string(output_dir.join("threads_testuser_1234567892.txt")).unwrap();
+        assert!(content.contains("Links:\n  example.com/a → https://example.com/a\n  example.org/b → https://example.org/b\n\n--- End of Thread ---"));
+        let (record, _) = thread_csv_record(&threads[0], CsvWriteOptions::default());
+        assert_eq!(record[9], "https://example.com/a|https://example.org/b");
+        assert_eq!(record[7], "en");
+        let mut tweets = threads[0].tweets.clone();
+        tweets[0].lang = Some("und".to_string());
+        let undetermined = Thread::from_tweets(tweets, "testuser");
+        assert_eq!(thread_csv_record(&undetermined, CsvWriteOptions::default()).0[7], "unknown");
+    }
+
+    #[tokio::test]
+    async fn test_write_weekly_stats_csv() {
+        use super::super::data_structures::{Tweet, Thread};
+
+        let temp_dir = tempdir().unwrap();
+        let output_dir = temp_dir.path();
+
+        let tweet = Tweet {
+            favorite_count: "5".to_string(),
+            retweet_count: "2".to_string(),
+            ..test_tweet("123", "Test tweet")
+        };
+        let thread = Thread {
+            id: "123".to_string(),
+            tweets: vec![tweet],
+            favorite_count: 5,
+            retweet_count: 2,
+            tweet_count: 1,
+            url: String::new(),
+            contains_poll: false,
+            first_tweet_url_entities: Vec::new(),
+            engagement_dropoff_rate: 0.0,
+        };
+
+        write_weekly_stats_csv(&[thread], "testuser", 1234567890, output_dir).await.unwrap();
+
+        let content = std::fs::read_to_string(output_dir.join("weekly_stats_testuser_1234567890.csv")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("Likes 4-Week Moving Avg"));
+        assert_eq!(lines[1], "2023,1,1,5,2,0,5.00");
     }
 
     #[test]
@@ -234,4 +1260,65 @@ mod tests {
         let dm = prompt_input_from_reader(&mut dm_reader, "Enter DM path: ").unwrap();
         assert_eq!(dm, "");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_input_file() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("tweets.js");
+        std::fs::write(&file_path, "[]").unwrap();
+
+        assert!(validate_input_file(file_path.to_str().unwrap()).is_ok());
+        assert!(validate_input_file("").is_err());
+        assert!(validate_input_file(temp_dir.path().join("missing.js").to_str().unwrap())
+            .unwrap_err()
+            .contains("does not exist"));
+        assert!(validate_input_file(temp_dir.path().to_str().unwrap())
+            .unwrap_err()
+            .contains("Not a file"));
+    }
+
+    #[test]
+    fn test_retain_output_format() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+        let preexisting = snapshot_paths(temp_dir.path()).unwrap();
+        std::fs::write(temp_dir.path().join("threads.csv"), "").unwrap();
+        std::fs::write(temp_dir.path().join("threads.txt"), "").unwrap();
+        std::fs::write(temp_dir.path().join("stats.json"), "").unwrap();
+        let profiles_dir = temp_dir.path().join("relationship_profiles_me_1");
+        std::fs::create_dir(&profiles_dir).unwrap();
+        std::fs::write(profiles_dir.join("user_1_profile.txt"), "").unwrap();
+
+        assert_eq!(retain_output_format(temp_dir.path(), OutputFormat::All, &preexisting).unwrap(), 0);
+        assert_eq!(retain_output_format(temp_dir.path(), OutputFormat::CsvOnly, &preexisting).unwrap(), 2);
+        assert!(temp_dir.path().join("threads.csv").exists());
+        assert!(!temp_dir.path().join("threads.txt").exists());
+        assert!(temp_dir.path().join("stats.json").exists());
+        assert!(!profiles_dir.exists());
+        // Files that were there before the run are never touched
+        assert!(temp_dir.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_find_dm_files_sorted_by_name() {
+        let temp_dir = tempdir().unwrap();
+        for name in ["direct-messages-part2.js", "direct-messages.js", "direct-messages-part1.js", "tweets.js", "direct-messages.txt"] {
+            std::fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+
+        let files = find_dm_files(temp_dir.path()).unwrap();
+        let names: Vec<&str> = files.iter()
+            .map(|path| Path::new(path).file_name().unwrap().to_str().unwrap())
+            .collect();
+
+        assert_eq!(names, vec!["direct-messages-part1.js", "direct-messages-part2.js", "direct-messages.js"]);
+        assert!(find_dm_files(&temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_find_dm_files_empty_when_none_match() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("tweets.js"), "").unwrap();
+        assert!(find_dm_files(temp_dir.path()).unwrap().is_empty());
+    }
+}
@@ -17,12 +17,18 @@ pub mod data_structures;
 pub mod mvp_analyzer;
 pub mod reply_threads;
 pub mod dm_threads;
+/// Comparison of thread output between runs
+pub mod diff;
+/// Built-in column transforms for `CsvWriter`
+pub mod transforms;
 
 // Re-export commonly used types
-pub use data_structures::{Tweet, TweetWrapper, Thread, ProcessedConversation, CsvWriter};
-pub use tweets::{process_tweets, process_tweets_simple};
-pub use direct_messages::{process_dm_file, process_dm_conversations};
-pub use file_io::{write_threads_to_file, write_csv, get_input_file, get_dm_file};
+pub use data_structures::{Tweet, TweetWrapper, Thread, ProcessedConversation, CsvWriter, ColumnType, THREAD_CSV_COLUMN_TYPES, ProcessingProgress, Stage};
+pub use tweets::{process_tweets, process_tweets_simple, export_to_obsidian, compute_thread_engagement_curve, compute_engagement_dropoff_rate, extract_quoted_tweet_ids, parse_tweet_wrappers, build_thread_map, thread_containing_tweet, thread_url, extract_thread_images, exclude_matching_tweets, filter_sensitive, tweet_is_poll, extract_mentioned_users_with_frequency, FREQUENT_MENTIONS_LIMIT, analyze_posting_around_events, EventActivityReport, DEFAULT_EVENT_WINDOW_DAYS, FilterMode, SampleConfig, TweetOptions};
+pub use direct_messages::{process_dm_file, process_all_dm_files, process_dm_conversations, parse_dm_wrappers, sort_conversations, recency_ranks, DmOptions, DmSortMode};
+pub use file_io::{write_threads_to_file, write_threads_to_file_in_tz, write_threads_to_file_with_options, TxtWriteOptions, SUMMARY_END_MARKER, write_weekly_stats_csv, write_language_breakdown_csv, write_quote_graph_csv, write_thread_media_csv, write_frequent_mentions_csv, write_ghost_contacts_csv, write_event_activity_csv, read_event_dates, write_sentiment_timeline_csv, write_csv, append_to_csv, CsvWriteOptions, get_input_file, get_dm_file, get_dm_files, find_dm_files,
+    validate_input_file, confirm_relationship_analysis, select_output_format, retain_output_format, snapshot_paths, OutputFormat};
 pub use mvp_analyzer::{MvpAnalyzer, SimpleRelationship, ActivityPattern};
-pub use reply_threads::{process_reply_threads, format_thread_as_text};
-pub use dm_threads::{convert_dms_to_threads, format_dm_thread_as_text, DmThread};
\ No newline at end of file
+pub use reply_threads::{process_reply_threads, format_thread_as_text, CitationMode};
+pub use dm_threads::{convert_dms_to_threads, merge_dm_files, compute_avg_message_length, format_dm_thread_as_text, HIDDEN_MESSAGE_TEXT, PRIVACY_MODE_BANNER, detect_content_warnings, has_media_attachment, DmThread};
+pub use diff::{diff_against_previous, ThreadDiff};
\ No newline at end of file
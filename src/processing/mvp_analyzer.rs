@@ -6,7 +6,7 @@
 //! - Clean, readable output
 
 use anyhow::Result;
-use chrono::{DateTime, Timelike};
+use chrono::Timelike;
 use std::collections::HashMap;
 use std::path::Path;
 use tokio::fs as async_fs;
@@ -14,6 +14,7 @@ use tokio::fs as async_fs;
 #[allow(unused_imports)]
 use super::data_structures::{Thread, Tweet, TweetEntities, UserMention, EditInfo, EditInitial};
 use crate::models::direct_message::DmWrapper;
+use crate::utils::parse_twitter_date;
 
 /// Simple relationship statistics
 #[derive(Debug, Clone)]
@@ -47,6 +48,10 @@ pub struct MvpAnalyzer {
     pub hourly_activity: HashMap<u32, u32>,
     /// Activity counts by day of week
     pub daily_activity: HashMap<String, u32>,
+    /// DM contacts never replied to or mentioned in tweets
+    pub ghost_contacts: usize,
+    /// Tweet contacts with no DM conversation
+    pub tweet_only_contacts: usize,
 }
 
 impl Default for MvpAnalyzer {
@@ -62,6 +67,8 @@ impl MvpAnalyzer {
             relationships: HashMap::new(),
             hourly_activity: HashMap::new(),
             daily_activity: HashMap::new(),
+            ghost_contacts: 0,
+            tweet_only_contacts: 0,
         }
     }
 
@@ -70,7 +77,7 @@ impl MvpAnalyzer {
         for thread in threads {
             for tweet in &thread.tweets {
                 // Extract timestamp for activity analysis
-                if let Ok(dt) = DateTime::parse_from_str(&tweet.created_at, "%a %b %d %H:%M:%S %z %Y") {
+                if let Ok(dt) = parse_twitter_date(&tweet.created_at) {
                     let hour = dt.hour();
                     let day = dt.format("%A").to_string();
                     
@@ -120,7 +127,7 @@ impl MvpAnalyzer {
             for message in &conversation.messages {
                 if let Some(message_create) = &message.message_create {
                     // Extract timestamp for activity analysis
-                    if let Ok(dt) = DateTime::parse_from_rfc3339(message_create.created_at.as_ref().unwrap_or(&"".to_string())) {
+                    if let Ok(dt) = parse_twitter_date(message_create.created_at.as_deref().unwrap_or_default()) {
                         let hour = dt.hour();
                         let day = dt.format("%A").to_string();
                         
@@ -165,21 +172,21 @@ impl MvpAnalyzer {
     /// Get top relationships by interaction count
     pub fn get_top_relationships(&self, limit: usize) -> Vec<SimpleRelationship> {
         let mut relationships: Vec<SimpleRelationship> = self.relationships.values().cloned().collect();
-        relationships.sort_by(|a, b| b.interaction_count.cmp(&a.interaction_count));
+        relationships.sort_by_key(|r| std::cmp::Reverse(r.interaction_count));
         relationships.into_iter().take(limit).collect()
     }
 
     /// Get peak activity hours
     pub fn get_peak_activity_hours(&self, limit: usize) -> Vec<(u32, u32)> {
         let mut hours: Vec<(u32, u32)> = self.hourly_activity.iter().map(|(&h, &c)| (h, c)).collect();
-        hours.sort_by(|a, b| b.1.cmp(&a.1));
+        hours.sort_by_key(|h| std::cmp::Reverse(h.1));
         hours.into_iter().take(limit).collect()
     }
 
     /// Get most active days
     pub fn get_most_active_days(&self) -> Vec<(String, u32)> {
         let mut days: Vec<(String, u32)> = self.daily_activity.iter().map(|(d, &c)| (d.clone(), c)).collect();
-        days.sort_by(|a, b| b.1.cmp(&a.1));
+        days.sort_by_key(|d| std::cmp::Reverse(d.1));
         days
     }
 
@@ -247,6 +254,8 @@ impl MvpAnalyzer {
         report.push_str(&format!("Total unique relationships: {}\n", self.relationships.len()));
         report.push_str(&format!("Total activities tracked: {}\n", 
             self.hourly_activity.values().sum::<u32>()));
+        report.push_str(&format!("Ghost contacts (DMs only): {}\n", self.ghost_contacts));
+        report.push_str(&format!("Tweet-only contacts: {}\n", self.tweet_only_contacts));
         
         let most_active_hour = self.hourly_activity.iter()
             .max_by_key(|(_, &count)| count)
@@ -324,7 +333,7 @@ mod tests {
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "Twitter Web App".to_string(),
             display_text_range: vec!["0".to_string(), text.len().to_string()],
             in_reply_to_status_id: None,
@@ -341,6 +350,7 @@ mod tests {
                 })
             }),
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: mentions.into_iter().map(|m| UserMention {
@@ -353,6 +363,8 @@ mod tests {
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         }
     }
 
@@ -381,6 +393,10 @@ mod tests {
             tweet_count: 1,
             favorite_count: 0,
             retweet_count: 0,
+            url: String::new(),
+            contains_poll: false,
+            first_tweet_url_entities: Vec::new(),
+            engagement_dropoff_rate: 0.0,
         };
         
         let result = analyzer.analyze_tweets(&[thread]);
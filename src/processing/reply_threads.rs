@@ -1,12 +1,20 @@
 //! Reply thread processing module
 //! Treats all replies as potential thread starters
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use crate::processing::data_structures::Tweet;
+use crate::utils::parse_twitter_date;
 
 /// Process tweets to identify and build reply threads
 ///
-/// This function treats all replies as potential threads, not just self-replies
+/// This function treats all replies as potential threads, not just self-replies.
+/// Tweets are first partitioned by the root of their reply chain; each partition
+/// is independent, so with the `parallel` feature they are built on all cores.
+///
+/// Tweets carrying the archive's `conversation_id` are grouped by it directly.
+/// Only tweets without one walk their reply chain, and then join the
+/// conversation of the root they reach when that root has an ID.
 ///
 /// # Arguments
 /// * `tweets` - Vector of tweets to process
@@ -15,94 +23,146 @@ use crate::processing::data_structures::Tweet;
 /// # Returns
 /// Vector of thread vectors, where each thread is a vector of related tweets
 pub fn process_reply_threads(tweets: &[Tweet], _screen_name: &str) -> Vec<Vec<Tweet>> {
-    let mut threads = Vec::new();
-    let mut tweet_map: HashMap<String, &Tweet> = HashMap::new();
-    let mut processed_ids: HashMap<String, bool> = HashMap::new();
+    let mut tweet_map: HashMap<&str, &Tweet> = HashMap::new();
     
     // Build lookup map
     for tweet in tweets {
-        tweet_map.insert(tweet.id_str.clone(), tweet);
+        tweet_map.insert(tweet.id_str.as_str(), tweet);
     }
     
-    // Process each tweet
-    for tweet in tweets {
-        if processed_ids.contains_key(&tweet.id_str) {
-            continue;
-        }
-        
-        // Build thread starting from this tweet
-        let thread = build_thread_from_tweet(tweet, &tweet_map, &mut processed_ids);
-        
-        if !thread.is_empty() {
-            threads.push(thread);
-        }
+    // Partition tweets by the root of their reply chain
+    let mut root_order: Vec<&str> = Vec::new();
+    let mut partitions: HashMap<&str, Vec<&Tweet>> = HashMap::new();
+    for tweet in tweet_map.values() {
+        let root = match tweet.conversation_id.as_deref() {
+            Some(conversation_id) => conversation_id,
+            None => {
+                let root = find_thread_root(tweet, &tweet_map);
+                tweet_map.get(root)
+                    .and_then(|root_tweet| root_tweet.conversation_id.as_deref())
+                    .unwrap_or(root)
+            }
+        };
+        partitions.entry(root).or_insert_with(|| {
+            root_order.push(root);
+            Vec::new()
+        }).push(tweet);
     }
+    let partitions: Vec<Vec<&Tweet>> = root_order.iter()
+        .filter_map(|root| partitions.remove(root))
+        .collect();
     
-    // Sort threads by first tweet timestamp (newest first)
-    threads.sort_by(|a, b| {
-        b.first().map(|t| &t.created_at)
-            .cmp(&a.first().map(|t| &t.created_at))
-    });
+    #[cfg(feature = "parallel")]
+    let mut threads: Vec<Vec<Tweet>> = {
+        use rayon::prelude::*;
+        partitions.into_par_iter().map(build_thread).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let mut threads: Vec<Vec<Tweet>> = partitions.into_iter().map(build_thread).collect();
     
+    sort_threads_newest_first(&mut threads);
     threads
 }
 
-/// Build a complete thread starting from a given tweet
-fn build_thread_from_tweet(
-    start_tweet: &Tweet,
-    tweet_map: &HashMap<String, &Tweet>,
-    processed_ids: &mut HashMap<String, bool>,
-) -> Vec<Tweet> {
-    let mut thread = Vec::new();
-    let _current_tweet = start_tweet;
-    
-    // First, trace back to find the root of the thread
-    let mut root_tweet = start_tweet;
-    while let Some(parent_id) = &root_tweet.in_reply_to_status_id {
-        if let Some(parent) = tweet_map.get(parent_id) {
-            root_tweet = parent;
-        } else {
-            break;
+/// Sorts threads by the parsed date of their first tweet, newest first
+///
+/// Threads whose first tweet has an unparseable `created_at` go to the end;
+/// ties are broken by tweet ID (highest first) for a stable order.
+pub fn sort_threads_newest_first(threads: &mut [Vec<Tweet>]) {
+    threads.sort_by_cached_key(|thread| {
+        let first = thread.first();
+        let date = first.and_then(|t| parse_twitter_date(&t.created_at).ok());
+        (date.is_none(), Reverse(date), Reverse(first.map(|t| t.id_str.clone())))
+    });
+}
+
+/// Follows `in_reply_to_status_id` links to the earliest tweet present in the map
+fn find_thread_root<'a>(tweet: &'a Tweet, tweet_map: &HashMap<&str, &'a Tweet>) -> &'a str {
+    let mut root = tweet;
+    let mut path = vec![root.id_str.as_str()];
+    while let Some(parent) = root.in_reply_to_status_id.as_deref().and_then(|id| tweet_map.get(id)) {
+        // Malformed archives can contain reply cycles; every member of a cycle
+        // must map to the same root, so pick its smallest ID
+        if let Some(cycle_start) = path.iter().position(|id| *id == parent.id_str) {
+            return path[cycle_start..].iter().copied().min().unwrap_or(parent.id_str.as_str());
         }
+        path.push(parent.id_str.as_str());
+        root = parent;
     }
-    
-    // Now build the thread forward from the root
-    let mut stack = vec![root_tweet];
-    let mut visited = HashMap::new();
-    
-    while let Some(tweet) = stack.pop() {
-        if visited.contains_key(&tweet.id_str) {
-            continue;
-        }
-        
-        visited.insert(tweet.id_str.clone(), true);
-        processed_ids.insert(tweet.id_str.clone(), true);
-        thread.push(tweet.clone());
-        
-        // Find all direct replies to this tweet
-        for candidate in tweet_map.values() {
-            if let Some(reply_to_id) = &candidate.in_reply_to_status_id {
-                if reply_to_id == &tweet.id_str && !visited.contains_key(&candidate.id_str) {
-                    stack.push(candidate);
+    root.id_str.as_str()
+}
+
+/// Builds one thread from all tweets sharing a root, sorted chronologically
+fn build_thread(partition: Vec<&Tweet>) -> Vec<Tweet> {
+    let mut thread: Vec<Tweet> = partition.into_iter().cloned().collect();
+    // Unparseable dates sort after every dated tweet
+    thread.sort_by_cached_key(|tweet| {
+        let date = parse_twitter_date(&tweet.created_at).ok();
+        (date.is_none(), date, tweet.id_str.clone())
+    });
+    thread
+}
+
+/// How tweets in a formatted thread are labelled for citation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CitationMode {
+    /// No citation labels (default)
+    #[default]
+    None,
+    /// `[1]`, `[2]`, ...
+    Numbered,
+    /// `[a]`, `[b]`, ... continuing with `[aa]`, `[ab]` after `[z]`
+    Lettered,
+}
+
+impl CitationMode {
+    /// Returns the citation label for the tweet at `index` (zero-based), if any
+    pub fn label(&self, index: usize) -> Option<String> {
+        match self {
+            CitationMode::None => None,
+            CitationMode::Numbered => Some(format!("[{}]", index + 1)),
+            CitationMode::Lettered => {
+                let mut letters = Vec::new();
+                let mut n = index + 1;
+                while n > 0 {
+                    n -= 1;
+                    letters.push((b'a' + (n % 26) as u8) as char);
+                    n /= 26;
                 }
+                Some(format!("[{}]", letters.iter().rev().collect::<String>()))
             }
         }
     }
-    
-    // Sort thread chronologically
-    thread.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-    
-    thread
+}
+
+impl std::str::FromStr for CitationMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(CitationMode::None),
+            "numbered" => Ok(CitationMode::Numbered),
+            "lettered" => Ok(CitationMode::Lettered),
+            other => anyhow::bail!("Unknown citation mode: {} (expected none, numbered or lettered)", other),
+        }
+    }
 }
 
 /// Convert a thread of tweets into a human-readable format
-pub fn format_thread_as_text(thread: &[Tweet], _screen_name: &str) -> String {
+///
+/// With a `citation_mode` other than `None`, each tweet is prefixed with its
+/// citation label and a footnote block listing tweet IDs and dates is appended.
+pub fn format_thread_as_text(thread: &[Tweet], _screen_name: &str, citation_mode: CitationMode) -> String {
     let mut output = String::new();
     
     output.push_str(&format!("🧵 Thread with {} tweets\n", thread.len()));
     output.push_str(&format!("{}\n", "─".repeat(50)));
     
     for (idx, tweet) in thread.iter().enumerate() {
+        if let Some(label) = citation_mode.label(idx) {
+            output.push_str(&format!("{} ", label));
+        }
+        
         // Add thread position indicator
         if idx == 0 {
             output.push_str("🔹 [Thread Start]\n");
@@ -126,6 +186,15 @@ pub fn format_thread_as_text(thread: &[Tweet], _screen_name: &str) -> String {
         }
     }
     
+    if citation_mode != CitationMode::None {
+        output.push('\n');
+        for (idx, tweet) in thread.iter().enumerate() {
+            if let Some(label) = citation_mode.label(idx) {
+                output.push_str(&format!("{} Tweet ID: {}, Date: {}\n", label, tweet.id_str, tweet.created_at));
+            }
+        }
+    }
+    
     output.push_str(&format!("{}\n\n", "─".repeat(50)));
     
     output
@@ -141,13 +210,13 @@ mod tests {
             id_str: id.to_string(),
             id: id.to_string(),
             full_text: text.to_string(),
-            created_at: format!("2023-01-01 12:{:02}:00", id.parse::<u32>().unwrap_or(0)),
+            created_at: format!("Sun Jan 01 12:{:02}:00 +0000 2023", id.parse::<u32>().unwrap_or(0)),
             favorite_count: "0".to_string(),
             retweet_count: "0".to_string(),
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "test".to_string(),
             display_text_range: vec!["0".to_string(), text.len().to_string()],
             in_reply_to_status_id: reply_to_id.map(|s| s.to_string()),
@@ -158,6 +227,8 @@ mod tests {
             edit_info: None,
             entities: TweetEntities::default(),
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         }
     }
     
@@ -197,6 +268,76 @@ mod tests {
         assert_eq!(threads.len(), 3);
     }
     
+    #[test]
+    fn test_reply_threads_grouped_by_conversation_id() {
+        let in_conversation = |id: &str, reply_to: Option<&str>, conversation_id: &str| {
+            let mut tweet = create_test_tweet(id, "text", reply_to, None);
+            tweet.conversation_id = Some(conversation_id.to_string());
+            tweet
+        };
+        let tweets = vec![
+            // Replies to a deleted root still share its conversation
+            in_conversation("2", Some("1"), "1"),
+            in_conversation("3", Some("1"), "1"),
+            in_conversation("4", Some("99"), "1"),
+            // No conversation_id: joins the conversation of the root it reaches
+            create_test_tweet("5", "text", Some("4"), None),
+            in_conversation("6", None, "6"),
+            create_test_tweet("7", "text", None, None),
+        ];
+        
+        let threads = process_reply_threads(&tweets, "testuser");
+        let ids: Vec<Vec<&str>> = threads.iter().map(|t| t.iter().map(|tw| tw.id_str.as_str()).collect()).collect();
+        
+        assert_eq!(ids, vec![vec!["7"], vec!["6"], vec!["2", "3", "4", "5"]]);
+    }
+    
+    #[test]
+    fn test_reply_threads_sort_by_parsed_date() {
+        let dated = |id: &str, reply_to: Option<&str>, created_at: &str| {
+            let mut tweet = create_test_tweet(id, "text", reply_to, None);
+            tweet.created_at = created_at.to_string();
+            tweet
+        };
+        // "Mon" sorts before "Sun" as a string, but Jan 02 is the later date
+        let tweets = vec![
+            dated("1", None, "Sun Jan 01 12:00:00 +0000 2023"),
+            dated("2", Some("1"), "Mon Jan 02 12:00:00 +0000 2023"),
+            dated("3", Some("1"), "not a date"),
+            dated("4", None, "Sat Jan 07 12:00:00 +0000 2023"),
+            dated("5", None, "garbage"),
+        ];
+        
+        let threads = process_reply_threads(&tweets, "testuser");
+        let ids: Vec<Vec<&str>> = threads.iter().map(|t| t.iter().map(|tw| tw.id_str.as_str()).collect()).collect();
+        
+        assert_eq!(ids, vec![vec!["4"], vec!["1", "2", "3"], vec!["5"]]);
+    }
+    
+    #[test]
+    fn test_reply_threads_are_deterministic() {
+        let mut tweets = vec![
+            create_test_tweet("1", "Root", None, None),
+            create_test_tweet("2", "Reply", Some("1"), None),
+            create_test_tweet("8", "Cycle a", Some("9"), None),
+            create_test_tweet("9", "Cycle b", Some("8"), None),
+        ];
+        for id in 10..40 {
+            tweets.push(create_test_tweet(&id.to_string(), "Standalone", None, None));
+        }
+        
+        let first = process_reply_threads(&tweets, "testuser");
+        tweets.reverse();
+        let second = process_reply_threads(&tweets, "testuser");
+        
+        let ids = |threads: &[Vec<Tweet>]| -> Vec<Vec<String>> {
+            threads.iter().map(|t| t.iter().map(|tw| tw.id_str.clone()).collect()).collect()
+        };
+        assert_eq!(ids(&first), ids(&second));
+        assert_eq!(first.len(), 32);
+        assert!(ids(&first).contains(&vec!["8".to_string(), "9".to_string()]));
+    }
+    
     #[test]
     fn test_thread_formatting() {
         let thread = vec![
@@ -204,7 +345,7 @@ mod tests {
             create_test_tweet("2", "Continuing the thought", Some("1"), Some("testuser")),
         ];
         
-        let formatted = format_thread_as_text(&thread, "testuser");
+        let formatted = format_thread_as_text(&thread, "testuser", CitationMode::None);
         
         assert!(formatted.contains("Thread with 2 tweets"));
         assert!(formatted.contains("[Thread Start]"));
@@ -212,4 +353,34 @@ mod tests {
         assert!(formatted.contains("Starting a thread"));
         assert!(formatted.contains("Continuing the thought"));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_thread_formatting_with_citations() {
+        let thread = vec![
+            create_test_tweet("1", "Starting a thread", None, None),
+            create_test_tweet("2", "Continuing the thought", Some("1"), Some("testuser")),
+        ];
+        
+        let numbered = format_thread_as_text(&thread, "testuser", CitationMode::Numbered);
+        assert!(numbered.contains("[1] 🔹 [Thread Start]"));
+        assert!(numbered.contains("[2] ↳ Reply to @testuser"));
+        assert!(numbered.contains("[2] Tweet ID: 2, Date: "));
+        
+        let lettered = format_thread_as_text(&thread, "testuser", CitationMode::Lettered);
+        assert!(lettered.contains("[a] Tweet ID: 1"));
+        assert!(lettered.contains("[b] Tweet ID: 2"));
+        
+        let plain = format_thread_as_text(&thread, "testuser", CitationMode::None);
+        assert!(!plain.contains("Tweet ID:"));
+    }
+    
+    #[test]
+    fn test_citation_mode_labels() {
+        assert_eq!(CitationMode::None.label(0), None);
+        assert_eq!(CitationMode::Numbered.label(9).as_deref(), Some("[10]"));
+        assert_eq!(CitationMode::Lettered.label(25).as_deref(), Some("[z]"));
+        assert_eq!(CitationMode::Lettered.label(26).as_deref(), Some("[aa]"));
+        assert_eq!("Numbered".parse::<CitationMode>().unwrap(), CitationMode::Numbered);
+        assert!("roman".parse::<CitationMode>().is_err());
+    }
+}
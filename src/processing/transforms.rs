@@ -0,0 +1,119 @@
+//! Built-in column transforms for `CsvWriter::with_transform`
+//!
+//! Each factory returns a closure that rewrites one column of a record and
+//! leaves the others untouched. Records too short to have the column pass
+//! through unchanged.
+
+/// Placeholder written in place of redacted email addresses
+pub const EMAIL_PLACEHOLDER: &str = "[email redacted]";
+
+/// Applies `f` to the column at `index`, if the record has one
+fn map_column(mut record: Vec<String>, index: usize, f: impl Fn(&str) -> String) -> Vec<String> {
+    if let Some(value) = record.get_mut(index) {
+        *value = f(value);
+    }
+    record
+}
+
+/// Shortens the column at `index` to at most `max_chars` characters
+pub fn truncate_column(index: usize, max_chars: usize) -> impl Fn(Vec<String>) -> Vec<String> + Send + 'static {
+    move |record| map_column(record, index, |value| value.chars().take(max_chars).collect())
+}
+
+/// Escapes `&`, `<`, `>`, `"` and `'` in the column at `index` as HTML entities
+pub fn escape_html(index: usize) -> impl Fn(Vec<String>) -> Vec<String> + Send + 'static {
+    move |record| map_column(record, index, |value| {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    })
+}
+
+/// Replaces email-like patterns in the column at `index` with [`EMAIL_PLACEHOLDER`]
+pub fn redact_emails(index: usize) -> impl Fn(Vec<String>) -> Vec<String> + Send + 'static {
+    move |record| map_column(record, index, redact_email_addresses)
+}
+
+/// Replaces every `local@domain.tld` pattern in `text` with [`EMAIL_PLACEHOLDER`]
+fn redact_email_addresses(text: &str) -> String {
+    let is_local = |c: char| c.is_ascii_alphanumeric() || "._%+-".contains(c);
+    let is_domain = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut redacted = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '@' {
+            let local_len = chars[..i].iter().rev().take_while(|&&c| is_local(c)).count();
+            let mut end = i + 1;
+            while end < chars.len() && is_domain(chars[end]) {
+                end += 1;
+            }
+            // Sentence punctuation after an address is not part of it
+            while end > i + 1 && matches!(chars[end - 1], '.' | '-') {
+                end -= 1;
+            }
+            let domain: String = chars[i + 1..end].iter().collect();
+            let valid_domain = !domain.starts_with('.')
+                && domain.rsplit_once('.')
+                    .is_some_and(|(_, tld)| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+
+            if local_len > 0 && valid_domain {
+                // The local part is ASCII, so its byte length equals its char count
+                redacted.truncate(redacted.len() - local_len);
+                redacted.push_str(EMAIL_PLACEHOLDER);
+                i = end;
+                continue;
+            }
+        }
+        redacted.push(chars[i]);
+        i += 1;
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_truncate_column() {
+        let truncate = truncate_column(1, 3);
+        assert_eq!(truncate(record(&["id", "héllo"])), record(&["id", "hél"]));
+        assert_eq!(truncate(record(&["id", "hi"])), record(&["id", "hi"]));
+        assert_eq!(truncate(record(&["id"])), record(&["id"]));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        let escape = escape_html(0);
+        assert_eq!(
+            escape(record(&["<b>\"Tom\" & 'Jerry'</b>", "<keep>"])),
+            record(&["&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;", "<keep>"])
+        );
+    }
+
+    #[test]
+    fn test_redact_emails() {
+        let redact = redact_emails(0);
+        assert_eq!(
+            redact(record(&["Mail jane.doe+x@example.co.uk or bob@test.io."])),
+            record(&["Mail [email redacted] or [email redacted]."])
+        );
+        assert_eq!(redact(record(&["Thanks @handle and me@localhost"])), record(&["Thanks @handle and me@localhost"]));
+        assert_eq!(redact(record(&["ünï a@b.com"])), record(&["ünï [email redacted]"]));
+    }
+}
@@ -2,106 +2,624 @@
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use regex::Regex;
 use serde_json::from_str;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::Instant;
 use tokio::fs as async_fs;
+use tokio::sync::watch;
 use tokio::task;
 
 #[allow(unused_imports)]
 use super::data_structures::{Tweet, TweetWrapper, Thread, TweetEntities};
-use super::file_io::write_threads_to_file;
+use super::data_structures::{report_progress, ProcessingProgress, Stage};
+use super::reply_threads::{format_thread_as_text, CitationMode};
+use super::file_io::{append_to_csv, write_event_activity_csv, write_frequent_mentions_csv, write_language_breakdown_csv, write_quote_graph_csv, write_thread_media_csv, write_thread_stats_json, write_threads_to_file_in_tz, write_weekly_stats_csv, CsvWriteOptions};
+use crate::relationship::anonymization::{update_shared_registry, SharedPseudonymRegistry};
 use crate::utils::enhanced_csv_writer::EnhancedCsvWriter;
+use crate::utils::explain::ExplainLog;
+use crate::utils::{format_bytes, parse_twitter_date, sanitize_filename};
 
-/// Processes tweets from a JSON file and generates output files
+/// Processes a reproducible random subset of tweets, for debugging large archives
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleConfig {
+    /// Seed for the PRNG; the same seed and archive always give the same sample
+    pub seed: u64,
+    /// Share of tweets to keep, between 0.0 and 1.0
+    pub fraction: f64,
+}
+
+impl SampleConfig {
+    /// Keeps `fraction * total` tweets (rounded) chosen with a ChaCha8 PRNG seeded by `seed`
+    ///
+    /// Kept tweets stay in their original order.
+    pub fn sample<T>(&self, items: Vec<T>) -> Vec<T> {
+        let total = items.len();
+        let amount = ((self.fraction.clamp(0.0, 1.0) * total as f64).round() as usize).min(total);
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut keep = vec![false; total];
+        for index in rand::seq::index::sample(&mut rng, total, amount) {
+            keep[index] = true;
+        }
+        items.into_iter()
+            .zip(keep)
+            .filter_map(|(item, kept)| kept.then_some(item))
+            .collect()
+    }
+
+    /// The notice written to the summary and at the top of the text outputs
+    pub fn banner(&self, sampled: usize, total: usize) -> String {
+        format!("SAMPLE MODE: Using {} of {} tweets (seed {})", sampled, total, self.seed)
+    }
+}
+
+/// Controls which tweets take part in processing based on retweet status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterMode {
+    /// Drop tweets the archive marks as `retweeted` (default)
+    ///
+    /// Only the `retweeted` flag counts here, as before retweet modes existed;
+    /// tweets whose text merely starts with `RT @` are kept.
+    #[default]
+    ExcludeRetweets,
+    /// Keep only retweets (see [`is_retweet`]); each one is written as a
+    /// standalone single-tweet thread
+    RetweetsOnly,
+    /// Keep every tweet, retweets included
+    All,
+}
+
+impl FilterMode {
+    /// Returns true if the tweet should be kept under this mode
+    pub fn keeps(&self, tweet: &Tweet) -> bool {
+        match self {
+            FilterMode::ExcludeRetweets => !tweet.retweeted,
+            FilterMode::RetweetsOnly => is_retweet(tweet),
+            FilterMode::All => true,
+        }
+    }
+}
+
+/// Returns true if the tweet is a retweet rather than original content
+///
+/// Besides the `retweeted` flag this matches text starting with `RT @`, which
+/// is how most archives record the user's own retweets.
+pub fn is_retweet(tweet: &Tweet) -> bool {
+    tweet.retweeted || tweet.full_text.starts_with("RT @")
+}
+
+/// Parses the tweet array out of a `tweets.js` file
+///
+/// The usual layout is `window.YTD.tweets.part0 = [...]`. Some older exports wrap
+/// the array in an object (`{"tweets": [...]}`); when the bare-array fast path
+/// fails, the top-level object is parsed and its `tweets` array is used instead.
+pub fn parse_tweet_wrappers(script_content: &str) -> Result<Vec<TweetWrapper>> {
+    let json_start = script_content.find('[').context("Invalid JSON format: missing opening bracket")?;
+    let json_end = script_content.rfind(']').context("Invalid JSON format: missing closing bracket")?;
+    let array_error = match from_str::<Vec<TweetWrapper>>(&script_content[json_start..=json_end]) {
+        Ok(tweets) => return Ok(tweets),
+        Err(e) => e,
+    };
+
+    let object = script_content.find('{')
+        .zip(script_content.rfind('}'))
+        .filter(|(start, end)| start < end && *start < json_start)
+        .and_then(|(start, end)| from_str::<serde_json::Value>(&script_content[start..=end]).ok());
+    match object.and_then(|mut value| value.get_mut("tweets").map(serde_json::Value::take)) {
+        Some(tweets) if tweets.is_array() => {
+            serde_json::from_value(tweets).context("Failed to parse JSON: invalid tweets array")
+        }
+        _ => Err(array_error).context("Failed to parse JSON"),
+    }
+}
+
+/// Maps each quote-tweet's ID to the ID of the tweet it quotes
+pub fn extract_quoted_tweet_ids(tweets: &[Tweet]) -> HashMap<String, String> {
+    tweets.iter()
+        .filter_map(|tweet| {
+            tweet.quoted_status_id.as_ref()
+                .filter(|quoted| !quoted.is_empty())
+                .map(|quoted| (tweet.id_str.clone(), quoted.clone()))
+        })
+        .collect()
+}
+
+/// Indexes threads by the ID of every tweet they contain, not just the root
+///
+/// If a tweet ID appears in more than one thread, the last thread wins.
+pub fn build_thread_map(threads: &[Thread]) -> HashMap<String, &Thread> {
+    threads.iter()
+        .flat_map(|thread| thread.tweets.iter().map(move |tweet| (tweet.id_str.clone(), thread)))
+        .collect()
+}
+
+/// Looks up the thread containing `tweet_id` in a map from [`build_thread_map`]
+pub fn thread_containing_tweet<'a>(map: &'a HashMap<String, &'a Thread>, tweet_id: &str) -> Option<&'a Thread> {
+    map.get(tweet_id).copied()
+}
+
+/// Removes tweets whose `full_text` matches any of the regex `patterns`
+///
+/// Returns how many tweets each pattern removed, in pattern order. A tweet
+/// matching several patterns is counted against the first one. Fails if any
+/// pattern is not a valid regex.
+pub fn exclude_matching_tweets(tweets: &mut Vec<Tweet>, patterns: &[String]) -> Result<Vec<(String, usize)>> {
+    let regexes = patterns.iter()
+        .map(|pattern| Regex::new(pattern).with_context(|| format!("Invalid exclude pattern: {}", pattern)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut removed = vec![0; regexes.len()];
+    tweets.retain(|tweet| match regexes.iter().position(|regex| regex.is_match(&tweet.full_text)) {
+        Some(index) => {
+            removed[index] += 1;
+            false
+        }
+        None => true,
+    });
+
+    Ok(patterns.iter().cloned().zip(removed).collect())
+}
+
+/// Records in `log` whether each tweet survives `filter_mode` and the `exclude_patterns`, and why
+///
+/// Mirrors the filtering in [`process_tweets`]: the retweet filter is checked
+/// first, then the patterns in order. Fails if any pattern is not a valid regex.
+pub fn explain_tweet_filters(tweets: &[Tweet], filter_mode: FilterMode, exclude_patterns: &[String], log: &ExplainLog) -> Result<()> {
+    let regexes = exclude_patterns.iter()
+        .map(|pattern| Regex::new(pattern).with_context(|| format!("Invalid exclude pattern: {}", pattern)))
+        .collect::<Result<Vec<_>>>()?;
+
+    for tweet in tweets {
+        let kind = if is_retweet(tweet) { "retweet" } else { "original" };
+        let decision = if !filter_mode.keeps(tweet) {
+            format!("dropped ({}, filter mode {:?})", kind, filter_mode)
+        } else if let Some(index) = regexes.iter().position(|regex| regex.is_match(&tweet.full_text)) {
+            format!("dropped (matches exclude pattern '{}')", exclude_patterns[index])
+        } else {
+            format!("kept ({}, no filter match)", kind)
+        };
+        log.record(format_args!("tweet {}", tweet.id_str), decision)?;
+    }
+    Ok(())
+}
+
+/// Returns true if the tweet is a poll
+///
+/// The archive keeps only a poll's question in `full_text`, so polls are
+/// recognised by a trailing `[poll]` marker or by poll option labels in the entities.
+pub fn tweet_is_poll(tweet: &Tweet) -> bool {
+    tweet.full_text.trim_end().ends_with("[poll]")
+        || tweet.entities.poll_option_labels.as_ref().is_some_and(|labels| !labels.is_empty())
+}
+
+/// Cuts every thread longer than `max_tweets` down to its first `max_tweets` tweets
+///
+/// Each thread's `tweet_count` keeps the original length, so the outputs can
+/// note the cut. Returns how many threads were truncated.
+pub fn truncate_threads(threads: &mut [Thread], max_tweets: usize) -> usize {
+    threads.iter_mut().map(|thread| thread.truncate(max_tweets)).filter(|&cut| cut).count()
+}
+
+/// Removes threads in which any tweet is marked `possibly_sensitive`
+///
+/// Returns how many threads were removed.
+pub fn filter_sensitive(threads: &mut Vec<Thread>) -> usize {
+    let before = threads.len();
+    threads.retain(|thread| !thread.tweets.iter().any(|tweet| tweet.possibly_sensitive == Some(true)));
+    before - threads.len()
+}
+
+/// Days before and after each event compared by [`analyze_posting_around_events`] in the pipeline
+pub const DEFAULT_EVENT_WINDOW_DAYS: i64 = 30;
+
+/// Thread counts before and after one user-supplied event date
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventActivityReport {
+    /// The event date
+    pub event_date: DateTime<Utc>,
+    /// Threads started in the window before the event
+    pub pre_window_count: usize,
+    /// Threads started in the window from the event onwards
+    pub post_window_count: usize,
+    /// Percentage change from before to after (100.0 when nothing was posted before)
+    pub change_pct: f64,
+}
+
+/// Compares how many threads were started in the `window_days` before and after each event
+///
+/// A thread counts at the time of its first tweet. The pre window is
+/// `[event - window_days, event)` and the post window `[event, event + window_days)`.
+/// Threads whose first tweet date doesn't parse are ignored. Reports are returned
+/// in the order of `event_dates`.
+pub fn analyze_posting_around_events(threads: &[Thread], event_dates: &[DateTime<Utc>], window_days: i64) -> Vec<EventActivityReport> {
+    let starts: Vec<DateTime<Utc>> = threads.iter()
+        .filter_map(|thread| thread.tweets.first())
+        .filter_map(|tweet| parse_twitter_date(&tweet.created_at).ok())
+        .collect();
+    let window = chrono::Duration::days(window_days);
+
+    event_dates.iter()
+        .map(|&event_date| {
+            let pre_window_count = starts.iter().filter(|&&start| start >= event_date - window && start < event_date).count();
+            let post_window_count = starts.iter().filter(|&&start| start >= event_date && start < event_date + window).count();
+            let change_pct = match (pre_window_count, post_window_count) {
+                (0, 0) => 0.0,
+                (0, _) => 100.0,
+                (pre, post) => (post as f64 - pre as f64) / pre as f64 * 100.0,
+            };
+            EventActivityReport { event_date, pre_window_count, post_window_count, change_pct }
+        })
+        .collect()
+}
+
+/// Number of handles written to `frequent_mentions_{screen_name}_{timestamp}.csv`
+pub const FREQUENT_MENTIONS_LIMIT: usize = 20;
+
+/// Matches an `@handle` not preceded by a word character, so emails are skipped
+fn mention_regex() -> &'static Regex {
+    static MENTION: OnceLock<Regex> = OnceLock::new();
+    MENTION.get_or_init(|| {
+        Regex::new(r"(?:^|[^A-Za-z0-9_@])@([A-Za-z0-9_]{1,15})").expect("mention regex is valid")
+    })
+}
+
+/// Counts the `@handle`s in the text of every tweet in `threads`, most mentioned first
+///
+/// Handles are raw (not hashed) and lowercased, since Twitter handles are
+/// case-insensitive. The archive owner's `screen_name` is left out. Ties are
+/// ordered alphabetically. Unlike the reply targets used for the mention
+/// network, this only looks at the tweet text.
+pub fn extract_mentioned_users_with_frequency(threads: &[Thread], screen_name: &str) -> Vec<(String, usize)> {
+    let owner = screen_name.to_lowercase();
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for tweet in threads.iter().flat_map(|thread| &thread.tweets) {
+        for captures in mention_regex().captures_iter(&tweet.full_text) {
+            let handle = captures[1].to_lowercase();
+            if handle != owner {
+                *counts.entry(handle).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut mentions: Vec<(String, usize)> = counts.into_iter().collect();
+    mentions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    mentions
+}
+
+/// Matches Twitter media CDN links and `t.co` short links in tweet text
+fn media_url_regex() -> &'static Regex {
+    static MEDIA_URL: OnceLock<Regex> = OnceLock::new();
+    MEDIA_URL.get_or_init(|| {
+        Regex::new(r"https://pbs\.twimg\.com/media/\S+|https://t\.co/\S+").expect("media URL regex is valid")
+    })
+}
+
+/// Collects `(tweet_id, url)` pairs for media links in a thread's tweets
+///
+/// Matches `https://pbs.twimg.com/media/…` CDN links and `https://t.co/…`
+/// short links, in tweet order. `t.co` links are redirects and may point at
+/// any page, so downloaders need to follow the redirect first.
+pub fn extract_thread_images(thread: &Thread) -> Vec<(String, String)> {
+    let regex = media_url_regex();
+    thread.tweets.iter()
+        .flat_map(|tweet| {
+            regex.find_iter(&tweet.full_text)
+                .map(move |url| (tweet.id_str.clone(), url.as_str().to_string()))
+        })
+        .collect()
+}
+
+/// Canonical `https://twitter.com/{screen_name}/status/{id}` URL of a thread
+///
+/// Threads that start with a retweet link to the retweeted tweet instead: the
+/// author comes from the `RT @author:` preamble and the ID from a status link
+/// in the tweet, falling back to the archive ID (which Twitter redirects) when
+/// the text carries no link.
+pub fn thread_url(thread: &Thread, screen_name: &str) -> String {
+    let Some(first) = thread.tweets.first() else {
+        return format!("https://twitter.com/{}/status/{}", screen_name, thread.id);
+    };
+    if !is_retweet(first) {
+        return format!("https://twitter.com/{}/status/{}", screen_name, first.id_str);
+    }
+
+    let author = first.full_text.strip_prefix("RT @")
+        .and_then(|rest| rest.split(':').next())
+        .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+        .unwrap_or(screen_name);
+    let status_id = first.entities.urls.iter()
+        .map(|url| url.expanded_url.as_str())
+        .chain(std::iter::once(first.full_text.as_str()))
+        .find_map(status_id_in_link)
+        .unwrap_or(&first.id_str);
+    format!("https://twitter.com/{}/status/{}", author, status_id)
+}
+
+/// Share of the thread's likes that each tweet received, in thread order
+///
+/// Entries sum to 1. A thread without likes gets an even split.
+pub fn compute_thread_engagement_curve(thread: &Thread) -> Vec<f64> {
+    let likes: Vec<f64> = thread.tweets.iter()
+        .map(|tweet| tweet.favorite_count.parse::<u32>().unwrap_or(0) as f64)
+        .collect();
+    let total: f64 = likes.iter().sum();
+    if total == 0.0 {
+        return vec![1.0 / likes.len() as f64; likes.len()];
+    }
+    likes.iter().map(|count| count / total).collect()
+}
+
+/// Pearson correlation between tweet position and its share of the thread's likes
+///
+/// Negative when engagement falls off as the thread goes on, positive when it
+/// builds. Threads with fewer than two tweets, or an even split, give 0.
+pub fn compute_engagement_dropoff_rate(thread: &Thread) -> f64 {
+    let curve = compute_thread_engagement_curve(thread);
+    let n = curve.len() as f64;
+    if curve.len() < 2 {
+        return 0.0;
+    }
+    let mean_position = (n - 1.0) / 2.0;
+    let mean_share = curve.iter().sum::<f64>() / n;
+    let (mut covariance, mut position_variance, mut share_variance) = (0.0, 0.0, 0.0);
+    for (i, share) in curve.iter().enumerate() {
+        let dx = i as f64 - mean_position;
+        let dy = share - mean_share;
+        covariance += dx * dy;
+        position_variance += dx * dx;
+        share_variance += dy * dy;
+    }
+    if share_variance == 0.0 {
+        return 0.0;
+    }
+    covariance / (position_variance * share_variance).sqrt()
+}
+
+/// Extracts the numeric ID following `/status/` in a link, if any
+fn status_id_in_link(text: &str) -> Option<&str> {
+    let start = text.find("/status/")? + "/status/".len();
+    let rest = &text[start..];
+    let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+/// Options for [`process_tweets`]
+#[derive(Debug, Clone)]
+pub struct TweetOptions {
+    /// Twitter handle used in output file names
+    pub screen_name: String,
+    /// Directory to write results to
+    pub output_dir: PathBuf,
+    /// Timestamp used in output file names
+    pub timestamp: i64,
+    /// Which tweets to process based on retweet status
+    pub filter_mode: FilterMode,
+    /// A mode other than `None` additionally writes
+    /// `cited_threads_{screen_name}_{timestamp}.txt` with labelled tweets
+    pub citation_mode: CitationMode,
+    /// Regexes; tweets whose text matches any of them are dropped before threads are built
+    pub exclude_patterns: Vec<String>,
+    /// Text column options for the threads CSV
+    pub csv_options: CsvWriteOptions,
+    /// Timezone for tweet timestamps in the threads text and CSV outputs
+    pub output_locale: Tz,
+    /// Drop threads containing any tweet marked `possibly_sensitive`
+    pub exclude_sensitive: bool,
+    /// Non-empty additionally writes `event_activity_{screen_name}_{timestamp}.csv`
+    /// (see [`analyze_posting_around_events`])
+    pub event_dates: Vec<DateTime<Utc>>,
+    /// Append threads not already listed in this CSV to it (see [`append_to_csv`])
+    pub append_to: Option<PathBuf>,
+    /// Thread only a seeded random subset of the parsed tweets; the summary and
+    /// text outputs then start with a notice
+    pub sample_mode: Option<SampleConfig>,
+    /// Also write archive-wide thread statistics to `thread_stats_{screen_name}_{timestamp}.json`
+    pub stats_json: bool,
+    /// Keep only the first N tweets of longer threads (see [`truncate_threads`])
+    pub max_thread_length: Option<usize>,
+}
+
+impl TweetOptions {
+    /// Creates options that thread every non-retweet and write the standard outputs
+    pub fn new(screen_name: impl Into<String>, output_dir: impl Into<PathBuf>, timestamp: i64) -> Self {
+        Self {
+            screen_name: screen_name.into(),
+            output_dir: output_dir.into(),
+            timestamp,
+            filter_mode: FilterMode::default(),
+            citation_mode: CitationMode::None,
+            exclude_patterns: Vec::new(),
+            csv_options: CsvWriteOptions::default(),
+            output_locale: chrono_tz::UTC,
+            exclude_sensitive: false,
+            event_dates: Vec::new(),
+            append_to: None,
+            sample_mode: None,
+            stats_json: false,
+            max_thread_length: None,
+        }
+    }
+}
+
+/// Processes tweets from a JSON file and generates output files as set in `options`
+///
+/// Progress updates are published to `progress` when provided; pass `None`
+/// to run silently. With `pseudonyms` set, every user the threads reply to is
+/// added to the run's shared pseudonym registry. With `explain` set, the filter
+/// decision for every tweet and every sensitive thread is logged to it.
 pub async fn process_tweets(
-    input_file: &str, 
-    screen_name: &str, 
-    output_dir: &Path, 
-    _timestamp: i64
+    input_file: &str,
+    options: &TweetOptions,
+    pseudonyms: Option<&SharedPseudonymRegistry>,
+    explain: Option<&ExplainLog>,
+    progress: Option<&watch::Sender<ProcessingProgress>>,
 ) -> Result<()> {
-    let screen_name = screen_name.to_string(); // Clone to own the String
+    let screen_name = options.screen_name.clone();
+    let output_dir = options.output_dir.as_path();
+    let filter_mode = options.filter_mode;
+    let citation_mode = options.citation_mode;
+    let exclude_patterns = options.exclude_patterns.as_slice();
+    let csv_options = options.csv_options;
+    let output_locale = options.output_locale;
+    let event_dates = options.event_dates.as_slice();
 
     let start_datetime = Local::now();
-    let timestamp = Utc::now().timestamp();
+    let timestamp = options.timestamp;
 
-    println!("🕰️ Avengers, assemble! Mission start time: {}", start_datetime.format("%Y-%m-%d %H:%M:%S"));
+    report_progress(progress, Stage::Starting, 0, 0, format!("🕰️ Avengers, assemble! Mission start time: {}", start_datetime.format("%Y-%m-%d %H:%M:%S")));
     let start_time = Instant::now();
 
-    println!("🕵️‍♀️ Black Widow is infiltrating the enemy base (reading the file)...");
+    report_progress(progress, Stage::Reading, 0, 0, "🕵️‍♀️ Black Widow is infiltrating the enemy base (reading the file)...");
     let script_content = async_fs::read_to_string(input_file).await.context("Failed to read input file")?;
-    println!("📂 Intelligence gathered. File size: {} bytes", script_content.len());
+    report_progress(progress, Stage::Reading, script_content.len(), script_content.len(), format!("📂 Intelligence gathered. File size: {}", format_bytes(script_content.len() as u64)));
 
-    println!("🧠 Tony and Bruce are decoding the alien artifact (parsing JSON)...");
-    let json_start = script_content.find('[').context("Invalid JSON format: missing opening bracket")?;
-    let json_end = script_content.rfind(']').context("Invalid JSON format: missing closing bracket")?;
-    let json_content = &script_content[json_start..=json_end];
-    let tweets: Vec<TweetWrapper> = from_str(json_content).context("Failed to parse JSON")?;
+    report_progress(progress, Stage::Parsing, 0, 0, "🧠 Tony and Bruce are decoding the alien artifact (parsing JSON)...");
+    let tweets = parse_tweet_wrappers(&script_content)?;
     let total_tweets = tweets.len();
-    println!("🎉 Decoding complete! We've identified {} potential threats (tweets).", total_tweets);
+    report_progress(progress, Stage::Parsing, total_tweets, total_tweets, format!("🎉 Decoding complete! We've identified {} potential threats (tweets).", total_tweets));
+    let (tweets, sample_banner) = match options.sample_mode {
+        Some(sample) => {
+            let tweets = sample.sample(tweets);
+            let banner = sample.banner(tweets.len(), total_tweets);
+            report_progress(progress, Stage::Parsing, tweets.len(), total_tweets, format!("🎲 {}", banner));
+            (tweets, Some(banner))
+        }
+        None => (tweets, None),
+    };
 
-    println!("🇺🇸 Captain America is assembling the strike team (filtering tweets)...");
+    report_progress(progress, Stage::Filtering, 0, total_tweets, "🇺🇸 Captain America is assembling the strike team (filtering tweets)...");
     let mut tweets: Vec<Tweet> = tweets.into_iter().map(|tw| tw.tweet).collect();
     let initial_tweet_count = tweets.len();
-    tweets.retain(|tweet| !tweet.retweeted);
+    if let Some(log) = explain {
+        explain_tweet_filters(&tweets, filter_mode, exclude_patterns, log)?;
+    }
+    tweets.retain(|tweet| filter_mode.keeps(tweet));
     let filtered_tweet_count = initial_tweet_count - tweets.len();
-    println!("👥 Strike team assembled. {} members are on standby, {} are joining the mission.", filtered_tweet_count, tweets.len());
+    report_progress(progress, Stage::Filtering, total_tweets, total_tweets, format!("👥 Strike team assembled. {} members are on standby, {} are joining the mission.", filtered_tweet_count, tweets.len()));
+    for (pattern, removed) in exclude_matching_tweets(&mut tweets, exclude_patterns)? {
+        report_progress(progress, Stage::Filtering, total_tweets, total_tweets, format!("🚫 Exclude pattern '{}' removed {} tweets", pattern, removed));
+    }
 
-    println!("📡 Shuri is establishing secure comms (organizing tweets)...");
+    let quote_graph = extract_quoted_tweet_ids(&tweets);
+
+    report_progress(progress, Stage::Threading, 0, 0, "📡 Shuri is establishing secure comms (organizing tweets)...");
     let tweets_map: HashMap<String, Tweet> = tweets.into_iter().map(|t| (t.id_str.clone(), t)).collect();
-    println!("🔐 Secure network established. We can now track {} individual operatives.", tweets_map.len());
+    report_progress(progress, Stage::Threading, 0, tweets_map.len(), format!("🔐 Secure network established. We can now track {} individual operatives.", tweets_map.len()));
 
-    println!("🕴️ Nick Fury is forming tactical units (grouping tweets into conversations)...");
-    let screen_name_clone = screen_name.clone();
-    let threads = task::spawn_blocking(move || {
-        // Use the enhanced reply thread processing that treats ALL replies as threads
-        crate::processing::reply_threads::process_reply_threads(&tweets_map.values().cloned().collect::<Vec<_>>(), &screen_name_clone)
-    }).await?;
+    let threads = if filter_mode == FilterMode::RetweetsOnly {
+        // Retweets are standalone, so each one becomes its own single-tweet thread
+        report_progress(progress, Stage::Threading, 0, tweets_map.len(), "🔁 Retweets-only mission: every operative works solo (skipping thread grouping)...");
+        tweets_map.into_values().map(|tweet| vec![tweet]).collect::<Vec<_>>()
+    } else {
+        report_progress(progress, Stage::Threading, 0, tweets_map.len(), "🕴️ Nick Fury is forming tactical units (grouping tweets into conversations)...");
+        let screen_name_clone = screen_name.clone();
+        task::spawn_blocking(move || {
+            // Use the enhanced reply thread processing that treats ALL replies as threads
+            crate::processing::reply_threads::process_reply_threads(&tweets_map.values().cloned().collect::<Vec<_>>(), &screen_name_clone)
+        }).await?
+    };
 
-    println!("👥 Tactical units formed. We have {} specialized teams ready for action.", threads.len());
+    report_progress(progress, Stage::Threading, threads.len(), threads.len(), format!("👥 Tactical units formed. We have {} specialized teams ready for action.", threads.len()));
 
-    println!("🔮 Dr. Strange is using the Time Stone to prioritize our missions (sorting threads)...");
+    report_progress(progress, Stage::Sorting, 0, threads.len(), "🔮 Dr. Strange is using the Time Stone to prioritize our missions (sorting threads)...");
     let mut threads = threads;
-    threads.sort_by(|a, b| {
-        let date_a = DateTime::parse_from_str(&a[0].created_at, "%a %b %d %H:%M:%S %z %Y").unwrap();
-        let date_b = DateTime::parse_from_str(&b[0].created_at, "%a %b %d %H:%M:%S %z %Y").unwrap();
-        date_b.cmp(&date_a)
-    });
-    println!("⏳ Timelines analyzed. Most critical missions identified.");
-
-    println!("📝 Agent Coulson is documenting our missions (writing threads to files)...");
-    let threads: Vec<Thread> = threads.into_iter().map(|thread| {
-        let id = thread[0].id_str.clone();
-        let tweet_count = thread.len();
-        let favorite_count = thread.iter().map(|t| t.favorite_count.parse::<u32>().unwrap_or(0)).sum();
-        let retweet_count = thread.iter().map(|t| t.retweet_count.parse::<u32>().unwrap_or(0)).sum();
-        Thread { 
-            id, 
-            tweets: thread,
-            tweet_count,
-            favorite_count,
-            retweet_count,
+    crate::processing::reply_threads::sort_threads_newest_first(&mut threads);
+    report_progress(progress, Stage::Sorting, threads.len(), threads.len(), "⏳ Timelines analyzed. Most critical missions identified.");
+
+    report_progress(progress, Stage::Writing, 0, threads.len(), "📝 Agent Coulson is documenting our missions (writing threads to files)...");
+    let mut threads: Vec<Thread> = threads.into_iter()
+        .map(|thread| Thread::from_tweets(thread, &screen_name))
+        .collect();
+    let sensitive_excluded = if options.exclude_sensitive {
+        if let Some(log) = explain {
+            for thread in threads.iter().filter(|thread| thread.tweets.iter().any(|tweet| tweet.possibly_sensitive == Some(true))) {
+                log.record(format_args!("thread {}", thread.id), "dropped (contains a possibly sensitive tweet)")?;
+            }
         }
-    }).collect();
+        let removed = filter_sensitive(&mut threads);
+        report_progress(progress, Stage::Writing, 0, threads.len(), format!("🙈 Excluded {} threads with sensitive content", removed));
+        Some(removed)
+    } else {
+        None
+    };
+    if let Some(max) = options.max_thread_length {
+        let truncated = truncate_threads(&mut threads, max);
+        report_progress(progress, Stage::Writing, 0, threads.len(), format!("✂️ Truncated {} threads to their first {} tweets", truncated, max));
+    }
+
+    if let Some(shared) = pseudonyms {
+        let reply_targets = threads.iter()
+            .flat_map(|thread| &thread.tweets)
+            .filter_map(|tweet| tweet.in_reply_to_user_id_str.as_deref().or(tweet.in_reply_to_user_id.as_deref()));
+        update_shared_registry(shared, reply_targets)?;
+    }
 
     // Write text output
-    write_threads_to_file(&threads, &screen_name, timestamp, output_dir).await?;
+    write_threads_to_file_in_tz(&threads, &screen_name, timestamp, output_dir, output_locale).await?;
+    if let Some(banner) = &sample_banner {
+        prepend_banner(&output_dir.join(format!("threads_{}_{}.txt", screen_name, timestamp)), banner).await?;
+    }
     
     // Write enhanced CSV output with tweet types and URLs
     let csv_path = output_dir.join(format!("threads_{}_{}.csv", screen_name, timestamp));
-    let mut csv_writer = EnhancedCsvWriter::new(csv_path.to_str().unwrap()).await?;
+    let mut csv_writer = EnhancedCsvWriter::new(csv_path.to_str().unwrap()).await?
+        .with_options(csv_options)
+        .with_output_tz(output_locale);
     for thread in &threads {
         csv_writer.write_thread(thread, &screen_name).await?;
     }
+    let truncated_cells = csv_writer.truncated_count();
     csv_writer.finalize().await?;
 
+    // Write weekly engagement statistics
+    write_weekly_stats_csv(&threads, &screen_name, timestamp, output_dir).await?;
+    write_language_breakdown_csv(&threads, &screen_name, timestamp, output_dir).await?;
+    write_quote_graph_csv(&quote_graph, &screen_name, timestamp, output_dir).await?;
+    write_thread_media_csv(&threads, &screen_name, timestamp, output_dir).await?;
+    let mentions = extract_mentioned_users_with_frequency(&threads, &screen_name);
+    write_frequent_mentions_csv(&mentions[..mentions.len().min(FREQUENT_MENTIONS_LIMIT)], &screen_name, timestamp, output_dir).await?;
+    if !event_dates.is_empty() {
+        let reports = analyze_posting_around_events(&threads, event_dates, DEFAULT_EVENT_WINDOW_DAYS);
+        write_event_activity_csv(&reports, &screen_name, timestamp, output_dir).await?;
+    }
+    if options.stats_json {
+        write_thread_stats_json(&threads, &screen_name, timestamp, output_dir).await?;
+    }
+    let appended_threads = match options.append_to.as_deref() {
+        Some(existing_path) => {
+            let appended = append_to_csv(&threads, &screen_name, existing_path).await?;
+            report_progress(progress, Stage::Writing, threads.len(), threads.len(),
+                            format!("📎 Appended {} new threads to {}", appended, existing_path.display()));
+            Some(appended)
+        }
+        None => None,
+    };
+
+    if citation_mode != CitationMode::None {
+        let cited_text: String = threads.iter()
+            .map(|thread| format_thread_as_text(&thread.tweets, &screen_name, citation_mode))
+            .collect();
+        let cited_path = output_dir.join(format!("cited_threads_{}_{}.txt", screen_name, timestamp));
+        async_fs::write(&cited_path, cited_text).await.context("Failed to write cited threads file")?;
+        if let Some(banner) = &sample_banner {
+            prepend_banner(&cited_path, banner).await?;
+        }
+    }
+
     let end_datetime = Local::now();
     let end_time = Instant::now();
     let duration = end_time.duration_since(start_time);
 
-    println!("🌍 Director Fury is compiling the final mission report...");
-    let results_content = format!(
+    report_progress(progress, Stage::Writing, threads.len(), threads.len(), "🌍 Director Fury is compiling the final mission report...");
+    let mut results_content = sample_banner.as_ref()
+        .map(|banner| format!("WARNING: {}\n\n", banner))
+        .unwrap_or_default();
+    results_content.push_str(&format!(
         "Avengers Operation Summary\n\
          ===========================\n\
          Mission Start: {}\n\
@@ -110,6 +628,7 @@ pub async fn process_tweets(
          Successful Interventions (Final Thread Count): {}\n\
          Mission End: {}\n\
          Operation Duration: {:.2} seconds\n\
+         Output Timezone: {}\n\
          ===========================\n\
          Status: Mission Accomplished",
         start_datetime.format("%Y-%m-%d %H:%M:%S"),
@@ -117,18 +636,81 @@ pub async fn process_tweets(
         filtered_tweet_count,
         threads.len(),
         end_datetime.format("%Y-%m-%d %H:%M:%S"),
-        duration.as_secs_f64()
-    );
+        duration.as_secs_f64(),
+        output_locale
+    ));
+    if let Some(removed) = sensitive_excluded {
+        results_content.push_str(&format!("\nSensitive Threads Excluded: {}", removed));
+    }
+    if let Some(appended) = appended_threads {
+        results_content.push_str(&format!("\nThreads Appended to Existing CSV: {}", appended));
+    }
+    if let (Some(max), true) = (csv_options.max_text_length, truncated_cells > 0) {
+        results_content.push_str(&format!("\nNote: {} CSV text cells were truncated to {} characters", truncated_cells, max));
+    }
 
     let results_file_path = output_dir.join(format!("results_{}_{}.txt", screen_name, timestamp));
     async_fs::write(&results_file_path, results_content).await.context("Failed to write results file")?;
-    println!("📊 Final mission report filed. Operation summary complete!");
+    report_progress(progress, Stage::Complete, threads.len(), threads.len(), "📊 Final mission report filed. Operation summary complete!");
+
+    Ok(())
+}
+
+/// Rewrites a text output file with `banner` and a blank line in front of its content
+async fn prepend_banner(path: &Path, banner: &str) -> Result<()> {
+    let content = async_fs::read_to_string(path).await
+        .with_context(|| format!("Failed to read output file: {}", path.display()))?;
+    async_fs::write(path, format!("{}\n\n{}", banner, content)).await
+        .with_context(|| format!("Failed to write output file: {}", path.display()))
+}
+
+/// Writes each thread as a Markdown note in an Obsidian vault
+///
+/// Notes go to `{vault_dir}/tweet-scrolls/{screen_name}/{thread_id}.md`, with
+/// YAML frontmatter (`date`, `tweet_count`, `likes`, `retweets`, `tags`) and one
+/// paragraph per tweet. A thread whose first tweet replies to a tweet in
+/// another of `threads` ends with a `See also: [[parent_thread_id]]` wikilink.
+pub async fn export_to_obsidian(threads: &[Thread], screen_name: &str, vault_dir: &Path, _timestamp: i64) -> Result<()> {
+    let notes_dir = vault_dir.join("tweet-scrolls").join(sanitize_filename(screen_name));
+    async_fs::create_dir_all(&notes_dir).await
+        .with_context(|| format!("Failed to create directory: {}", notes_dir.display()))?;
+
+    let thread_map = build_thread_map(threads);
+    for thread in threads {
+        let first_tweet = thread.tweets.first();
+        let date = first_tweet
+            .and_then(|tweet| parse_twitter_date(&tweet.created_at).ok())
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+
+        let mut note = format!(
+            "---\ndate: {}\ntweet_count: {}\nlikes: {}\nretweets: {}\ntags: [tweet-scrolls]\n---\n\n",
+            date, thread.tweet_count, thread.favorite_count, thread.retweet_count
+        );
+        for tweet in &thread.tweets {
+            note.push_str(&tweet.full_text);
+            note.push_str("\n\n");
+        }
+
+        let parent = first_tweet
+            .and_then(|tweet| tweet.in_reply_to_status_id_str.as_ref().or(tweet.in_reply_to_status_id.as_ref()))
+            .and_then(|reply_to| thread_containing_tweet(&thread_map, reply_to))
+            .filter(|parent| parent.id != thread.id);
+        if let Some(parent) = parent {
+            note.push_str(&format!("See also: [[{}]]\n", parent.id));
+        }
+
+        let note_path = notes_dir.join(format!("{}.md", sanitize_filename(&thread.id)));
+        async_fs::write(&note_path, note).await
+            .with_context(|| format!("Failed to write note: {}", note_path.display()))?;
+    }
 
+    println!("🗒️ Exported {} threads to Obsidian notes in {}", threads.len(), notes_dir.display());
     Ok(())
 }
 
 /// Simple tweet processing function for testing
-pub async fn process_tweets_simple(tweets: &[TweetWrapper], _screen_name: &str) -> Result<Vec<Thread>> {
+pub async fn process_tweets_simple(tweets: &[TweetWrapper], screen_name: &str) -> Result<Vec<Thread>> {
     let mut threads = Vec::new();
     
     for tweet_wrapper in tweets {
@@ -140,15 +722,7 @@ pub async fn process_tweets_simple(tweets: &[TweetWrapper], _screen_name: &str)
         }
         
         // Create a simple thread for each tweet
-        let thread = Thread {
-            id: tweet.id_str.clone(),
-            tweets: vec![tweet.clone()],
-            tweet_count: 1,
-            favorite_count: tweet.favorite_count.parse().unwrap_or(0),
-            retweet_count: tweet.retweet_count.parse().unwrap_or(0),
-        };
-        
-        threads.push(thread);
+        threads.push(Thread::from_tweets(vec![tweet.clone()], screen_name));
     }
     
     Ok(threads)
@@ -170,15 +744,457 @@ mod tests {
         // but tests the function signature and basic structure
         let result = process_tweets(
             "nonexistent_file.js",
-            "testuser",
-            &output_dir,
-            1234567890
+            &TweetOptions::new("testuser", &output_dir, 42),
+            None,
+            None,
+            None,
         ).await;
         
         // Should fail due to missing file, but not due to compilation issues
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_exclude_matching_tweets_counts_per_pattern() {
+        let mut tweets = vec![
+            create_test_tweet("1", "A thread 🧵 about Rust"),
+            create_test_tweet("2", "Huge GIVEAWAY today"),
+            create_test_tweet("3", "🧵 giveaway thread"),
+            create_test_tweet("4", "Just a normal tweet"),
+        ];
+        let patterns = vec!["🧵".to_string(), "(?i)giveaway".to_string()];
+
+        let removed = exclude_matching_tweets(&mut tweets, &patterns).unwrap();
+
+        assert_eq!(removed, vec![("🧵".to_string(), 2), ("(?i)giveaway".to_string(), 1)]);
+        assert_eq!(tweets.iter().map(|t| t.id_str.as_str()).collect::<Vec<_>>(), vec!["4"]);
+        assert!(exclude_matching_tweets(&mut tweets, &["(unclosed".to_string()]).is_err());
+        assert_eq!(tweets.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_posting_around_events() {
+        let thread_on = |id: &str, created_at: &str| {
+            let mut tweet = create_test_tweet(id, "text");
+            tweet.created_at = created_at.to_string();
+            Thread::from_tweets(vec![tweet], "testuser")
+        };
+        let threads = vec![
+            thread_on("1", "Sun Jan 01 12:00:00 +0000 2023"),
+            thread_on("2", "Mon Jan 09 12:00:00 +0000 2023"),
+            thread_on("3", "Sun Jan 15 12:00:00 +0000 2023"),
+            thread_on("4", "Mon Jan 16 12:00:00 +0000 2023"),
+            thread_on("5", "Fri Jan 20 12:00:00 +0000 2023"),
+            thread_on("6", "Wed Mar 01 12:00:00 +0000 2023"),
+            thread_on("7", "not a date"),
+        ];
+        let event = |day: u32| chrono::TimeZone::with_ymd_and_hms(&Utc, 2023, 1, day, 12, 0, 0).unwrap();
+
+        let reports = analyze_posting_around_events(&threads, &[event(15), event(25)], 10);
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].event_date, event(15));
+        assert_eq!((reports[0].pre_window_count, reports[0].post_window_count), (1, 3));
+        assert_eq!(reports[0].change_pct, 200.0);
+        assert_eq!((reports[1].pre_window_count, reports[1].post_window_count), (3, 0));
+        assert_eq!(reports[1].change_pct, -100.0);
+        assert_eq!(analyze_posting_around_events(&threads, &[event(2)], 0)[0].change_pct, 0.0);
+    }
+
+    #[test]
+    fn test_tweet_is_poll() {
+        let plain = create_test_tweet("1", "Which editor do you use?");
+        let marked = create_test_tweet("2", "Which editor do you use? [poll] ");
+        let mut with_options = create_test_tweet("3", "Tabs or spaces?");
+        with_options.entities.poll_option_labels = Some(vec!["Tabs".to_string(), "Spaces".to_string()]);
+        let mut empty_options = create_test_tweet("4", "Tabs or spaces?");
+        empty_options.entities.poll_option_labels = Some(vec![]);
+
+        assert!(!tweet_is_poll(&plain));
+        assert!(tweet_is_poll(&marked));
+        assert!(tweet_is_poll(&with_options));
+        assert!(!tweet_is_poll(&empty_options));
+        assert!(Thread::from_tweets(vec![plain.clone(), with_options], "testuser").contains_poll);
+        assert!(!Thread::from_tweets(vec![plain], "testuser").contains_poll);
+
+        let entities: TweetEntities = serde_json::from_value(serde_json::json!({
+            "hashtags": [], "symbols": [], "user_mentions": [], "urls": [], "poll": ["Yes", "No"]
+        })).unwrap();
+        assert_eq!(entities.poll_option_labels, Some(vec!["Yes".to_string(), "No".to_string()]));
+    }
+
+    #[test]
+    fn test_filter_sensitive() {
+        let mut flagged = create_test_tweet("2", "Spoilers");
+        flagged.possibly_sensitive = Some(true);
+        let mut cleared = create_test_tweet("3", "Cat picture");
+        cleared.possibly_sensitive = Some(false);
+        let mut threads = vec![
+            Thread::from_tweets(vec![create_test_tweet("1", "Hello"), flagged], "testuser"),
+            Thread::from_tweets(vec![cleared], "testuser"),
+            Thread::from_tweets(vec![create_test_tweet("4", "Unmarked")], "testuser"),
+        ];
+
+        assert_eq!(filter_sensitive(&mut threads), 1);
+        assert_eq!(threads.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["3", "4"]);
+    }
+
+    #[tokio::test]
+    async fn test_truncate_threads_keeps_first_tweets() {
+        let mut threads = vec![
+            Thread::from_tweets((1..=5).map(|i| create_test_tweet(&i.to_string(), &format!("Part {}", i))).collect(), "testuser"),
+            Thread::from_tweets(vec![create_test_tweet("6", "Short")], "testuser"),
+        ];
+
+        assert_eq!(truncate_threads(&mut threads, 2), 1);
+        assert_eq!(threads[0].tweets.len(), 2);
+        assert_eq!(threads[0].tweet_count, 5);
+        assert!(threads[0].is_truncated());
+        assert!(!threads[1].is_truncated());
+
+        let temp_dir = tempdir().unwrap();
+        crate::processing::file_io::write_threads_to_file(&threads, "testuser", 1, temp_dir.path()).await.unwrap();
+        let txt = std::fs::read_to_string(temp_dir.path().join("threads_testuser_1.txt")).unwrap();
+        assert!(txt.contains("Part 2\n\n[Thread truncated at 2 of 5 tweets]"));
+        assert!(!txt.contains("Part 3"));
+        assert_eq!(txt.matches("[Thread truncated").count(), 1);
+
+        let csv_path = temp_dir.path().join("threads.csv");
+        append_to_csv(&threads, "testuser", &csv_path).await.unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].ends_with(",Truncated"));
+        assert!(lines[1].starts_with("1,") && lines[1].contains(",5,") && lines[1].ends_with(",Part 1 Part 2,,true"));
+        assert!(lines[2].ends_with(",Short,,false"));
+    }
+
+    #[test]
+    fn test_extract_thread_images() {
+        let thread = Thread::from_tweets(vec![
+            create_test_tweet("1", "Sunset https://pbs.twimg.com/media/Fabc123.jpg and https://t.co/xyz789"),
+            create_test_tweet("2", "No media here, just https://example.com/page"),
+            create_test_tweet("3", "https://pbs.twimg.com/media/Gdef456?format=png&name=large"),
+        ], "me");
+
+        assert_eq!(extract_thread_images(&thread), vec![
+            ("1".to_string(), "https://pbs.twimg.com/media/Fabc123.jpg".to_string()),
+            ("1".to_string(), "https://t.co/xyz789".to_string()),
+            ("3".to_string(), "https://pbs.twimg.com/media/Gdef456?format=png&name=large".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_thread_url_for_own_and_retweeted_threads() {
+        let own = Thread::from_tweets(vec![create_test_tweet("10", "Hello"), create_test_tweet("11", "More")], "me");
+        assert_eq!(own.url, "https://twitter.com/me/status/10");
+        assert_eq!(thread_url(&own, "me"), own.url);
+
+        let linked = create_test_tweet("20", "RT @jack: just setting up https://twitter.com/jack/status/20999?s=20");
+        assert_eq!(Thread::from_tweets(vec![linked], "me").url, "https://twitter.com/jack/status/20999");
+
+        let retweet = create_test_tweet("30", "RT @jack: just setting up my twttr");
+        assert_eq!(Thread::from_tweets(vec![retweet], "me").url, "https://twitter.com/jack/status/30");
+    }
+
+    #[test]
+    fn test_compute_thread_engagement_curve() {
+        let with_likes = |id: &str, likes: u32| {
+            let mut tweet = create_test_tweet(id, "Thread part");
+            tweet.favorite_count = likes.to_string();
+            tweet
+        };
+        let fading = Thread::from_tweets(vec![with_likes("1", 60), with_likes("2", 30), with_likes("3", 10)], "me");
+        assert_eq!(compute_thread_engagement_curve(&fading), vec![0.6, 0.3, 0.1]);
+        assert!(fading.engagement_dropoff_rate < -0.9);
+
+        let building = Thread::from_tweets(vec![with_likes("4", 1), with_likes("5", 9)], "me");
+        assert!((building.engagement_dropoff_rate - 1.0).abs() < 1e-9);
+
+        let unliked = Thread::from_tweets(vec![with_likes("6", 0), with_likes("7", 0), with_likes("8", 0), with_likes("9", 0)], "me");
+        assert_eq!(compute_thread_engagement_curve(&unliked), vec![0.25; 4]);
+        assert_eq!(unliked.engagement_dropoff_rate, 0.0);
+        assert_eq!(Thread::from_tweets(vec![with_likes("10", 5)], "me").engagement_dropoff_rate, 0.0);
+    }
+
+    #[test]
+    fn test_build_thread_map_indexes_every_tweet() {
+        let make_thread = |id: &str, tweets: Vec<Tweet>| Thread {
+            id: id.to_string(),
+            tweet_count: tweets.len(),
+            tweets,
+            favorite_count: 0,
+            retweet_count: 0,
+            url: String::new(),
+            contains_poll: false,
+            first_tweet_url_entities: Vec::new(),
+            engagement_dropoff_rate: 0.0,
+        };
+        let threads = vec![
+            make_thread("1", vec![create_test_tweet("1", "Root"), create_test_tweet("2", "Reply")]),
+            make_thread("3", vec![create_test_tweet("3", "Standalone")]),
+        ];
+
+        let map = build_thread_map(&threads);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(thread_containing_tweet(&map, "2").map(|t| t.id.as_str()), Some("1"));
+        assert_eq!(thread_containing_tweet(&map, "3").map(|t| t.id.as_str()), Some("3"));
+        assert!(thread_containing_tweet(&map, "4").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_to_obsidian() {
+        let temp_dir = tempdir().unwrap();
+        let mut reply = create_test_tweet("5", "Following up");
+        reply.in_reply_to_status_id_str = Some("2".to_string());
+        let threads = vec![
+            Thread::from_tweets(vec![create_test_tweet("1", "Root"), create_test_tweet("2", "Reply")], "testuser"),
+            Thread::from_tweets(vec![reply], "testuser"),
+        ];
+
+        export_to_obsidian(&threads, "testuser", temp_dir.path(), 42).await.unwrap();
+
+        let notes_dir = temp_dir.path().join("tweet-scrolls").join("testuser");
+        let root = std::fs::read_to_string(notes_dir.join("1.md")).unwrap();
+        assert!(root.starts_with("---\ndate: 2023-01-01\ntweet_count: 2\nlikes: 0\nretweets: 0\ntags: [tweet-scrolls]\n---\n\nRoot\n\nReply\n"));
+        assert!(!root.contains("See also"));
+        let follow_up = std::fs::read_to_string(notes_dir.join("5.md")).unwrap();
+        assert!(follow_up.ends_with("Following up\n\nSee also: [[1]]\n"));
+    }
+
+    #[test]
+    fn test_filter_mode_keeps() {
+        let mut original = create_test_tweet("1", "Original thought");
+        let retweet = create_test_tweet("2", "RT @someone: Shared thought");
+
+        assert!(FilterMode::ExcludeRetweets.keeps(&original));
+        // The default mode only trusts the `retweeted` flag, not the text
+        assert!(FilterMode::ExcludeRetweets.keeps(&retweet));
+        assert!(!FilterMode::RetweetsOnly.keeps(&original));
+        assert!(FilterMode::RetweetsOnly.keeps(&retweet));
+        assert!(FilterMode::All.keeps(&original));
+        assert!(FilterMode::All.keeps(&retweet));
+
+        original.retweeted = true;
+        assert!(FilterMode::RetweetsOnly.keeps(&original));
+        assert!(!FilterMode::ExcludeRetweets.keeps(&original));
+        assert_eq!(FilterMode::default(), FilterMode::ExcludeRetweets);
+    }
+
+    #[tokio::test]
+    async fn test_process_tweets_retweets_only() {
+        let temp_dir = tempdir().unwrap();
+        let input_path = temp_dir.path().join("tweets.js");
+        let tweets: Vec<serde_json::Value> = [
+            create_test_tweet("1", "Original thought"),
+            create_test_tweet("2", "RT @someone: Shared thought"),
+            create_test_tweet("3", "RT @other: Another share"),
+        ]
+        .into_iter()
+        .map(|tweet| serde_json::json!({ "tweet": tweet }))
+        .collect();
+        let content = format!("window.YTD.tweets.part0 = {}", serde_json::to_string(&tweets).unwrap());
+        std::fs::write(&input_path, content).unwrap();
+
+        let (progress, receiver) = watch::channel(ProcessingProgress::default());
+        let options = TweetOptions {
+            filter_mode: FilterMode::RetweetsOnly,
+            output_locale: chrono_tz::America::New_York,
+            ..TweetOptions::new("testuser", temp_dir.path(), 42)
+        };
+        process_tweets(input_path.to_str().unwrap(), &options, None, None, Some(&progress))
+            .await
+            .unwrap();
+
+        let last = receiver.borrow().clone();
+        assert_eq!(last.stage, Stage::Complete);
+        assert_eq!((last.current, last.total), (2, 2));
+
+        let txt_file = std::fs::read_dir(temp_dir.path()).unwrap()
+            .filter_map(|e| e.ok())
+            .find(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name.starts_with("threads_testuser_") && name.ends_with(".txt")
+            })
+            .expect("threads TXT file should be written");
+        let text = std::fs::read_to_string(txt_file.path()).unwrap();
+        assert_eq!(text.matches("--- Start of Thread ---").count(), 2);
+        assert!(!text.contains("Original thought"));
+        assert!(text.contains("Timestamp: Sun Jan 01 07:00:00 -0500 2023\n"));
+
+        let results_file = std::fs::read_dir(temp_dir.path()).unwrap()
+            .filter_map(|e| e.ok())
+            .find(|e| e.file_name().to_string_lossy().starts_with("results_testuser_"))
+            .expect("results file should be written");
+        let results = std::fs::read_to_string(results_file.path()).unwrap();
+        assert!(results.contains("Output Timezone: America/New_York\n"));
+
+        let csv_file = txt_file.path().with_extension("csv");
+        let csv = std::fs::read_to_string(csv_file).unwrap();
+        assert!(csv.lines().next().unwrap().ends_with(",created_at_local,is_sensitive,contains_poll,thread_truncated"));
+        assert!(csv.contains("Sun Jan 01 12:00:00 +0000 2023,"));
+        assert!(csv.contains(",Sun Jan 01 07:00:00 -0500 2023,false,false,false\n"));
+    }
+
+    #[test]
+    fn test_sample_config_is_reproducible() {
+        let sample = SampleConfig { seed: 7, fraction: 0.3 };
+        let items: Vec<u32> = (0..100).collect();
+
+        let first = sample.sample(items.clone());
+        assert_eq!(first.len(), 30);
+        assert_eq!(first, sample.sample(items.clone()));
+        assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_ne!(first, SampleConfig { seed: 8, fraction: 0.3 }.sample(items.clone()));
+        assert_eq!(SampleConfig { seed: 7, fraction: 1.5 }.sample(items).len(), 100);
+        assert_eq!(sample.banner(30, 100), "SAMPLE MODE: Using 30 of 100 tweets (seed 7)");
+    }
+
+    #[test]
+    fn test_explain_tweet_filters() {
+        let temp_dir = tempdir().unwrap();
+        let log = ExplainLog::create(temp_dir.path(), "testuser", 0).unwrap();
+        let mut retweet = create_test_tweet("2", "RT @someone: hi");
+        retweet.retweeted = true;
+        let tweets = vec![create_test_tweet("1", "Original thought"), retweet, create_test_tweet("3", "Buy crypto now")];
+
+        explain_tweet_filters(&tweets, FilterMode::default(), &["crypto".to_string()], &log).unwrap();
+        log.flush().unwrap();
+
+        let lines: Vec<String> = std::fs::read_to_string(log.path()).unwrap().lines().map(str::to_string).collect();
+        assert_eq!(lines, vec![
+            "tweet 1 → kept (original, no filter match)",
+            "tweet 2 → dropped (retweet, filter mode ExcludeRetweets)",
+            "tweet 3 → dropped (matches exclude pattern 'crypto')",
+        ]);
+        assert!(explain_tweet_filters(&tweets, FilterMode::All, &["(".to_string()], &log).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_process_tweets_sample_mode() {
+        let temp_dir = tempdir().unwrap();
+        let input_path = temp_dir.path().join("tweets.js");
+        let tweets: Vec<serde_json::Value> = (1..=4)
+            .map(|id| serde_json::json!({ "tweet": create_test_tweet(&id.to_string(), "Original thought") }))
+            .collect();
+        let content = format!("window.YTD.tweets.part0 = {}", serde_json::to_string(&tweets).unwrap());
+        std::fs::write(&input_path, content).unwrap();
+
+        let sample = SampleConfig { seed: 1, fraction: 0.5 };
+        let options = TweetOptions {
+            sample_mode: Some(sample),
+            stats_json: true,
+            ..TweetOptions::new("testuser", temp_dir.path(), 42)
+        };
+        process_tweets(input_path.to_str().unwrap(), &options, None, None, None)
+            .await
+            .unwrap();
+
+        let read_output = |prefix: &str, extension: &str| {
+            let entry = std::fs::read_dir(temp_dir.path()).unwrap()
+                .filter_map(|e| e.ok())
+                .find(|e| {
+                    let name = e.file_name().to_string_lossy().to_string();
+                    name.starts_with(prefix) && name.ends_with(extension)
+                })
+                .expect("output file should be written");
+            std::fs::read_to_string(entry.path()).unwrap()
+        };
+        let text = read_output("threads_testuser_", ".txt");
+        assert!(text.starts_with("SAMPLE MODE: Using 2 of 4 tweets (seed 1)\n\n"));
+        assert_eq!(text.matches("--- Start of Thread ---").count(), 2);
+        let results = read_output("results_testuser_", ".txt");
+        assert!(results.starts_with("WARNING: SAMPLE MODE: Using 2 of 4 tweets (seed 1)\n"));
+        let stats: serde_json::Value = serde_json::from_str(&read_output("thread_stats_testuser_", ".json")).unwrap();
+        assert_eq!(stats["total_threads"], 2);
+        assert_eq!(stats["max_thread_length"], 1);
+    }
+
+    #[test]
+    fn test_extract_mentioned_users_with_frequency() {
+        let threads = vec![
+            Thread::from_tweets(vec![
+                create_test_tweet("1", "Thanks @Alice and @bob!"),
+                create_test_tweet("2", "@alice agreed, ping me@example.com or @testuser"),
+            ], "testuser"),
+            Thread::from_tweets(vec![create_test_tweet("3", "cc @carol @bob @alice")], "testuser"),
+        ];
+
+        let mentions = extract_mentioned_users_with_frequency(&threads, "TestUser");
+
+        assert_eq!(mentions, vec![
+            ("alice".to_string(), 3),
+            ("bob".to_string(), 2),
+            ("carol".to_string(), 1),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_quoted_tweet_ids() {
+        let mut quote = create_test_tweet("10", "Worth reading");
+        quote.quoted_status_id = Some("99".to_string());
+        let plain = create_test_tweet("11", "Just a tweet");
+
+        let graph = extract_quoted_tweet_ids(&[quote, plain]);
+
+        assert_eq!(graph.len(), 1);
+        assert_eq!(graph.get("10").map(String::as_str), Some("99"));
+
+        let parsed: Tweet = serde_json::from_value(serde_json::json!(create_test_tweet("12", "x"))).unwrap();
+        assert_eq!(parsed.quoted_status_id, None);
+        let mut value = serde_json::json!(create_test_tweet("13", "y"));
+        value["quoted_status_id_str"] = serde_json::json!("42");
+        let parsed: Tweet = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.quoted_status_id.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn test_parse_tweet_wrappers_object_root() {
+        let tweets = serde_json::json!([
+            { "tweet": create_test_tweet("1", "First [draft]") },
+            { "tweet": create_test_tweet("2", "Second") },
+        ]);
+        
+        let bare = format!("window.YTD.tweets.part0 = {}", tweets);
+        assert_eq!(parse_tweet_wrappers(&bare).unwrap().len(), 2);
+        
+        // The first '[' belongs to another key, so the bare-array fast path fails
+        let wrapped = format!("window.YTD.tweets.part0 = {}", serde_json::json!({ "account": { "ids": ["9"] }, "tweets": tweets }));
+        let parsed = parse_tweet_wrappers(&wrapped).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].tweet.id_str, "2");
+        
+        let wrong_key = format!("window.YTD.tweets.part0 = {}", serde_json::json!({ "account": { "ids": ["9"] }, "statuses": tweets }));
+        assert!(parse_tweet_wrappers(&wrong_key).is_err());
+    }
+
+    fn create_test_tweet(id: &str, text: &str) -> Tweet {
+        Tweet {
+            id_str: id.to_string(),
+            id: id.to_string(),
+            full_text: text.to_string(),
+            created_at: "Sun Jan 01 12:00:00 +0000 2023".to_string(),
+            favorite_count: "0".to_string(),
+            retweet_count: "0".to_string(),
+            retweeted: false,
+            favorited: false,
+            truncated: false,
+            lang: Some("en".to_string()),
+            source: "Twitter Web App".to_string(),
+            display_text_range: vec!["0".to_string(), text.len().to_string()],
+            in_reply_to_status_id: None,
+            in_reply_to_status_id_str: None,
+            in_reply_to_user_id: None,
+            in_reply_to_user_id_str: None,
+            in_reply_to_screen_name: None,
+            edit_info: None,
+            entities: TweetEntities::default(),
+            possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
+        }
+    }
+
     #[test]
     fn test_thread_creation_logic() {
         // Test the core logic for creating threads from tweets
@@ -192,7 +1208,7 @@ mod tests {
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
             display_text_range: vec!["0".to_string(), "11".to_string()],
             in_reply_to_status_id: None,
@@ -202,12 +1218,15 @@ mod tests {
             in_reply_to_screen_name: None,
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         };
 
         let tweet2 = Tweet {
@@ -220,7 +1239,7 @@ mod tests {
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
             display_text_range: vec!["0".to_string(), "12".to_string()],
             in_reply_to_status_id: Some("1".to_string()),
@@ -230,16 +1249,19 @@ mod tests {
             in_reply_to_screen_name: Some("testuser".to_string()),
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         };
 
         // Test that tweets can be organized into threads
-        let tweets = vec![tweet1, tweet2];
+        let tweets = [tweet1, tweet2];
         assert_eq!(tweets.len(), 2);
         
         // Verify the reply relationship
@@ -258,7 +1280,7 @@ mod tests {
             retweeted: true, // This should be filtered out
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
             display_text_range: vec!["0".to_string(), "30".to_string()],
             in_reply_to_status_id: None,
@@ -268,12 +1290,15 @@ mod tests {
             in_reply_to_screen_name: None,
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         };
 
         let original_tweet = Tweet {
@@ -286,7 +1311,7 @@ mod tests {
             retweeted: false, // This should be kept
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
             display_text_range: vec!["0".to_string(), "15".to_string()],
             in_reply_to_status_id: None,
@@ -296,12 +1321,15 @@ mod tests {
             in_reply_to_screen_name: None,
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         };
 
         let mut tweets = vec![retweet, original_tweet];
@@ -1,21 +1,37 @@
 //! Core relationship analysis functionality
 
-use std::collections::{HashMap, HashSet};
-use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Timelike, Utc};
 use crate::models::{
     direct_message::DmWrapper,
-    interaction::InteractionEvent,
-    profile::UserProfile,
+    interaction::{load_events_from_cache, save_events_to_cache, timeline_cache_key_path, InteractionEvent},
+    profile::{classify_contact, LifecycleStage, RelationshipStage, SentimentTrend, TweetReplyStats, UserProfile, AVG_RESPONSE_TIME_SECS_KEY, CONVERSATION_COUNT_KEY, FIRST_HALF_INTERACTIONS_KEY, PEAK_ACTIVITY_HOUR_KEY, SECOND_HALF_INTERACTIONS_KEY, TOP_TOPIC_KEY},
 };
+use crate::processing::data_structures::Tweet;
+use crate::utils::parse_twitter_date;
 use crate::services::timeline_analyzer::TimelineAnalyzer;
 
-use super::communication::{CommunicationFrequency, calculate_communication_frequency};
+use super::anonymization::{hash_user_id, PseudonymRegistry};
+use super::communication::{CommunicationFrequency, SenderFilter, calculate_communication_frequency, calculate_response_times};
+use super::text_generators::{generate_user_profile_text, relationship_strength_label, Locale, RenderStyle};
+use super::topics::enrich_timeline_with_topics;
+
+/// Quarters with fewer events than this are left out of sentiment timelines
+pub const MIN_EVENTS_PER_QUARTER: usize = 5;
+
+/// Days without interaction after which [`RelationshipAnalyzer::detect_relationship_lifecycle`] calls a contact dormant
+pub const DORMANT_AFTER_DAYS: i64 = 90;
 
 /// Relationship analyzer for extracting and analyzing user interactions
 #[derive(Debug)]
 pub struct RelationshipAnalyzer {
     /// Map of user IDs to their profile data
     pub profiles: HashMap<String, UserProfile>,
+    /// Directed graph of who messaged whom (node = user hash, edge weight = message count)
+    #[cfg(feature = "graph-analysis")]
+    pub interaction_graph: petgraph::graph::DiGraph<String, u32>,
 }
 
 impl Default for RelationshipAnalyzer {
@@ -29,6 +45,8 @@ impl RelationshipAnalyzer {
     pub fn new() -> Self {
         Self {
             profiles: HashMap::new(),
+            #[cfg(feature = "graph-analysis")]
+            interaction_graph: petgraph::graph::DiGraph::new(),
         }
     }
 
@@ -103,11 +121,12 @@ impl RelationshipAnalyzer {
     ///
     /// * `user_id` - The user ID
     /// * `dm_data` - DM conversation data
+    /// * `tweet_data` - Tweets scanned for replies to the user
     ///
     /// # Returns
     ///
     /// A UserProfile with basic statistics
-    pub fn create_user_profile(&self, user_id: &str, dm_data: &[DmWrapper]) -> UserProfile {
+    pub fn create_user_profile(&self, user_id: &str, dm_data: &[DmWrapper], tweet_data: &[Tweet]) -> UserProfile {
         let (first_interaction, last_interaction) = self.find_interaction_timespan(user_id, dm_data);
         
         let mut profile = UserProfile::new(user_id);
@@ -118,6 +137,10 @@ impl RelationshipAnalyzer {
         
         // Calculate basic statistics
         let mut total_messages = 0;
+        let mut conversations = 0;
+        let mut response_times = Vec::new();
+        let mut messages_per_hour = [0u32; 24];
+        let mut message_times = Vec::new();
         for wrapper in dm_data {
             let conversation_id = &wrapper.dm_conversation.conversation_id;
             
@@ -127,22 +150,185 @@ impl RelationshipAnalyzer {
                 let user2_id = &conversation_id[dash_pos + 1..];
                 
                 if user_id == user1_id || user_id == user2_id {
+                    conversations += 1;
                     // Count messages in this conversation
                     for message in &wrapper.dm_conversation.messages {
-                        if message.message_create.is_some() {
+                        if let Some(message_create) = &message.message_create {
                             total_messages += 1;
+                            if let Some(sent_at) = message_create.created_at.as_deref().and_then(|date| parse_twitter_date(date).ok()) {
+                                messages_per_hour[sent_at.hour() as usize] += 1;
+                                message_times.push(sent_at);
+                            }
                         }
                     }
+                    response_times.extend(calculate_response_times(&wrapper.dm_conversation.messages, SenderFilter::CrossSenderOnly));
                 }
             }
         }
         
         profile.total_interactions = total_messages;
         profile.interaction_counts.insert("dm_messages".to_string(), total_messages);
+        profile.metadata.insert(CONVERSATION_COUNT_KEY.to_string(), conversations.to_string());
+        if !response_times.is_empty() {
+            let average = response_times.iter().sum::<std::time::Duration>() / response_times.len() as u32;
+            profile.metadata.insert(AVG_RESPONSE_TIME_SECS_KEY.to_string(), average.as_secs().to_string());
+        }
+        // Earliest hour wins a tie
+        if let Some((hour, _)) = messages_per_hour.iter().enumerate().filter(|(_, &count)| count > 0).max_by_key(|&(hour, &count)| (count, std::cmp::Reverse(hour))) {
+            profile.metadata.insert(PEAK_ACTIVITY_HOUR_KEY.to_string(), hour.to_string());
+        }
+        if let (Some(first), Some(last)) = (message_times.iter().min(), message_times.iter().max()) {
+            if first < last {
+                let midpoint = *first + (*last - *first) / 2;
+                let first_half = message_times.iter().filter(|&&sent_at| sent_at < midpoint).count();
+                profile.metadata.insert(FIRST_HALF_INTERACTIONS_KEY.to_string(), first_half.to_string());
+                profile.metadata.insert(SECOND_HALF_INTERACTIONS_KEY.to_string(), (message_times.len() - first_half).to_string());
+            }
+        }
+        profile.tweet_reply_stats = self.tweet_reply_stats(user_id, tweet_data);
         
         profile
     }
 
+    /// Stats for the tweets in `tweet_data` that reply to `user_id`
+    ///
+    /// Replies are matched on the reply-to user ID, either raw (as in DM
+    /// conversation IDs) or already anonymized with [`hash_user_id`].
+    fn tweet_reply_stats(&self, user_id: &str, tweet_data: &[Tweet]) -> TweetReplyStats {
+        let user_hash = hash_user_id(user_id);
+        let replies: Vec<&Tweet> = tweet_data.iter()
+            .filter(|tweet| {
+                tweet.in_reply_to_user_id_str.as_ref()
+                    .or(tweet.in_reply_to_user_id.as_ref())
+                    .is_some_and(|reply_to| reply_to == user_id || hash_user_id(reply_to) == user_hash)
+            })
+            .collect();
+        if replies.is_empty() {
+            return TweetReplyStats::default();
+        }
+
+        let total_chars: usize = replies.iter().map(|tweet| tweet.full_text.chars().count()).sum();
+        let dates: Vec<DateTime<Utc>> = replies.iter()
+            .filter_map(|tweet| parse_twitter_date(&tweet.created_at).ok())
+            .collect();
+
+        TweetReplyStats {
+            total_replies: replies.len(),
+            avg_reply_length_chars: total_chars as f64 / replies.len() as f64,
+            first_reply_date: dates.iter().min().copied(),
+            last_reply_date: dates.iter().max().copied(),
+        }
+    }
+
+    /// Classify every profile as growing, stable, declining or dormant as of now
+    ///
+    /// See [`Self::detect_relationship_lifecycle_as_of`].
+    pub fn detect_relationship_lifecycle(&self) -> HashMap<String, LifecycleStage> {
+        self.detect_relationship_lifecycle_as_of(Utc::now())
+    }
+
+    /// Classify every profile as growing, stable, declining or dormant as of `reference`
+    ///
+    /// A contact is `Dormant` when their last interaction is more than
+    /// [`DORMANT_AFTER_DAYS`] days before `reference`. Otherwise their
+    /// timespan is cut in half and the later half's DM count is compared with the
+    /// earlier half's: over 20% more is `Growing`, over 20% fewer is `Declining`
+    /// and anything in between is `Stable`. Contacts whose DMs all share one
+    /// timestamp, or that have no halves recorded by [`Self::build_profiles`],
+    /// are `Stable` with a trend of 0%. Keyed by user ID.
+    pub fn detect_relationship_lifecycle_as_of(&self, reference: DateTime<Utc>) -> HashMap<String, LifecycleStage> {
+        let half = |profile: &UserProfile, key: &str| profile.metadata.get(key).and_then(|count| count.parse::<f64>().ok());
+
+        self.profiles.values()
+            .map(|profile| {
+                let trend_pct = match (half(profile, FIRST_HALF_INTERACTIONS_KEY), half(profile, SECOND_HALF_INTERACTIONS_KEY)) {
+                    (Some(first), Some(second)) if first > 0.0 => (second - first) / first * 100.0,
+                    _ => 0.0,
+                };
+                let dormant = matches!(profile.last_interaction,
+                    Some(last) if reference - last > chrono::Duration::days(DORMANT_AFTER_DAYS));
+                let stage = if dormant {
+                    RelationshipStage::Dormant
+                } else if trend_pct > 20.0 {
+                    RelationshipStage::Growing
+                } else if trend_pct < -20.0 {
+                    RelationshipStage::Declining
+                } else {
+                    RelationshipStage::Stable
+                };
+                (profile.user_id.clone(), LifecycleStage { stage, trend_pct })
+            })
+            .collect()
+    }
+
+    /// Fill `profiles` with message counts, timespans and tweet reply stats for
+    /// every DM participant
+    ///
+    /// Existing profiles keep their sentiment fields; counts, timestamps, reply
+    /// stats and the contact type are replaced. The contact type is classified
+    /// against the number of replies to the user in `tweet_data` (zero when no
+    /// tweets are given).
+    pub fn build_profiles(&mut self, dm_data: &[DmWrapper], tweet_data: &[Tweet]) {
+        for user_id in self.extract_users_from_dms(dm_data) {
+            let fresh = self.create_user_profile(&user_id, dm_data, tweet_data);
+            let profile = self.profiles.entry(user_id).or_insert_with(|| fresh.clone());
+            profile.total_interactions = fresh.total_interactions;
+            profile.first_interaction = fresh.first_interaction;
+            profile.last_interaction = fresh.last_interaction;
+            profile.interaction_counts = fresh.interaction_counts;
+            profile.tweet_reply_stats = fresh.tweet_reply_stats;
+            profile.metadata.extend(fresh.metadata);
+            profile.contact_type = classify_contact(profile, profile.tweet_reply_stats.total_replies);
+        }
+    }
+
+    /// Combine interaction volume, recency and regularity into a score in `[0, 1]`
+    ///
+    /// The score is `0.4 * count + 0.4 * recency + 0.2 * density`, where:
+    /// - `count` is `ln(total_interactions + 1)` normalized by the largest value among `profiles`
+    /// - `recency` is `1 / (1 + days_since_last_interaction / 30)`, measured from the
+    ///   newest interaction across all profiles so old archives are not penalized
+    /// - `density` is `active_days / span_days`: the share of calendar days (UTC)
+    ///   from the first to the last interaction, inclusive, with any messages in
+    ///   `freq.sent_per_day` or `freq.received_per_day`
+    pub fn relationship_strength_score(&self, profile: &UserProfile, freq: &CommunicationFrequency) -> f64 {
+        let max_count = self.profiles.values()
+            .map(|p| p.total_interactions)
+            .chain(std::iter::once(profile.total_interactions))
+            .max()
+            .unwrap_or(0);
+        let count = if max_count == 0 {
+            0.0
+        } else {
+            (profile.total_interactions as f64 + 1.0).ln() / (max_count as f64 + 1.0).ln()
+        };
+
+        let reference = self.profiles.values()
+            .filter_map(|p| p.last_interaction)
+            .chain(profile.last_interaction)
+            .max();
+        let recency = match (profile.last_interaction, reference) {
+            (Some(last), Some(reference)) => {
+                let days_since = (reference - last).num_seconds().max(0) as f64 / 86_400.0;
+                1.0 / (1.0 + days_since / 30.0)
+            }
+            _ => 0.0,
+        };
+
+        let density = match (profile.first_interaction, profile.last_interaction) {
+            (Some(first), Some(last)) => {
+                let span_days = (last.date_naive() - first.date_naive()).num_days() + 1;
+                let active_days: HashSet<&chrono::NaiveDate> = freq.sent_per_day.keys()
+                    .chain(freq.received_per_day.keys())
+                    .collect();
+                (active_days.len() as f64 / span_days.max(1) as f64).min(1.0)
+            }
+            _ => 0.0,
+        };
+
+        0.4 * count + 0.4 * recency + 0.2 * density
+    }
+
     /// Find the first and last interaction timestamps for a user
     fn find_interaction_timespan(&self, user_id: &str, dm_data: &[DmWrapper]) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
         let mut timestamps = Vec::new();
@@ -160,8 +346,8 @@ impl RelationshipAnalyzer {
                     for message in &wrapper.dm_conversation.messages {
                         if let Some(message_create) = &message.message_create {
                             if let Some(created_at) = &message_create.created_at {
-                                if let Ok(timestamp) = DateTime::parse_from_rfc3339(created_at) {
-                                    timestamps.push(timestamp.with_timezone(&Utc));
+                                if let Ok(timestamp) = parse_twitter_date(created_at) {
+                                    timestamps.push(timestamp);
                                 }
                             }
                         }
@@ -187,8 +373,11 @@ impl RelationshipAnalyzer {
     /// 
     /// # Returns
     /// 
-    /// A vector of InteractionEvent objects sorted chronologically (newest first)
-    pub fn build_timeline(&self, dm_data: &[DmWrapper], tweet_data: &[crate::processing::data_structures::Tweet]) -> Vec<InteractionEvent> {
+    /// A vector of InteractionEvent objects sorted chronologically (newest first),
+    /// each with its TF-IDF `topics` (see [`enrich_timeline_with_topics`]).
+    /// Each user's `avg_sentiment`, `sentiment_trend` and most frequent topic in
+    /// `profiles` is updated from the events in the timeline.
+    pub fn build_timeline(&mut self, dm_data: &[DmWrapper], tweet_data: &[crate::processing::data_structures::Tweet]) -> Vec<InteractionEvent> {
         let mut timeline = Vec::new();
         
         // Add DM events to timeline
@@ -198,19 +387,94 @@ impl RelationshipAnalyzer {
                 if let Some(event) = InteractionEvent::from_dm_message(message, conversation_id) {
                     timeline.push(event);
                 }
+                timeline.extend(InteractionEvent::reactions_from_dm_message(message, conversation_id));
             }
         }
         
-        // Add tweet events to timeline (if we had a from_tweet method)
-        // For now, we'll skip tweet events since the method doesn't exist
-        let _ = tweet_data; // Suppress unused parameter warning
+        // Add reply tweets to timeline
+        timeline.extend(tweet_data.iter().filter_map(InteractionEvent::from_tweet));
         
         // Sort timeline chronologically (newest first)
-        timeline.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        timeline.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        
+        let texts: Vec<String> = timeline.iter().map(|e| e.content.clone()).collect();
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        enrich_timeline_with_topics(&mut timeline, &texts);
+        
+        // Each event's gap to the one before it in time, i.e. the next entry in the list
+        for i in 0..timeline.len() {
+            timeline[i].duration_since_prev = timeline.get(i + 1)
+                .and_then(|prev| (timeline[i].timestamp - prev.timestamp).to_std().ok());
+        }
+        
+        self.aggregate_sentiment(&timeline);
+        self.aggregate_topics(&timeline);
         
         timeline
     }
 
+    /// Like [`Self::build_timeline`], but reuses the timeline saved at `cache_path`
+    ///
+    /// The cache is only loaded when it was saved for the same `source_key`
+    /// (see [`crate::models::interaction::timeline_cache_key`]); otherwise the
+    /// timeline is rebuilt and saved with the new key for the next run. Either
+    /// way the per-user sentiment is updated. Also returns whether the timeline
+    /// came from the cache.
+    pub fn build_timeline_cached(&mut self, dm_data: &[DmWrapper], tweet_data: &[Tweet], cache_path: &Path, source_key: &str) -> Result<(Vec<InteractionEvent>, bool)> {
+        let key_path = timeline_cache_key_path(cache_path);
+        let cached_key = std::fs::read_to_string(&key_path).ok();
+        if cache_path.exists() && cached_key.as_deref() == Some(source_key) {
+            let timeline = load_events_from_cache(cache_path)?;
+            self.aggregate_sentiment(&timeline);
+            self.aggregate_topics(&timeline);
+            return Ok((timeline, true));
+        }
+        let timeline = self.build_timeline(dm_data, tweet_data);
+        save_events_to_cache(cache_path, &timeline)?;
+        std::fs::write(&key_path, source_key)
+            .with_context(|| format!("Failed to write timeline cache key: {}", key_path.display()))?;
+        Ok((timeline, false))
+    }
+
+    /// Record each user's most frequent event topic under [`TOP_TOPIC_KEY`]
+    ///
+    /// Ties go to the alphabetically first topic. Users whose events have no
+    /// topics are left unchanged.
+    fn aggregate_topics(&mut self, timeline: &[InteractionEvent]) {
+        let mut topics_by_user: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+        for event in timeline {
+            let counts = topics_by_user.entry(&event.user_id).or_default();
+            for topic in &event.topics {
+                *counts.entry(topic).or_insert(0) += 1;
+            }
+        }
+
+        for (user_id, counts) in topics_by_user {
+            if let Some((topic, _)) = counts.into_iter().max_by_key(|&(topic, count)| (count, std::cmp::Reverse(topic))) {
+                self.profiles
+                    .entry(user_id.to_string())
+                    .or_insert_with(|| UserProfile::new(user_id))
+                    .metadata.insert(TOP_TOPIC_KEY.to_string(), topic.to_string());
+            }
+        }
+    }
+
+    /// Update per-user sentiment averages and trends from a newest-first timeline
+    fn aggregate_sentiment(&mut self, timeline: &[InteractionEvent]) {
+        let mut scores_by_user: HashMap<&str, Vec<f32>> = HashMap::new();
+        for event in timeline.iter().rev() {
+            scores_by_user.entry(&event.user_id).or_default().push(event.sentiment_score);
+        }
+        
+        for (user_id, scores) in scores_by_user {
+            let profile = self.profiles
+                .entry(user_id.to_string())
+                .or_insert_with(|| UserProfile::new(user_id));
+            profile.avg_sentiment = scores.iter().map(|&s| s as f64).sum::<f64>() / scores.len() as f64;
+            profile.sentiment_trend = SentimentTrend::from_scores(&scores);
+        }
+    }
+
     /// Calculate communication frequency for a user
     /// 
     /// # Arguments
@@ -238,6 +502,248 @@ impl RelationshipAnalyzer {
         let analyzer = TimelineAnalyzer::new(events.to_vec());
         analyzer.analyze()
     }
+
+    /// Average sentiment per calendar quarter of one user's events
+    ///
+    /// Returns `("YYYY-Q{n}", avg_sentiment)` pairs, oldest first, for quarters
+    /// with at least [`MIN_EVENTS_PER_QUARTER`] events from the user whose
+    /// hashed ID is `user_hash`.
+    pub fn sentiment_over_time(&self, user_hash: &str, events: &[InteractionEvent]) -> Vec<(String, f64)> {
+        quarterly_sentiment(events.iter().filter(|event| hash_user_id(&event.user_id) == user_hash))
+            .into_iter()
+            .map(|(period, avg_sentiment, _)| (period, avg_sentiment))
+            .collect()
+    }
+}
+
+/// Average sentiment and event count per calendar quarter, oldest first
+///
+/// Quarters with fewer than [`MIN_EVENTS_PER_QUARTER`] events are skipped.
+pub fn quarterly_sentiment<'a>(events: impl IntoIterator<Item = &'a InteractionEvent>) -> Vec<(String, f64, usize)> {
+    let mut quarters: BTreeMap<(i32, u32), (f64, usize)> = BTreeMap::new();
+    for event in events {
+        let quarter = (event.timestamp.year(), event.timestamp.month0() / 3 + 1);
+        let (total, count) = quarters.entry(quarter).or_default();
+        *total += event.sentiment_score as f64;
+        *count += 1;
+    }
+
+    quarters.into_iter()
+        .filter(|(_, (_, count))| *count >= MIN_EVENTS_PER_QUARTER)
+        .map(|((year, quarter), (total, count))| (format!("{}-Q{}", year, quarter), total / count as f64, count))
+        .collect()
+}
+
+#[cfg(feature = "graph-analysis")]
+impl RelationshipAnalyzer {
+    /// Build the directed interaction graph from DM data
+    ///
+    /// Each node is a hashed user ID and each edge runs from sender to
+    /// recipient, weighted by the number of messages sent along it.
+    /// Replaces any previously built graph.
+    pub fn build_interaction_graph(&mut self, dm_data: &[DmWrapper]) {
+        use petgraph::graph::{DiGraph, NodeIndex};
+
+        let mut graph: DiGraph<String, u32> = DiGraph::new();
+        let mut nodes: HashMap<String, NodeIndex> = HashMap::new();
+        let mut node_for = |graph: &mut DiGraph<String, u32>, user_id: &str| {
+            *nodes.entry(user_id.to_string())
+                .or_insert_with(|| graph.add_node(hash_user_id(user_id)))
+        };
+
+        for wrapper in dm_data {
+            for message in &wrapper.dm_conversation.messages {
+                let Some(create) = &message.message_create else { continue };
+                let (Some(sender), Some(recipient)) = (&create.sender_id, &create.recipient_id) else {
+                    continue;
+                };
+                if sender == recipient {
+                    continue;
+                }
+                let from = node_for(&mut graph, sender);
+                let to = node_for(&mut graph, recipient);
+                match graph.find_edge(from, to) {
+                    Some(edge) => graph[edge] += 1,
+                    None => {
+                        graph.add_edge(from, to, 1);
+                    }
+                }
+            }
+        }
+
+        self.interaction_graph = graph;
+    }
+
+    fn graph_node(&self, user_hash: &str) -> Option<petgraph::graph::NodeIndex> {
+        self.interaction_graph
+            .node_indices()
+            .find(|&index| self.interaction_graph[index] == user_hash)
+    }
+
+    /// Find the closest chain of contacts between two user hashes
+    ///
+    /// Uses A* search with a zero heuristic, which makes it Dijkstra's algorithm
+    /// stopped at the goal, and an edge cost of `1 / message count`, so heavily
+    /// used connections count as shorter. Returns the hashes along the
+    /// path including both ends, or `None` if either user is unknown or
+    /// unreachable.
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let start = self.graph_node(from)?;
+        let goal = self.graph_node(to)?;
+
+        let (_, path) = petgraph::algo::astar(
+            &self.interaction_graph,
+            start,
+            |node| node == goal,
+            |edge| 1.0 / f64::from(*edge.weight()),
+            |_| 0.0,
+        )?;
+
+        Some(path.into_iter().map(|index| self.interaction_graph[index].clone()).collect())
+    }
+
+    /// Group users into strongly connected clusters using Kosaraju's algorithm
+    ///
+    /// Users in the same cluster can all reach each other through messages.
+    /// Each cluster is sorted, and clusters are ordered largest first.
+    pub fn strongly_connected_clusters(&self) -> Vec<Vec<String>> {
+        let mut clusters: Vec<Vec<String>> = petgraph::algo::kosaraju_scc(&self.interaction_graph)
+            .into_iter()
+            .map(|component| {
+                let mut hashes: Vec<String> = component.into_iter()
+                    .map(|index| self.interaction_graph[index].clone())
+                    .collect();
+                hashes.sort();
+                hashes
+            })
+            .collect();
+        clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        clusters
+    }
+}
+
+/// Writes one Anki flashcard per profile as a tab-separated file with no header row
+///
+/// Each row is `Who is {pseudonym}?`, the profile's narrative summary and the
+/// tags `tweet-scrolls {contact_type} {activity_level}`. Pseudonyms are assigned
+/// in a fresh `PseudonymRegistry`; use `export_anki_deck_with` to reuse a
+/// persisted registry and include per-event details in the summaries.
+pub fn export_anki_deck(analyzer: &RelationshipAnalyzer, output_path: &Path) -> Result<()> {
+    let mut registry = PseudonymRegistry::new();
+    registry.assign_all(analyzer.profiles.keys().map(String::as_str));
+    export_anki_deck_with(analyzer, &[], &registry, output_path)
+}
+
+/// Writes the Anki deck of `export_anki_deck` using the given registry and timeline
+///
+/// Each summary is built from the profile's events in `timeline`. Contacts
+/// missing from `registry` are named by their hash. Line breaks in the
+/// summary become `<br>` so Anki keeps them.
+pub fn export_anki_deck_with(analyzer: &RelationshipAnalyzer, timeline: &[InteractionEvent], registry: &PseudonymRegistry, output_path: &Path) -> Result<()> {
+    let mut cards: Vec<(String, String, String)> = analyzer.profiles.values()
+        .map(|profile| {
+            let hash = hash_user_id(&profile.user_id);
+            let pseudonym = registry.get(&hash).unwrap_or(&hash).to_string();
+            let events: Vec<InteractionEvent> = timeline.iter()
+                .filter(|event| event.user_id == profile.user_id)
+                .cloned()
+                .collect();
+            let mut card_profile = profile.clone();
+            card_profile.user_id = pseudonym.clone();
+            let back = generate_user_profile_text(&card_profile, &events, Locale::EnUs, RenderStyle::Narrative);
+            let tags = format!(
+                "tweet-scrolls {} {}",
+                profile.contact_type.to_string().to_lowercase(),
+                relationship_strength_label(profile.total_interactions),
+            );
+            (pseudonym, back, tags)
+        })
+        .collect();
+    cards.sort();
+
+    let anki_field = |text: &str| text.trim().replace('\t', " ").replace('\n', "<br>");
+    let content: String = cards.iter()
+        .map(|(pseudonym, back, tags)| format!("Who is {}?\t{}\t{}\n", pseudonym, anki_field(back), tags))
+        .collect();
+    std::fs::write(output_path, content)
+        .with_context(|| format!("Failed to write Anki deck: {}", output_path.display()))
+}
+
+/// Hashed IDs of every user the tweets reply to or @-mention
+pub fn tweet_user_hashes(tweets: &[Tweet]) -> HashSet<String> {
+    tweets.iter()
+        .flat_map(|tweet| {
+            tweet.in_reply_to_user_id_str.iter()
+                .chain(tweet.entities.user_mentions.iter().map(|mention| &mention.id_str))
+        })
+        .filter(|id| !id.is_empty())
+        .map(|id| hash_user_id(id))
+        .collect()
+}
+
+/// DM contacts who never appear in `tweet_users` (hashed IDs, see [`tweet_user_hashes`])
+///
+/// These are private-only relationships. Sorted by interaction count, highest first.
+pub fn find_ghost_contacts<'a>(analyzer: &'a RelationshipAnalyzer, tweet_users: &HashSet<String>) -> Vec<&'a UserProfile> {
+    let mut ghosts: Vec<&UserProfile> = analyzer.profiles.values()
+        .filter(|profile| !tweet_users.contains(&hash_user_id(&profile.user_id)))
+        .collect();
+    ghosts.sort_by(|a, b| b.total_interactions.cmp(&a.total_interactions).then_with(|| a.user_id.cmp(&b.user_id)));
+    ghosts
+}
+
+/// Number of conversations each pair of profiles both take part in
+///
+/// A user takes part in a conversation when their ID is part of the
+/// conversation ID or they sent one of its messages. Keys are user ID pairs
+/// with the smaller ID first; pairs sharing no conversation are left out.
+/// Every pair is checked against every conversation, which is fine for the
+/// size of a personal archive.
+pub fn compute_mutual_contacts(profiles: &HashMap<String, UserProfile>, dm_data: &[DmWrapper]) -> HashMap<(String, String), usize> {
+    let conversation_members: Vec<HashSet<&str>> = dm_data.iter()
+        .map(|wrapper| {
+            let conversation = &wrapper.dm_conversation;
+            conversation.conversation_id.split('-')
+                .chain(conversation.messages.iter().filter_map(|m| m.message_create.as_ref()?.sender_id.as_deref()))
+                .collect()
+        })
+        .collect();
+    let mut user_ids: Vec<&String> = profiles.keys().collect();
+    user_ids.sort();
+
+    let mut mutual = HashMap::new();
+    for (i, a) in user_ids.iter().enumerate() {
+        for b in &user_ids[i + 1..] {
+            let shared = conversation_members.iter()
+                .filter(|members| members.contains(a.as_str()) && members.contains(b.as_str()))
+                .count();
+            if shared > 0 {
+                mutual.insert(((*a).clone(), (*b).clone()), shared);
+            }
+        }
+    }
+    mutual
+}
+
+/// Sets each profile's `mutual_contact_count` from the pairs returned by [`compute_mutual_contacts`]
+pub fn apply_mutual_contact_counts(profiles: &mut HashMap<String, UserProfile>, mutual: &HashMap<(String, String), usize>) {
+    for profile in profiles.values_mut() {
+        profile.mutual_contact_count = mutual.keys()
+            .filter(|(a, b)| *a == profile.user_id || *b == profile.user_id)
+            .count();
+    }
+}
+
+/// Hashed IDs in `tweet_users` with no DM profile in `analyzer`, sorted
+pub fn find_tweet_only_contacts<'a>(analyzer: &RelationshipAnalyzer, tweet_users: &'a HashSet<String>) -> Vec<&'a String> {
+    let dm_users: HashSet<String> = analyzer.profiles.values()
+        .map(|profile| hash_user_id(&profile.user_id))
+        .collect();
+    let mut tweet_only: Vec<&String> = tweet_users.iter()
+        .filter(|hash| !dm_users.contains(*hash))
+        .collect();
+    tweet_only.sort();
+    tweet_only
 }
 
 #[cfg(test)]
@@ -253,6 +759,9 @@ mod tests {
             DmWrapper {
                 dm_conversation: DmConversation {
                     conversation_id: "3382-1132151165410455552".to_string(),
+                    group_name: None,
+                    join_events: Vec::new(),
+                    leave_events: Vec::new(),
                     messages: vec![
                         DmMessage {
                             message_create: Some(DmMessageCreate {
@@ -273,6 +782,9 @@ mod tests {
             DmWrapper {
                 dm_conversation: DmConversation {
                     conversation_id: "1132151165410455552-9876543210".to_string(),
+                    group_name: None,
+                    join_events: Vec::new(),
+                    leave_events: Vec::new(),
                     messages: vec![
                         DmMessage {
                             message_create: Some(DmMessageCreate {
@@ -308,7 +820,7 @@ mod tests {
                 retweeted: false,
                 favorited: false,
                 truncated: false,
-                lang: "en".to_string(),
+                lang: Some("en".to_string()),
                 source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
                 display_text_range: vec!["0".to_string(), "12".to_string()],
                 in_reply_to_status_id: None,
@@ -318,6 +830,7 @@ mod tests {
                 in_reply_to_screen_name: Some("alice".to_string()),
                 edit_info: None,
                 entities: TweetEntities {
+                    poll_option_labels: None,
                     hashtags: vec![],
                     symbols: vec![],
                     user_mentions: vec![
@@ -332,6 +845,8 @@ mod tests {
                     urls: vec![],
                 },
                 possibly_sensitive: None,
+                quoted_status_id: None,
+                conversation_id: None,
             },
             Tweet {
                 id_str: "tweet2".to_string(),
@@ -343,7 +858,7 @@ mod tests {
                 retweeted: false,
                 favorited: false,
                 truncated: false,
-                lang: "en".to_string(),
+                lang: Some("en".to_string()),
                 source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
                 display_text_range: vec!["0".to_string(), "13".to_string()],
                 in_reply_to_status_id: None,
@@ -353,6 +868,7 @@ mod tests {
                 in_reply_to_screen_name: None,
                 edit_info: None,
                 entities: TweetEntities {
+                    poll_option_labels: None,
                     hashtags: vec![],
                     symbols: vec![],
                     user_mentions: vec![
@@ -367,6 +883,8 @@ mod tests {
                     urls: vec![],
                 },
                 possibly_sensitive: None,
+                quoted_status_id: None,
+                conversation_id: None,
             },
         ]
     }
@@ -425,12 +943,18 @@ mod tests {
             DmWrapper {
                 dm_conversation: DmConversation {
                     conversation_id: "no_dash_here".to_string(), // No dash separator
+                    group_name: None,
+                    join_events: Vec::new(),
+                    leave_events: Vec::new(),
                     messages: vec![],
                 },
             },
             DmWrapper {
                 dm_conversation: DmConversation {
                     conversation_id: "user1-user2".to_string(), // Valid format
+                    group_name: None,
+                    join_events: Vec::new(),
+                    leave_events: Vec::new(),
                     messages: vec![],
                 },
             },
@@ -451,7 +975,7 @@ mod tests {
         let analyzer = RelationshipAnalyzer::new();
         let user_id = "3382".to_string();
         
-        let profile = analyzer.create_user_profile(&user_id, &sample_data);
+        let profile = analyzer.create_user_profile(&user_id, &sample_data, &[]);
         
         assert_eq!(profile.user_id, user_id);
         assert!(profile.total_interactions > 0);
@@ -464,7 +988,7 @@ mod tests {
     fn test_build_interaction_timeline() {
         let dm_data = create_sample_dm_data();
         let tweet_data = create_sample_tweet_data();
-        let analyzer = RelationshipAnalyzer::new();
+        let mut analyzer = RelationshipAnalyzer::new();
         
         let timeline = analyzer.build_timeline(&dm_data, &tweet_data);
         
@@ -474,14 +998,17 @@ mod tests {
         // Timeline should be sorted chronologically (newest first)
         for window in timeline.windows(2) {
             assert!(window[0].timestamp >= window[1].timestamp);
+            let gap = (window[0].timestamp - window[1].timestamp).to_std().unwrap();
+            assert_eq!(window[0].duration_since_prev, Some(gap));
         }
+        assert_eq!(timeline.last().unwrap().duration_since_prev, None);
     }
 
     #[test]
     fn test_timeline_analysis_integration() {
         let dm_data = create_sample_dm_data();
         let tweet_data = create_sample_tweet_data();
-        let analyzer = RelationshipAnalyzer::new();
+        let mut analyzer = RelationshipAnalyzer::new();
         
         let timeline = analyzer.build_timeline(&dm_data, &tweet_data);
         let analysis = analyzer.analyze_timeline(&timeline);
@@ -491,4 +1018,407 @@ mod tests {
         assert!(analysis.unique_participants > 0);
         assert!(!analysis.patterns.is_empty() || analysis.patterns.is_empty()); // Either way is valid
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_timeline_aggregates_sentiment() {
+        let message = |id: &str, text: &str, created_at: &str| DmMessage {
+            message_create: Some(DmMessageCreate {
+                id: Some(id.to_string()),
+                text: Some(text.to_string()),
+                created_at: Some(created_at.to_string()),
+                sender_id: Some("111".to_string()),
+                recipient_id: Some("222".to_string()),
+                reactions: vec![],
+                urls: vec![],
+                media_urls: vec![],
+                edit_history: vec![],
+            }),
+        };
+        let dm_data = vec![DmWrapper {
+            dm_conversation: DmConversation {
+                conversation_id: "111-222".to_string(),
+                group_name: None,
+                join_events: Vec::new(),
+                leave_events: Vec::new(),
+                messages: vec![
+                    message("m1", "This is terrible", "2023-01-01T10:00:00.000Z"),
+                    message("m2", "Ugh, so annoying", "2023-01-02T10:00:00.000Z"),
+                    message("m3", "Thanks, that was great", "2023-01-03T10:00:00.000Z"),
+                    message("m4", "Love it, awesome", "2023-01-04T10:00:00.000Z"),
+                ],
+            },
+        }];
+        let mut analyzer = RelationshipAnalyzer::new();
+        
+        let timeline = analyzer.build_timeline(&dm_data, &[]);
+        
+        assert_eq!(timeline.len(), 4);
+        assert!(timeline[0].sentiment_score > 0.0);
+        let profile = &analyzer.profiles["111"];
+        assert!(profile.avg_sentiment.abs() < f64::EPSILON);
+        assert_eq!(profile.sentiment_trend, SentimentTrend::Improving);
+        assert!(profile.metadata.contains_key(TOP_TOPIC_KEY));
+    }
+
+    #[test]
+    fn test_aggregate_topics_picks_most_frequent() {
+        let event = |user_id: &str, topics: &[&str]| {
+            let mut event = InteractionEvent::new("e", Utc::now(), crate::models::interaction::InteractionType::DmSent, user_id, "");
+            event.topics = topics.iter().map(|t| t.to_string()).collect();
+            event
+        };
+        let mut analyzer = RelationshipAnalyzer::new();
+
+        analyzer.aggregate_topics(&[
+            event("111", &["rust", "coffee"]),
+            event("111", &["coffee"]),
+            event("222", &["music", "art"]),
+            event("333", &[]),
+        ]);
+
+        assert_eq!(analyzer.profiles["111"].metadata.get(TOP_TOPIC_KEY).map(String::as_str), Some("coffee"));
+        assert_eq!(analyzer.profiles["222"].metadata.get(TOP_TOPIC_KEY).map(String::as_str), Some("art"));
+        assert!(!analyzer.profiles.contains_key("333"));
+    }
+
+    #[test]
+    fn test_relationship_strength_score() {
+        use chrono::TimeZone;
+        
+        let mut analyzer = RelationshipAnalyzer::new();
+        let mut strong = UserProfile::new("strong");
+        strong.total_interactions = 100;
+        strong.first_interaction = Some(Utc.with_ymd_and_hms(2023, 3, 18, 9, 0, 0).unwrap());
+        strong.last_interaction = Some(Utc.with_ymd_and_hms(2023, 3, 20, 18, 0, 0).unwrap());
+        let mut faded = UserProfile::new("faded");
+        faded.total_interactions = 3;
+        faded.first_interaction = Some(Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap());
+        faded.last_interaction = Some(Utc.with_ymd_and_hms(2022, 3, 1, 0, 0, 0).unwrap());
+        analyzer.profiles.insert("strong".to_string(), strong.clone());
+        analyzer.profiles.insert("faded".to_string(), faded.clone());
+        
+        let day = |d: u32| chrono::NaiveDate::from_ymd_opt(2023, 3, d).unwrap();
+        let mut busy = CommunicationFrequency::default();
+        busy.sent_per_day.insert(day(18), 40);
+        busy.sent_per_day.insert(day(19), 30);
+        busy.received_per_day.insert(day(20), 30);
+        let mut sparse = CommunicationFrequency::default();
+        sparse.sent_per_month.insert((2022, 1), 3);
+        sparse.sent_per_day.insert(chrono::NaiveDate::from_ymd_opt(2022, 1, 1).unwrap(), 3);
+        
+        let strong_score = analyzer.relationship_strength_score(&strong, &busy);
+        let faded_score = analyzer.relationship_strength_score(&faded, &sparse);
+        
+        // Max count, most recent and active every day of its span
+        assert!((strong_score - 1.0).abs() < 1e-9);
+        assert!(faded_score > 0.0 && faded_score < strong_score);
+        
+        // Density is active days over span days: 1 of the 60 days from Jan 1 to Mar 1
+        let mut monthly_only = CommunicationFrequency::default();
+        monthly_only.sent_per_month.insert((2022, 1), 3);
+        let density_part = faded_score - analyzer.relationship_strength_score(&faded, &monthly_only);
+        assert!((density_part - 0.2 / 60.0).abs() < 1e-9);
+        assert_eq!(analyzer.relationship_strength_score(&UserProfile::new("nobody"), &CommunicationFrequency::default()), 0.0);
+    }
+    
+    #[cfg(feature = "graph-analysis")]
+    #[test]
+    fn test_interaction_graph_paths_and_clusters() {
+        crate::relationship::anonymization::set_test_mode(true);
+        let message = |sender: &str, recipient: &str| DmMessage {
+            message_create: Some(DmMessageCreate {
+                id: None,
+                text: Some("hi".to_string()),
+                created_at: Some("2023-01-01T10:00:00.000Z".to_string()),
+                sender_id: Some(sender.to_string()),
+                recipient_id: Some(recipient.to_string()),
+                reactions: vec![],
+                urls: vec![],
+                media_urls: vec![],
+                edit_history: vec![],
+            }),
+        };
+        let conversation = |id: &str, messages: Vec<DmMessage>| DmWrapper {
+            dm_conversation: DmConversation { conversation_id: id.to_string(), messages, group_name: None, join_events: Vec::new(), leave_events: Vec::new() },
+        };
+        let dm_data = vec![
+            conversation("a-b", vec![message("a", "b"), message("a", "b"), message("b", "a")]),
+            conversation("b-c", vec![message("b", "c")]),
+            conversation("a-d", vec![message("a", "d")]),
+        ];
+        let mut analyzer = RelationshipAnalyzer::new();
+
+        analyzer.build_interaction_graph(&dm_data);
+
+        assert_eq!(analyzer.interaction_graph.node_count(), 4);
+        assert_eq!(analyzer.interaction_graph.edge_count(), 4);
+        assert_eq!(analyzer.shortest_path("a", "c"), Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+        assert_eq!(analyzer.shortest_path("c", "a"), None);
+        assert_eq!(analyzer.shortest_path("a", "unknown"), None);
+
+        let clusters = analyzer.strongly_connected_clusters();
+        assert_eq!(clusters, vec![vec!["a", "b"], vec!["c"], vec!["d"]]);
+    }
+
+    #[test]
+    fn test_sentiment_over_time() {
+        use crate::models::interaction::InteractionType;
+        use chrono::TimeZone;
+
+        crate::relationship::anonymization::set_test_mode(true);
+        let event = |user: &str, month: u32, day: u32, text: &str| {
+            let timestamp = Utc.with_ymd_and_hms(2023, month, day, 12, 0, 0).unwrap();
+            InteractionEvent::new(format!("{}-{}-{}", user, month, day), timestamp, InteractionType::DmSent, user, text)
+        };
+        let mut events = Vec::new();
+        for day in 1..=5 {
+            events.push(event("alice", 2, day, if day <= 3 { "great" } else { "hello" }));
+            events.push(event("alice", 7, day, "awful"));
+            events.push(event("bob", 2, day, "awful"));
+        }
+        // Only four events in Q2: skipped
+        for day in 1..=4 {
+            events.push(event("alice", 5, day, "great"));
+        }
+        events.reverse();
+
+        let analyzer = RelationshipAnalyzer::new();
+        let periods = analyzer.sentiment_over_time("alice", &events);
+
+        assert_eq!(periods.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>(), vec!["2023-Q1", "2023-Q3"]);
+        assert!(periods[0].1 > 0.0);
+        assert!(periods[1].1 < 0.0);
+        assert!(analyzer.sentiment_over_time("carol", &events).is_empty());
+
+        let overall = quarterly_sentiment(&events);
+        assert_eq!(overall.iter().map(|(p, _, n)| (p.as_str(), *n)).collect::<Vec<_>>(),
+                   vec![("2023-Q1", 10), ("2023-Q3", 5)]);
+    }
+
+    #[test]
+    fn test_build_timeline_cached_reuses_saved_events() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache_path = temp_dir.path().join("timeline_cache_jack.json");
+        let dm_data = create_sample_dm_data();
+        
+        let mut analyzer = RelationshipAnalyzer::new();
+        let (built, loaded_from_cache) = analyzer.build_timeline_cached(&dm_data, &[], &cache_path, "archive v1").unwrap();
+        assert!(!loaded_from_cache);
+        assert!(cache_path.exists());
+        
+        // A cached run with the same key ignores the (now empty) input and restores sentiment from the cache
+        let mut cached_analyzer = RelationshipAnalyzer::new();
+        let (loaded, loaded_from_cache) = cached_analyzer.build_timeline_cached(&[], &[], &cache_path, "archive v1").unwrap();
+        assert!(loaded_from_cache);
+        assert_eq!(loaded.len(), built.len());
+        assert_eq!(loaded.iter().map(|e| &e.id).collect::<Vec<_>>(), built.iter().map(|e| &e.id).collect::<Vec<_>>());
+        assert_eq!(cached_analyzer.profiles["3382"].avg_sentiment, analyzer.profiles["3382"].avg_sentiment);
+
+        // A different key means the inputs changed, so the cache is rebuilt from them
+        let mut stale_analyzer = RelationshipAnalyzer::new();
+        let (rebuilt, loaded_from_cache) = stale_analyzer.build_timeline_cached(&[], &[], &cache_path, "archive v2").unwrap();
+        assert!(!loaded_from_cache);
+        assert!(rebuilt.is_empty());
+        assert!(load_events_from_cache(&cache_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_build_profiles_keeps_sentiment() {
+        let dm_data = create_sample_dm_data();
+        let mut analyzer = RelationshipAnalyzer::new();
+        analyzer.build_timeline(&dm_data, &[]);
+        let sentiment_before = analyzer.profiles["3382"].avg_sentiment;
+        
+        analyzer.build_profiles(&dm_data, &[]);
+        
+        let profile = &analyzer.profiles["3382"];
+        assert_eq!(profile.total_interactions, 1);
+        assert!(profile.last_interaction.is_some());
+        assert_eq!(profile.avg_sentiment, sentiment_before);
+        assert_eq!(analyzer.profiles["1132151165410455552"].total_interactions, 2);
+    }
+
+    #[test]
+    fn test_create_user_profile_response_time_and_peak_hour() {
+        let mut dm_data = create_sample_dm_data();
+        let reply = DmMessage {
+            message_create: Some(DmMessageCreate {
+                id: Some("msg3".to_string()),
+                text: Some("Hi!".to_string()),
+                created_at: Some("2023-01-01T10:30:00.000Z".to_string()),
+                sender_id: Some("1132151165410455552".to_string()),
+                recipient_id: Some("3382".to_string()),
+                reactions: vec![],
+                urls: vec![],
+                media_urls: vec![],
+                edit_history: vec![],
+            }),
+        };
+        dm_data[0].dm_conversation.messages.push(reply);
+        let analyzer = RelationshipAnalyzer::new();
+
+        let profile = analyzer.create_user_profile("3382", &dm_data, &[]);
+
+        assert_eq!(profile.metadata.get(AVG_RESPONSE_TIME_SECS_KEY).map(String::as_str), Some("1800"));
+        assert_eq!(profile.metadata.get(PEAK_ACTIVITY_HOUR_KEY).map(String::as_str), Some("10"));
+        let quiet = analyzer.create_user_profile("9876543210", &create_sample_dm_data(), &[]);
+        assert!(!quiet.metadata.contains_key(AVG_RESPONSE_TIME_SECS_KEY));
+        assert_eq!(quiet.metadata.get(PEAK_ACTIVITY_HOUR_KEY).map(String::as_str), Some("10"));
+    }
+
+    #[test]
+    fn test_create_user_profile_tweet_reply_stats() {
+        let dm_data = create_sample_dm_data();
+        let mut tweet_data = create_sample_tweet_data();
+        for tweet in &mut tweet_data {
+            tweet.in_reply_to_user_id_str = Some("3382".to_string());
+            tweet.created_at = tweet.created_at.replace("Mon", "Sun");
+        }
+        let analyzer = RelationshipAnalyzer::new();
+        
+        let stats = analyzer.create_user_profile("3382", &dm_data, &tweet_data).tweet_reply_stats;
+        
+        assert_eq!(stats.total_replies, 2);
+        assert_eq!(stats.avg_reply_length_chars, 12.5);
+        assert_eq!(stats.first_reply_date, Some("2023-01-01T10:00:00Z".parse().unwrap()));
+        assert_eq!(stats.last_reply_date, Some("2023-01-01T11:00:00Z".parse().unwrap()));
+        let other = analyzer.create_user_profile("1132151165410455552", &dm_data, &tweet_data);
+        assert_eq!(other.tweet_reply_stats, TweetReplyStats::default());
+    }
+
+    #[test]
+    fn test_export_anki_deck() {
+        crate::relationship::anonymization::set_test_mode(true);
+        let dm_data = create_sample_dm_data();
+        let mut analyzer = RelationshipAnalyzer::new();
+        let timeline = analyzer.build_timeline(&dm_data, &[]);
+        analyzer.build_profiles(&dm_data, &[]);
+        let mut registry = PseudonymRegistry::new();
+        registry.get_or_create("3382");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let deck_path = temp_dir.path().join("deck.tsv");
+        
+        export_anki_deck_with(&analyzer, &timeline, &registry, &deck_path).unwrap();
+        
+        let deck = std::fs::read_to_string(&deck_path).unwrap();
+        let rows: Vec<Vec<&str>> = deck.lines().map(|line| line.split('\t').collect()).collect();
+        assert_eq!(rows.len(), analyzer.profiles.len());
+        assert!(rows.iter().all(|row| row.len() == 3));
+        let card = rows.iter().find(|row| row[0] == "Who is Contact_001?").unwrap();
+        assert!(card[1].contains("User ID: Contact_001<br>"));
+        assert!(!card[1].contains("3382"));
+        assert_eq!(card[2], "tweet-scrolls minimal minimal");
+
+        let plain_path = temp_dir.path().join("plain.tsv");
+        export_anki_deck(&analyzer, &plain_path).unwrap();
+        let plain = std::fs::read_to_string(&plain_path).unwrap();
+        assert_eq!(plain.lines().count(), analyzer.profiles.len());
+        assert!(plain.lines().all(|line| line.starts_with("Who is Contact_") && line.split('\t').count() == 3));
+    }
+
+    #[test]
+    fn test_ghost_and_tweet_only_contacts() {
+        crate::relationship::anonymization::set_test_mode(true);
+        let mut analyzer = RelationshipAnalyzer::new();
+        for user_id in ["alice", "bob", "carol"] {
+            analyzer.profiles.insert(user_id.to_string(), UserProfile::new(user_id));
+        }
+        analyzer.profiles.get_mut("carol").unwrap().total_interactions = 5;
+        let tweet_users: HashSet<String> = ["alice", "dave"].iter().map(|id| id.to_string()).collect();
+
+        let ghosts: Vec<&str> = find_ghost_contacts(&analyzer, &tweet_users).iter()
+            .map(|profile| profile.user_id.as_str())
+            .collect();
+        assert_eq!(ghosts, vec!["carol", "bob"]);
+
+        let tweet_only = find_tweet_only_contacts(&analyzer, &tweet_users);
+        assert_eq!(tweet_only, vec!["dave"]);
+    }
+
+    #[test]
+    fn test_compute_mutual_contacts() {
+        let mut dm_data = create_sample_dm_data();
+        let mut group = dm_data[0].clone();
+        group.dm_conversation.conversation_id = "1500000000000000000".to_string();
+        let mut second_sender = group.dm_conversation.messages[0].clone();
+        second_sender.message_create.as_mut().unwrap().sender_id = Some("9876543210".to_string());
+        group.dm_conversation.messages.push(second_sender);
+        dm_data.push(group);
+        let mut analyzer = RelationshipAnalyzer::new();
+        analyzer.build_profiles(&dm_data, &[]);
+
+        let mutual = compute_mutual_contacts(&analyzer.profiles, &dm_data);
+
+        let pair = |a: &str, b: &str| mutual.get(&(a.to_string(), b.to_string())).copied();
+        assert_eq!(pair("1132151165410455552", "3382"), Some(1));
+        assert_eq!(pair("1132151165410455552", "9876543210"), Some(1));
+        assert_eq!(pair("3382", "9876543210"), Some(1));
+        assert_eq!(mutual.len(), 3);
+
+        dm_data.pop();
+        let mutual = compute_mutual_contacts(&analyzer.profiles, &dm_data);
+        assert_eq!(mutual.len(), 2);
+        apply_mutual_contact_counts(&mut analyzer.profiles, &mutual);
+        assert_eq!(analyzer.profiles["1132151165410455552"].mutual_contact_count, 2);
+        assert_eq!(analyzer.profiles["3382"].mutual_contact_count, 1);
+    }
+
+    #[test]
+    fn test_detect_relationship_lifecycle() {
+        use chrono::TimeZone;
+
+        let mut analyzer = RelationshipAnalyzer::new();
+        let base = Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap();
+        let mut add = |user_id: &str, halves: Option<(u32, u32)>, days_before_base: i64| {
+            let mut profile = UserProfile::new(user_id);
+            profile.last_interaction = Some(base - chrono::Duration::days(days_before_base));
+            if let Some((first, second)) = halves {
+                profile.metadata.insert(FIRST_HALF_INTERACTIONS_KEY.to_string(), first.to_string());
+                profile.metadata.insert(SECOND_HALF_INTERACTIONS_KEY.to_string(), second.to_string());
+            }
+            analyzer.profiles.insert(user_id.to_string(), profile);
+        };
+        add("growing", Some((10, 13)), 0);
+        add("stable", Some((10, 12)), 5);
+        add("declining", Some((10, 7)), 90);
+        add("dormant", Some((10, 20)), 91);
+        add("single", None, 1);
+
+        let lifecycle = analyzer.detect_relationship_lifecycle_as_of(base);
+
+        assert_eq!(lifecycle["growing"].stage, RelationshipStage::Growing);
+        assert!((lifecycle["growing"].trend_pct - 30.0).abs() < 1e-9);
+        assert_eq!(lifecycle["stable"].stage, RelationshipStage::Stable);
+        assert_eq!(lifecycle["declining"].stage, RelationshipStage::Declining);
+        assert!((lifecycle["declining"].trend_pct + 30.0).abs() < 1e-9);
+        assert_eq!(lifecycle["dormant"].stage, RelationshipStage::Dormant);
+        assert_eq!(lifecycle["single"], LifecycleStage { stage: RelationshipStage::Stable, trend_pct: 0.0 });
+
+        // Measured against the reference date, not the newest contact
+        let later = analyzer.detect_relationship_lifecycle_as_of(base + chrono::Duration::days(100));
+        assert!(later.values().all(|lifecycle| lifecycle.stage == RelationshipStage::Dormant));
+        assert!(analyzer.detect_relationship_lifecycle().values().all(|lifecycle| lifecycle.stage == RelationshipStage::Dormant));
+    }
+
+    #[test]
+    fn test_create_user_profile_interaction_halves() {
+        let mut dm_data = create_sample_dm_data();
+        let template = dm_data[0].dm_conversation.messages[0].clone();
+        for created_at in ["2023-01-08T10:00:00.000Z", "2023-01-09T10:00:00.000Z", "2023-01-10T10:00:00.000Z"] {
+            let mut message = template.clone();
+            message.message_create.as_mut().unwrap().created_at = Some(created_at.to_string());
+            dm_data[0].dm_conversation.messages.push(message);
+        }
+        let analyzer = RelationshipAnalyzer::new();
+
+        let profile = analyzer.create_user_profile("3382", &dm_data, &[]);
+        let single = analyzer.create_user_profile("9876543210", &dm_data, &[]);
+
+        assert_eq!(profile.metadata.get(FIRST_HALF_INTERACTIONS_KEY).map(String::as_str), Some("1"));
+        assert_eq!(profile.metadata.get(SECOND_HALF_INTERACTIONS_KEY).map(String::as_str), Some("3"));
+        assert!(!single.metadata.contains_key(FIRST_HALF_INTERACTIONS_KEY));
+        assert_eq!(single.metadata.get(CONVERSATION_COUNT_KEY).map(String::as_str), Some("1"));
+        let shared = analyzer.create_user_profile("1132151165410455552", &dm_data, &[]);
+        assert_eq!(shared.metadata.get(CONVERSATION_COUNT_KEY).map(String::as_str), Some("2"));
+    }
+}
@@ -1,5 +1,176 @@
 //! User anonymization utilities using Blake3 hashing
 
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[cfg(any(test, feature = "test-utils"))]
+thread_local! {
+    static TEST_MODE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Makes [`hash_user_id`] return IDs unchanged on the current thread
+///
+/// Lets test assertions and failure messages show readable names instead of
+/// 64-character hashes.
+#[cfg(any(test, feature = "test-utils"))]
+pub fn set_test_mode(enabled: bool) {
+    TEST_MODE.with(|mode| mode.set(enabled));
+}
+
+/// Hashes a user ID into a stable, anonymized hex identifier
+pub fn hash_user_id(user_id: &str) -> String {
+    #[cfg(any(test, feature = "test-utils"))]
+    if TEST_MODE.with(|mode| mode.get()) {
+        return user_id.to_string();
+    }
+    blake3::hash(user_id.as_bytes()).to_hex().to_string()
+}
+
+/// Maps user hashes to short, human-readable pseudonyms like `Contact_001`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PseudonymRegistry {
+    inner: HashMap<String, String>,
+}
+
+impl PseudonymRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pseudonym for a hash, assigning the next free one on first use
+    pub fn get_or_create(&mut self, hash: &str) -> &str {
+        let next = self.inner.len() + 1;
+        self.inner
+            .entry(hash.to_string())
+            .or_insert_with(|| format!("Contact_{:03}", next))
+    }
+
+    /// Returns the pseudonym for a hash if one has been assigned
+    pub fn get(&self, hash: &str) -> Option<&str> {
+        self.inner.get(hash).map(String::as_str)
+    }
+
+    /// Number of assigned pseudonyms
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if no pseudonyms have been assigned
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Assigns pseudonyms to any new users, in sorted hash order so the result
+    /// doesn't depend on input order
+    pub fn assign_all<'a>(&mut self, user_ids: impl IntoIterator<Item = &'a str>) {
+        let mut hashes: Vec<String> = user_ids.into_iter().map(hash_user_id).collect();
+        hashes.sort();
+        hashes.dedup();
+        for hash in &hashes {
+            self.get_or_create(hash);
+        }
+    }
+
+    /// Path of the registry file for a screen name inside `dir`
+    pub fn path_for(dir: &Path, screen_name: &str) -> PathBuf {
+        dir.join(format!("pseudonym_registry_{}.json", screen_name))
+    }
+
+    /// Loads a registry from disk, returning an empty one if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pseudonym registry: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse pseudonym registry: {}", path.display()))
+    }
+
+    /// Saves the registry to disk as JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize pseudonym registry")?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write pseudonym registry: {}", path.display()))
+    }
+}
+
+/// Loads (or creates) the pseudonym registry in `dir`, assigns pseudonyms to any
+/// new users and saves it back as `pseudonym_registry_{screen_name}.json`
+///
+/// New users are assigned in sorted hash order so the result doesn't depend on
+/// input order. Existing labels are never changed.
+pub fn build_pseudonym_registry<'a>(
+    dir: &Path,
+    screen_name: &str,
+    user_ids: impl IntoIterator<Item = &'a str>,
+) -> Result<PseudonymRegistry> {
+    let path = PseudonymRegistry::path_for(dir, screen_name);
+    let mut registry = PseudonymRegistry::load(&path)?;
+    registry.assign_all(user_ids);
+    registry.save(&path)?;
+    Ok(registry)
+}
+
+/// One [`PseudonymRegistry`] shared by every stage of a pipeline run, so a
+/// pseudonym means the same person in every output file
+///
+/// The registry remembers the file it was loaded from and is saved back there,
+/// so it can live outside the per-run output directory and keep labels stable
+/// across runs.
+#[derive(Debug, Clone)]
+pub struct SharedPseudonymRegistry {
+    registry: Arc<Mutex<PseudonymRegistry>>,
+    path: PathBuf,
+}
+
+impl SharedPseudonymRegistry {
+    /// Loads the registry at `path` (empty if the file doesn't exist yet)
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let registry = PseudonymRegistry::load(&path)?;
+        Ok(Self { registry: Arc::new(Mutex::new(registry)), path })
+    }
+
+    /// File the registry is saved to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A copy of the registry as it is now
+    pub fn snapshot(&self) -> Result<PseudonymRegistry> {
+        Ok(self.lock()?.clone())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, PseudonymRegistry>> {
+        self.registry.lock().map_err(|_| anyhow::anyhow!("Pseudonym registry lock poisoned"))
+    }
+}
+
+/// Assigns pseudonyms to any new `user_ids` in `shared` and returns a copy
+///
+/// When new pseudonyms were assigned the registry is saved to its
+/// [`SharedPseudonymRegistry::path`].
+pub fn update_shared_registry<'a>(
+    shared: &SharedPseudonymRegistry,
+    user_ids: impl IntoIterator<Item = &'a str>,
+) -> Result<PseudonymRegistry> {
+    let mut registry = shared.lock()?;
+    let known = registry.len();
+    registry.assign_all(user_ids);
+    if registry.len() > known {
+        registry.save(shared.path())?;
+    }
+    Ok(registry.clone())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,13 +178,24 @@ mod tests {
     #[test]
     fn test_hash_consistency() {
         let user_id = "test_user_123";
-        let hash1 = user_id.to_string();
+        let hash1 = hash_user_id(user_id);
         
         // Hash multiple times to ensure consistency
         for _ in 0..10 {
-            let hash_n = user_id.to_string();
+            let hash_n = hash_user_id(user_id);
             assert_eq!(hash1, hash_n, "Hash should be consistent across multiple calls");
         }
+        assert_ne!(hash1, user_id, "Hash should not expose the original ID");
+        assert_eq!(hash1.len(), 64);
+    }
+
+    #[test]
+    fn test_set_test_mode_returns_ids_unchanged() {
+        set_test_mode(true);
+        assert_eq!(hash_user_id("alice"), "alice");
+        
+        set_test_mode(false);
+        assert_eq!(hash_user_id("alice").len(), 64);
     }
 
     #[test]
@@ -23,7 +205,7 @@ mod tests {
         // Generate hashes for different inputs
         for i in 0..100 {
             let user_id = format!("user_{}", i);
-            let hash = user_id.to_string();
+            let hash = hash_user_id(&user_id);
             
             // Each hash should be unique
             assert!(hashes.insert(hash), "Hash collision detected for user_{}", i);
@@ -31,4 +213,55 @@ mod tests {
         
         assert_eq!(hashes.len(), 100, "Should have 100 unique hashes");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_pseudonym_registry_get_or_create() {
+        let mut registry = PseudonymRegistry::new();
+        
+        assert_eq!(registry.get_or_create("hash_a"), "Contact_001");
+        assert_eq!(registry.get_or_create("hash_b"), "Contact_002");
+        assert_eq!(registry.get_or_create("hash_a"), "Contact_001");
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get("hash_c"), None);
+    }
+
+    #[test]
+    fn test_build_pseudonym_registry_is_stable_across_runs() {
+        set_test_mode(true);
+        let temp_dir = tempfile::tempdir().unwrap();
+        
+        let first = build_pseudonym_registry(temp_dir.path(), "me", ["alice", "bob"]).unwrap();
+        assert!(temp_dir.path().join("pseudonym_registry_me.json").exists());
+        
+        let second = build_pseudonym_registry(temp_dir.path(), "me", ["carol", "bob", "alice"]).unwrap();
+        
+        assert_eq!(second.len(), 3);
+        assert_eq!(first.get("alice"), Some("Contact_001"));
+        assert_eq!(second.get("alice"), Some("Contact_001"));
+        assert_eq!(second.get("bob"), first.get("bob"));
+        assert_eq!(second.get("carol"), Some("Contact_003"));
+    }
+
+    #[test]
+    fn test_update_shared_registry_keeps_earlier_pseudonyms() {
+        set_test_mode(true);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = PseudonymRegistry::path_for(temp_dir.path(), "me");
+        let shared = SharedPseudonymRegistry::load(&path).unwrap();
+
+        update_shared_registry(&shared, []).unwrap();
+        assert!(!path.exists());
+        update_shared_registry(&shared, ["bob"]).unwrap();
+        let snapshot = update_shared_registry(&shared, ["alice", "bob"]).unwrap();
+
+        assert_eq!(snapshot.get("bob"), Some("Contact_001"));
+        assert_eq!(snapshot.get("alice"), Some("Contact_002"));
+        assert_eq!(shared.snapshot().unwrap(), snapshot);
+        let saved = PseudonymRegistry::load(&path).unwrap();
+        assert_eq!(saved, snapshot);
+
+        // A later run loading the same file keeps the labels
+        let reloaded = SharedPseudonymRegistry::load(&path).unwrap();
+        assert_eq!(update_shared_registry(&reloaded, ["carol", "alice"]).unwrap().get("alice"), Some("Contact_002"));
+    }
+}
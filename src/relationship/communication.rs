@@ -1,8 +1,10 @@
 //! Communication pattern analysis and response time calculations
 
-use chrono::{DateTime, Utc, Datelike};
+use chrono::{Datelike, NaiveDate};
 use std::collections::HashMap;
 use crate::models::direct_message::DmMessage;
+use crate::utils::parse_twitter_date;
+use crate::models::interaction::{InteractionEvent, InteractionType};
 
 /// Communication frequency analysis
 #[derive(Debug, Clone)]
@@ -15,6 +17,21 @@ pub struct CommunicationFrequency {
     pub avg_per_month_sent: f64,
     /// Average number of messages received per month
     pub avg_per_month_received: f64,
+    /// Map of calendar day (UTC) to count of messages sent
+    pub sent_per_day: HashMap<NaiveDate, u32>,
+    /// Map of calendar day (UTC) to count of messages received
+    pub received_per_day: HashMap<NaiveDate, u32>,
+}
+
+/// Daily messaging streaks found by [`CommunicationFrequency::streak_analysis`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StreakReport {
+    /// Most consecutive days with at least one message in either direction
+    pub longest_streak_days: u32,
+    /// Consecutive active days ending on the last active day
+    pub current_streak_days: u32,
+    /// Last day with any messages (`None` if there were none)
+    pub last_active_date: Option<NaiveDate>,
 }
 
 impl Default for CommunicationFrequency {
@@ -24,15 +41,99 @@ impl Default for CommunicationFrequency {
             received_per_month: HashMap::new(),
             avg_per_month_sent: 0.0,
             avg_per_month_received: 0.0,
+            sent_per_day: HashMap::new(),
+            received_per_day: HashMap::new(),
+        }
+    }
+}
+
+impl CommunicationFrequency {
+    /// Builds frequency metrics from the DM events of an interaction timeline
+    ///
+    /// `DmSent` events count as sent and `DmReceived` as received; other
+    /// interaction types are ignored.
+    pub fn from_timeline(events: &[InteractionEvent]) -> Self {
+        let mut frequency = Self::default();
+        for event in events {
+            let (per_month, per_day) = match event.interaction_type {
+                InteractionType::DmSent => (&mut frequency.sent_per_month, &mut frequency.sent_per_day),
+                InteractionType::DmReceived => (&mut frequency.received_per_month, &mut frequency.received_per_day),
+                _ => continue,
+            };
+            *per_month.entry((event.timestamp.year(), event.timestamp.month())).or_insert(0) += 1;
+            *per_day.entry(event.timestamp.date_naive()).or_insert(0) += 1;
+        }
+        frequency.avg_per_month_sent = average_per_key(&frequency.sent_per_month);
+        frequency.avg_per_month_received = average_per_key(&frequency.received_per_month);
+        frequency
+    }
+
+    /// Finds the longest and the current run of consecutive days with messages
+    ///
+    /// Every day between the first and last active day is checked for a non-zero
+    /// sent or received count. The current streak ends on the last active day.
+    pub fn streak_analysis(&self) -> StreakReport {
+        let is_active = |day: &NaiveDate| {
+            self.sent_per_day.get(day).copied().unwrap_or(0)
+                + self.received_per_day.get(day).copied().unwrap_or(0) > 0
+        };
+        let active_days = || self.sent_per_day.keys().chain(self.received_per_day.keys()).filter(|day| is_active(day));
+        let (Some(first), Some(last)) = (active_days().min().copied(), active_days().max().copied()) else {
+            return StreakReport::default();
+        };
+
+        let mut longest = 0;
+        let mut current = 0;
+        for day in first.iter_days().take_while(|day| *day <= last) {
+            if is_active(&day) {
+                current += 1;
+                longest = longest.max(current);
+            } else {
+                current = 0;
+            }
         }
+
+        StreakReport {
+            longest_streak_days: longest,
+            current_streak_days: current,
+            last_active_date: Some(last),
+        }
+    }
+}
+
+/// Mean count per key of a per-period map (0.0 if empty)
+fn average_per_key<K>(counts: &HashMap<K, u32>) -> f64 {
+    if counts.is_empty() {
+        return 0.0;
     }
+    counts.values().sum::<u32>() as f64 / counts.len() as f64
+}
+
+/// Which consecutive message pairs count as a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SenderFilter {
+    /// Every pair of consecutive messages, including same-sender follow-ups
+    #[default]
+    AnyConsecutive,
+    /// Only pairs where the sender changes, i.e. one person answering another
+    CrossSenderOnly,
 }
 
+/// Stand-in response time for a pair whose timestamps are identical
+pub const TIED_RESPONSE_TIME: std::time::Duration = std::time::Duration::from_millis(1);
+
 /// Calculate response times between consecutive messages in a conversation
 /// 
+/// Messages are ordered by timestamp first, since archives list them
+/// newest-first. Messages sent in the same instant still count as a response:
+/// the pair gets [`TIED_RESPONSE_TIME`] instead of being dropped, so two or
+/// more messages with valid timestamps always yield response times under
+/// [`SenderFilter::AnyConsecutive`].
+/// 
 /// # Arguments
 /// 
-/// * `messages` - A slice of DM messages in chronological order
+/// * `messages` - A slice of DM messages, in any order
+/// * `filter` - Which consecutive pairs to measure
 /// 
 /// # Returns
 /// 
@@ -41,36 +142,40 @@ impl Default for CommunicationFrequency {
 /// # Examples
 /// 
 /// ```
-/// use tweet_scrolls::relationship::calculate_response_times;
+/// use tweet_scrolls::relationship::{calculate_response_times, SenderFilter};
 /// use tweet_scrolls::models::direct_message::{DmMessage, DmMessageCreate};
 /// 
 /// let messages = vec![
 ///     // Create test messages with timestamps
 /// ];
-/// let response_times = calculate_response_times(&messages);
+/// let response_times = calculate_response_times(&messages, SenderFilter::CrossSenderOnly);
 /// assert!(response_times.len() <= messages.len());
 /// ```
-pub fn calculate_response_times(messages: &[DmMessage]) -> Vec<std::time::Duration> {
+pub fn calculate_response_times(messages: &[DmMessage], filter: SenderFilter) -> Vec<std::time::Duration> {
     let mut response_times = Vec::new();
     let mut timestamps = Vec::new();
     
-    // Extract valid timestamps from messages
+    // Extract valid timestamps (with their senders) from messages
     for message in messages {
         if let Some(message_create) = &message.message_create {
             if let Some(created_at) = &message_create.created_at {
-                if let Ok(timestamp) = DateTime::parse_from_rfc3339(created_at) {
-                    timestamps.push(timestamp.with_timezone(&Utc));
+                if let Ok(timestamp) = parse_twitter_date(created_at) {
+                    timestamps.push((timestamp, message_create.sender_id.as_deref()));
                 }
             }
         }
     }
     
+    // Stable, so messages sharing a timestamp keep their archive order
+    timestamps.sort_by_key(|(timestamp, _)| *timestamp);
+    
     // Calculate time differences between consecutive messages
     for window in timestamps.windows(2) {
-        let duration = window[1] - window[0];
-        if let Ok(std_duration) = duration.to_std() {
-            response_times.push(std_duration);
+        if filter == SenderFilter::CrossSenderOnly && window[1].1 == window[0].1 {
+            continue;
         }
+        let duration = (window[1].0 - window[0].0).to_std().unwrap_or_default();
+        response_times.push(if duration.is_zero() { TIED_RESPONSE_TIME } else { duration });
     }
     
     response_times
@@ -80,7 +185,8 @@ pub fn calculate_response_times(messages: &[DmMessage]) -> Vec<std::time::Durati
 /// 
 /// # Arguments
 /// 
-/// * `messages` - A slice of DM messages in chronological order
+/// * `messages` - A slice of DM messages, in any order
+/// * `filter` - Which consecutive pairs to measure
 /// 
 /// # Returns
 /// 
@@ -89,17 +195,17 @@ pub fn calculate_response_times(messages: &[DmMessage]) -> Vec<std::time::Durati
 /// # Examples
 /// 
 /// ```
-/// use tweet_scrolls::relationship::calculate_average_response_time;
+/// use tweet_scrolls::relationship::{calculate_average_response_time, SenderFilter};
 /// use tweet_scrolls::models::direct_message::{DmMessage, DmMessageCreate};
 /// 
 /// let messages = vec![
 ///     // Create test messages with timestamps
 /// ];
-/// let avg_time = calculate_average_response_time(&messages);
+/// let avg_time = calculate_average_response_time(&messages, SenderFilter::AnyConsecutive);
 /// assert!(avg_time >= std::time::Duration::from_secs(0));
 /// ```
-pub fn calculate_average_response_time(messages: &[DmMessage]) -> std::time::Duration {
-    let response_times = calculate_response_times(messages);
+pub fn calculate_average_response_time(messages: &[DmMessage], filter: SenderFilter) -> std::time::Duration {
+    let response_times = calculate_response_times(messages, filter);
     
     if response_times.is_empty() {
         return std::time::Duration::from_secs(0);
@@ -120,6 +226,8 @@ pub fn calculate_communication_frequency(
 ) -> CommunicationFrequency {
     let mut sent_per_month: HashMap<(i32, u32), u32> = HashMap::new();
     let mut received_per_month: HashMap<(i32, u32), u32> = HashMap::new();
+    let mut sent_per_day: HashMap<NaiveDate, u32> = HashMap::new();
+    let mut received_per_day: HashMap<NaiveDate, u32> = HashMap::new();
     let mut total_sent = 0;
     let mut total_received = 0;
 
@@ -127,17 +235,19 @@ pub fn calculate_communication_frequency(
         for message in &dm_wrapper.dm_conversation.messages {
             if let Some(create) = &message.message_create {
                 if let Some(created_at) = &create.created_at {
-                    if let Ok(timestamp) = DateTime::parse_from_rfc3339(created_at) {
-                        let datetime = timestamp.with_timezone(&Utc);
+                    if let Ok(datetime) = parse_twitter_date(created_at) {
                         let month_key = (datetime.year(), datetime.month());
+                        let day_key = datetime.date_naive();
 
                         // Check if this user sent or received the message
                         if let Some(sender_id) = &create.sender_id {
                             if sender_id == user_id {
                                 *sent_per_month.entry(month_key).or_insert(0) += 1;
+                                *sent_per_day.entry(day_key).or_insert(0) += 1;
                                 total_sent += 1;
                             } else {
                                 *received_per_month.entry(month_key).or_insert(0) += 1;
+                                *received_per_day.entry(day_key).or_insert(0) += 1;
                                 total_received += 1;
                             }
                         }
@@ -164,6 +274,8 @@ pub fn calculate_communication_frequency(
         received_per_month,
         avg_per_month_sent,
         avg_per_month_received,
+        sent_per_day,
+        received_per_day,
     }
 }
 
@@ -191,7 +303,7 @@ mod tests {
     #[test]
     fn test_calculate_response_times_empty() {
         let messages: Vec<DmMessage> = vec![];
-        let response_times = calculate_response_times(&messages);
+        let response_times = calculate_response_times(&messages, SenderFilter::AnyConsecutive);
         assert!(response_times.is_empty());
     }
 
@@ -200,7 +312,7 @@ mod tests {
         let messages = vec![
             create_test_message("1", "2023-01-01T10:00:00.000Z", Some("user1"))
         ];
-        let response_times = calculate_response_times(&messages);
+        let response_times = calculate_response_times(&messages, SenderFilter::AnyConsecutive);
         assert!(response_times.is_empty()); // Need at least 2 messages for response time
     }
 
@@ -212,7 +324,7 @@ mod tests {
             create_test_message("3", "2023-01-01T10:10:00.000Z", Some("user1")),
         ];
         
-        let response_times = calculate_response_times(&messages);
+        let response_times = calculate_response_times(&messages, SenderFilter::AnyConsecutive);
         assert_eq!(response_times.len(), 2);
         
         // First response time should be 5 minutes (300 seconds)
@@ -230,14 +342,61 @@ mod tests {
             create_test_message("3", "2023-01-01T10:10:00.000Z", Some("user1")),
         ];
         
-        let avg_time = calculate_average_response_time(&messages);
+        let avg_time = calculate_average_response_time(&messages, SenderFilter::AnyConsecutive);
         assert_eq!(avg_time.as_secs(), 300); // Average of 300 and 300 is 300
     }
 
+    #[test]
+    fn test_cross_sender_response_times_skip_follow_ups() {
+        let messages = vec![
+            create_test_message("1", "2023-01-01T10:00:00.000Z", Some("user1")),
+            create_test_message("2", "2023-01-01T10:01:00.000Z", Some("user1")),
+            create_test_message("3", "2023-01-01T10:11:00.000Z", Some("user2")),
+            create_test_message("4", "2023-01-01T10:12:00.000Z", Some("user2")),
+            create_test_message("5", "2023-01-01T10:32:00.000Z", Some("user1")),
+        ];
+        
+        let all = calculate_response_times(&messages, SenderFilter::AnyConsecutive);
+        let cross = calculate_response_times(&messages, SenderFilter::CrossSenderOnly);
+        
+        assert_eq!(all.len(), 4);
+        assert_eq!(cross.iter().map(|d| d.as_secs()).collect::<Vec<_>>(), vec![600, 1200]);
+        assert_eq!(calculate_average_response_time(&messages, SenderFilter::CrossSenderOnly).as_secs(), 900);
+    }
+
+    #[test]
+    fn test_calculate_response_times_identical_timestamps() {
+        let messages = vec![
+            create_test_message("1", "2023-01-01T10:00:00.000Z", Some("user1")),
+            create_test_message("2", "2023-01-01T10:00:00.000Z", Some("user2")),
+            create_test_message("3", "2023-01-01T10:00:00.000Z", Some("user1")),
+        ];
+        
+        let response_times = calculate_response_times(&messages, SenderFilter::AnyConsecutive);
+        assert_eq!(response_times, vec![TIED_RESPONSE_TIME; 2]);
+        assert_eq!(calculate_average_response_time(&messages, SenderFilter::CrossSenderOnly), TIED_RESPONSE_TIME);
+        
+    }
+
+    #[test]
+    fn test_calculate_response_times_newest_first() {
+        // Archives list messages newest-first
+        let messages = vec![
+            create_test_message("4", "2023-01-01T10:32:00.000Z", Some("user1")),
+            create_test_message("3", "2023-01-01T10:12:00.000Z", Some("user2")),
+            create_test_message("2", "2023-01-01T10:02:00.000Z", Some("user1")),
+            create_test_message("1", "2023-01-01T10:00:00.000Z", Some("user2")),
+        ];
+        
+        let response_times = calculate_response_times(&messages, SenderFilter::CrossSenderOnly);
+        assert_eq!(response_times.iter().map(|d| d.as_secs()).collect::<Vec<_>>(), vec![120, 600, 1200]);
+        assert_eq!(calculate_average_response_time(&messages, SenderFilter::CrossSenderOnly).as_secs(), 640);
+    }
+
     #[test]
     fn test_calculate_average_response_time_empty() {
         let messages: Vec<DmMessage> = vec![];
-        let avg_time = calculate_average_response_time(&messages);
+        let avg_time = calculate_average_response_time(&messages, SenderFilter::AnyConsecutive);
         assert_eq!(avg_time.as_secs(), 0);
     }
 
@@ -270,7 +429,45 @@ mod tests {
             }
         ];
         
-        let response_times = calculate_response_times(&messages);
+        let response_times = calculate_response_times(&messages, SenderFilter::AnyConsecutive);
         assert!(response_times.is_empty()); // Should handle invalid timestamps gracefully
     }
+
+    #[test]
+    fn test_streak_analysis() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2023, 1, d).unwrap();
+        let mut freq = CommunicationFrequency::default();
+        for d in [1, 2, 3, 4] {
+            freq.sent_per_day.insert(day(d), 1);
+        }
+        freq.received_per_day.insert(day(5), 2);
+        freq.sent_per_day.insert(day(6), 0);
+        freq.received_per_day.insert(day(8), 1);
+        freq.sent_per_day.insert(day(9), 3);
+
+        let report = freq.streak_analysis();
+        assert_eq!(report.longest_streak_days, 5);
+        assert_eq!(report.current_streak_days, 2);
+        assert_eq!(report.last_active_date, Some(day(9)));
+
+        assert_eq!(CommunicationFrequency::default().streak_analysis(), StreakReport::default());
+    }
+
+    #[test]
+    fn test_from_timeline_counts_dms_per_day() {
+        use chrono::{TimeZone, Utc};
+
+        let at = |d: u32| Utc.with_ymd_and_hms(2023, 1, d, 12, 0, 0).unwrap();
+        let events = vec![
+            InteractionEvent::new("1", at(1), InteractionType::DmSent, "u", "hi"),
+            InteractionEvent::new("2", at(1), InteractionType::DmReceived, "u", "hey"),
+            InteractionEvent::new("3", at(2), InteractionType::DmReceived, "u", "yo"),
+            InteractionEvent::new("4", at(3), InteractionType::TweetReply, "u", "reply"),
+        ];
+
+        let freq = CommunicationFrequency::from_timeline(&events);
+        assert_eq!(freq.sent_per_month.get(&(2023, 1)), Some(&1));
+        assert_eq!(freq.received_per_month.get(&(2023, 1)), Some(&2));
+        assert_eq!(freq.streak_analysis().longest_streak_days, 2);
+    }
 }
\ No newline at end of file
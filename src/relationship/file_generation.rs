@@ -1,10 +1,27 @@
-use crate::models::profile::UserProfile;
-use crate::models::interaction::InteractionEvent;
+use crate::models::profile::{UserProfile, AVG_RESPONSE_TIME_SECS_KEY, PEAK_ACTIVITY_HOUR_KEY};
+use crate::models::interaction::{InteractionEvent, InteractionType};
+use crate::processing::dm_threads::DmThread;
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 use std::fmt::Write; // For String formatting
 
+use crate::utils::{humanize_duration, truncate_to_utf8_boundary};
+
+use super::analyzer::RelationshipAnalyzer;
+use super::anonymization::{hash_user_id, PseudonymRegistry};
+use super::timeline_integration::{find_most_active_day, find_peak_activity_hours, longest_streak_days};
+
+/// Maximum number of contacts listed in the YAML context file
+const YAML_TOP_CONTACTS: usize = 10;
+
+/// Maximum characters kept from each message in a conversation excerpts file
+const EXCERPT_MAX_CHARS: usize = 200;
+
+/// Warning written at the top of every conversation excerpts file
+pub const EXCERPTS_WARNING: &str = "These excerpts may contain personal information. Review before sharing.";
+
 /// LLM File Generator for relationship intelligence profiles
 pub struct LLMFileGenerator {
     /// Directory where all output files will be written
@@ -109,6 +126,54 @@ impl LLMFileGenerator {
         Ok(())
     }
 
+    /// Write a compact, structured YAML context file for LLM tools
+    ///
+    /// The document has three sections: `profile` (screen name, archive span and
+    /// total interactions), `top_contacts` (hashed IDs with interaction counts,
+    /// average gap between interactions in minutes and average sentiment) and
+    /// `activity_summary` (peak hours, most active weekday and longest daily streak).
+    pub fn write_yaml_context(&self, analyzer: &RelationshipAnalyzer, timeline: &[InteractionEvent], path: &Path) -> Result<()> {
+        let content = generate_yaml_context(&self.screen_name, analyzer, timeline);
+        
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write YAML context file: {}", path.display()))?;
+        
+        Ok(())
+    }
+
+    /// Write a numbered sample of a DM thread's messages for use in LLM prompts
+    ///
+    /// Picks up to `max_excerpts` messages: the first, the last and the rest evenly
+    /// spaced in between. Senders are shown as `Contact_NNN` pseudonyms and each
+    /// message is cut to 200 characters. The file is written to `output_path` (a
+    /// directory) as `{user_hash}_excerpts_{timestamp}.txt`, where `user_hash` is
+    /// the hashed conversation ID.
+    pub fn write_conversation_excerpts(&self, thread: &DmThread, max_excerpts: usize, output_path: &Path) -> Result<()> {
+        let conversation_id = thread.thread_id.strip_prefix("dm_").unwrap_or(&thread.thread_id);
+        let file_path = output_path.join(format!("{}_excerpts_{}.txt", hash_user_id(conversation_id), self.timestamp));
+        let content = generate_conversation_excerpts(thread, max_excerpts);
+        
+        fs::write(&file_path, content)
+            .with_context(|| format!("Failed to write conversation excerpts file: {}", file_path.display()))?;
+        
+        Ok(())
+    }
+
+    /// Write a side-by-side comparison of two contacts to `output_path`
+    ///
+    /// Each row holds a metric label followed by its value for `a` and for `b`:
+    /// interaction count, average response time, first and last interaction,
+    /// contact type, sentiment score and peak activity hour. Contacts are headed
+    /// by their pseudonym in `reg`, or by their hashed ID when they have none.
+    pub fn write_relationship_comparison(&self, a: &UserProfile, b: &UserProfile, reg: &PseudonymRegistry, output_path: &Path) -> Result<()> {
+        let content = generate_relationship_comparison(a, b, reg);
+        
+        fs::write(output_path, content)
+            .with_context(|| format!("Failed to write relationship comparison file: {}", output_path.display()))?;
+        
+        Ok(())
+    }
+
     /// Generate LLM analysis prompts file
     fn generate_llm_prompts_file(&self, profiles: &[UserProfile]) -> Result<()> {
         let file_path = Path::new(&self.output_dir).join("llm_analysis_prompts.txt");
@@ -121,6 +186,105 @@ impl LLMFileGenerator {
     }
 }
 
+/// Indices of up to `max_excerpts` messages out of `total`: first, last and evenly spaced middle ones
+fn excerpt_indices(total: usize, max_excerpts: usize) -> Vec<usize> {
+    if total <= max_excerpts {
+        return (0..total).collect();
+    }
+    match max_excerpts {
+        0 => Vec::new(),
+        1 => vec![0],
+        _ => {
+            let middle = max_excerpts - 2;
+            let mut indices = vec![0];
+            indices.extend((0..middle).map(|i| 1 + i * (total - 2) / middle));
+            indices.push(total - 1);
+            indices
+        }
+    }
+}
+
+/// Generate the text of a conversation excerpts file (see [`LLMFileGenerator::write_conversation_excerpts`])
+pub fn generate_conversation_excerpts(thread: &DmThread, max_excerpts: usize) -> String {
+    let mut output = String::new();
+    let mut registry = PseudonymRegistry::new();
+    let indices = excerpt_indices(thread.messages.len(), max_excerpts);
+    writeln!(&mut output, "⚠️  {}", EXCERPTS_WARNING).unwrap();
+    writeln!(&mut output).unwrap();
+    writeln!(&mut output, "# CONVERSATION EXCERPTS").unwrap();
+    writeln!(&mut output, "{} of {} messages", indices.len(), thread.messages.len()).unwrap();
+    writeln!(&mut output).unwrap();
+    
+    for (number, index) in indices.into_iter().enumerate() {
+        let message = &thread.messages[index];
+        let sender = registry.get_or_create(&hash_user_id(&message.sender_id)).to_string();
+        let mut text: String = message.text.chars().take(EXCERPT_MAX_CHARS).collect();
+        if message.text.chars().count() > EXCERPT_MAX_CHARS {
+            text.push('…');
+        }
+        let timestamp = message.timestamp
+            .map(|ts| format!(" [{}]", ts.format("%Y-%m-%d %H:%M")))
+            .unwrap_or_default();
+        writeln!(&mut output, "{}. {}{}: {}", number + 1, sender, timestamp, text).unwrap();
+    }
+    
+    output
+}
+
+/// Values of each comparison row for one contact, in row order
+fn comparison_values(profile: &UserProfile) -> Vec<String> {
+    let date = |value: Option<chrono::DateTime<chrono::Utc>>| value
+        .map(|ts| ts.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "N/A".to_string());
+    let response_time = profile.metadata.get(AVG_RESPONSE_TIME_SECS_KEY)
+        .and_then(|secs| secs.parse::<i64>().ok())
+        .map(|secs| humanize_duration(chrono::Duration::seconds(secs)))
+        .unwrap_or_else(|| "N/A".to_string());
+    let peak_hour = profile.metadata.get(PEAK_ACTIVITY_HOUR_KEY)
+        .and_then(|hour| hour.parse::<u32>().ok())
+        .map(|hour| format!("{:02}:00 UTC", hour))
+        .unwrap_or_else(|| "N/A".to_string());
+    vec![
+        profile.total_interactions.to_string(),
+        response_time,
+        date(profile.first_interaction),
+        date(profile.last_interaction),
+        profile.contact_type.to_string(),
+        format!("{:.2}", profile.avg_sentiment),
+        peak_hour,
+    ]
+}
+
+/// Generate the text of a relationship comparison file (see [`LLMFileGenerator::write_relationship_comparison`])
+pub fn generate_relationship_comparison(a: &UserProfile, b: &UserProfile, reg: &PseudonymRegistry) -> String {
+    const LABELS: [&str; 7] = [
+        "Interaction count",
+        "Avg response time",
+        "First interaction",
+        "Last interaction",
+        "Contact type",
+        "Sentiment score",
+        "Peak activity hour",
+    ];
+    let name = |profile: &UserProfile| {
+        let hash = hash_user_id(&profile.user_id);
+        reg.get(&hash).map(str::to_string).unwrap_or(hash)
+    };
+    let (name_a, name_b) = (name(a), name(b));
+    let (values_a, values_b) = (comparison_values(a), comparison_values(b));
+    let label_width = LABELS.iter().map(|label| label.len()).max().unwrap_or(0);
+    let width_a = values_a.iter().chain(std::iter::once(&name_a)).map(|value| value.chars().count()).max().unwrap_or(0);
+
+    let mut output = String::new();
+    writeln!(&mut output, "# RELATIONSHIP COMPARISON").unwrap();
+    writeln!(&mut output).unwrap();
+    writeln!(&mut output, "{:<label_width$}  {:<width_a$}  {}", "Metric", name_a, name_b).unwrap();
+    for ((label, value_a), value_b) in LABELS.iter().zip(&values_a).zip(&values_b) {
+        writeln!(&mut output, "{:<label_width$}  {:<width_a$}  {}", label, value_a, value_b).unwrap();
+    }
+    output
+}
+
 /// Generate formatted profile text for a user
 pub fn generate_profile_text(profile: &UserProfile) -> String {
     let mut output = String::new();
@@ -159,7 +323,7 @@ pub fn generate_timeline_text(interactions: &[InteractionEvent]) -> String {
         
         if !interaction.content.is_empty() {
             let preview = if interaction.content.len() > 100 {
-                format!("{}...", &interaction.content[..100])
+                format!("{}...", truncate_to_utf8_boundary(&interaction.content, 100))
             } else {
                 interaction.content.clone()
             };
@@ -180,6 +344,104 @@ pub fn generate_timeline_text(interactions: &[InteractionEvent]) -> String {
     content
 }
 
+/// Minutes each sender took to answer a DM from someone else in the same conversation
+///
+/// Only DM messages carrying a `conversation_id` are considered; consecutive
+/// messages from the same sender are not replies.
+fn reply_latencies_min(timeline: &[InteractionEvent]) -> HashMap<&str, Vec<f64>> {
+    let mut messages: Vec<(&str, &InteractionEvent)> = timeline.iter()
+        .filter(|e| matches!(e.interaction_type, InteractionType::DmSent | InteractionType::DmReceived))
+        .filter_map(|e| e.metadata.get("conversation_id").map(|id| (id.as_str(), e)))
+        .collect();
+    messages.sort_by(|a, b| a.0.cmp(b.0).then_with(|| a.1.timestamp.cmp(&b.1.timestamp)));
+
+    let mut latencies: HashMap<&str, Vec<f64>> = HashMap::new();
+    for pair in messages.windows(2) {
+        let ((prev_conversation, prev), (conversation, reply)) = (pair[0], pair[1]);
+        if prev_conversation == conversation && prev.user_id != reply.user_id {
+            let minutes = reply.timestamp.signed_duration_since(prev.timestamp).num_seconds() as f64 / 60.0;
+            latencies.entry(reply.user_id.as_str()).or_default().push(minutes);
+        }
+    }
+    latencies
+}
+
+/// Generate the YAML context document for a timeline
+///
+/// `avg_response_time_min` is the mean time a contact took to reply to a DM
+/// from someone else in the same conversation, or `null` when they never did.
+pub fn generate_yaml_context(screen_name: &str, analyzer: &RelationshipAnalyzer, timeline: &[InteractionEvent]) -> String {
+    let mut output = String::new();
+
+    writeln!(&mut output, "profile:").unwrap();
+    writeln!(&mut output, "  screen_name: {}", yaml_string(screen_name)).unwrap();
+    let first = timeline.iter().map(|e| e.timestamp).min();
+    let last = timeline.iter().map(|e| e.timestamp).max();
+    match (first, last) {
+        (Some(first), Some(last)) => {
+            writeln!(&mut output, "  archive_span:").unwrap();
+            writeln!(&mut output, "    start: {}", first.format("%Y-%m-%d")).unwrap();
+            writeln!(&mut output, "    end: {}", last.format("%Y-%m-%d")).unwrap();
+            writeln!(&mut output, "    days: {}", last.signed_duration_since(first).num_days()).unwrap();
+        }
+        _ => writeln!(&mut output, "  archive_span: null").unwrap(),
+    }
+    writeln!(&mut output, "  total_interactions: {}", timeline.len()).unwrap();
+
+    // Group events per contact in chronological order
+    let mut events_by_user: HashMap<&str, Vec<&InteractionEvent>> = HashMap::new();
+    for event in timeline {
+        events_by_user.entry(event.user_id.as_str()).or_default().push(event);
+    }
+    let mut contacts: Vec<_> = events_by_user.into_iter().collect();
+    contacts.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(b.0)));
+
+    if contacts.is_empty() {
+        writeln!(&mut output, "top_contacts: []").unwrap();
+    } else {
+        writeln!(&mut output, "top_contacts:").unwrap();
+    }
+    let latencies = reply_latencies_min(timeline);
+    for (user_id, events) in contacts.into_iter().take(YAML_TOP_CONTACTS) {
+        let gaps = latencies.get(user_id).map(Vec::as_slice).unwrap_or_default();
+        let sentiment = analyzer.profiles.get(user_id)
+            .map(|p| p.avg_sentiment)
+            .unwrap_or_else(|| events.iter().map(|e| e.sentiment_score as f64).sum::<f64>() / events.len() as f64);
+
+        writeln!(&mut output, "  - hash: {}", yaml_string(&hash_user_id(user_id))).unwrap();
+        writeln!(&mut output, "    interaction_count: {}", events.len()).unwrap();
+        if gaps.is_empty() {
+            writeln!(&mut output, "    avg_response_time_min: null").unwrap();
+        } else {
+            writeln!(&mut output, "    avg_response_time_min: {:.1}", gaps.iter().sum::<f64>() / gaps.len() as f64).unwrap();
+        }
+        writeln!(&mut output, "    sentiment: {:.2}", sentiment).unwrap();
+    }
+
+    writeln!(&mut output, "activity_summary:").unwrap();
+    let peak_hours: Vec<String> = find_peak_activity_hours(timeline).iter().map(|h| h.to_string()).collect();
+    writeln!(&mut output, "  peak_hours: [{}]", peak_hours.join(", ")).unwrap();
+    match find_most_active_day(timeline) {
+        Some((day, confidence)) => {
+            writeln!(&mut output, "  most_active_weekday: {}", day).unwrap();
+            writeln!(&mut output, "  most_active_weekday_confidence: {:.2}", confidence).unwrap();
+        }
+        None => writeln!(&mut output, "  most_active_weekday: null").unwrap(),
+    }
+    writeln!(&mut output, "  longest_streak_days: {}", longest_streak_days(timeline)).unwrap();
+
+    output
+}
+
+/// Quote a string as a YAML double-quoted scalar
+fn yaml_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
 /// Generate LLM analysis prompts
 pub fn generate_llm_analysis_prompts(profiles: &[UserProfile]) -> String {
     format!(
@@ -378,4 +640,135 @@ mod tests {
         assert_eq!(generator.timestamp, 1234567890);
         assert!(generator.output_dir.contains("testuser"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_excerpt_indices() {
+        assert_eq!(excerpt_indices(3, 5), vec![0, 1, 2]);
+        assert_eq!(excerpt_indices(10, 0), Vec::<usize>::new());
+        assert_eq!(excerpt_indices(10, 1), vec![0]);
+        assert_eq!(excerpt_indices(10, 2), vec![0, 9]);
+        assert_eq!(excerpt_indices(10, 5), vec![0, 1, 3, 6, 9]);
+    }
+
+    #[test]
+    fn test_write_conversation_excerpts() {
+        use crate::processing::dm_threads::{DmThreadMessage, ThreadMetadata};
+
+        crate::relationship::anonymization::set_test_mode(true);
+        let messages: Vec<DmThreadMessage> = (0..10)
+            .map(|i| DmThreadMessage {
+                id: i.to_string(),
+                sender_id: if i % 2 == 0 { "111".to_string() } else { "222".to_string() },
+                recipient_id: None,
+                text: if i == 9 { "x".repeat(250) } else { format!("message {}", i) },
+                timestamp: None,
+                position: i + 1,
+                reply_context: None,
+            })
+            .collect();
+        let thread = DmThread {
+            thread_id: "dm_111-222".to_string(),
+            group_name: None,
+            participants: vec!["111".to_string(), "222".to_string()],
+            metadata: ThreadMetadata {
+                message_count: messages.len(),
+                duration_seconds: None,
+                avg_response_time: None,
+                start_time: None,
+                end_time: None,
+            },
+            messages,
+            content_warnings: Vec::new(),
+            average_message_length: 0.0,
+            longest_message_chars: 250,
+            shortest_message_chars: 9,
+            media_message_count: 0,
+            participant_events: Vec::new(),
+            topic_shift_points: Vec::new(),
+        };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let generator = LLMFileGenerator::new(temp_dir.path().to_str().unwrap(), "testuser", 42);
+
+        generator.write_conversation_excerpts(&thread, 4, temp_dir.path()).unwrap();
+        let content = fs::read_to_string(temp_dir.path().join("111-222_excerpts_42.txt")).unwrap();
+
+        assert!(content.starts_with(&format!("⚠️  {}\n", EXCERPTS_WARNING)));
+        assert!(content.contains("4 of 10 messages"));
+        assert!(content.contains("1. Contact_001: message 0\n"));
+        assert!(content.contains("2. Contact_002: message 1\n"));
+        assert!(content.contains("3. Contact_002: message 5\n"));
+        assert!(content.contains(&format!("4. Contact_002: {}…\n", "x".repeat(200))));
+        assert!(!content.contains("111") && !content.contains("222"));
+    }
+
+    #[test]
+    fn test_write_yaml_context() {
+        use chrono::{TimeZone, Utc};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("context.yaml");
+        let base = Utc.with_ymd_and_hms(2023, 1, 2, 10, 0, 0).unwrap();
+        let dm = |id: &str, minutes: i64, user_id: &str, content: &str| {
+            let mut event = InteractionEvent::new(id, base + chrono::Duration::minutes(minutes), InteractionType::DmSent, user_id, content);
+            event.metadata.insert("conversation_id".to_string(), "alice-bob".to_string());
+            event
+        };
+        // Only alice's first message answers bob; her second follows her own
+        let timeline = vec![
+            dm("1", 0, "bob", "Hello"),
+            dm("2", 30, "alice", "Thanks, great!"),
+            dm("3", 24 * 60, "alice", "See you"),
+        ];
+        let generator = LLMFileGenerator::new(temp_dir.path().to_str().unwrap(), "test\"user", 1234567890);
+
+        generator.write_yaml_context(&RelationshipAnalyzer::new(), &timeline, &path).unwrap();
+        let yaml = fs::read_to_string(&path).unwrap();
+
+        assert!(yaml.starts_with("profile:\n  screen_name: \"test\\\"user\"\n"));
+        assert!(yaml.contains("    start: 2023-01-02\n    end: 2023-01-03\n    days: 1\n"));
+        assert!(yaml.contains("  total_interactions: 3\n"));
+        assert!(yaml.contains(&format!("  - hash: \"{}\"\n    interaction_count: 2\n    avg_response_time_min: 30.0\n    sentiment: 0.50\n", hash_user_id("alice"))));
+        assert!(yaml.contains("    avg_response_time_min: null\n"));
+        assert!(!yaml.contains("alice"));
+        assert!(yaml.contains("  most_active_weekday: Mon\n"));
+        assert!(yaml.contains("  most_active_weekday_confidence: 0.00\n"));
+        assert!(yaml.contains("  longest_streak_days: 2\n"));
+    }
+
+    #[test]
+    fn test_write_relationship_comparison() {
+        use chrono::{TimeZone, Utc};
+
+        let mut a = UserProfile::new("alice");
+        a.total_interactions = 12;
+        a.first_interaction = Some(Utc.with_ymd_and_hms(2023, 1, 2, 10, 0, 0).unwrap());
+        a.last_interaction = Some(Utc.with_ymd_and_hms(2023, 3, 4, 10, 0, 0).unwrap());
+        a.avg_sentiment = 0.5;
+        a.metadata.insert(AVG_RESPONSE_TIME_SECS_KEY.to_string(), "7200".to_string());
+        a.metadata.insert(PEAK_ACTIVITY_HOUR_KEY.to_string(), "9".to_string());
+        let b = UserProfile::new("bob");
+        let mut registry = PseudonymRegistry::new();
+        registry.get_or_create(&hash_user_id("alice"));
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("comparison.txt");
+        let generator = LLMFileGenerator::new(temp_dir.path().to_str().unwrap(), "testuser", 42);
+
+        generator.write_relationship_comparison(&a, &b, &registry, &path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        let alice = registry.get(&hash_user_id("alice")).unwrap();
+        assert_eq!(lines.len(), 10);
+        assert!(lines[2].starts_with("Metric") && lines[2].contains(alice) && lines[2].ends_with(&hash_user_id("bob")));
+        assert!(!text.contains("alice") && !text.contains("bob"));
+        let row = |label: &str| lines.iter().find(|line| line.starts_with(label)).unwrap().split_whitespace().collect::<Vec<_>>().join(" ");
+        assert!(row("Interaction count").ends_with(" 12 0"));
+        assert!(row("Avg response time").ends_with(&format!(" {} N/A", humanize_duration(chrono::Duration::hours(2)))));
+        assert!(row("First interaction").ends_with(" 2023-01-02 N/A"));
+        assert!(row("Last interaction").ends_with(" 2023-03-04 N/A"));
+        assert!(row("Sentiment score").ends_with(" 0.50 0.00"));
+        assert!(row("Peak activity hour").ends_with(" 09:00 UTC N/A"));
+        let value_column = lines[2].find(alice).unwrap();
+        assert!(lines[3..].iter().all(|line| line[value_column..].starts_with(|c: char| !c.is_whitespace())));
+    }
+}
@@ -2,21 +2,25 @@
 //! 
 //! Handles directory creation and file I/O operations.
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use std::collections::HashMap;
 use tokio::fs as async_fs;
 
 use crate::models::profile::UserProfile;
 use crate::models::interaction::InteractionEvent;
-use super::text_generators::generate_user_profile_text;
+use super::text_generators::{generate_user_profile_text, Locale, RenderStyle};
 use super::timeline_text::generate_timeline_text;
-use super::prompts_generator::generate_llm_analysis_prompts;
+use super::prompts_generator::{generate_llm_analysis_prompts, PromptLength};
+use crate::utils::truncate_to_utf8_boundary;
 
 /// File writing service for relationship intelligence
 pub struct FileWriter {
     output_dir: String,
     screen_name: String,
     timestamp: i64,
+    locale: Locale,
+    render_style: RenderStyle,
+    prompt_length: PromptLength,
 }
 
 impl FileWriter {
@@ -26,9 +30,32 @@ impl FileWriter {
             output_dir: output_dir.into(),
             screen_name: screen_name.into(),
             timestamp,
+            locale: Locale::default(),
+            render_style: RenderStyle::default(),
+            prompt_length: PromptLength::default(),
         }
     }
 
+    /// Sets the language of the individual profile files (other files stay in English)
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Sets whether individual profile files are structured statistics or a narrative paragraph
+    ///
+    /// Narrative profiles are English only; `write_all_files` rejects them with any other locale.
+    pub fn with_render_style(mut self, render_style: RenderStyle) -> Self {
+        self.render_style = render_style;
+        self
+    }
+
+    /// Sets how much context goes into the LLM analysis prompts file
+    pub fn with_prompt_length(mut self, prompt_length: PromptLength) -> Self {
+        self.prompt_length = prompt_length;
+        self
+    }
+
     /// Creates the relationship profiles directory structure
     pub async fn create_directory_structure(&self) -> Result<String> {
         let profiles_dir = format!("{}/relationship_profiles_{}_{}", 
@@ -41,12 +68,18 @@ impl FileWriter {
     }
 
     /// Writes all relationship intelligence files
+    ///
+    /// Fails before writing anything when narrative profiles are requested in a
+    /// locale other than en-US, since the narrative paragraph is English only.
     pub async fn write_all_files(&self, profiles: &HashMap<String, UserProfile>, timeline: &[InteractionEvent]) -> Result<()> {
+        if self.render_style == RenderStyle::Narrative && self.locale != Locale::EnUs {
+            bail!("Narrative profiles are only available in en-US, not {:?}", self.locale);
+        }
         let profiles_dir = self.create_directory_structure().await?;
         
         self.write_user_profiles(&profiles_dir, profiles, timeline).await?;
         self.write_timeline_file(&profiles_dir, timeline).await?;
-        self.write_prompts_file(&profiles_dir, profiles).await?;
+        self.write_prompts_file(&profiles_dir, profiles, timeline).await?;
         self.write_summary_file(&profiles_dir, profiles, timeline).await?;
         
         println!("✅ LLM-ready relationship intelligence files generated in: {}", profiles_dir);
@@ -57,12 +90,12 @@ impl FileWriter {
     async fn write_user_profiles(&self, profiles_dir: &str, profiles: &HashMap<String, UserProfile>, timeline: &[InteractionEvent]) -> Result<()> {
         for (user_hash, profile) in profiles {
             let user_timeline: Vec<_> = timeline.iter()
-                .filter(|event| event.user_hash == *user_hash)
+                .filter(|event| event.user_id == profile.user_id)
                 .cloned()
                 .collect();
             
-            let profile_text = generate_user_profile_text(profile, &user_timeline);
-            let profile_file = format!("{}/user_{}_profile.txt", profiles_dir, &user_hash[..16]);
+            let profile_text = generate_user_profile_text(profile, &user_timeline, self.locale, self.render_style);
+            let profile_file = format!("{}/user_{}_profile.txt", profiles_dir, truncate_to_utf8_boundary(user_hash, 16));
             
             async_fs::write(&profile_file, profile_text).await
                 .with_context(|| format!("Failed to write profile file: {}", profile_file))?;
@@ -79,8 +112,8 @@ impl FileWriter {
     }
 
     /// Writes LLM analysis prompts file
-    async fn write_prompts_file(&self, profiles_dir: &str, profiles: &HashMap<String, UserProfile>) -> Result<()> {
-        let prompts_text = generate_llm_analysis_prompts(profiles);
+    async fn write_prompts_file(&self, profiles_dir: &str, profiles: &HashMap<String, UserProfile>, timeline: &[InteractionEvent]) -> Result<()> {
+        let prompts_text = generate_llm_analysis_prompts(profiles, timeline, self.prompt_length);
         let prompts_file = format!("{}/llm_analysis_prompts.txt", profiles_dir);
         async_fs::write(&prompts_file, prompts_text).await
             .with_context(|| format!("Failed to write prompts file: {}", prompts_file))?;
@@ -135,11 +168,11 @@ impl FileWriter {
         output.push_str("=======================================\n");
         
         let mut sorted_profiles: Vec<_> = profiles.iter().collect();
-        sorted_profiles.sort_by(|a, b| b.1.total_interactions.cmp(&a.1.total_interactions));
+        sorted_profiles.sort_by_key(|p| std::cmp::Reverse(p.1.total_interactions));
         
         for (i, (user_hash, profile)) in sorted_profiles.iter().take(10).enumerate() {
             output.push_str(&format!("{}. {} - {} interactions\n", 
-                                   i + 1, &user_hash[..16], profile.total_interactions));
+                                   i + 1, truncate_to_utf8_boundary(user_hash, 16), profile.total_interactions));
         }
         
         output.push('\n');
@@ -214,4 +247,32 @@ mod tests {
         let result = writer.write_all_files(&profiles, &timeline).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_narrative_profiles_reject_other_locales() {
+        let temp_dir = tempdir().unwrap();
+        let writer = FileWriter::new(temp_dir.path().to_str().unwrap(), "testuser", 1234567890)
+            .with_locale(Locale::FrFr)
+            .with_render_style(RenderStyle::Narrative);
+        
+        let mut profiles = HashMap::new();
+        profiles.insert("test_user_hash_123456".to_string(), create_test_profile());
+        assert!(writer.write_all_files(&profiles, &create_test_timeline()).await.is_err());
+        assert!(!temp_dir.path().join("relationship_profiles_testuser_1234567890").exists());
+    }
+
+    #[tokio::test]
+    async fn test_write_profiles_with_locale() {
+        let temp_dir = tempdir().unwrap();
+        let writer = FileWriter::new(temp_dir.path().to_str().unwrap(), "testuser", 1234567890)
+            .with_locale(Locale::DeDe);
+        
+        let mut profiles = HashMap::new();
+        profiles.insert("test_user_hash_123456".to_string(), create_test_profile());
+        writer.write_all_files(&profiles, &create_test_timeline()).await.unwrap();
+        
+        let profile_text = std::fs::read_to_string(temp_dir.path()
+            .join("relationship_profiles_testuser_1234567890/user_test_user_hash_1_profile.txt")).unwrap();
+        assert!(profile_text.contains("Interaktionen insgesamt: 42"));
+    }
 }
\ No newline at end of file
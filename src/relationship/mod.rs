@@ -14,15 +14,19 @@ pub mod communication;
 pub mod timeline_integration;
 /// File generation for relationship intelligence output
 pub mod file_generation;
+/// Writes relationship profile directories
+pub mod file_writer;
 pub mod text_generators;
 pub mod timeline_text;
 pub mod prompts_generator;
+pub mod topics;
 
 // Re-export commonly used types
-pub use analyzer::RelationshipAnalyzer;
-pub use communication::{CommunicationFrequency, calculate_response_times, calculate_average_response_time};
-pub use timeline_integration::{analyze_hourly_activity, find_most_active_day};
+pub use analyzer::{RelationshipAnalyzer, apply_mutual_contact_counts, compute_mutual_contacts, export_anki_deck, export_anki_deck_with, quarterly_sentiment, find_ghost_contacts, find_tweet_only_contacts, tweet_user_hashes};
+pub use communication::{CommunicationFrequency, StreakReport, SenderFilter, calculate_response_times, calculate_average_response_time};
+pub use timeline_integration::{analyze_hourly_activity, avg_words_per_interaction, find_most_active_day};
 pub use file_generation::LLMFileGenerator;
-pub use text_generators::generate_user_profile_text;
+pub use text_generators::{generate_user_profile_text, generate_narrative_summary, generate_goodbye_letter, Locale, RenderStyle};
 pub use timeline_text::generate_timeline_text;
-pub use prompts_generator::generate_llm_analysis_prompts;
\ No newline at end of file
+pub use prompts_generator::{generate_llm_analysis_prompts, PromptLength};
+pub use topics::{cosine_similarity, enrich_timeline_with_topics, tfidf_topic_words, tfidf_vectors};
\ No newline at end of file
@@ -2,20 +2,239 @@
 //! 
 //! Creates suggested questions for LLM analysis of relationship data.
 
+use chrono::Timelike;
 use std::collections::HashMap;
+use crate::models::interaction::InteractionEvent;
 use crate::models::profile::UserProfile;
+use crate::services::timeline_analyzer::TimelineAnalyzer;
+
+/// Silences between consecutive interactions at least this long count as gap periods
+const GAP_PERIOD_DAYS: i64 = 30;
+
+/// How much context goes into the generated prompts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PromptLength {
+    /// About 500 tokens: the top 3 contacts and peak activity hours
+    Short,
+    /// About 2,000 tokens: the top 10 contacts with their stats and all question sets
+    #[default]
+    Medium,
+    /// About 8,000 tokens: adds timeline analysis, gap periods, sentiment
+    /// trends and suggested conversation topics
+    Detailed,
+}
+
+impl PromptLength {
+    /// Upper bound on the estimated token count of the generated prompts
+    pub fn token_limit(self) -> usize {
+        match self {
+            PromptLength::Short => 500,
+            PromptLength::Medium => 2000,
+            PromptLength::Detailed => 8000,
+        }
+    }
+}
+
+/// Estimates the token count of `text` as `word_count * 1.3`
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.split_whitespace().count() as f64 * 1.3).ceil() as usize
+}
 
 /// Generates LLM analysis prompts for relationship intelligence
-pub fn generate_llm_analysis_prompts(profiles: &HashMap<String, UserProfile>) -> String {
+///
+/// Sections are added in order of importance and the output stops at the
+/// last whole line that keeps [`estimate_tokens`] within `length.token_limit()`.
+pub fn generate_llm_analysis_prompts(
+    profiles: &HashMap<String, UserProfile>,
+    timeline: &[InteractionEvent],
+    length: PromptLength,
+) -> String {
+    let mut sections = Vec::new();
+    
+    let mut header = String::new();
+    add_prompts_header(&mut header);
+    sections.push(header);
+    
+    let top_count = if length == PromptLength::Short { 3 } else { 10 };
+    sections.push(top_contacts_section(profiles, top_count, length != PromptLength::Short));
+    sections.push(peak_hours_section(timeline));
+    
+    let mut questions = String::new();
+    add_relationship_health_prompts(&mut questions);
+    if length != PromptLength::Short {
+        add_communication_optimization_prompts(&mut questions);
+        add_network_insights_prompts(&mut questions);
+        add_behavioral_patterns_prompts(&mut questions);
+    }
+    sections.push(questions);
+    
+    if length == PromptLength::Detailed {
+        sections.push(timeline_analysis_section(timeline));
+        sections.push(gap_periods_section(timeline));
+        sections.push(sentiment_trends_section(profiles));
+        sections.push(suggested_topics_section(timeline));
+    }
+    
+    let mut context = String::new();
+    add_analysis_context(&mut context, profiles);
+    sections.push(context);
+    
     let mut output = String::new();
+    let limit = length.token_limit();
+    for section in sections {
+        if !append_within_budget(&mut output, &section, limit) {
+            break;
+        }
+    }
+    output
+}
+
+/// Appends `section` line by line while the estimated token count stays within `limit`
+///
+/// Returns `false` if any line had to be left out.
+fn append_within_budget(output: &mut String, section: &str, limit: usize) -> bool {
+    let mut tokens = estimate_tokens(output);
+    for line in section.split_inclusive('\n') {
+        let line_tokens = estimate_tokens(line);
+        if tokens + line_tokens > limit {
+            return false;
+        }
+        tokens += line_tokens;
+        output.push_str(line);
+    }
+    true
+}
+
+/// Lists the `count` most active contacts, optionally with their stats
+fn top_contacts_section(profiles: &HashMap<String, UserProfile>, count: usize, with_stats: bool) -> String {
+    let mut ranked: Vec<(&String, &UserProfile)> = profiles.iter().collect();
+    ranked.sort_by(|a, b| b.1.total_interactions.cmp(&a.1.total_interactions).then_with(|| a.0.cmp(b.0)));
+    
+    let mut output = format!("TOP {} CONTACTS\n", count);
+    output.push_str(&"-".repeat(output.len() - 1));
+    output.push('\n');
+    for (i, (user_hash, profile)) in ranked.into_iter().take(count).enumerate() {
+        output.push_str(&format!("{}. {} - {} interactions", i + 1, user_hash, profile.total_interactions));
+        if with_stats {
+            output.push_str(&format!(" ({}", profile.contact_type));
+            if let (Some(first), Some(last)) = (profile.first_interaction, profile.last_interaction) {
+                output.push_str(&format!(", {} to {}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d")));
+            }
+            output.push_str(&format!(", avg sentiment {:.2})", profile.avg_sentiment));
+        }
+        output.push('\n');
+    }
+    output.push('\n');
+    output
+}
+
+/// Lists the three hours of the day with the most interactions
+fn peak_hours_section(timeline: &[InteractionEvent]) -> String {
+    let mut output = String::from("PEAK ACTIVITY HOURS\n-------------------\n");
+    if timeline.is_empty() {
+        output.push_str("No timeline data available\n\n");
+        return output;
+    }
     
-    add_prompts_header(&mut output);
-    add_relationship_health_prompts(&mut output);
-    add_communication_optimization_prompts(&mut output);
-    add_network_insights_prompts(&mut output);
-    add_behavioral_patterns_prompts(&mut output);
-    add_analysis_context(&mut output, profiles);
+    let mut hour_counts = [0usize; 24];
+    for event in timeline {
+        hour_counts[event.timestamp.hour() as usize] += 1;
+    }
+    let mut hours: Vec<(usize, usize)> = hour_counts.into_iter().enumerate()
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    hours.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (hour, count) in hours.into_iter().take(3) {
+        output.push_str(&format!("- {:02}:00-{:02}:59: {} interactions\n", hour, hour, count));
+    }
+    output.push('\n');
+    output
+}
+
+/// Summarizes the full timeline analysis
+fn timeline_analysis_section(timeline: &[InteractionEvent]) -> String {
+    let mut output = String::from("TIMELINE ANALYSIS\n-----------------\n");
+    if timeline.is_empty() {
+        output.push_str("No timeline data available\n\n");
+        return output;
+    }
     
+    let analysis = TimelineAnalyzer::new(timeline.to_vec()).analyze();
+    output.push_str(&format!("Period: {} to {}\n", analysis.start_time.format("%Y-%m-%d"), analysis.end_time.format("%Y-%m-%d")));
+    output.push_str(&format!("Total interactions: {}\n", analysis.total_interactions));
+    output.push_str(&format!("Unique participants: {}\n", analysis.unique_participants));
+    output.push_str(&format!("Average interactions per day: {:.2}\n", analysis.density.avg_interactions_per_day));
+    output.push_str(&format!("Daily variance: {:.2}\n", analysis.density.daily_variance));
+    output.push_str(&format!("Bursty hours: {}\n", analysis.bursty_periods));
+    output.push_str(&format!("Conversation rhythm: {:?}\n", analysis.conversation_rhythm));
+    output.push_str(&format!("Average response time: {:.0} seconds\n", analysis.response_times.average));
+    output.push_str(&format!("Median response time: {:.0} seconds\n", analysis.response_times.median));
+    for pattern in &analysis.patterns {
+        output.push_str(&format!("Pattern: {:?}\n", pattern));
+    }
+    output.push('\n');
+    output
+}
+
+/// Lists silences of at least [`GAP_PERIOD_DAYS`] between consecutive interactions
+fn gap_periods_section(timeline: &[InteractionEvent]) -> String {
+    let mut output = String::from("GAP PERIODS\n-----------\n");
+    let mut timestamps: Vec<_> = timeline.iter().map(|event| event.timestamp).collect();
+    timestamps.sort();
+    
+    let mut found = false;
+    for pair in timestamps.windows(2) {
+        let days = (pair[1] - pair[0]).num_days();
+        if days >= GAP_PERIOD_DAYS {
+            found = true;
+            output.push_str(&format!("- {} to {} ({} days without interaction)\n",
+                                     pair[0].format("%Y-%m-%d"), pair[1].format("%Y-%m-%d"), days));
+        }
+    }
+    if !found {
+        output.push_str(&format!("No gaps of {} days or more\n", GAP_PERIOD_DAYS));
+    }
+    output.push('\n');
+    output
+}
+
+/// Lists each contact's sentiment trend, most active contacts first
+fn sentiment_trends_section(profiles: &HashMap<String, UserProfile>) -> String {
+    let mut ranked: Vec<(&String, &UserProfile)> = profiles.iter().collect();
+    ranked.sort_by(|a, b| b.1.total_interactions.cmp(&a.1.total_interactions).then_with(|| a.0.cmp(b.0)));
+    
+    let mut output = String::from("SENTIMENT TRENDS\n----------------\n");
+    for (user_hash, profile) in ranked {
+        output.push_str(&format!("- {}: {} (avg {:.2})\n", user_hash, profile.sentiment_trend, profile.avg_sentiment));
+    }
+    output.push('\n');
+    output
+}
+
+/// Suggests topics from the words that come up most often in the timeline
+fn suggested_topics_section(timeline: &[InteractionEvent]) -> String {
+    const COMMON_WORDS: [&str; 12] = ["about", "after", "again", "could", "their", "there", "these", "thing", "think", "where", "which", "would"];
+    
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for event in timeline {
+        for word in event.content.split(|c: char| !c.is_alphanumeric()) {
+            let word = word.to_lowercase();
+            if word.chars().count() >= 5 && !COMMON_WORDS.contains(&word.as_str()) {
+                *counts.entry(word).or_insert(0) += 1;
+            }
+        }
+    }
+    let mut topics: Vec<(String, usize)> = counts.into_iter().filter(|(_, count)| *count > 1).collect();
+    topics.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    
+    let mut output = String::from("SUGGESTED CONVERSATION TOPICS\n-----------------------------\n");
+    if topics.is_empty() {
+        output.push_str("Not enough message text to suggest topics\n");
+    }
+    for (word, count) in topics.into_iter().take(10) {
+        output.push_str(&format!("- {} (mentioned {} times)\n", word, count));
+    }
+    output.push('\n');
     output
 }
 
@@ -86,7 +305,28 @@ fn add_analysis_context(output: &mut String, profiles: &HashMap<String, UserProf
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::interaction::InteractionType;
     use crate::models::profile::UserProfile;
+    use chrono::{Duration, TimeZone, Utc};
+    
+    fn sample_profiles(count: usize) -> HashMap<String, UserProfile> {
+        (0..count)
+            .map(|i| {
+                let mut profile = UserProfile::new(format!("user{:02}", i));
+                profile.total_interactions = (i as u32 + 1) * 10;
+                (format!("hash{:02}", i), profile)
+            })
+            .collect()
+    }
+    
+    fn sample_timeline() -> Vec<InteractionEvent> {
+        let start = Utc.with_ymd_and_hms(2023, 1, 2, 9, 0, 0).unwrap();
+        let mut timeline: Vec<InteractionEvent> = (0..6)
+            .map(|i| InteractionEvent::new(format!("e{}", i), start + Duration::hours(i), InteractionType::DmSent, "user01", "Planning the hiking trip this weekend"))
+            .collect();
+        timeline.push(InteractionEvent::new("late", start + Duration::days(60), InteractionType::DmReceived, "user02", "Back from travels"));
+        timeline
+    }
 
     #[test]
     fn test_generate_llm_analysis_prompts() {
@@ -95,7 +335,7 @@ mod tests {
         profile.total_interactions = 42;
         profiles.insert("user1".to_string(), profile);
         
-        let prompts = generate_llm_analysis_prompts(&profiles);
+        let prompts = generate_llm_analysis_prompts(&profiles, &[], PromptLength::Medium);
         
         // Verify essential prompts are present
         assert!(prompts.contains("Which relationships need more attention?"));
@@ -120,9 +360,49 @@ mod tests {
         profile2.total_interactions = 100;
         profiles.insert("user2".to_string(), profile2);
         
-        let prompts = generate_llm_analysis_prompts(&profiles);
+        let prompts = generate_llm_analysis_prompts(&profiles, &[], PromptLength::Medium);
         
         assert!(prompts.contains("Total relationships analyzed: 2"));
         assert!(prompts.contains("Most active relationship: 100 interactions"));
     }
+
+    #[test]
+    fn test_prompt_lengths() {
+        let profiles = sample_profiles(15);
+        let timeline = sample_timeline();
+        
+        let short = generate_llm_analysis_prompts(&profiles, &timeline, PromptLength::Short);
+        assert!(short.contains("TOP 3 CONTACTS"));
+        assert!(short.contains("3. hash12 - 130 interactions\n"));
+        assert!(!short.contains("4. hash11"));
+        assert!(short.contains("- 09:00-09:59: 2 interactions"));
+        assert!(!short.contains("NETWORK INSIGHTS"));
+        
+        let medium = generate_llm_analysis_prompts(&profiles, &timeline, PromptLength::Medium);
+        assert!(medium.contains("TOP 10 CONTACTS"));
+        assert!(medium.contains("10. hash05 - 60 interactions (Minimal"));
+        assert!(medium.contains("NETWORK INSIGHTS"));
+        assert!(!medium.contains("GAP PERIODS"));
+        
+        let detailed = generate_llm_analysis_prompts(&profiles, &timeline, PromptLength::Detailed);
+        assert!(detailed.contains("TIMELINE ANALYSIS"));
+        assert!(detailed.contains("2023-01-02 to 2023-03-03 (59 days without interaction)"));
+        assert!(detailed.contains("- hash14: Stable"));
+        assert!(detailed.contains("- hiking (mentioned 6 times)"));
+        
+        for (text, length) in [(short, PromptLength::Short), (medium, PromptLength::Medium), (detailed, PromptLength::Detailed)] {
+            assert!(estimate_tokens(&text) <= length.token_limit());
+        }
+    }
+
+    #[test]
+    fn test_prompts_stop_at_token_limit() {
+        let profiles = sample_profiles(2000);
+        
+        let detailed = generate_llm_analysis_prompts(&profiles, &[], PromptLength::Detailed);
+        
+        assert!(estimate_tokens(&detailed) <= PromptLength::Detailed.token_limit());
+        assert!(detailed.ends_with('\n'));
+        assert!(!detailed.contains("ANALYSIS CONTEXT"));
+    }
 }
\ No newline at end of file
@@ -5,68 +5,387 @@
 use chrono::{Datelike, Timelike};
 use std::collections::HashMap;
 
-use crate::models::profile::UserProfile;
-use crate::models::interaction::InteractionEvent;
+use crate::models::profile::{ContactType, UserProfile, CONVERSATION_COUNT_KEY, TOP_TOPIC_KEY};
+use crate::models::interaction::{InteractionEvent, InteractionType};
+use crate::models::timeline::ConversationRhythm;
+use crate::services::timeline_analyzer::TimelineAnalyzer;
+use crate::utils::humanize_duration;
+use super::anonymization::{hash_user_id, PseudonymRegistry};
+use super::communication::CommunicationFrequency;
+
+/// Language used for the fixed text of generated profiles
+///
+/// Only headers, labels and fixed phrases are translated; message content,
+/// interaction type keys and dates are written as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    /// English (United States)
+    #[default]
+    EnUs,
+    /// Spanish (Spain)
+    EsEs,
+    /// French (France)
+    FrFr,
+    /// German (Germany)
+    DeDe,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "en" | "en-us" => Ok(Locale::EnUs),
+            "es" | "es-es" => Ok(Locale::EsEs),
+            "fr" | "fr-fr" => Ok(Locale::FrFr),
+            "de" | "de-de" => Ok(Locale::DeDe),
+            other => anyhow::bail!("Unknown locale: {} (expected en-US, es-ES, fr-FR or de-DE)", other),
+        }
+    }
+}
+
+/// Layout of the generated profile text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderStyle {
+    /// Labelled sections of statistics, translated into the profile locale
+    #[default]
+    Structured,
+    /// A short summary paragraph in plain English, like a LinkedIn "About" blurb
+    Narrative,
+}
+
+/// Fixed phrases that appear in profile text
+#[derive(Debug, Clone, Copy)]
+enum Phrase {
+    ProfileHeader,
+    UserId,
+    ContactType,
+    FirstInteraction,
+    LastInteraction,
+    TotalInteractionDays,
+    CommunicationStatistics,
+    TotalInteractions,
+    InteractionTypes,
+    TemporalPatterns,
+    NoTimelineData,
+    MostActiveHour,
+    MostActiveDay,
+    TotalTimelineEvents,
+    RelationshipInsights,
+    RelationshipStrength,
+    Interactions,
+    CommunicationBalance,
+    You,
+    Them,
+    InteractionConsistency,
+    PerDay,
+    Minimal,
+    Low,
+    Medium,
+    High,
+    VeryHigh,
+    YouInitiated,
+    TheyInitiated,
+    Balanced,
+    DmHeavy,
+    TweetHeavy,
+    Mixed,
+    ConversationRhythm,
+    DailyChat,
+    WeeklyCheckin,
+    MonthlyUpdate,
+    Sporadic,
+    Intensive,
+    AverageMessageLength,
+    Characters,
+    LongestDailyStreak,
+    CurrentDailyStreak,
+    Days,
+}
+
+impl Phrase {
+    /// Looks up the phrase in the given locale
+    fn text(self, locale: Locale) -> &'static str {
+        let [en_us, es_es, fr_fr, de_de] = match self {
+            Phrase::ProfileHeader => ["USER RELATIONSHIP PROFILE", "PERFIL DE RELACIÓN DEL USUARIO", "PROFIL DE RELATION DE L'UTILISATEUR", "BEZIEHUNGSPROFIL DES BENUTZERS"],
+            Phrase::UserId => ["User ID", "ID de usuario", "ID utilisateur", "Benutzer-ID"],
+            Phrase::ContactType => ["Contact Type", "Tipo de contacto", "Type de contact", "Kontakttyp"],
+            Phrase::FirstInteraction => ["First Interaction", "Primera interacción", "Première interaction", "Erste Interaktion"],
+            Phrase::LastInteraction => ["Last Interaction", "Última interacción", "Dernière interaction", "Letzte Interaktion"],
+            Phrase::TotalInteractionDays => ["Total Interaction Days", "Días totales de interacción", "Nombre total de jours d'interaction", "Interaktionstage insgesamt"],
+            Phrase::CommunicationStatistics => ["COMMUNICATION STATISTICS", "ESTADÍSTICAS DE COMUNICACIÓN", "STATISTIQUES DE COMMUNICATION", "KOMMUNIKATIONSSTATISTIK"],
+            Phrase::TotalInteractions => ["Total Interactions", "Interacciones totales", "Nombre total d'interactions", "Interaktionen insgesamt"],
+            Phrase::InteractionTypes => ["Interaction Types", "Tipos de interacción", "Types d'interactions", "Interaktionsarten"],
+            Phrase::TemporalPatterns => ["TEMPORAL PATTERNS", "PATRONES TEMPORALES", "TENDANCES TEMPORELLES", "ZEITLICHE MUSTER"],
+            Phrase::NoTimelineData => ["No timeline data available", "No hay datos de cronología disponibles", "Aucune donnée chronologique disponible", "Keine Zeitleistendaten verfügbar"],
+            Phrase::MostActiveHour => ["Most active hour", "Hora más activa", "Heure la plus active", "Aktivste Stunde"],
+            Phrase::MostActiveDay => ["Most active day", "Día más activo", "Jour le plus actif", "Aktivster Tag"],
+            Phrase::TotalTimelineEvents => ["Total timeline events", "Eventos totales en la cronología", "Nombre total d'événements chronologiques", "Ereignisse in der Zeitleiste insgesamt"],
+            Phrase::RelationshipInsights => ["RELATIONSHIP INSIGHTS", "ANÁLISIS DE LA RELACIÓN", "ANALYSE DE LA RELATION", "BEZIEHUNGSEINBLICKE"],
+            Phrase::RelationshipStrength => ["Relationship strength", "Fuerza de la relación", "Force de la relation", "Beziehungsstärke"],
+            Phrase::Interactions => ["interactions", "interacciones", "interactions", "Interaktionen"],
+            Phrase::CommunicationBalance => ["Communication balance", "Equilibrio de la comunicación", "Équilibre de la communication", "Kommunikationsbalance"],
+            Phrase::You => ["you", "tú", "vous", "du"],
+            Phrase::Them => ["them", "ellos", "eux", "sie"],
+            Phrase::InteractionConsistency => ["Interaction consistency", "Constancia de las interacciones", "Régularité des interactions", "Interaktionskonstanz"],
+            Phrase::PerDay => ["per day", "por día", "par jour", "pro Tag"],
+            Phrase::Minimal => ["Minimal", "Mínima", "Minimale", "Minimal"],
+            Phrase::Low => ["Low", "Baja", "Faible", "Niedrig"],
+            Phrase::Medium => ["Medium", "Media", "Moyenne", "Mittel"],
+            Phrase::High => ["High", "Alta", "Élevée", "Hoch"],
+            Phrase::VeryHigh => ["Very High", "Muy alta", "Très élevée", "Sehr hoch"],
+            Phrase::YouInitiated => ["You-initiated", "Iniciada por ti", "Initiée par vous", "Von dir initiiert"],
+            Phrase::TheyInitiated => ["They-initiated", "Iniciada por ellos", "Initiée par eux", "Von ihnen initiiert"],
+            Phrase::Balanced => ["Balanced", "Equilibrada", "Équilibrée", "Ausgewogen"],
+            Phrase::DmHeavy => ["DM-heavy", "Principalmente MD", "Surtout des MP", "Überwiegend DMs"],
+            Phrase::TweetHeavy => ["Tweet-heavy", "Principalmente tweets", "Surtout des tweets", "Überwiegend Tweets"],
+            Phrase::Mixed => ["Mixed", "Mixto", "Mixte", "Gemischt"],
+            Phrase::ConversationRhythm => ["Conversation rhythm", "Ritmo de conversación", "Rythme de conversation", "Gesprächsrhythmus"],
+            Phrase::DailyChat => ["Daily chat", "Charla diaria", "Discussion quotidienne", "Täglicher Austausch"],
+            Phrase::WeeklyCheckin => ["Weekly check-in", "Contacto semanal", "Point hebdomadaire", "Wöchentlicher Kontakt"],
+            Phrase::MonthlyUpdate => ["Monthly update", "Actualización mensual", "Nouvelles mensuelles", "Monatliches Update"],
+            Phrase::Sporadic => ["Sporadic", "Esporádico", "Sporadique", "Sporadisch"],
+            Phrase::Intensive => ["Intensive", "Intensivo", "Intensif", "Intensiv"],
+            Phrase::AverageMessageLength => ["Average message length", "Longitud media de los mensajes", "Longueur moyenne des messages", "Durchschnittliche Nachrichtenlänge"],
+            Phrase::Characters => ["characters", "caracteres", "caractères", "Zeichen"],
+            Phrase::LongestDailyStreak => ["Longest daily messaging streak", "Racha diaria de mensajes más larga", "Plus longue série de jours de messages", "Längste tägliche Nachrichtenserie"],
+            Phrase::CurrentDailyStreak => ["Current daily messaging streak", "Racha diaria de mensajes actual", "Série actuelle de jours de messages", "Aktuelle tägliche Nachrichtenserie"],
+            Phrase::Days => ["days", "días", "jours", "Tage"],
+        };
+        match locale {
+            Locale::EnUs => en_us,
+            Locale::EsEs => es_es,
+            Locale::FrFr => fr_fr,
+            Locale::DeDe => de_de,
+        }
+    }
+}
+
+/// Translated name of a contact type
+fn contact_type_text(contact_type: ContactType, locale: Locale) -> &'static str {
+    match contact_type {
+        ContactType::DmHeavy => Phrase::DmHeavy.text(locale),
+        ContactType::TweetHeavy => Phrase::TweetHeavy.text(locale),
+        ContactType::Mixed => Phrase::Mixed.text(locale),
+        ContactType::Minimal => Phrase::Minimal.text(locale),
+    }
+}
+
+/// Translated name of a conversation rhythm
+fn conversation_rhythm_text(rhythm: ConversationRhythm, locale: Locale) -> &'static str {
+    match rhythm {
+        ConversationRhythm::DailyChat => Phrase::DailyChat.text(locale),
+        ConversationRhythm::WeeklyCheckin => Phrase::WeeklyCheckin.text(locale),
+        ConversationRhythm::MonthlyUpdate => Phrase::MonthlyUpdate.text(locale),
+        ConversationRhythm::Sporadic => Phrase::Sporadic.text(locale),
+        ConversationRhythm::Intensive => Phrase::Intensive.text(locale),
+    }
+}
+
+/// Short weekday name in the given locale
+fn weekday_text(weekday: chrono::Weekday, locale: Locale) -> &'static str {
+    let names = match locale {
+        Locale::EnUs => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+        Locale::EsEs => ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+        Locale::FrFr => ["lun", "mar", "mer", "jeu", "ven", "sam", "dim"],
+        Locale::DeDe => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    };
+    names[weekday.num_days_from_monday() as usize]
+}
+
+/// Appends a section header with an underline of matching length
+fn push_section_header(output: &mut String, header: &str) {
+    output.push_str(header);
+    output.push('\n');
+    output.push_str(&"=".repeat(header.chars().count()));
+    output.push('\n');
+}
 
 /// Generates user profile text optimized for LLM analysis
-pub fn generate_user_profile_text(profile: &UserProfile, timeline: &[InteractionEvent]) -> String {
+///
+/// In [`RenderStyle::Structured`], section headers, labels and fixed phrases are
+/// written in `locale`; message content is not translated. [`RenderStyle::Narrative`]
+/// writes an English summary paragraph (see [`generate_narrative_summary`]) and
+/// ignores `locale`; `FileWriter` refuses that combination for other locales.
+pub fn generate_user_profile_text(profile: &UserProfile, timeline: &[InteractionEvent], locale: Locale, style: RenderStyle) -> String {
     let mut output = String::new();
     
+    if style == RenderStyle::Narrative {
+        push_section_header(&mut output, Phrase::ProfileHeader.text(Locale::EnUs));
+        output.push_str(&format!("{}: {}\n\n", Phrase::UserId.text(Locale::EnUs), profile.user_id));
+        output.push_str(&generate_narrative_summary(timeline));
+        output.push('\n');
+        return output;
+    }
+    
     // Header section
-    output.push_str("USER RELATIONSHIP PROFILE\n");
-    output.push_str("========================\n");
-    output.push_str(&format!("User ID: {}\n", profile.user_id));
+    push_section_header(&mut output, Phrase::ProfileHeader.text(locale));
+    output.push_str(&format!("{}: {}\n", Phrase::UserId.text(locale), profile.user_id));
+    output.push_str(&format!("{}: {}\n", Phrase::ContactType.text(locale), contact_type_text(profile.contact_type, locale)));
     
-    add_temporal_info(&mut output, profile);
-    add_communication_stats(&mut output, profile);
-    add_temporal_patterns(&mut output, timeline);
-    add_relationship_insights(&mut output, profile);
+    add_temporal_info(&mut output, profile, locale);
+    add_communication_stats(&mut output, profile, timeline, locale);
+    add_temporal_patterns(&mut output, timeline, locale);
+    add_relationship_insights(&mut output, profile, locale);
     
     output
 }
 
+/// Summarises the DMs in a contact's timeline as one natural-language paragraph
+///
+/// Covers the message count and first date, the busiest month and how quickly
+/// you usually answer their messages. Sentences without data are left out.
+pub fn generate_narrative_summary(timeline: &[InteractionEvent]) -> String {
+    let mut messages: Vec<&InteractionEvent> = timeline.iter()
+        .filter(|event| matches!(event.interaction_type, InteractionType::DmSent | InteractionType::DmReceived))
+        .collect();
+    messages.sort_by_key(|event| event.timestamp);
+
+    let Some(first) = messages.first() else {
+        return "You and this contact haven't exchanged any messages yet.".to_string();
+    };
+
+    let mut sentences = vec![format!(
+        "You and this contact have exchanged {} since {}.",
+        pluralize(messages.len(), "message", "messages"),
+        first.timestamp.format("%B %-d, %Y"),
+    )];
+
+    let mut per_month: HashMap<(i32, u32), usize> = HashMap::new();
+    for event in &messages {
+        *per_month.entry((event.timestamp.year(), event.timestamp.month())).or_insert(0) += 1;
+    }
+    // Earliest month wins a tie
+    if let Some(((year, month), count)) = per_month.into_iter().max_by_key(|(key, count)| (*count, std::cmp::Reverse(*key))) {
+        if let Some(peak) = chrono::NaiveDate::from_ymd_opt(year, month, 1) {
+            sentences.push(format!(
+                "Your peak communication was in {} ({}).",
+                peak.format("%B %Y"),
+                pluralize(count, "message", "messages"),
+            ));
+        }
+    }
+
+    let response_times: Vec<chrono::Duration> = messages.windows(2)
+        .filter(|pair| pair[0].interaction_type == InteractionType::DmReceived && pair[1].interaction_type == InteractionType::DmSent)
+        .map(|pair| pair[1].timestamp - pair[0].timestamp)
+        .collect();
+    if !response_times.is_empty() {
+        let total_secs: i64 = response_times.iter().map(|d| d.num_seconds()).sum();
+        let average = chrono::Duration::seconds(total_secs / response_times.len() as i64);
+        sentences.push(format!("You typically respond within {}.", humanize_duration(average)));
+    }
+
+    sentences.join(" ")
+}
+
+/// Closing sentences of [`generate_goodbye_letter`], from most negative to most positive average sentiment
+const GOODBYE_SENTIMENT_SENTENCES: [&str; 5] = [
+    "We argued more than we agreed, and I learned from every round of it",
+    "Not every conversation was easy, but I'm glad we kept talking",
+    "We kept things steady and honest, and that counted for a lot",
+    "You made my inbox a warmer place",
+    "Every message from you felt like good news",
+];
+
+/// Drafts a fictional farewell letter from the archive owner to a contact
+///
+/// Fills a fixed template with the contact's pseudonym from `reg`, first
+/// interaction date, DM and conversation counts, most frequent topic and
+/// busiest month in `freq`, closing with a sentence picked by average
+/// sentiment. The text is marked `[GENERATED FICTION]` since none of it was
+/// actually written by the owner.
+pub fn generate_goodbye_letter(profile: &UserProfile, freq: &CommunicationFrequency, reg: &PseudonymRegistry) -> String {
+    let pseudonym = reg.get(&hash_user_id(&profile.user_id)).unwrap_or("friend");
+    let first_date = profile.first_interaction
+        .map(|first| first.format("%B %-d, %Y").to_string())
+        .unwrap_or_else(|| "a day I can no longer place".to_string());
+    let conversations: usize = profile.metadata.get(CONVERSATION_COUNT_KEY)
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0);
+    let top_topic = profile.metadata.get(TOP_TOPIC_KEY).map(String::as_str).unwrap_or("so many things");
+
+    let mut per_month: HashMap<(i32, u32), u32> = freq.sent_per_month.clone();
+    for (month, count) in &freq.received_per_month {
+        *per_month.entry(*month).or_insert(0) += count;
+    }
+    // Earliest month wins a tie
+    let peak_month = per_month.into_iter()
+        .max_by_key(|&(key, count)| (count, std::cmp::Reverse(key)))
+        .and_then(|((year, month), _)| chrono::NaiveDate::from_ymd_opt(year, month, 1))
+        .map(|peak| peak.format("%B %Y").to_string())
+        .unwrap_or_else(|| "a time I only half remember".to_string());
+
+    let bucket = (((profile.avg_sentiment.clamp(-1.0, 1.0) + 1.0) / 2.0) * GOODBYE_SENTIMENT_SENTENCES.len() as f64) as usize;
+    let sentiment_sentence = GOODBYE_SENTIMENT_SENTENCES[bucket.min(GOODBYE_SENTIMENT_SENTENCES.len() - 1)];
+
+    format!(
+        "[GENERATED FICTION]\n\nDear {}, I remember when we first spoke on {}. Over {} across {}, you shaped my thinking on {}. Our most active period was {}. {}.\n",
+        pseudonym,
+        first_date,
+        pluralize(profile.total_interactions as usize, "message", "messages"),
+        pluralize(conversations, "conversation", "conversations"),
+        top_topic,
+        peak_month,
+        sentiment_sentence,
+    )
+}
+
+/// `"1 message"` or `"3 messages"`
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
 /// Adds temporal information to profile text
-fn add_temporal_info(output: &mut String, profile: &UserProfile) {
+fn add_temporal_info(output: &mut String, profile: &UserProfile, locale: Locale) {
     if let Some(first) = profile.first_interaction {
-        output.push_str(&format!("First Interaction: {}\n", first.format("%Y-%m-%d")));
+        output.push_str(&format!("{}: {}\n", Phrase::FirstInteraction.text(locale), first.format("%Y-%m-%d")));
     }
     
     if let Some(last) = profile.last_interaction {
-        output.push_str(&format!("Last Interaction: {}\n", last.format("%Y-%m-%d")));
+        output.push_str(&format!("{}: {}\n", Phrase::LastInteraction.text(locale), last.format("%Y-%m-%d")));
     }
     
     if let (Some(first), Some(last)) = (profile.first_interaction, profile.last_interaction) {
         let days = (last - first).num_days();
-        output.push_str(&format!("Total Interaction Days: {}\n", days));
+        output.push_str(&format!("{}: {}\n", Phrase::TotalInteractionDays.text(locale), days));
     }
     
     output.push('\n');
 }
 
 /// Adds communication statistics to profile text
-fn add_communication_stats(output: &mut String, profile: &UserProfile) {
-    output.push_str("COMMUNICATION STATISTICS\n");
-    output.push_str("========================\n");
-    output.push_str(&format!("Total Interactions: {}\n", profile.total_interactions));
+fn add_communication_stats(output: &mut String, profile: &UserProfile, timeline: &[InteractionEvent], locale: Locale) {
+    push_section_header(output, Phrase::CommunicationStatistics.text(locale));
+    output.push_str(&format!("{}: {}\n", Phrase::TotalInteractions.text(locale), profile.total_interactions));
     
     if !profile.interaction_counts.is_empty() {
-        output.push_str("Interaction Types:\n");
+        output.push_str(&format!("{}:\n", Phrase::InteractionTypes.text(locale)));
         for (interaction_type, count) in &profile.interaction_counts {
             let percentage = calculate_percentage(*count, profile.total_interactions);
             output.push_str(&format!("- {}: {} ({:.1}%)\n", interaction_type, count, percentage));
         }
     }
     
+    if let Some(average) = average_dm_length(timeline) {
+        output.push_str(&format!("{}: {:.1} {}\n", Phrase::AverageMessageLength.text(locale), average, Phrase::Characters.text(locale)));
+    }
+    
+    let (media_files, media_conversations) = count_shared_media(timeline);
+    if media_files > 0 {
+        output.push_str(&media_shared_text(media_files, media_conversations, locale));
+        output.push('\n');
+    }
+    
     output.push('\n');
 }
 
 /// Adds temporal patterns analysis to profile text
-fn add_temporal_patterns(output: &mut String, timeline: &[InteractionEvent]) {
-    output.push_str("TEMPORAL PATTERNS\n");
-    output.push_str("================\n");
+fn add_temporal_patterns(output: &mut String, timeline: &[InteractionEvent], locale: Locale) {
+    push_section_header(output, Phrase::TemporalPatterns.text(locale));
     
     if timeline.is_empty() {
-        output.push_str("No timeline data available\n\n");
+        output.push_str(&format!("{}\n\n", Phrase::NoTimelineData.text(locale)));
         return;
     }
     
@@ -74,27 +393,83 @@ fn add_temporal_patterns(output: &mut String, timeline: &[InteractionEvent]) {
     
     if let Some((most_active_hour, _)) = hourly_activity.iter().enumerate()
         .max_by_key(|(_, &count)| count) {
-        output.push_str(&format!("Most active hour: {}:00-{}:59\n", most_active_hour, most_active_hour));
+        output.push_str(&format!("{}: {}:00-{}:59\n", Phrase::MostActiveHour.text(locale), most_active_hour, most_active_hour));
     }
     
     if let Some((most_active_day, _)) = daily_activity.iter()
         .max_by_key(|(_, &count)| count) {
-        output.push_str(&format!("Most active day: {:?}\n", most_active_day));
+        output.push_str(&format!("{}: {}\n", Phrase::MostActiveDay.text(locale), weekday_text(*most_active_day, locale)));
     }
     
-    output.push_str(&format!("Total timeline events: {}\n\n", timeline.len()));
+    output.push_str(&format!("{}: {}\n", Phrase::TotalTimelineEvents.text(locale), timeline.len()));
+    
+    let streaks = CommunicationFrequency::from_timeline(timeline).streak_analysis();
+    if let Some(last_active) = streaks.last_active_date {
+        output.push_str(&format!("{}: {} {}\n", Phrase::LongestDailyStreak.text(locale), streaks.longest_streak_days, Phrase::Days.text(locale)));
+        output.push_str(&format!("{}: {} {} ({})\n", Phrase::CurrentDailyStreak.text(locale), streaks.current_streak_days,
+                                 Phrase::Days.text(locale), last_active.format("%Y-%m-%d")));
+    }
+    
+    let rhythm = TimelineAnalyzer::new(timeline.to_vec()).analyze().conversation_rhythm;
+    output.push_str(&format!("{}: {}\n\n", Phrase::ConversationRhythm.text(locale), conversation_rhythm_text(rhythm, locale)));
 }
 
 /// Adds relationship insights to profile text
-fn add_relationship_insights(output: &mut String, profile: &UserProfile) {
-    output.push_str("RELATIONSHIP INSIGHTS\n");
-    output.push_str("====================\n");
+fn add_relationship_insights(output: &mut String, profile: &UserProfile, locale: Locale) {
+    push_section_header(output, Phrase::RelationshipInsights.text(locale));
     
     let strength = calculate_relationship_strength(profile.total_interactions);
-    output.push_str(&format!("- Relationship strength: {} ({} interactions)\n", strength, profile.total_interactions));
+    output.push_str(&format!("- {}: {} ({} {})\n", Phrase::RelationshipStrength.text(locale), strength.text(locale),
+                             profile.total_interactions, Phrase::Interactions.text(locale)));
     
-    add_communication_balance(output, profile);
-    add_interaction_consistency(output, profile);
+    add_communication_balance(output, profile, locale);
+    add_interaction_consistency(output, profile, locale);
+}
+
+/// Mean length in characters of the DMs in the timeline (`None` if there are none)
+fn average_dm_length(timeline: &[InteractionEvent]) -> Option<f64> {
+    let lengths: Vec<usize> = timeline.iter()
+        .filter(|event| matches!(event.interaction_type, InteractionType::DmSent | InteractionType::DmReceived))
+        .map(|event| event.content.chars().count())
+        .collect();
+    if lengths.is_empty() {
+        return None;
+    }
+    Some(lengths.iter().sum::<usize>() as f64 / lengths.len() as f64)
+}
+
+/// Media files attached to DMs in the timeline and the number of conversations they were in
+///
+/// Reads the `media_count` and `conversation_id` metadata set by
+/// [`InteractionEvent::from_dm_message`].
+fn count_shared_media(timeline: &[InteractionEvent]) -> (usize, usize) {
+    let mut files = 0;
+    let mut conversations = std::collections::HashSet::new();
+    for event in timeline {
+        let count: usize = event.metadata.get("media_count").and_then(|c| c.parse().ok()).unwrap_or(0);
+        if count > 0 {
+            files += count;
+            conversations.insert(event.metadata.get("conversation_id").map(String::as_str).unwrap_or(event.id.as_str()));
+        }
+    }
+    (files, conversations.len())
+}
+
+/// "Shared N media files across M conversations" with plurals in the given locale
+fn media_shared_text(files: usize, conversations: usize, locale: Locale) -> String {
+    let one_file = files == 1;
+    let one_conversation = conversations == 1;
+    match locale {
+        Locale::EnUs => format!("Shared {} {} across {} {}", files, if one_file { "media file" } else { "media files" },
+                                conversations, if one_conversation { "conversation" } else { "conversations" }),
+        Locale::EsEs => format!("{} {} {} en {} {}", if one_file { "Se compartió" } else { "Se compartieron" }, files,
+                                if one_file { "archivo multimedia" } else { "archivos multimedia" },
+                                conversations, if one_conversation { "conversación" } else { "conversaciones" }),
+        Locale::FrFr => format!("{} {} dans {} {}", files, if one_file { "fichier média partagé" } else { "fichiers médias partagés" },
+                                conversations, if one_conversation { "conversation" } else { "conversations" }),
+        Locale::DeDe => format!("{} {} in {} {} geteilt", files, if one_file { "Mediendatei" } else { "Mediendateien" },
+                                conversations, if one_conversation { "Unterhaltung" } else { "Unterhaltungen" }),
+    }
 }
 
 /// Calculates percentage with zero division protection
@@ -125,17 +500,27 @@ fn analyze_activity_patterns(timeline: &[InteractionEvent]) -> (Vec<u32>, HashMa
 }
 
 /// Calculates relationship strength based on interaction count
-fn calculate_relationship_strength(interactions: u32) -> &'static str {
+fn calculate_relationship_strength(interactions: u32) -> Phrase {
     match interactions {
-        0..=5 => "Minimal",
-        6..=20 => "Low", 
-        21..=100 => "Medium",
-        _ => "High",
+        0..=5 => Phrase::Minimal,
+        6..=20 => Phrase::Low, 
+        21..=100 => Phrase::Medium,
+        _ => Phrase::High,
+    }
+}
+
+/// English label ("minimal", "low", "medium" or "high") for a contact's interaction count
+pub(crate) fn relationship_strength_label(interactions: u32) -> &'static str {
+    match calculate_relationship_strength(interactions) {
+        Phrase::Minimal => "minimal",
+        Phrase::Low => "low",
+        Phrase::Medium => "medium",
+        _ => "high",
     }
 }
 
 /// Adds communication balance analysis
-fn add_communication_balance(output: &mut String, profile: &UserProfile) {
+fn add_communication_balance(output: &mut String, profile: &UserProfile, locale: Locale) {
     if let (Some(dm_sent), Some(dm_received)) = (
         profile.interaction_counts.get("dm_messages"),
         profile.interaction_counts.get("dm_received")
@@ -144,44 +529,193 @@ fn add_communication_balance(output: &mut String, profile: &UserProfile) {
         if total_dm > 0 {
             let sent_percentage = (*dm_sent as f64 / total_dm as f64) * 100.0;
             let balance = classify_communication_balance(sent_percentage);
-            output.push_str(&format!("- Communication balance: {} ({:.0}% you / {:.0}% them)\n", 
-                                   balance, sent_percentage, 100.0 - sent_percentage));
+            output.push_str(&format!("- {}: {} ({:.0}% {} / {:.0}% {})\n", 
+                                   Phrase::CommunicationBalance.text(locale), balance.text(locale),
+                                   sent_percentage, Phrase::You.text(locale),
+                                   100.0 - sent_percentage, Phrase::Them.text(locale)));
         }
     }
 }
 
 /// Adds interaction consistency analysis
-fn add_interaction_consistency(output: &mut String, profile: &UserProfile) {
+fn add_interaction_consistency(output: &mut String, profile: &UserProfile, locale: Locale) {
     if let (Some(first), Some(last)) = (profile.first_interaction, profile.last_interaction) {
         let days = (last - first).num_days().max(1);
         let interactions_per_day = profile.total_interactions as f64 / days as f64;
         
         let consistency = classify_interaction_consistency(interactions_per_day);
-        output.push_str(&format!("- Interaction consistency: {} ({:.2} per day)\n", 
-                               consistency, interactions_per_day));
+        output.push_str(&format!("- {}: {} ({:.2} {})\n", 
+                               Phrase::InteractionConsistency.text(locale), consistency.text(locale),
+                               interactions_per_day, Phrase::PerDay.text(locale)));
     }
 }
 
 /// Classifies communication balance
-fn classify_communication_balance(sent_percentage: f64) -> &'static str {
+fn classify_communication_balance(sent_percentage: f64) -> Phrase {
     if sent_percentage > 60.0 {
-        "You-initiated"
+        Phrase::YouInitiated
     } else if sent_percentage < 40.0 {
-        "They-initiated"
+        Phrase::TheyInitiated
     } else {
-        "Balanced"
+        Phrase::Balanced
     }
 }
 
 /// Classifies interaction consistency
-fn classify_interaction_consistency(interactions_per_day: f64) -> &'static str {
+fn classify_interaction_consistency(interactions_per_day: f64) -> Phrase {
     if interactions_per_day > 1.0 {
-        "Very High"
+        Phrase::VeryHigh
     } else if interactions_per_day > 0.5 {
-        "High"
+        Phrase::High
     } else if interactions_per_day > 0.1 {
-        "Medium"
+        Phrase::Medium
     } else {
-        "Low"
+        Phrase::Low
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_profile() -> UserProfile {
+        let mut profile = UserProfile::new("user_123");
+        profile.total_interactions = 30;
+        profile.interaction_counts.insert("dm_messages".to_string(), 20);
+        profile.interaction_counts.insert("dm_received".to_string(), 10);
+        profile
+    }
+
+    #[test]
+    fn test_locale_from_str() {
+        assert_eq!("fr-FR".parse::<Locale>().unwrap(), Locale::FrFr);
+        assert_eq!("de".parse::<Locale>().unwrap(), Locale::DeDe);
+        assert_eq!("es_ES".parse::<Locale>().unwrap(), Locale::EsEs);
+        assert!("pt-BR".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_french_profile_text() {
+        let text = generate_user_profile_text(&sample_profile(), &[], Locale::FrFr, RenderStyle::Structured);
+
+        assert!(text.contains("interactions"));
+        assert!(text.starts_with("PROFIL DE RELATION DE L'UTILISATEUR\n"));
+        assert!(text.contains("Force de la relation: Moyenne (30 interactions)"));
+        assert!(text.contains("Initiée par vous"));
+        assert!(!text.contains("Relationship strength"));
+    }
+
+    #[test]
+    fn test_german_and_spanish_profile_text() {
+        let german = generate_user_profile_text(&sample_profile(), &[], Locale::DeDe, RenderStyle::Structured);
+        assert!(german.contains("Interaktionen insgesamt: 30"));
+        assert!(german.contains("Keine Zeitleistendaten verfügbar"));
+
+        let spanish = generate_user_profile_text(&sample_profile(), &[], Locale::EsEs, RenderStyle::Structured);
+        assert!(spanish.contains("Interacciones totales: 30"));
+        // Interaction type keys are not translated
+        assert!(spanish.contains("dm_messages: 20"));
+    }
+
+    #[test]
+    fn test_profile_text_includes_conversation_rhythm() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let start = Utc.with_ymd_and_hms(2023, 1, 2, 9, 0, 0).unwrap();
+        let timeline: Vec<InteractionEvent> = (0..10)
+            .map(|day| InteractionEvent::new(format!("e{}", day), start + Duration::days(day), InteractionType::DmSent, "user_123", "hi"))
+            .collect();
+
+        let text = generate_user_profile_text(&sample_profile(), &timeline, Locale::EnUs, RenderStyle::Structured);
+        assert!(text.contains("Conversation rhythm: Daily chat"));
+        assert!(text.contains("Average message length: 2.0 characters\n"));
+        assert!(text.contains("Longest daily messaging streak: 10 days\n"));
+        assert!(text.contains("Current daily messaging streak: 10 days (2023-01-11)\n"));
+
+        let german = generate_user_profile_text(&sample_profile(), &timeline, Locale::DeDe, RenderStyle::Structured);
+        assert!(german.contains("Gesprächsrhythmus: Täglicher Austausch"));
+    }
+
+    #[test]
+    fn test_narrative_profile_text() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let start = Utc.with_ymd_and_hms(2023, 3, 5, 9, 0, 0).unwrap();
+        let timeline = vec![
+            InteractionEvent::new("1", start, InteractionType::DmReceived, "user_123", "hi"),
+            InteractionEvent::new("2", start + Duration::minutes(118), InteractionType::DmSent, "user_123", "hello"),
+            InteractionEvent::new("3", start + Duration::days(30), InteractionType::DmReceived, "user_123", "again"),
+            InteractionEvent::new("4", start + Duration::days(30) + Duration::minutes(122), InteractionType::DmSent, "user_123", "yes"),
+            InteractionEvent::new("5", start + Duration::days(31), InteractionType::DmSent, "user_123", "bye"),
+            InteractionEvent::new("6", start + Duration::days(31), InteractionType::TweetReply, "user_123", "not a DM"),
+        ];
+
+        let text = generate_user_profile_text(&sample_profile(), &timeline, Locale::FrFr, RenderStyle::Narrative);
+        assert!(text.starts_with("USER RELATIONSHIP PROFILE\n"));
+        assert!(text.contains(
+            "You and this contact have exchanged 5 messages since March 5, 2023. \
+             Your peak communication was in April 2023 (3 messages). \
+             You typically respond within about 2 hours.\n"
+        ));
+        assert!(!text.contains("COMMUNICATION STATISTICS"));
+
+        assert_eq!(
+            generate_narrative_summary(&timeline[..1]),
+            "You and this contact have exchanged 1 message since March 5, 2023. Your peak communication was in March 2023 (1 message)."
+        );
+        assert_eq!(generate_narrative_summary(&[]), "You and this contact haven't exchanged any messages yet.");
+    }
+
+    #[test]
+    fn test_profile_text_mentions_shared_media() {
+        use chrono::{TimeZone, Utc};
+
+        let at = Utc.with_ymd_and_hms(2023, 1, 2, 9, 0, 0).unwrap();
+        let dm = |id: &str, conversation: &str, media: Option<&str>| {
+            let event = InteractionEvent::new(id, at, InteractionType::DmSent, "user_123", "look")
+                .with_metadata("conversation_id", conversation);
+            match media {
+                Some(count) => event.with_metadata("media_count", count),
+                None => event,
+            }
+        };
+        let timeline = vec![dm("1", "a-b", Some("2")), dm("2", "a-b", Some("1")), dm("3", "a-c", Some("1")), dm("4", "a-c", None)];
+
+        let text = generate_user_profile_text(&sample_profile(), &timeline, Locale::EnUs, RenderStyle::Structured);
+        assert!(text.contains("Shared 4 media files across 2 conversations\n"));
+        let single = generate_user_profile_text(&sample_profile(), &timeline[2..], Locale::DeDe, RenderStyle::Structured);
+        assert!(single.contains("1 Mediendatei in 1 Unterhaltung geteilt\n"));
+        let none = generate_user_profile_text(&sample_profile(), &timeline[3..], Locale::EnUs, RenderStyle::Structured);
+        assert!(!none.contains("Shared"));
+    }
+
+    #[test]
+    fn test_generate_goodbye_letter() {
+        use chrono::{TimeZone, Utc};
+        crate::relationship::anonymization::set_test_mode(true);
+
+        let mut profile = sample_profile();
+        profile.first_interaction = Some(Utc.with_ymd_and_hms(2021, 3, 14, 9, 0, 0).unwrap());
+        profile.avg_sentiment = 0.9;
+        profile.metadata.insert(CONVERSATION_COUNT_KEY.to_string(), "2".to_string());
+        profile.metadata.insert(TOP_TOPIC_KEY.to_string(), "rust".to_string());
+        let mut freq = CommunicationFrequency::default();
+        freq.sent_per_month.insert((2021, 3), 4);
+        freq.sent_per_month.insert((2022, 5), 6);
+        freq.received_per_month.insert((2021, 3), 3);
+        let mut reg = PseudonymRegistry::new();
+        reg.assign_all(["user_123"]);
+
+        let letter = generate_goodbye_letter(&profile, &freq, &reg);
+        assert_eq!(letter, format!(
+            "[GENERATED FICTION]\n\nDear {}, I remember when we first spoke on March 14, 2021. Over 30 messages across 2 conversations, \
+             you shaped my thinking on rust. Our most active period was March 2021. Every message from you felt like good news.\n",
+            reg.get("user_123").unwrap(),
+        ));
+
+        let bare = generate_goodbye_letter(&UserProfile::new("someone"), &CommunicationFrequency::default(), &PseudonymRegistry::new());
+        assert!(bare.starts_with("[GENERATED FICTION]\n\nDear friend, I remember when we first spoke on a day I can no longer place."));
+        assert!(bare.contains("Over 0 messages across 0 conversations, you shaped my thinking on so many things."));
+        assert!(bare.contains(GOODBYE_SENTIMENT_SENTENCES[2]));
+    }
+}
@@ -36,6 +36,9 @@ pub fn analyze_hourly_activity(events: &[InteractionEvent]) -> Vec<usize> {
     hourly_counts
 }
 
+/// Number of events at which the most active day is reported with full confidence
+pub const FULL_CONFIDENCE_EVENTS: usize = 700;
+
 /// Find the most active day of the week from interaction events
 /// 
 /// # Arguments
@@ -44,7 +47,9 @@ pub fn analyze_hourly_activity(events: &[InteractionEvent]) -> Vec<usize> {
 /// 
 /// # Returns
 /// 
-/// The most active day of the week, or None if no events
+/// The most active day of the week and a confidence in `[0, 1]`, or None if no events.
+/// Confidence is `0.0` with fewer than 7 events (one per weekday) and otherwise
+/// `events / 700`, reaching `1.0` at 700 or more events.
 /// 
 /// # Examples
 /// 
@@ -54,7 +59,7 @@ pub fn analyze_hourly_activity(events: &[InteractionEvent]) -> Vec<usize> {
 /// let events = vec![]; // Your interaction events
 /// let most_active = find_most_active_day(&events);
 /// ```
-pub fn find_most_active_day(events: &[InteractionEvent]) -> Option<Weekday> {
+pub fn find_most_active_day(events: &[InteractionEvent]) -> Option<(Weekday, f64)> {
     let mut day_counts = HashMap::new();
     
     for event in events {
@@ -62,9 +67,15 @@ pub fn find_most_active_day(events: &[InteractionEvent]) -> Option<Weekday> {
         *day_counts.entry(weekday).or_insert(0) += 1;
     }
     
+    let confidence = if events.len() < 7 {
+        0.0
+    } else {
+        (events.len() as f64 / FULL_CONFIDENCE_EVENTS as f64).min(1.0)
+    };
+    
     day_counts.into_iter()
         .max_by_key(|(_, count)| *count)
-        .map(|(day, _)| day)
+        .map(|(day, _)| (day, confidence))
 }
 
 /// Calculate activity distribution across days of the week
@@ -116,6 +127,51 @@ pub fn find_peak_activity_hours(events: &[InteractionEvent]) -> Vec<u32> {
         .collect()
 }
 
+/// Find the longest run of consecutive calendar days (UTC) with at least one interaction
+/// 
+/// # Arguments
+/// 
+/// * `events` - A slice of InteractionEvent objects
+/// 
+/// # Returns
+/// 
+/// The length of the longest streak in days, or 0 if there are no events
+pub fn longest_streak_days(events: &[InteractionEvent]) -> u32 {
+    let mut days: Vec<_> = events.iter().map(|e| e.timestamp.date_naive()).collect();
+    days.sort();
+    days.dedup();
+    
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous = None;
+    for day in days {
+        current = match previous {
+            Some(prev) if day.signed_duration_since(prev).num_days() == 1 => current + 1,
+            _ => 1,
+        };
+        longest = longest.max(current);
+        previous = Some(day);
+    }
+    
+    longest
+}
+
+/// Calculate the mean word count of interaction events
+/// 
+/// # Arguments
+/// 
+/// * `events` - A slice of InteractionEvent objects
+/// 
+/// # Returns
+/// 
+/// The average `word_count` per event, or 0.0 if there are no events
+pub fn avg_words_per_interaction(events: &[InteractionEvent]) -> f64 {
+    if events.is_empty() {
+        return 0.0;
+    }
+    events.iter().map(|e| e.word_count).sum::<usize>() as f64 / events.len() as f64
+}
+
 /// Calculate interaction density over time periods
 /// 
 /// # Arguments
@@ -218,7 +274,17 @@ mod tests {
         ];
         
         let most_active = find_most_active_day(&events);
-        assert_eq!(most_active, Some(Weekday::Tue)); // Tuesday has 3 events
+        assert_eq!(most_active, Some((Weekday::Tue, 0.0))); // Tuesday has 3 events
+    }
+
+    #[test]
+    fn test_find_most_active_day_confidence() {
+        let events: Vec<_> = (0..70).map(|i| create_test_event(10, i % 7)).collect();
+        let (_, confidence) = find_most_active_day(&events).unwrap();
+        assert!((confidence - 0.1).abs() < 1e-9);
+        
+        let events: Vec<_> = (0..1400).map(|i| create_test_event(10, i % 7)).collect();
+        assert_eq!(find_most_active_day(&events).unwrap().1, 1.0);
     }
 
     #[test]
@@ -307,4 +373,31 @@ mod tests {
         let distribution = calculate_weekly_distribution(&events);
         assert_eq!(distribution.get(&Weekday::Sun), Some(&2)); // Both events on Sunday
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_longest_streak_days() {
+        assert_eq!(longest_streak_days(&[]), 0);
+        
+        let events = vec![
+            create_test_event(10, 0),
+            create_test_event(12, 0), // same day counts once
+            create_test_event(10, 1),
+            create_test_event(10, 5),
+            create_test_event(10, 6),
+            create_test_event(10, 7),
+        ];
+        
+        assert_eq!(longest_streak_days(&events), 3);
+    }
+
+    #[test]
+    fn test_avg_words_per_interaction() {
+        assert_eq!(avg_words_per_interaction(&[]), 0.0);
+
+        let mut events = vec![create_test_event(9, 0), create_test_event(10, 0)];
+        events[1] = InteractionEvent::new("long", events[1].timestamp, InteractionType::DmSent, "user1", "  four words  right here\n");
+        assert_eq!(events[0].word_count, 2);
+        assert_eq!(events[1].word_count, 4);
+        assert_eq!(avg_words_per_interaction(&events), 3.0);
+    }
+}
@@ -5,6 +5,10 @@
 use std::collections::HashMap;
 use chrono::Datelike;
 use crate::models::interaction::InteractionEvent;
+use super::timeline_integration::{avg_words_per_interaction, find_most_active_day};
+
+/// Confidence below which the most active day is flagged as unreliable
+const LOW_CONFIDENCE_THRESHOLD: f64 = 0.5;
 
 /// Generates interaction timeline text for LLM analysis
 pub fn generate_timeline_text(timeline: &[InteractionEvent]) -> String {
@@ -27,6 +31,15 @@ fn add_timeline_header(output: &mut String, timeline: &[InteractionEvent]) {
         output.push_str(&format!("Time Range: {} to {}\n", 
                                first.timestamp.format("%Y-%m-%d"), 
                                last.timestamp.format("%Y-%m-%d")));
+        output.push_str(&format!("Average Words per Event: {:.1}\n", avg_words_per_interaction(timeline)));
+    }
+    
+    if let Some((day, confidence)) = find_most_active_day(timeline) {
+        output.push_str(&format!("Most Active Day: {}", day));
+        if confidence < LOW_CONFIDENCE_THRESHOLD {
+            output.push_str(&format!(" (low confidence: only {} events)", timeline.len()));
+        }
+        output.push('\n');
     }
     
     output.push('\n');
@@ -61,11 +74,15 @@ fn add_recent_activity(output: &mut String, timeline: &[InteractionEvent]) {
     
     for event in timeline.iter().take(20) {
         let content_preview = event.content.chars().take(50).collect::<String>();
-        output.push_str(&format!("{} | {:?} | User: {} | {}\n",
+        output.push_str(&format!("{} | {:?} | User: {} | {}",
                                event.timestamp.format("%Y-%m-%d %H:%M"),
                                event.interaction_type,
-                               &event.user_id[..8],
+                               event.user_id.get(..8).unwrap_or(&event.user_id),
                                content_preview));
+        if !event.topics.is_empty() {
+            output.push_str(&format!(" | Topics: {}", event.topics.join(", ")));
+        }
+        output.push('\n');
     }
 }
 
@@ -77,7 +94,7 @@ fn build_monthly_summary(timeline: &[InteractionEvent]) -> HashMap<String, (u32,
         let month_key = format!("{}-{:02}", event.timestamp.year(), event.timestamp.month());
         let entry = monthly_summary.entry(month_key).or_insert((0, HashMap::new()));
         entry.0 += 1;
-        *entry.1.entry(event.interaction_type).or_insert(0) += 1;
+        *entry.1.entry(event.interaction_type.clone()).or_insert(0) += 1;
     }
     
     monthly_summary
@@ -117,9 +134,20 @@ mod tests {
         assert!(timeline_text.contains("MONTHLY ACTIVITY SUMMARY"));
         assert!(timeline_text.contains("RECENT ACTIVITY"));
         assert!(timeline_text.contains("Total Events: 2"));
+        assert!(timeline_text.contains("Average Words per Event: 4.5\n"));
         assert!(timeline_text.contains("2023-06"));
     }
 
+    #[test]
+    fn test_timeline_text_shows_topics() {
+        let mut timeline = create_test_timeline();
+        timeline[0].topics = vec!["hiking".to_string(), "weekend".to_string()];
+        let timeline_text = generate_timeline_text(&timeline);
+
+        assert!(timeline_text.contains("| Test message content | Topics: hiking, weekend\n"));
+        assert!(timeline_text.contains("| Reply message content (including author replies)\n"));
+    }
+
     #[test]
     fn test_build_monthly_summary() {
         let timeline = create_test_timeline();
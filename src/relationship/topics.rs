@@ -0,0 +1,160 @@
+//! TF-IDF topic words for interaction events
+//!
+//! Scores each word of an event's text by how often it appears there against
+//! how many other events use it, so the words that set an event apart rise to
+//! the top.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::models::interaction::InteractionEvent;
+
+/// Number of topic words stored on each event by [`enrich_timeline_with_topics`]
+pub const TOPICS_PER_EVENT: usize = 3;
+
+/// Words shorter than this are never topics
+const MIN_TOPIC_WORD_CHARS: usize = 3;
+
+/// Filler words and link fragments that carry no topic
+const STOP_WORDS: [&str; 40] = [
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was", "one",
+    "our", "out", "has", "him", "his", "how", "its", "let", "she", "too", "use", "that", "with",
+    "have", "this", "will", "your", "from", "they", "been", "were", "what", "when", "just", "http", "https",
+];
+
+/// Lowercase words of `text` that can be topics
+fn topic_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TOPIC_WORD_CHARS && !word.chars().all(|c| c.is_numeric()))
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+}
+
+/// How many documents of a corpus contain each word
+struct DocumentFrequencies {
+    documents: usize,
+    counts: HashMap<String, usize>,
+}
+
+impl DocumentFrequencies {
+    fn new(corpus: &[&str]) -> Self {
+        let mut counts = HashMap::new();
+        for text in corpus {
+            for word in topic_words(text).collect::<HashSet<_>>() {
+                *counts.entry(word).or_insert(0) += 1;
+            }
+        }
+        Self { documents: corpus.len(), counts }
+    }
+
+    /// Smoothed inverse document frequency; never zero, so unique documents still get topics
+    fn idf(&self, word: &str) -> f64 {
+        let frequency = self.counts.get(word).copied().unwrap_or(0);
+        ((1 + self.documents) as f64 / (1 + frequency) as f64).ln() + 1.0
+    }
+
+    /// TF-IDF score of every topic word of `document`
+    fn scores(&self, document: &str) -> HashMap<String, f64> {
+        let mut term_counts: HashMap<String, usize> = HashMap::new();
+        for word in topic_words(document) {
+            *term_counts.entry(word).or_insert(0) += 1;
+        }
+        let total: usize = term_counts.values().sum();
+
+        term_counts.into_iter()
+            .map(|(word, count)| {
+                let score = count as f64 / total as f64 * self.idf(&word);
+                (word, score)
+            })
+            .collect()
+    }
+
+    /// The `top_n` highest-scoring words of `document`, ties broken alphabetically
+    fn top_words(&self, document: &str, top_n: usize) -> Vec<String> {
+        let mut scored: Vec<(String, f64)> = self.scores(document).into_iter().collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored.into_iter().take(top_n).map(|(word, _)| word).collect()
+    }
+}
+
+/// The `top_n` words of `document` with the highest TF-IDF score against `corpus`
+///
+/// Words are lowercased; short words, numbers and common filler words are skipped.
+pub fn tfidf_topic_words(document: &str, corpus: &[&str], top_n: usize) -> Vec<String> {
+    DocumentFrequencies::new(corpus).top_words(document, top_n)
+}
+
+/// TF-IDF vector of each document, scored against all of `documents`
+///
+/// Documents without any topic words get an empty vector.
+pub fn tfidf_vectors(documents: &[&str]) -> Vec<HashMap<String, f64>> {
+    let frequencies = DocumentFrequencies::new(documents);
+    documents.iter().map(|document| frequencies.scores(document)).collect()
+}
+
+/// Cosine similarity of two TF-IDF vectors; 0.0 when either is empty
+pub fn cosine_similarity(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(word, score)| b.get(word).map(|other| score * other)).sum();
+    let norm = |vector: &HashMap<String, f64>| vector.values().map(|score| score * score).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+/// Sets each event's `topics` to its top [`TOPICS_PER_EVENT`] TF-IDF words
+///
+/// Each event's `content` is scored against `all_texts`, normally the content
+/// of every event in the timeline.
+pub fn enrich_timeline_with_topics(events: &mut [InteractionEvent], all_texts: &[&str]) {
+    let frequencies = DocumentFrequencies::new(all_texts);
+    for event in events {
+        event.topics = frequencies.top_words(&event.content, TOPICS_PER_EVENT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::interaction::InteractionType;
+    use chrono::Utc;
+
+    #[test]
+    fn test_tfidf_topic_words_prefers_distinctive_words() {
+        let corpus = [
+            "Going hiking this weekend in the mountains",
+            "Weekend plans: brunch and a movie",
+            "The weekend was great",
+        ];
+
+        let topics = tfidf_topic_words(corpus[0], &corpus, 3);
+
+        assert_eq!(topics.len(), 3);
+        assert!(!topics.contains(&"weekend".to_string()));
+        assert!(topics.contains(&"hiking".to_string()));
+        assert!(tfidf_topic_words("the and 2023", &corpus, 3).is_empty());
+    }
+
+    #[test]
+    fn test_enrich_timeline_with_topics() {
+        let mut events = vec![
+            InteractionEvent::new("1", Utc::now(), InteractionType::DmSent, "u", "pizza pizza tonight"),
+            InteractionEvent::new("2", Utc::now(), InteractionType::DmReceived, "u", "tonight works, bring guitar"),
+        ];
+        let texts: Vec<String> = events.iter().map(|event| event.content.clone()).collect();
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+
+        enrich_timeline_with_topics(&mut events, &texts);
+
+        assert_eq!(events[0].topics, vec!["pizza", "tonight"]);
+        assert_eq!(events[1].topics, vec!["bring", "guitar", "works"]);
+    }
+
+    #[test]
+    fn test_cosine_similarity_of_tfidf_vectors() {
+        let vectors = tfidf_vectors(&["pizza pasta dinner", "pizza pasta tonight", "guitar concert tickets", "the and"]);
+
+        assert!((cosine_similarity(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-9);
+        assert!(cosine_similarity(&vectors[0], &vectors[1]) > 0.3);
+        assert_eq!(cosine_similarity(&vectors[0], &vectors[2]), 0.0);
+        assert!(vectors[3].is_empty());
+        assert_eq!(cosine_similarity(&vectors[0], &vectors[3]), 0.0);
+    }
+}
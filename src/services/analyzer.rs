@@ -146,6 +146,10 @@ mod tests {
             metadata: [
                 ("conversation_id".to_string(), "conv1".to_string()),
             ].into_iter().collect(),
+            sentiment_score: 0.0,
+            duration_since_prev: None,
+            topics: Vec::new(),
+            word_count: 2,
         }
     }
 
@@ -1,19 +1,24 @@
 //! Timeline analysis service for the Tweet-Scrolls application
 
-use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
 use std::collections::{HashMap, HashSet};
 
 
 #[allow(unused_imports)]
 use crate::models::interaction::{InteractionEvent, InteractionType};
 use crate::models::statistics::calculate_percentiles;
+use crate::relationship::timeline_integration::avg_words_per_interaction;
 use crate::models::timeline::{
-    ResponseTimeStats, TimelineAnalysis, TimelineDensity, TimelinePattern,
+    ConversationRhythm, ResponseTimeStats, TimelineAnalysis, TimelineDensity, TimelinePattern,
 };
 
+/// Default window length for bursty period detection
+pub const DEFAULT_BURST_WINDOW_HOURS: i64 = 1;
+
 /// Analyzes a timeline of interaction events and extracts patterns and statistics
 pub struct TimelineAnalyzer {
     events: Vec<InteractionEvent>,
+    burst_window: Duration,
 }
 
 impl TimelineAnalyzer {
@@ -21,8 +26,16 @@ impl TimelineAnalyzer {
     pub fn new(events: Vec<InteractionEvent>) -> Self {
         // Ensure events are sorted by timestamp
         let mut events = events;
-        events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-        TimelineAnalyzer { events }
+        events.sort_by_key(|a| a.timestamp);
+        TimelineAnalyzer { events, burst_window: Duration::hours(DEFAULT_BURST_WINDOW_HOURS) }
+    }
+
+    /// Sets the window length used to count bursty periods (ignored if not positive)
+    pub fn with_burst_window(mut self, window: Duration) -> Self {
+        if window > Duration::zero() {
+            self.burst_window = window;
+        }
+        self
     }
 
     /// Performs timeline analysis and returns the results
@@ -50,9 +63,52 @@ impl TimelineAnalyzer {
         // Calculate response times
         analysis.response_times = self.calculate_response_times();
         
+        analysis.bursty_periods = self.count_bursty_periods();
+        analysis.avg_words_per_event = avg_words_per_interaction(&self.events);
+        
+        analysis.conversation_rhythm = ConversationRhythm::classify(
+            analysis.density.avg_interactions_per_day,
+            analysis.response_times.average,
+            analysis.total_interactions,
+        );
+        
         analysis
     }
 
+    /// Buckets events into consecutive windows from the first event and
+    /// returns the non-empty window counts with the mean and variance over
+    /// all windows in the span, empty ones included
+    fn window_counts(&self, window: Duration) -> (HashMap<i64, usize>, f64, f64) {
+        let (Some(first), Some(last)) = (self.events.first(), self.events.last()) else {
+            return (HashMap::new(), 0.0, 0.0);
+        };
+        let window_secs = window.num_seconds().max(1);
+        let bucket = |timestamp: DateTime<Utc>| (timestamp - first.timestamp).num_seconds() / window_secs;
+
+        let mut counts: HashMap<i64, usize> = HashMap::new();
+        for event in &self.events {
+            *counts.entry(bucket(event.timestamp)).or_insert(0) += 1;
+        }
+
+        let total_windows = (bucket(last.timestamp) + 1) as f64;
+        let mean = self.events.len() as f64 / total_windows;
+        let empty_windows = total_windows - counts.len() as f64;
+        let variance = (counts.values().map(|&c| (c as f64 - mean).powi(2)).sum::<f64>()
+            + empty_windows * mean * mean) / total_windows;
+        (counts, mean, variance)
+    }
+
+    /// Counts windows whose interaction count is more than two standard
+    /// deviations above the mean count per window
+    ///
+    /// Interaction arrivals are treated like a Poisson process, so a window
+    /// this far above the mean is unlikely to be ordinary variation.
+    fn count_bursty_periods(&self) -> usize {
+        let (counts, mean, variance) = self.window_counts(self.burst_window);
+        let threshold = mean + 2.0 * variance.sqrt();
+        counts.values().filter(|&&count| count as f64 > threshold).count()
+    }
+
     /// Calculates the number of unique participants in the timeline
     fn calculate_unique_participants(&self) -> usize {
         let mut participants = HashSet::new();
@@ -232,8 +288,11 @@ impl TimelineAnalyzer {
             })
             .collect();
         
+        let (_, _, daily_variance) = self.window_counts(Duration::days(1));
+        
         TimelineDensity {
             avg_interactions_per_day: self.events.len() as f64 / total_days,
+            daily_variance,
             peak_hours,
             peak_days,
             peak_hour: peak_hour as u32,
@@ -241,7 +300,9 @@ impl TimelineAnalyzer {
         }
     }
 
-    /// Calculates response time statistics
+    /// Calculates response time statistics (average, median and p50-p99
+    /// percentiles) from gaps between consecutive cross-sender messages in
+    /// each conversation
     fn calculate_response_times(&self) -> ResponseTimeStats {
         let mut response_times = Vec::new();
         
@@ -266,8 +327,11 @@ impl TimelineAnalyzer {
             let mut sorted_events = events;
             sorted_events.sort_by_key(|e| e.timestamp);
             
-            // Calculate time between consecutive messages
+            // Calculate time between consecutive messages from different senders
             for window in sorted_events.windows(2) {
+                if window[1].user_id == window[0].user_id {
+                    continue;
+                }
                 let duration = (window[1].timestamp - window[0].timestamp)
                     .num_seconds() as f64;
                 response_times.push(duration);
@@ -337,6 +401,10 @@ mod tests {
             user_id: user_id.to_string(),
             content: "Test message".to_string(),
             metadata,
+            sentiment_score: 0.0,
+            duration_since_prev: None,
+            topics: Vec::new(),
+            word_count: 2,
         }
     }
 
@@ -365,6 +433,7 @@ mod tests {
         
         assert_eq!(analysis.total_interactions, 5);
         assert_eq!(analysis.unique_participants, 1);
+        assert_eq!(analysis.avg_words_per_event, 2.0);
         assert!(!analysis.patterns.is_empty());
     }
 
@@ -388,4 +457,60 @@ mod tests {
         assert!(!active_hours.contains(&14));
         assert!(!active_hours.contains(&22));
     }
+
+    #[test]
+    fn test_bursty_periods_and_daily_variance() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+        let mut events = Vec::new();
+        
+        // One message a day for three weeks, plus a burst of 10 in one hour
+        for day in 0..21 {
+            events.push(create_test_event(start + Duration::days(day), "user1", "conv1"));
+        }
+        for minute in 0..10 {
+            events.push(create_test_event(start + Duration::days(10) + Duration::minutes(minute * 5 + 1), "user2", "conv1"));
+        }
+        
+        let analysis = TimelineAnalyzer::new(events.clone()).analyze();
+        
+        assert_eq!(analysis.bursty_periods, 1);
+        assert!(analysis.density.daily_variance > 4.0);
+        
+        let per_day = TimelineAnalyzer::new(events).with_burst_window(Duration::days(1)).analyze();
+        assert_eq!(per_day.bursty_periods, 1);
+    }
+
+    #[test]
+    fn test_response_times_skip_same_sender() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 2, 9, 0, 0).unwrap();
+        let events = vec![
+            create_test_event(start, "user1", "conv1"),
+            create_test_event(start + Duration::minutes(1), "user1", "conv1"),
+            create_test_event(start + Duration::minutes(11), "user2", "conv1"),
+            create_test_event(start + Duration::minutes(41), "user1", "conv1"),
+        ];
+        
+        let analysis = TimelineAnalyzer::new(events).analyze();
+        
+        assert_eq!(analysis.response_times.average, 1200.0);
+        assert_eq!(analysis.response_times.median, 1200.0);
+        assert!(analysis.response_times.percentiles.contains_key("p90"));
+    }
+
+    #[test]
+    fn test_conversation_rhythm() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 2, 9, 0, 0).unwrap();
+        
+        // One message a week for ten weeks
+        let weekly: Vec<_> = (0..10)
+            .map(|week| create_test_event(start + Duration::weeks(week), "user1", "conv1"))
+            .collect();
+        assert_eq!(TimelineAnalyzer::new(weekly).analyze().conversation_rhythm, ConversationRhythm::WeeklyCheckin);
+        
+        // 60 quick back-and-forth replies five minutes apart
+        let rapid: Vec<_> = (0..60)
+            .map(|i| create_test_event(start + Duration::minutes(i * 5), if i % 2 == 0 { "user1" } else { "user2" }, "conv1"))
+            .collect();
+        assert_eq!(TimelineAnalyzer::new(rapid).analyze().conversation_rhythm, ConversationRhythm::Intensive);
+    }
 }
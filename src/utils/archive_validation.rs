@@ -0,0 +1,49 @@
+//! Detection of the Twitter archive layout in an archive folder
+
+use std::path::Path;
+
+/// Twitter's help article on requesting a current-format archive
+pub const ARCHIVE_HELP_URL: &str = "https://help.twitter.com/en/managing-your-account/how-to-download-your-twitter-archive";
+
+/// Layout of a downloaded Twitter archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveVersion {
+    /// Pre-2018 layout with one `data/js/tweets/YYYY_MM.js` file per month (not supported)
+    Old,
+    /// Current layout with a single `tweets.js`
+    New,
+    /// Neither layout was found
+    Unknown,
+}
+
+/// Detects which archive layout `archive_dir` uses
+///
+/// A `data/js/tweets/` subdirectory means `Old`; otherwise a root `tweets.js`
+/// means `New`. Anything else is `Unknown`.
+pub fn detect_archive_version(archive_dir: &Path) -> ArchiveVersion {
+    if archive_dir.join("data").join("js").join("tweets").is_dir() {
+        ArchiveVersion::Old
+    } else if archive_dir.join("tweets.js").is_file() {
+        ArchiveVersion::New
+    } else {
+        ArchiveVersion::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_archive_version() {
+        let dir = tempdir().unwrap();
+        assert_eq!(detect_archive_version(dir.path()), ArchiveVersion::Unknown);
+
+        std::fs::write(dir.path().join("tweets.js"), "[]").unwrap();
+        assert_eq!(detect_archive_version(dir.path()), ArchiveVersion::New);
+
+        std::fs::create_dir_all(dir.path().join("data/js/tweets")).unwrap();
+        assert_eq!(detect_archive_version(dir.path()), ArchiveVersion::Old);
+    }
+}
@@ -1,7 +1,11 @@
+use crate::models::statistics::language_label;
 use crate::processing::data_structures::{Tweet, Thread};
+use crate::processing::file_io::CsvWriteOptions;
+use crate::utils::format_twitter_date_in_tz;
 use crate::utils::tweet_classifier::{classify_tweet_type, generate_twitter_url, create_reply_context};
 
 use anyhow::Result;
+use chrono_tz::Tz;
 use csv::WriterBuilder;
 use serde::Serialize;
 use std::fs::File;
@@ -16,7 +20,7 @@ pub struct CsvRecord {
     pub tweet_text: String,
     /// Type of tweet (Original, ReplyToUser, ReplyToOthers)
     pub tweet_type: String,
-    /// Creation timestamp of the tweet
+    /// Creation timestamp of the tweet, in UTC as given by the archive
     pub created_at: String,
     /// Number of favorites/likes on the tweet
     pub favorite_count: String,
@@ -36,10 +40,24 @@ pub struct CsvRecord {
     pub twitter_url: String,
     /// Context about what this tweet is replying to
     pub reply_context: String,
-    /// Language code of the tweet
+    /// Language code of the tweet, as reported by [`language_label`]
     pub lang: String,
     /// Source application used to post the tweet
     pub source: String,
+    /// Canonical URL of the thread this tweet belongs to
+    ///
+    /// Kept after the original columns so their positions don't shift.
+    pub thread_url: String,
+    /// Creation timestamp converted to the writer's output timezone
+    ///
+    /// Kept after the original columns so their positions don't shift.
+    pub created_at_local: String,
+    /// Whether the archive marks the tweet `possibly_sensitive`
+    pub is_sensitive: bool,
+    /// Whether the tweet's thread contains a poll
+    pub contains_poll: bool,
+    /// Whether the tweet's thread was cut short by `--max-thread-length`
+    pub thread_truncated: bool,
 }
 
 impl CsvRecord {
@@ -68,8 +86,13 @@ impl CsvRecord {
             thread_retweet_count: thread.retweet_count,
             twitter_url,
             reply_context,
-            lang: tweet.lang.clone(),
+            lang: language_label(tweet.lang.as_deref()).to_string(),
             source: tweet.source.clone(),
+            thread_url: thread.url.clone(),
+            created_at_local: tweet.created_at.clone(),
+            is_sensitive: tweet.possibly_sensitive == Some(true),
+            contains_poll: thread.contains_poll,
+            thread_truncated: thread.is_truncated(),
         }
     }
 }
@@ -78,6 +101,9 @@ impl CsvRecord {
 pub struct EnhancedCsvWriter {
     output_path: String,
     records: Vec<CsvRecord>,
+    options: CsvWriteOptions,
+    output_tz: Tz,
+    truncated_count: usize,
 }
 
 impl EnhancedCsvWriter {
@@ -86,13 +112,37 @@ impl EnhancedCsvWriter {
         Ok(EnhancedCsvWriter {
             output_path: output_path.to_string(),
             records: Vec::new(),
+            options: CsvWriteOptions::default(),
+            output_tz: chrono_tz::UTC,
+            truncated_count: 0,
         })
     }
 
+    /// Sets the options applied to the `tweet_text` column and the quoting of every field
+    pub fn with_options(mut self, options: CsvWriteOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the timezone used for the `created_at_local` column (UTC by default)
+    pub fn with_output_tz(mut self, output_tz: Tz) -> Self {
+        self.output_tz = output_tz;
+        self
+    }
+
+    /// Number of `tweet_text` cells cut short by `max_text_length` so far
+    pub fn truncated_count(&self) -> usize {
+        self.truncated_count
+    }
+
     /// Write a thread to the CSV buffer
     pub async fn write_thread(&mut self, thread: &Thread, screen_name: &str) -> Result<()> {
         for (position, tweet) in thread.tweets.iter().enumerate() {
-            let record = CsvRecord::from_tweet_and_thread(tweet, thread, screen_name, position + 1);
+            let mut record = CsvRecord::from_tweet_and_thread(tweet, thread, screen_name, position + 1);
+            let (tweet_text, truncated) = self.options.truncate_text(record.tweet_text);
+            record.tweet_text = tweet_text;
+            record.created_at_local = format_twitter_date_in_tz(&tweet.created_at, self.output_tz);
+            self.truncated_count += usize::from(truncated);
             self.records.push(record);
         }
         Ok(())
@@ -101,7 +151,7 @@ impl EnhancedCsvWriter {
     /// Finalize and write all records to the CSV file
     pub async fn finalize(self) -> Result<()> {
         let file = File::create(&self.output_path)?;
-        let mut writer = WriterBuilder::new().has_headers(true).from_writer(file);
+        let mut writer = WriterBuilder::new().has_headers(true).quote_style(self.options.quote_style()).from_writer(file);
 
         for record in self.records {
             writer.serialize(record)?;
@@ -0,0 +1,66 @@
+//! Decision log written in `--explain` mode
+//!
+//! Each line records why one tweet, thread, conversation or contact was kept,
+//! dropped or classified the way it was, e.g. `tweet 5678 → dropped (retweet)`.
+
+use anyhow::{anyhow, Context, Result};
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Buffered `explain_{screen_name}_{timestamp}.log` shared by every processing stage
+#[derive(Debug)]
+pub struct ExplainLog {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl ExplainLog {
+    /// Creates (or truncates) the log file in `output_dir`
+    pub fn create(output_dir: &Path, screen_name: &str, timestamp: i64) -> Result<Self> {
+        let path = output_dir.join(format!("explain_{}_{}.log", screen_name, timestamp));
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create explain log: {}", path.display()))?;
+        Ok(Self { path, writer: Mutex::new(BufWriter::new(file)) })
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `{subject} → {decision}` as one line
+    pub fn record(&self, subject: impl Display, decision: impl Display) -> Result<()> {
+        let mut writer = self.writer.lock().map_err(|_| anyhow!("Explain log lock poisoned"))?;
+        writeln!(writer, "{} → {}", subject, decision)
+            .with_context(|| format!("Failed to write explain log: {}", self.path.display()))
+    }
+
+    /// Writes any buffered lines to disk
+    pub fn flush(&self) -> Result<()> {
+        self.writer.lock().map_err(|_| anyhow!("Explain log lock poisoned"))?
+            .flush()
+            .with_context(|| format!("Failed to write explain log: {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_log_writes_one_line_per_decision() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log = ExplainLog::create(temp_dir.path(), "testuser", 42).unwrap();
+
+        log.record("tweet 1234", "kept (original, no filter match)").unwrap();
+        log.record("tweet 5678", "dropped (retweet)").unwrap();
+        log.flush().unwrap();
+
+        assert_eq!(log.path(), temp_dir.path().join("explain_testuser_42.log"));
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(content, "tweet 1234 → kept (original, no filter match)\ntweet 5678 → dropped (retweet)\n");
+    }
+}
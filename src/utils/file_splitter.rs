@@ -5,10 +5,12 @@
 
 use anyhow::{Context, Result, bail};
 use std::fs::File;
-use std::io::{Read, Write, BufReader, BufWriter};
+use std::io::{BufRead, Write, BufReader, BufWriter};
 use std::path::{Path, PathBuf};
 use std::fmt;
 
+use super::{format_bytes, sanitize_filename};
+
 /// Configuration for file splitting operations
 #[derive(Debug, Clone)]
 pub struct SplitConfig {
@@ -16,22 +18,106 @@ pub struct SplitConfig {
     pub input_path: PathBuf,
     /// Directory where chunks will be written (defaults to input file's directory)
     pub output_dir: Option<PathBuf>,
-    /// Size of each chunk in bytes
-    pub chunk_size: u64,
+    /// How much of the input goes into each chunk
+    pub strategy: ChunkStrategy,
     /// Prefix for chunk filenames (defaults to input filename)
     pub prefix: Option<String>,
     /// Number of digits for chunk numbering (default: 3)
     pub digits: u8,
+    /// Re-read each chunk after writing and compare CRC32 checksums (needs the `verify` feature)
+    pub verify_after_split: bool,
+    /// In `Lines` mode, don't count blank or whitespace-only lines toward the
+    /// per-chunk limit (they are still written)
+    ///
+    /// Chunks then hold `n` non-blank lines each, so their byte sizes vary
+    /// more; `ChunkInfo::size` still reports the exact bytes written. Has no
+    /// effect in `Bytes` mode.
+    pub exclude_empty_lines: bool,
+    /// Refuse to split if it would create more chunk files than this
+    /// (default: [`DEFAULT_MAX_OUTPUT_FILES`]; `None` means no limit)
+    pub max_output_files: Option<usize>,
+    /// In `Bytes` mode, fill the last chunk with zero bytes up to the chunk
+    /// size so every chunk is the same size (for block-based consumers)
+    ///
+    /// The padded chunk's `ChunkInfo::size` is the full chunk size;
+    /// `SplitResult::original_size` keeps the unpadded input size. Has no
+    /// effect in `Lines` mode.
+    pub pad_last_chunk: bool,
 }
 
+/// Default cap on the number of chunk files a single split may create
+pub const DEFAULT_MAX_OUTPUT_FILES: usize = 10_000;
+
 impl Default for SplitConfig {
     fn default() -> Self {
         Self {
             input_path: PathBuf::new(),
             output_dir: None,
-            chunk_size: 1024 * 1024, // 1MB default
+            strategy: ChunkStrategy::default(),
             prefix: None,
             digits: 3,
+            verify_after_split: false,
+            exclude_empty_lines: false,
+            max_output_files: Some(DEFAULT_MAX_OUTPUT_FILES),
+            pad_last_chunk: false,
+        }
+    }
+}
+
+/// How the input is divided into chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkStrategy {
+    /// Each chunk holds this many bytes (the last one may be smaller)
+    Bytes(u64),
+    /// Each chunk holds this many lines (the last one may have fewer),
+    /// so text files are never split mid-line
+    Lines(u64),
+    /// Each chunk starts at a line matching a pattern; only used by
+    /// [`split_by_regex_delimiter`], which takes the pattern
+    Delimiter,
+}
+
+impl Default for ChunkStrategy {
+    fn default() -> Self {
+        ChunkStrategy::Bytes(1024 * 1024) // 1MB default
+    }
+}
+
+impl fmt::Display for ChunkStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkStrategy::Bytes(size) => write!(f, "{}", format_bytes(*size)),
+            ChunkStrategy::Lines(lines) => write!(f, "{} lines", lines),
+            ChunkStrategy::Delimiter => write!(f, "up to the next delimiter line"),
+        }
+    }
+}
+
+/// Errors from splitting that callers may want to match on
+///
+/// Returned inside `anyhow::Error`; use `downcast_ref::<SplitError>()` to inspect.
+#[derive(Debug)]
+pub enum SplitError {
+    /// A chunk could not be read back, or its contents differ from what was written
+    Io(std::io::Error),
+    /// The chunk size would produce an unreasonable number of output files
+    InvalidChunkSize(String),
+}
+
+impl fmt::Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitError::Io(e) => write!(f, "Chunk verification failed: {}", e),
+            SplitError::InvalidChunkSize(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for SplitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SplitError::Io(e) => Some(e),
+            SplitError::InvalidChunkSize(_) => None,
         }
     }
 }
@@ -49,10 +135,10 @@ pub struct ChunkInfo {
 
 impl fmt::Display for ChunkInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Chunk {}: {} ({} bytes)", 
+        write!(f, "Chunk {}: {} ({})", 
                self.number, 
                self.path.display(), 
-               self.size)
+               format_bytes(self.size))
     }
 }
 
@@ -63,34 +149,89 @@ pub struct SplitResult {
     pub input_path: PathBuf,
     /// Output directory used
     pub output_dir: PathBuf,
-    /// Chunk size used
-    pub chunk_size: u64,
+    /// Chunking strategy used
+    pub strategy: ChunkStrategy,
     /// Information about created chunks
     pub chunks: Vec<ChunkInfo>,
-    /// Total size of original file
+    /// Number of chunks actually written; always `chunks.len()`, which may be
+    /// fewer than the size-based estimate if the input ends early
+    pub total_chunks: usize,
+    /// Total bytes written across all chunks, including any padding
     pub total_size: u64,
+    /// Size of the input file; less than `total_size` when the last chunk was
+    /// padded (see [`SplitConfig::pad_last_chunk`])
+    pub original_size: u64,
+}
+
+/// Maximum number of chunk lines shown by `SplitResult`'s `Display` before eliding the middle
+pub const MAX_DISPLAY_CHUNKS: usize = 20;
+
+/// Maximum displayed path length, chosen so lines fit an 80-column terminal
+const MAX_DISPLAY_PATH_LEN: usize = 60;
+
+/// Abbreviate a path to its last characters when it is too long to display
+fn abbreviate_path(path: &Path) -> String {
+    let display = path.display().to_string();
+    let len = display.chars().count();
+    if len <= MAX_DISPLAY_PATH_LEN {
+        return display;
+    }
+    let tail: String = display.chars().skip(len - (MAX_DISPLAY_PATH_LEN - 3)).collect();
+    format!("...{}", tail)
 }
 
 impl fmt::Display for SplitResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "📄 Split '{}' into {} chunks", 
-                 self.input_path.display(), 
-                 self.chunks.len())?;
-        writeln!(f, "📁 Output directory: {}", self.output_dir.display())?;
-        writeln!(f, "📊 Total size: {} bytes", self.total_size)?;
-        writeln!(f, "🔢 Chunk size: {} bytes", self.chunk_size)?;
+                 abbreviate_path(&self.input_path), 
+                 self.total_chunks)?;
+        writeln!(f, "📁 Output directory: {}", abbreviate_path(&self.output_dir))?;
+        writeln!(f, "📊 Total size: {}", format_bytes(self.total_size))?;
+        if self.total_size > self.original_size {
+            writeln!(f, "🧱 Last chunk zero-padded by {} (original size: {})",
+                     format_bytes(self.total_size - self.original_size),
+                     format_bytes(self.original_size))?;
+        }
+        writeln!(f, "🔢 Chunk size: {}", self.strategy)?;
         writeln!(f, "\n📋 Created chunks:")?;
         
-        for chunk in &self.chunks {
-            writeln!(f, "  {}", chunk)?;
+        let write_chunk = |f: &mut fmt::Formatter<'_>, chunk: &ChunkInfo| {
+            writeln!(f, "  Chunk {}: {} ({})", chunk.number, abbreviate_path(&chunk.path), format_bytes(chunk.size))
+        };
+        
+        if self.chunks.len() > MAX_DISPLAY_CHUNKS {
+            let shown = MAX_DISPLAY_CHUNKS / 2;
+            for chunk in &self.chunks[..shown] {
+                write_chunk(f, chunk)?;
+            }
+            writeln!(f, "  ... ({} more chunks) ...", self.chunks.len() - 2 * shown)?;
+            for chunk in &self.chunks[self.chunks.len() - shown..] {
+                write_chunk(f, chunk)?;
+            }
+        } else {
+            for chunk in &self.chunks {
+                write_chunk(f, chunk)?;
+            }
         }
         
         Ok(())
     }
 }
 
+/// Progress callback invoked after each chunk is flushed with
+/// `(bytes_written, total_bytes)`
+pub type ProgressCallback = Box<dyn Fn(u64, u64)>;
+
 /// Split a file into chunks according to the provided configuration
 pub fn split_file(config: &SplitConfig) -> Result<SplitResult> {
+    split_file_with_progress(config, Box::new(|_, _| {}))
+}
+
+/// Split a file like [`split_file`], reporting progress after each chunk
+pub fn split_file_with_progress(config: &SplitConfig, on_progress: ProgressCallback) -> Result<SplitResult> {
+    if config.strategy == ChunkStrategy::Delimiter {
+        bail!("Splitting at delimiter lines needs a pattern; use split_by_regex_delimiter");
+    }
     validate_config(config)?;
     
     let input_path = config.input_path.canonicalize()
@@ -107,21 +248,104 @@ pub fn split_file(config: &SplitConfig) -> Result<SplitResult> {
         bail!("Input file is empty");
     }
     
-    let chunks = create_chunks(&input_path, &output_dir, &base_name, &extension, config)?;
+    if let Some(max) = config.max_output_files {
+        let total_chunks = count_chunks(&input_path, config, file_size)?;
+        if total_chunks > max {
+            return Err(SplitError::InvalidChunkSize(format!(
+                "Would create {total_chunks} chunks; max is {max}. Increase chunk_size or set max_output_files."
+            )).into());
+        }
+    }
+    
+    let chunks = create_chunks(&input_path, &output_dir, &base_name, &extension, config, file_size, &on_progress)?;
+    // The reader may hit EOF before the estimate from the file size, so count what was written
+    let total_chunks = chunks.len();
     
     Ok(SplitResult {
         input_path,
         output_dir,
-        chunk_size: config.chunk_size,
+        strategy: config.strategy,
+        total_size: chunks.iter().map(|chunk| chunk.size).sum(),
         chunks,
+        total_chunks,
+        original_size: file_size,
+    })
+}
+
+/// Split a text file into records, starting a new chunk at every line matching `pattern`
+///
+/// Suits formats whose records open with a header line, such as FASTA (`^>`)
+/// or logs with a timestamp prefix: each chunk begins with its delimiter line.
+/// Lines before the first match form a chunk of their own. `config.strategy`,
+/// `exclude_empty_lines` and `pad_last_chunk` are ignored; the result reports
+/// [`ChunkStrategy::Delimiter`]. Lines are matched without their line ending,
+/// and invalid UTF-8 is matched lossily.
+pub fn split_by_regex_delimiter(config: &SplitConfig, pattern: &str) -> Result<SplitResult> {
+    let delimiter = regex::Regex::new(pattern)
+        .with_context(|| format!("Invalid delimiter pattern: {}", pattern))?;
+    // Neutral strategy so validation only checks the strategy-independent settings
+    let config = SplitConfig { strategy: ChunkStrategy::Lines(1), exclude_empty_lines: false, pad_last_chunk: false, ..config.clone() };
+    validate_config(&config)?;
+    
+    let input_path = config.input_path.canonicalize()
+        .context("Failed to resolve input file path")?;
+    
+    let output_dir = determine_output_dir(&config, &input_path)?;
+    let (base_name, extension) = determine_filename_parts(&config, &input_path);
+    
+    let file_size = input_path.metadata()
+        .context("Failed to read input file metadata")?
+        .len();
+    
+    if file_size == 0 {
+        bail!("Input file is empty");
+    }
+    
+    if let Some(max) = config.max_output_files {
+        let mut total_chunks = 0;
+        for_each_delimited_line(&input_path, &delimiter, |_, starts_chunk| {
+            total_chunks += usize::from(starts_chunk);
+            Ok(())
+        })?;
+        if total_chunks > max {
+            return Err(SplitError::InvalidChunkSize(format!(
+                "Would create {total_chunks} chunks; max is {max}. Use a less frequent delimiter or set max_output_files."
+            )).into());
+        }
+    }
+    
+    let mut chunks = Vec::new();
+    let mut buffer = Vec::new();
+    for_each_delimited_line(&input_path, &delimiter, |line, starts_chunk| {
+        if starts_chunk && !buffer.is_empty() {
+            chunks.push(write_chunk_file(&output_dir, &base_name, &extension, &config, chunks.len() + 1, &buffer)?);
+            buffer.clear();
+        }
+        buffer.extend_from_slice(line);
+        Ok(())
+    })?;
+    if !buffer.is_empty() {
+        chunks.push(write_chunk_file(&output_dir, &base_name, &extension, &config, chunks.len() + 1, &buffer)?);
+    }
+    let total_chunks = chunks.len();
+    
+    Ok(SplitResult {
+        input_path,
+        output_dir,
+        strategy: ChunkStrategy::Delimiter,
+        chunks,
+        total_chunks,
         total_size: file_size,
+        original_size: file_size,
     })
 }
 
 /// Validate the split configuration
 fn validate_config(config: &SplitConfig) -> Result<()> {
-    if config.chunk_size == 0 {
-        bail!("Chunk size must be greater than 0");
+    match config.strategy {
+        ChunkStrategy::Bytes(0) => bail!("Chunk size must be greater than 0"),
+        ChunkStrategy::Lines(0) => bail!("Lines per chunk must be greater than 0"),
+        _ => {}
     }
     
     if config.digits == 0 || config.digits > 10 {
@@ -136,6 +360,18 @@ fn validate_config(config: &SplitConfig) -> Result<()> {
         bail!("Input path is not a file: {}", config.input_path.display());
     }
     
+    if config.verify_after_split && !cfg!(feature = "verify") {
+        bail!("verify_after_split requires the `verify` feature");
+    }
+    
+    if config.exclude_empty_lines && matches!(config.strategy, ChunkStrategy::Bytes(_)) {
+        eprintln!("⚠️  exclude_empty_lines only applies when splitting by lines; ignoring it for byte-sized chunks");
+    }
+    
+    if config.pad_last_chunk && matches!(config.strategy, ChunkStrategy::Lines(_)) {
+        eprintln!("⚠️  pad_last_chunk only applies to byte-sized chunks; ignoring it when splitting by lines");
+    }
+    
     Ok(())
 }
 
@@ -163,7 +399,7 @@ fn determine_output_dir(config: &SplitConfig, input_path: &Path) -> Result<PathB
 /// Determine the base name and extension for chunk files
 fn determine_filename_parts(config: &SplitConfig, input_path: &Path) -> (String, String) {
     match &config.prefix {
-        Some(prefix) => (prefix.clone(), String::new()),
+        Some(prefix) => (sanitize_filename(prefix), String::new()),
         None => {
             let file_name = input_path.file_name()
                 .and_then(|name| name.to_str())
@@ -188,6 +424,8 @@ fn create_chunks(
     base_name: &str,
     extension: &str,
     config: &SplitConfig,
+    total_bytes: u64,
+    on_progress: &ProgressCallback,
 ) -> Result<Vec<ChunkInfo>> {
     let mut input_file = BufReader::new(
         File::open(input_path)
@@ -195,41 +433,32 @@ fn create_chunks(
     );
     
     let mut chunks = Vec::new();
-    let mut buffer = vec![0u8; config.chunk_size as usize];
+    let mut buffer = match config.strategy {
+        ChunkStrategy::Bytes(size) => vec![0u8; size as usize],
+        ChunkStrategy::Lines(_) | ChunkStrategy::Delimiter => Vec::new(),
+    };
     let mut chunk_number = 1;
+    let mut bytes_written = 0u64;
     
     loop {
-        let bytes_read = input_file.read(&mut buffer)
-            .context("Failed to read from input file")?;
+        let bytes_read = read_next_chunk(&mut input_file, config.strategy, config.exclude_empty_lines, &mut buffer)?;
         
         if bytes_read == 0 {
             break; // End of file
         }
         
-        let chunk_path = output_dir.join(format!(
-            "{}-{:0width$}{}",
-            base_name,
-            chunk_number,
-            extension,
-            width = config.digits as usize
-        ));
-        
-        let mut output_file = BufWriter::new(
-            File::create(&chunk_path)
-                .with_context(|| format!("Failed to create chunk file: {}", chunk_path.display()))?
-        );
-        
-        output_file.write_all(&buffer[..bytes_read])
-            .context("Failed to write chunk data")?;
-        
-        output_file.flush()
-            .context("Failed to flush chunk file")?;
+        let mut chunk_len = bytes_read;
+        if let (true, ChunkStrategy::Bytes(size)) = (config.pad_last_chunk, config.strategy) {
+            // Chunks are filled until EOF, so only the final chunk can come up short
+            if bytes_read < size as usize {
+                buffer[bytes_read..].fill(0);
+                chunk_len = size as usize;
+            }
+        }
         
-        chunks.push(ChunkInfo {
-            path: chunk_path,
-            size: bytes_read as u64,
-            number: chunk_number,
-        });
+        chunks.push(write_chunk_file(output_dir, base_name, extension, config, chunk_number, &buffer[..chunk_len])?);
+        bytes_written += bytes_read as u64;
+        on_progress(bytes_written, total_bytes);
         
         chunk_number += 1;
     }
@@ -237,6 +466,181 @@ fn create_chunks(
     Ok(chunks)
 }
 
+/// Calls `on_line(line, starts_chunk)` for every line of the input, line ending included
+///
+/// A line starts a chunk when it matches `delimiter`; the first line always does.
+fn for_each_delimited_line(input_path: &Path, delimiter: &regex::Regex, mut on_line: impl FnMut(&[u8], bool) -> Result<()>) -> Result<()> {
+    let mut input = BufReader::new(
+        File::open(input_path)
+            .with_context(|| format!("Failed to open input file: {}", input_path.display()))?
+    );
+    let mut line = Vec::new();
+    let mut first = true;
+    loop {
+        line.clear();
+        if input.read_until(b'\n', &mut line).context("Failed to read from input file")? == 0 {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&line);
+        let starts_chunk = first || delimiter.is_match(text.trim_end_matches(['\n', '\r']));
+        on_line(&line, starts_chunk)?;
+        first = false;
+    }
+}
+
+/// Writes one chunk file, `{base_name}-{number}{extension}`, verifying it when configured
+fn write_chunk_file(
+    output_dir: &Path,
+    base_name: &str,
+    extension: &str,
+    config: &SplitConfig,
+    chunk_number: usize,
+    data: &[u8],
+) -> Result<ChunkInfo> {
+    let chunk_path = output_dir.join(format!(
+        "{}-{:0width$}{}",
+        base_name,
+        chunk_number,
+        extension,
+        width = config.digits as usize
+    ));
+    
+    let mut output_file = BufWriter::new(
+        File::create(&chunk_path)
+            .with_context(|| format!("Failed to create chunk file: {}", chunk_path.display()))?
+    );
+    
+    output_file.write_all(data)
+        .context("Failed to write chunk data")?;
+    
+    output_file.flush()
+        .context("Failed to flush chunk file")?;
+    drop(output_file);
+    
+    #[cfg(feature = "verify")]
+    if config.verify_after_split {
+        // The chunk stays on disk on failure so it can be inspected
+        let expected_crc = crc32fast::hash(data);
+        match verify_chunk(&chunk_path, expected_crc) {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(SplitError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("checksum mismatch in {}", chunk_path.display()),
+                )).into());
+            }
+            Err(e) => {
+                let io_error = std::io::Error::other(format!("{}: {:#}", chunk_path.display(), e));
+                return Err(SplitError::Io(io_error).into());
+            }
+        }
+    }
+    
+    Ok(ChunkInfo {
+        path: chunk_path,
+        size: data.len() as u64,
+        number: chunk_number,
+    })
+}
+
+/// Number of chunks `config` would split the input into
+///
+/// Computed from the file size in `Bytes` mode; `Lines` mode reads through the
+/// file once, counting lines the same way [`read_next_chunk`] does.
+fn count_chunks(input_path: &Path, config: &SplitConfig, file_size: u64) -> Result<usize> {
+    let total = match config.strategy {
+        ChunkStrategy::Bytes(size) => file_size.div_ceil(size),
+        ChunkStrategy::Delimiter => unreachable!("delimiter splits are counted by split_by_regex_delimiter"),
+        ChunkStrategy::Lines(lines) => {
+            let mut input = BufReader::new(
+                File::open(input_path)
+                    .with_context(|| format!("Failed to open input file: {}", input_path.display()))?
+            );
+            let mut line = Vec::new();
+            let mut counted = 0u64;
+            let mut trailing_blank = false;
+            loop {
+                line.clear();
+                if input.read_until(b'\n', &mut line).context("Failed to read from input file")? == 0 {
+                    break;
+                }
+                if config.exclude_empty_lines && line.iter().all(u8::is_ascii_whitespace) {
+                    trailing_blank = counted.is_multiple_of(lines);
+                } else {
+                    counted += 1;
+                    trailing_blank = false;
+                }
+            }
+            // Blank lines after the last full chunk still get a chunk of their own
+            counted.div_ceil(lines) + u64::from(trailing_blank)
+        }
+    };
+    Ok(usize::try_from(total).unwrap_or(usize::MAX))
+}
+
+/// Reads the next chunk's data into `buffer`, returning its length (0 at end of file)
+///
+/// In `Bytes` mode `buffer` is pre-sized to the chunk size and filled completely
+/// unless the input ends first, even when the reader returns short reads; in `Lines` mode it
+/// is cleared and refilled with up to `n` lines, each keeping its line ending.
+/// With `skip_blank_lines`, blank lines are kept but not counted toward `n`.
+fn read_next_chunk(input: &mut impl BufRead, strategy: ChunkStrategy, skip_blank_lines: bool, buffer: &mut Vec<u8>) -> Result<usize> {
+    match strategy {
+        ChunkStrategy::Bytes(_) => {
+            let mut filled = 0;
+            while filled < buffer.len() {
+                match input.read(&mut buffer[filled..]) {
+                    Ok(0) => break,
+                    Ok(read) => filled += read,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e).context("Failed to read from input file"),
+                }
+            }
+            Ok(filled)
+        }
+        ChunkStrategy::Delimiter => unreachable!("delimiter splits are read by split_by_regex_delimiter"),
+        ChunkStrategy::Lines(lines) => {
+            buffer.clear();
+            let mut counted = 0;
+            while counted < lines {
+                let line_start = buffer.len();
+                let line_len = input.read_until(b'\n', buffer)
+                    .context("Failed to read from input file")?;
+                if line_len == 0 {
+                    break;
+                }
+                if !(skip_blank_lines && buffer[line_start..].iter().all(u8::is_ascii_whitespace)) {
+                    counted += 1;
+                }
+            }
+            Ok(buffer.len())
+        }
+    }
+}
+
+/// Re-reads a chunk from disk and checks its CRC32 against `expected_crc`
+///
+/// Returns `Ok(false)` on a mismatch and `Err` if the file cannot be read.
+#[cfg(feature = "verify")]
+pub fn verify_chunk(path: &Path, expected_crc: u32) -> Result<bool> {
+    use std::io::Read;
+
+    let mut file = BufReader::new(
+        File::open(path).with_context(|| format!("Failed to open chunk file: {}", path.display()))?
+    );
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let bytes_read = file.read(&mut buffer)
+            .with_context(|| format!("Failed to read chunk file: {}", path.display()))?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+    }
+    Ok(hasher.finalize() == expected_crc)
+}
+
 /// Parse a size string like "1M", "500K", "2G" into bytes
 pub fn parse_size_string(size_str: &str) -> Result<u64> {
     let size_str = size_str.trim().to_uppercase();
@@ -282,7 +686,7 @@ mod tests {
     #[test]
     fn test_split_config_default() {
         let config = SplitConfig::default();
-        assert_eq!(config.chunk_size, 1024 * 1024);
+        assert_eq!(config.strategy, ChunkStrategy::Bytes(1024 * 1024));
         assert_eq!(config.digits, 3);
         assert!(config.output_dir.is_none());
         assert!(config.prefix.is_none());
@@ -320,21 +724,75 @@ mod tests {
         
         let config = SplitConfig {
             input_path,
-            chunk_size: 5,
+            strategy: ChunkStrategy::Bytes(5),
             ..Default::default()
         };
         
         let result = split_file(&config)?;
         
         assert_eq!(result.chunks.len(), 3); // "Hello", ", Wor", "ld!"
+        assert_eq!(result.total_chunks, result.chunks.len());
         assert_eq!(result.chunks[0].size, 5);
         assert_eq!(result.chunks[1].size, 5);
         assert_eq!(result.chunks[2].size, 3);
         assert_eq!(result.total_size, 13);
+        assert_eq!(result.original_size, 13);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_split_pads_last_chunk() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "image.bin", b"Hello, World!")?;
+        
+        let config = SplitConfig {
+            input_path,
+            strategy: ChunkStrategy::Bytes(5),
+            pad_last_chunk: true,
+            ..Default::default()
+        };
+        
+        let result = split_file(&config)?;
+        
+        assert_eq!(result.chunks.iter().map(|c| c.size).collect::<Vec<_>>(), vec![5, 5, 5]);
+        assert_eq!(fs::read(&result.chunks[1].path)?, b", Wor");
+        assert_eq!(fs::read(&result.chunks[2].path)?, b"ld!\0\0");
+        assert_eq!(result.total_size, 15);
+        assert_eq!(result.original_size, 13);
+        assert!(result.to_string().contains("🧱 Last chunk zero-padded by 2 B (original size: 13 B)"));
+        
+        // An input that fills its last chunk exactly gets no padding
+        let exact = create_test_file(temp_dir.path(), "exact.bin", b"0123456789")?;
+        let result = split_file(&SplitConfig { input_path: exact, ..config })?;
+        assert_eq!(result.total_size, result.original_size);
+        assert!(!result.to_string().contains("zero-padded"));
         
         Ok(())
     }
     
+    #[test]
+    fn test_split_file_with_progress_reports_each_chunk() -> Result<()> {
+        use std::sync::{Arc, Mutex};
+        
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "test.txt", b"Hello, World!")?;
+        let config = SplitConfig {
+            input_path,
+            strategy: ChunkStrategy::Bytes(5),
+            ..Default::default()
+        };
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&calls);
+        
+        split_file_with_progress(&config, Box::new(move |written, total| {
+            recorded.lock().unwrap().push((written, total));
+        }))?;
+        
+        assert_eq!(*calls.lock().unwrap(), vec![(5, 13), (10, 13), (13, 13)]);
+        Ok(())
+    }
+    
     #[test]
     fn test_split_with_extension_preservation() -> Result<()> {
         let temp_dir = tempdir()?;
@@ -342,7 +800,7 @@ mod tests {
         
         let config = SplitConfig {
             input_path,
-            chunk_size: 4,
+            strategy: ChunkStrategy::Bytes(4),
             ..Default::default()
         };
         
@@ -363,7 +821,7 @@ mod tests {
         
         let config = SplitConfig {
             input_path,
-            chunk_size: 4,
+            strategy: ChunkStrategy::Bytes(4),
             prefix: Some("custom".to_string()),
             ..Default::default()
         };
@@ -383,7 +841,7 @@ mod tests {
         
         let config = SplitConfig {
             input_path,
-            chunk_size: 4,
+            strategy: ChunkStrategy::Bytes(4),
             output_dir: Some(output_dir.clone()),
             ..Default::default()
         };
@@ -399,13 +857,141 @@ mod tests {
     #[test]
     fn test_validate_config_invalid_chunk_size() {
         let config = SplitConfig {
-            chunk_size: 0,
+            strategy: ChunkStrategy::Bytes(0),
             ..Default::default()
         };
         
         assert!(validate_config(&config).is_err());
     }
     
+    #[test]
+    fn test_validate_config_invalid_line_count() {
+        let config = SplitConfig {
+            strategy: ChunkStrategy::Lines(0),
+            ..Default::default()
+        };
+        
+        assert!(validate_config(&config).is_err());
+    }
+    
+    #[test]
+    fn test_split_by_lines() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "lines.txt", b"one\ntwo\nthree\nfour\nfive")?;
+        
+        let config = SplitConfig {
+            input_path,
+            strategy: ChunkStrategy::Lines(2),
+            ..Default::default()
+        };
+        
+        let result = split_file(&config)?;
+        
+        assert_eq!(result.chunks.len(), 3);
+        assert_eq!(result.total_chunks, result.chunks.len());
+        assert_eq!(fs::read_to_string(&result.chunks[0].path)?, "one\ntwo\n");
+        assert_eq!(fs::read_to_string(&result.chunks[1].path)?, "three\nfour\n");
+        assert_eq!(fs::read_to_string(&result.chunks[2].path)?, "five");
+        assert_eq!(result.chunks[1].size, 11);
+        assert!(result.to_string().contains("Chunk size: 2 lines"));
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_split_by_lines_excluding_empty_lines() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "notes.txt", b"one\n\ntwo\n   \nthree\nfour\n")?;
+        
+        let config = SplitConfig {
+            input_path,
+            strategy: ChunkStrategy::Lines(2),
+            exclude_empty_lines: true,
+            ..Default::default()
+        };
+        
+        let result = split_file(&config)?;
+        
+        assert_eq!(result.chunks.len(), 2);
+        assert_eq!(fs::read_to_string(&result.chunks[0].path)?, "one\n\ntwo\n");
+        assert_eq!(fs::read_to_string(&result.chunks[1].path)?, "   \nthree\nfour\n");
+        assert_eq!(result.chunks[0].size, 9);
+        Ok(())
+    }
+    
+    #[test]
+    fn test_max_output_files_guard() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "tiny.txt", b"0123456789")?;
+        
+        let config = SplitConfig {
+            input_path: input_path.clone(),
+            strategy: ChunkStrategy::Bytes(1),
+            max_output_files: Some(5),
+            ..Default::default()
+        };
+        
+        let err = split_file(&config).unwrap_err();
+        match err.downcast_ref::<SplitError>() {
+            Some(SplitError::InvalidChunkSize(message)) => {
+                assert_eq!(message, "Would create 10 chunks; max is 5. Increase chunk_size or set max_output_files.");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+        
+        let unlimited = SplitConfig { max_output_files: None, ..config };
+        assert_eq!(split_file(&unlimited)?.chunks.len(), 10);
+        Ok(())
+    }
+    
+    #[test]
+    fn test_count_chunks_matches_split_by_lines() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "notes.txt", b"one\n\ntwo\nthree\n\n\n")?;
+        
+        for (lines, exclude_empty_lines) in [(1, false), (2, false), (1, true), (2, true), (3, true)] {
+            let config = SplitConfig {
+                input_path: input_path.clone(),
+                output_dir: Some(temp_dir.path().join(format!("out_{}_{}", lines, exclude_empty_lines))),
+                strategy: ChunkStrategy::Lines(lines),
+                exclude_empty_lines,
+                ..Default::default()
+            };
+            let expected = count_chunks(&input_path, &config, 0)?;
+            assert_eq!(split_file(&config)?.chunks.len(), expected, "lines={} exclude={}", lines, exclude_empty_lines);
+        }
+        Ok(())
+    }
+    
+    #[test]
+    fn test_read_next_chunk_fills_bytes_chunks_across_short_reads() -> Result<()> {
+        /// Hands out at most three bytes per read, like a pipe or network stream
+        struct Trickle<'a>(&'a [u8]);
+        impl std::io::Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = buf.len().min(self.0.len()).min(3);
+                buf[..len].copy_from_slice(&self.0[..len]);
+                self.0 = &self.0[len..];
+                Ok(len)
+            }
+        }
+        
+        let data = [7u8; 25];
+        let mut input = BufReader::with_capacity(1, Trickle(&data));
+        let mut buffer = vec![0u8; 10];
+        let mut lengths = Vec::new();
+        loop {
+            let read = read_next_chunk(&mut input, ChunkStrategy::Bytes(10), false, &mut buffer)?;
+            lengths.push(read);
+            if read == 0 {
+                break;
+            }
+        }
+        assert_eq!(lengths, vec![10, 10, 5, 0]);
+        Ok(())
+    }
+    
     #[test]
     fn test_validate_config_nonexistent_file() {
         let config = SplitConfig {
@@ -423,7 +1009,7 @@ mod tests {
         
         let config = SplitConfig {
             input_path,
-            chunk_size: 1024,
+            strategy: ChunkStrategy::Bytes(1024),
             ..Default::default()
         };
         
@@ -440,7 +1026,7 @@ mod tests {
         
         let config = SplitConfig {
             input_path,
-            chunk_size: 5,
+            strategy: ChunkStrategy::Bytes(5),
             ..Default::default()
         };
         
@@ -452,4 +1038,97 @@ mod tests {
         
         Ok(())
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_split_result_display_elides_many_chunks() {
+        let chunks = (1..=25)
+            .map(|number| ChunkInfo {
+                number,
+                path: PathBuf::from(format!("/out/part{:03}.txt", number)),
+                size: 10,
+            })
+            .collect();
+        let result = SplitResult {
+            input_path: PathBuf::from("/in/big.txt"),
+            output_dir: PathBuf::from("/out"),
+            strategy: ChunkStrategy::Bytes(10),
+            chunks,
+            total_chunks: 25,
+            total_size: 250,
+            original_size: 250,
+        };
+        
+        let output = result.to_string();
+        
+        assert!(output.contains("Chunk 10: /out/part010.txt"));
+        assert!(!output.contains("Chunk 11:"));
+        assert!(output.contains("  ... (5 more chunks) ..."));
+        assert!(!output.contains("Chunk 15:"));
+        assert!(output.contains("Chunk 16: /out/part016.txt"));
+        assert!(output.contains("Chunk 25: /out/part025.txt"));
+        assert!(output.contains("into 25 chunks"));
+    }
+    
+    #[test]
+    fn test_abbreviate_path() {
+        assert_eq!(abbreviate_path(Path::new("/short/path.txt")), "/short/path.txt");
+        
+        let long = format!("/{}/file.txt", "a".repeat(80));
+        let abbreviated = abbreviate_path(Path::new(&long));
+        assert_eq!(abbreviated.chars().count(), 60);
+        assert!(abbreviated.starts_with("..."));
+        assert!(abbreviated.ends_with("/file.txt"));
+    }
+    
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_split_with_verification() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "verify.txt", b"Hello, World!")?;
+        
+        let config = SplitConfig {
+            input_path,
+            strategy: ChunkStrategy::Bytes(5),
+            verify_after_split: true,
+            ..Default::default()
+        };
+        let result = split_file(&config)?;
+        assert_eq!(result.chunks.len(), 3);
+        
+        let first = &result.chunks[0].path;
+        assert!(verify_chunk(first, crc32fast::hash(b"Hello"))?);
+        assert!(!verify_chunk(first, crc32fast::hash(b"Jello"))?);
+        assert!(verify_chunk(&temp_dir.path().join("missing.txt"), 0).is_err());
+        
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_by_regex_delimiter() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let input_path = create_test_file(temp_dir.path(), "genes.fasta", b"; comment\n>seq1\nACGT\nGG\n>seq2\nTTAA\n>seq3\n")?;
+        
+        let config = SplitConfig {
+            input_path: input_path.clone(),
+            strategy: ChunkStrategy::Lines(100),
+            ..Default::default()
+        };
+        
+        let result = split_by_regex_delimiter(&config, "^>")?;
+        
+        assert_eq!(result.strategy, ChunkStrategy::Delimiter);
+        assert_eq!(result.total_chunks, 4);
+        assert_eq!(fs::read_to_string(&result.chunks[0].path)?, "; comment\n");
+        assert_eq!(fs::read_to_string(&result.chunks[1].path)?, ">seq1\nACGT\nGG\n");
+        assert_eq!(fs::read_to_string(&result.chunks[2].path)?, ">seq2\nTTAA\n");
+        assert_eq!(fs::read_to_string(&result.chunks[3].path)?, ">seq3\n");
+        assert!(result.chunks[1].path.ends_with("genes-002.fasta"));
+        assert_eq!(result.chunks.iter().map(|c| c.size).sum::<u64>(), result.total_size);
+        
+        let too_many = SplitConfig { max_output_files: Some(3), ..config.clone() };
+        assert!(split_by_regex_delimiter(&too_many, "^>").unwrap_err().downcast_ref::<SplitError>().is_some());
+        assert!(split_by_regex_delimiter(&config, "(").is_err());
+        assert!(split_file(&SplitConfig { strategy: ChunkStrategy::Delimiter, ..config }).is_err());
+        Ok(())
+    }
+}
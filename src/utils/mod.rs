@@ -1,16 +1,24 @@
 //! Utility functions and helpers
 
+/// Archive layout detection
+pub mod archive_validation;
 /// Enhanced CSV writer for tweet data
 pub mod enhanced_csv_writer;
+/// Decision log for `--explain` mode
+pub mod explain;
 /// File splitter utility for large files
 pub mod file_splitter;
 /// Schema discovery utilities for JSON data
 pub mod schema_discovery;
+/// Lexicon-based sentiment scoring
+pub mod sentiment;
 /// Tweet classification utilities
 pub mod tweet_classifier;
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::Path;
@@ -40,11 +48,66 @@ pub fn format_duration(duration: chrono::Duration) -> String {
     }
 }
 
+/// Describes a duration as a rounded, conversational approximation
+///
+/// Rounds to the nearest minute, hour or day, e.g. 1h 58m becomes
+/// `"about 2 hours"` and 25 hours becomes `"about a day"`.
+pub fn humanize_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0) as f64;
+    let minutes = (secs / 60.0).round() as i64;
+    let hours = (secs / 3600.0).round() as i64;
+    let days = (secs / 86400.0).round() as i64;
+    if minutes < 1 {
+        "less than a minute".to_string()
+    } else if minutes < 60 {
+        if minutes == 1 { "about a minute".to_string() } else { format!("about {} minutes", minutes) }
+    } else if hours < 24 {
+        if hours == 1 { "about an hour".to_string() } else { format!("about {} hours", hours) }
+    } else if days == 1 {
+        "about a day".to_string()
+    } else {
+        format!("about {} days", days)
+    }
+}
+
+/// Formats a byte count with an IEC unit (B, KiB, MiB, GiB, TiB)
+///
+/// Plain byte counts are shown as integers; larger sizes use two decimal
+/// places, e.g. `"512.00 KiB"` or `"1.00 GiB"`.
+pub fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", value, UNITS[unit])
+}
+
 /// Formats a timestamp in a human-readable relative format
-pub fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
-    let now = Utc::now();
-    let duration = now.signed_duration_since(*timestamp);
-    
+///
+/// When `timezone` is `Some`, the timestamp is converted to that timezone before
+/// formatting, so absolute dates reflect the viewer's local calendar. `None`
+/// keeps the UTC behaviour.
+pub fn format_timestamp(timestamp: &DateTime<Utc>, timezone: Option<Tz>) -> String {
+    let duration = Utc::now().signed_duration_since(*timestamp);
+    match timezone {
+        Some(tz) => format_relative(&timestamp.with_timezone(&tz), duration),
+        None => format_relative(timestamp, duration),
+    }
+}
+
+/// Formats `timestamp` given how long ago it happened
+fn format_relative<Z: TimeZone>(timestamp: &DateTime<Z>, duration: chrono::Duration) -> String
+where
+    Z::Offset: Display,
+{
     if duration.num_seconds() < 60 {
         "just now".to_string()
     } else if duration.num_minutes() < 60 {
@@ -58,6 +121,73 @@ pub fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
     }
 }
 
+/// Parses an IANA timezone name such as `Asia/Tokyo` or `Europe/Berlin`
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| anyhow::anyhow!("Unknown timezone: {} (expected an IANA name like Asia/Tokyo)", name))
+}
+
+/// Parses an archive timestamp in either of Twitter's formats
+///
+/// DMs use RFC 3339 (`2023-01-01T10:00:00.000Z`), tweets use
+/// `Sun Jan 01 10:00:00 +0000 2023`. RFC 3339 is tried first; the error of the
+/// tweet format is returned when neither matches.
+pub fn parse_twitter_date(s: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(s)
+        .or_else(|_| DateTime::parse_from_str(s, "%a %b %d %H:%M:%S %z %Y"))
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Rewrites a Twitter `created_at` string in `tz`, keeping the archive's date format
+///
+/// The offset in the result reflects `tz`. Strings that don't parse are returned unchanged.
+pub fn format_twitter_date_in_tz(created_at: &str, tz: Tz) -> String {
+    match parse_twitter_date(created_at) {
+        Ok(date) => date.with_timezone(&tz).format("%a %b %d %H:%M:%S %z %Y").to_string(),
+        Err(_) => created_at.to_string(),
+    }
+}
+
+/// Returns the longest prefix of `s` that is at most `max_bytes` bytes long
+/// without splitting a multi-byte character
+///
+/// Use this instead of `&s[..max_bytes]`, which panics when `max_bytes` falls
+/// inside a character.
+pub fn truncate_to_utf8_boundary(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let end = s.char_indices()
+        .map(|(index, _)| index)
+        .take_while(|&index| index <= max_bytes)
+        .last()
+        .unwrap_or(0);
+    &s[..end]
+}
+
+/// Longest name, in characters, returned by [`sanitize_filename`]
+pub const MAX_FILENAME_COMPONENT_CHARS: usize = 200;
+
+/// Makes user-supplied text (screen names, prefixes) safe to use in a file name
+///
+/// Characters Windows rejects (`:`, `*`, `?`, `"`, `<`, `>`, `|`, `\`), path
+/// separators and control characters become `_`. Leading and trailing dots and
+/// spaces are trimmed, and the result is cut to [`MAX_FILENAME_COMPONENT_CHARS`].
+pub fn sanitize_filename(s: &str) -> String {
+    let replaced: String = s.chars()
+        .map(|c| match c {
+            ':' | '*' | '?' | '"' | '<' | '>' | '|' | '\\' | '/' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    replaced.trim_matches(|c| c == '.' || c == ' ')
+        .chars()
+        .take(MAX_FILENAME_COMPONENT_CHARS)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +204,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_humanize_duration() {
+        assert_eq!(humanize_duration(Duration::seconds(20)), "less than a minute");
+        assert_eq!(humanize_duration(Duration::seconds(50)), "about a minute");
+        assert_eq!(humanize_duration(Duration::minutes(14) + Duration::seconds(40)), "about 15 minutes");
+        assert_eq!(humanize_duration(Duration::minutes(59) + Duration::seconds(50)), "about an hour");
+        assert_eq!(humanize_duration(Duration::minutes(118)), "about 2 hours");
+        assert_eq!(humanize_duration(Duration::hours(25)), "about a day");
+        assert_eq!(humanize_duration(Duration::hours(80)), "about 3 days");
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(Duration::seconds(30)), "30s");
@@ -82,6 +223,17 @@ mod tests {
         assert_eq!(format_duration(Duration::days(3) + Duration::hours(5)), "3d 5h");
     }
 
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.00 KiB");
+        assert_eq!(format_bytes(512 * 1024), "512.00 KiB");
+        assert_eq!(format_bytes(1_048_576), "1.00 MiB");
+        assert_eq!(format_bytes(1_073_741_824), "1.00 GiB");
+        assert_eq!(format_bytes(u64::MAX), "16777216.00 TiB");
+    }
+
     #[test]
     fn test_format_timestamp() {
         let now = Utc::now();
@@ -90,10 +242,67 @@ mod tests {
         let one_day_ago = now - Duration::days(1);
         let one_month_ago = now - Duration::days(35);
         
-        assert_eq!(format_timestamp(&now), "just now");
-        assert!(format_timestamp(&one_min_ago).ends_with("m ago"));
-        assert!(format_timestamp(&one_hour_ago).ends_with("h ago"));
-        assert!(format_timestamp(&one_day_ago).ends_with("d ago"));
-        assert!(format_timestamp(&one_month_ago).contains(", 20"));
+        assert_eq!(format_timestamp(&now, None), "just now");
+        assert!(format_timestamp(&one_min_ago, None).ends_with("m ago"));
+        assert!(format_timestamp(&one_hour_ago, None).ends_with("h ago"));
+        assert!(format_timestamp(&one_day_ago, None).ends_with("d ago"));
+        assert!(format_timestamp(&one_month_ago, None).contains(", 20"));
+    }
+
+    #[test]
+    fn test_format_timestamp_with_timezone() {
+        use chrono::TimeZone;
+
+        // 23:30 UTC on Dec 31 is already Jan 1 in Tokyo
+        let timestamp = Utc.with_ymd_and_hms(2020, 12, 31, 23, 30, 0).unwrap();
+        assert_eq!(format_timestamp(&timestamp, None), "Dec 31, 2020");
+        assert_eq!(format_timestamp(&timestamp, Some(chrono_tz::Asia::Tokyo)), "Jan 01, 2021");
+
+        let recent = Utc::now() - Duration::hours(3);
+        assert_eq!(format_timestamp(&recent, Some(chrono_tz::Asia::Tokyo)), "3h ago");
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(parse_timezone("Asia/Tokyo").unwrap(), chrono_tz::Asia::Tokyo);
+        assert_eq!(parse_timezone(" UTC ").unwrap(), chrono_tz::UTC);
+        assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_format_twitter_date_in_tz() {
+        let created_at = "Mon Jan 02 12:00:00 +0000 2023";
+        assert_eq!(format_twitter_date_in_tz(created_at, chrono_tz::America::New_York), "Mon Jan 02 07:00:00 -0500 2023");
+        assert_eq!(format_twitter_date_in_tz(created_at, chrono_tz::UTC), created_at);
+        assert_eq!(format_twitter_date_in_tz("not a date", chrono_tz::Asia::Tokyo), "not a date");
+    }
+
+    #[test]
+    fn test_parse_twitter_date_accepts_both_formats() {
+        let expected = Utc.with_ymd_and_hms(2023, 1, 1, 10, 0, 0).unwrap();
+        assert_eq!(parse_twitter_date("2023-01-01T10:00:00.000Z"), Ok(expected));
+        assert_eq!(parse_twitter_date("Sun Jan 01 10:00:00 +0000 2023"), Ok(expected));
+        assert_eq!(parse_twitter_date("Sun Jan 01 19:00:00 +0900 2023"), Ok(expected));
+        assert!(parse_twitter_date("2023-01-01").is_err());
+        assert!(parse_twitter_date("").is_err());
+    }
+
+    #[test]
+    fn test_truncate_to_utf8_boundary() {
+        assert_eq!(truncate_to_utf8_boundary("hello", 10), "hello");
+        assert_eq!(truncate_to_utf8_boundary("hello", 3), "hel");
+        assert_eq!(truncate_to_utf8_boundary("héllo", 2), "h");
+        assert_eq!(truncate_to_utf8_boundary("héllo", 3), "hé");
+        assert_eq!(truncate_to_utf8_boundary("🦀🦀", 5), "🦀");
+        assert_eq!(truncate_to_utf8_boundary("🦀", 3), "");
+        assert_eq!(truncate_to_utf8_boundary("", 0), "");
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("evil:name*/test"), "evil_name__test");
+        assert_eq!(sanitize_filename("jack"), "jack");
+        assert_eq!(sanitize_filename(" ..a<b>c|d?\"e\\f. "), "a_b_c_d__e_f");
+        assert_eq!(sanitize_filename(&"x".repeat(300)).chars().count(), MAX_FILENAME_COMPONENT_CHARS);
     }
 }
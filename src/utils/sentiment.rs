@@ -0,0 +1,89 @@
+//! Lightweight lexicon-based sentiment scoring for tweets and DMs
+
+/// Words that carry positive sentiment
+const POSITIVE_WORDS: &[&str] = &[
+    "amazing", "appreciate", "awesome", "beautiful", "best", "brilliant", "congrats",
+    "congratulations", "cool", "delighted", "enjoy", "enjoyed", "excellent", "excited",
+    "fantastic", "fun", "glad", "good", "grateful", "great", "happy", "haha", "helpful",
+    "hope", "impressive", "kind", "like", "lol", "love", "loved", "lovely", "nice",
+    "perfect", "pleased", "proud", "thank", "thanks", "welcome", "wonderful", "wow", "yay",
+];
+
+/// Words that carry negative sentiment
+const NEGATIVE_WORDS: &[&str] = &[
+    "angry", "annoyed", "annoying", "awful", "bad", "boring", "broken", "confused",
+    "disappointed", "disappointing", "dislike", "fail", "failed", "frustrated", "hate",
+    "hated", "horrible", "hurt", "lonely", "mad", "miss", "problem", "sad", "scared",
+    "sick", "sorry", "stressed", "stupid", "terrible", "tired", "ugh", "unfortunately",
+    "upset", "worried", "worse", "worst", "wrong",
+];
+
+/// Words that flip the polarity of the word that follows them
+const NEGATIONS: &[&str] = &["not", "no", "never", "dont", "don't", "isnt", "isn't", "wasnt", "wasn't", "cant", "can't"];
+
+/// Scores the sentiment of a piece of text
+///
+/// Returns a value in `[-1.0, 1.0]`: `0.0` is neutral (or no sentiment words
+/// found), negative values are negative sentiment and positive values are
+/// positive sentiment. A negation ("not", "never", ...) flips the polarity of
+/// the word immediately after it.
+pub fn score_text(text: &str) -> f32 {
+    let mut total = 0i32;
+    let mut matched = 0i32;
+    let mut negate = false;
+
+    for word in text.split_whitespace() {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+            .to_lowercase();
+        if word.is_empty() {
+            continue;
+        }
+
+        if NEGATIONS.contains(&word.as_str()) {
+            negate = true;
+            continue;
+        }
+
+        let polarity = if POSITIVE_WORDS.contains(&word.as_str()) {
+            1
+        } else if NEGATIVE_WORDS.contains(&word.as_str()) {
+            -1
+        } else {
+            0
+        };
+
+        if polarity != 0 {
+            total += if negate { -polarity } else { polarity };
+            matched += 1;
+        }
+        negate = false;
+    }
+
+    if matched == 0 {
+        0.0
+    } else {
+        total as f32 / matched as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_text_polarity() {
+        assert_eq!(score_text("Meeting at 5pm tomorrow"), 0.0);
+        assert_eq!(score_text(""), 0.0);
+        assert!(score_text("Thanks, this is awesome!") > 0.0);
+        assert!(score_text("Ugh, this is terrible.") < 0.0);
+        assert_eq!(score_text("good bad"), 0.0);
+    }
+
+    #[test]
+    fn test_score_text_negation() {
+        assert!(score_text("This is not good") < 0.0);
+        assert!(score_text("Never sad around you") > 0.0);
+        assert!(score_text("NOT GREAT") < 0.0);
+    }
+}
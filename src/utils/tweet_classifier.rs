@@ -46,7 +46,7 @@ mod tests {
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "Twitter Web App".to_string(),
             display_text_range: vec!["0".to_string(), "10".to_string()],
             in_reply_to_status_id: reply_to_id.map(|s| s.to_string()),
@@ -56,12 +56,15 @@ mod tests {
             in_reply_to_screen_name: reply_to_user.map(|s| s.to_string()),
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         }
     }
 
@@ -17,7 +17,7 @@ mod enhanced_csv_writer_tests {
             retweeted: false,
             favorited: false,
             truncated: false,
-            lang: "en".to_string(),
+            lang: Some("en".to_string()),
             source: "Twitter Web App".to_string(),
             display_text_range: vec!["0".to_string(), text.len().to_string()],
             in_reply_to_status_id: reply_to_id.map(|s| s.to_string()),
@@ -27,12 +27,15 @@ mod enhanced_csv_writer_tests {
             in_reply_to_screen_name: reply_to_user.map(|s| s.to_string()),
             edit_info: None,
             entities: TweetEntities {
+                poll_option_labels: None,
                 hashtags: vec![],
                 symbols: vec![],
                 user_mentions: vec![],
                 urls: vec![],
             },
             possibly_sensitive: None,
+            quoted_status_id: None,
+            conversation_id: None,
         }
     }
 
@@ -51,6 +54,10 @@ mod enhanced_csv_writer_tests {
             tweet_count,
             favorite_count,
             retweet_count,
+            url: format!("https://twitter.com/testuser/status/{}", id),
+            contains_poll: false,
+            first_tweet_url_entities: Vec::new(),
+            engagement_dropoff_rate: 0.0,
         }
     }
 
@@ -68,6 +75,18 @@ mod enhanced_csv_writer_tests {
         assert_eq!(record.thread_position, 1);
         assert_eq!(record.twitter_url, "https://twitter.com/testuser/status/1");
         assert_eq!(record.reply_context, "");
+        assert_eq!(record.lang, "en");
+    }
+
+    #[tokio::test]
+    async fn test_create_csv_record_unknown_language() {
+        let mut tweet = create_test_tweet("1", "Hello world", None, None);
+        let thread = create_test_thread("thread1", vec![tweet.clone()]);
+        for lang in [None, Some(""), Some("und")] {
+            tweet.lang = lang.map(str::to_string);
+            let record = CsvRecord::from_tweet_and_thread(&tweet, &thread, "testuser", 1);
+            assert_eq!(record.lang, "unknown");
+        }
     }
 
     #[tokio::test]
@@ -108,6 +127,11 @@ mod enhanced_csv_writer_tests {
         
         // Verify file exists
         assert!(output_path.exists());
+        
+        // Later columns are appended after the original ones
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.starts_with("tweet_id,tweet_text,tweet_type,created_at,favorite_count,retweet_count,thread_id,thread_position,\
+            thread_tweet_count,thread_favorite_count,thread_retweet_count,twitter_url,reply_context,lang,source,thread_url,created_at_local,"));
     }
 
     #[tokio::test]
@@ -138,4 +162,20 @@ mod enhanced_csv_writer_tests {
         // Verify file exists
         assert!(output_path.exists());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_always_quote_option() {
+        let temp_dir = tempdir().unwrap();
+        let output_path = temp_dir.path().join("quoted.csv");
+        let options = tweet_scrolls::processing::CsvWriteOptions { always_quote: true, ..Default::default() };
+        
+        let mut writer = EnhancedCsvWriter::new(output_path.to_str().unwrap()).await.unwrap().with_options(options);
+        let thread = create_test_thread("thread1", vec![create_test_tweet("1", "Hello world", None, None)]);
+        writer.write_thread(&thread, "testuser").await.unwrap();
+        writer.finalize().await.unwrap();
+        
+        let content = std::fs::read_to_string(&output_path).unwrap();
+        assert!(content.starts_with("\"tweet_id\",\"tweet_text\","));
+        assert!(content.contains("\"1\",\"Hello world\","));
+    }
+}
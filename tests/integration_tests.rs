@@ -18,6 +18,9 @@ fn create_realistic_dm_data() -> Vec<models::direct_message::DmWrapper> {
         models::direct_message::DmWrapper {
             dm_conversation: models::direct_message::DmConversation {
                 conversation_id: "3382-1132151165410455552".to_string(),
+                group_name: None,
+                join_events: Vec::new(),
+                leave_events: Vec::new(),
                 messages: vec![
                     models::direct_message::DmMessage {
                         message_create: Some(models::direct_message::DmMessageCreate {
@@ -51,6 +54,9 @@ fn create_realistic_dm_data() -> Vec<models::direct_message::DmWrapper> {
         models::direct_message::DmWrapper {
             dm_conversation: models::direct_message::DmConversation {
                 conversation_id: "3382-9876543210".to_string(),
+                group_name: None,
+                join_events: Vec::new(),
+                leave_events: Vec::new(),
                 messages: vec![
                     models::direct_message::DmMessage {
                         message_create: Some(models::direct_message::DmMessageCreate {
@@ -87,7 +93,7 @@ fn create_realistic_tweet_data() -> Vec<processing::data_structures::TweetWrappe
                 retweeted: false,
                 favorited: false,
                 truncated: false,
-                lang: "en".to_string(),
+                lang: Some("en".to_string()),
                 source: "<a href=\"https://mobile.twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
                 display_text_range: vec!["0".to_string(), "270".to_string()],
                 in_reply_to_status_id: None,
@@ -97,6 +103,7 @@ fn create_realistic_tweet_data() -> Vec<processing::data_structures::TweetWrappe
                 in_reply_to_screen_name: Some("testuser".to_string()),
                 edit_info: None,
                 entities: TweetEntities {
+                    poll_option_labels: None,
                     hashtags: vec![],
                     symbols: vec![],
                     user_mentions: vec![
@@ -111,6 +118,8 @@ fn create_realistic_tweet_data() -> Vec<processing::data_structures::TweetWrappe
                     urls: vec![],
                 },
                 possibly_sensitive: None,
+                quoted_status_id: None,
+                conversation_id: None,
             }
         },
         TweetWrapper {
@@ -124,7 +133,7 @@ fn create_realistic_tweet_data() -> Vec<processing::data_structures::TweetWrappe
                 retweeted: false,
                 favorited: false,
                 truncated: false,
-                lang: "en".to_string(),
+                lang: Some("en".to_string()),
                 source: "<a href=\"http://twitter.com/download/android\" rel=\"nofollow\">Twitter for Android</a>".to_string(),
                 display_text_range: vec!["0".to_string(), "68".to_string()],
                 in_reply_to_status_id: Some("1947467485424562448".to_string()),
@@ -134,6 +143,7 @@ fn create_realistic_tweet_data() -> Vec<processing::data_structures::TweetWrappe
                 in_reply_to_screen_name: Some("TnvMadhav".to_string()),
                 edit_info: None,
                 entities: TweetEntities {
+                    poll_option_labels: None,
                     hashtags: vec![],
                     symbols: vec![],
                     user_mentions: vec![
@@ -148,6 +158,8 @@ fn create_realistic_tweet_data() -> Vec<processing::data_structures::TweetWrappe
                     urls: vec![],
                 },
                 possibly_sensitive: None,
+                quoted_status_id: None,
+                conversation_id: None,
             }
         }
     ]
@@ -190,7 +202,7 @@ async fn test_dm_processing_end_to_end() {
     let dm_data = create_realistic_dm_data();
     
     // Test the core DM processing pipeline
-    let conversations = tweet_scrolls::processing::direct_messages::process_dm_conversations(&dm_data, "testuser").await.unwrap();
+    let conversations = tweet_scrolls::processing::direct_messages::process_dm_conversations(&dm_data, "testuser", tweet_scrolls::processing::direct_messages::DmSortMode::default()).await.unwrap();
     
     // Verify conversations were created
     assert!(!conversations.is_empty(), "Should create conversations from DM data");
@@ -215,7 +227,7 @@ async fn test_relationship_analysis_pipeline() {
     let tweet_data = create_realistic_tweet_data();
     
     // Test relationship analyzer creation
-    let analyzer = relationship::RelationshipAnalyzer::new();
+    let mut analyzer = relationship::RelationshipAnalyzer::new();
     
     // Test user extraction from both data sources
     let dm_users = analyzer.extract_users_from_dms(&dm_data);
@@ -228,7 +240,7 @@ async fn test_relationship_analysis_pipeline() {
     
     // Test profile creation
     for user_id in &dm_users {
-        let profile = analyzer.create_user_profile(user_id, &dm_data);
+        let profile = analyzer.create_user_profile(user_id, &dm_data, &tweets);
         assert_eq!(profile.user_id, *user_id, "Profile should match user id");
         assert!(profile.total_interactions > 0, "Profile should have interactions");
     }
@@ -359,6 +371,9 @@ fn test_large_data_structures() {
         let conversation = models::direct_message::DmWrapper {
             dm_conversation: models::direct_message::DmConversation {
                 conversation_id: format!("user_{}-sender_{}", i % 10, (i + 1) % 20),
+                group_name: None,
+                join_events: Vec::new(),
+                leave_events: Vec::new(),
                 messages: (0..5).map(|j| {
                     models::direct_message::DmMessage {
                         message_create: Some(models::direct_message::DmMessageCreate {
@@ -388,7 +403,7 @@ fn test_large_data_structures() {
     
     // Test profile creation with larger dataset
     let first_user = users.iter().next().unwrap();
-    let profile = analyzer.create_user_profile(first_user, &large_dm_data);
+    let profile = analyzer.create_user_profile(first_user, &large_dm_data, &[]);
     
     assert!(profile.total_interactions > 0, "Profile should have interactions from large dataset");
 }
@@ -421,14 +436,14 @@ fn test_user_profile_add_interaction() {
 #[test]
 fn test_calculate_response_times_empty() {
     let messages = vec![];
-    let response_times = relationship::communication::calculate_response_times(&messages);
+    let response_times = relationship::communication::calculate_response_times(&messages, relationship::SenderFilter::AnyConsecutive);
     assert!(response_times.is_empty());
 }
 
 #[test]
 fn test_calculate_average_response_time_empty() {
     let response_times = vec![];
-    let avg = relationship::communication::calculate_average_response_time(&response_times);
+    let avg = relationship::communication::calculate_average_response_time(&response_times, relationship::SenderFilter::AnyConsecutive);
     assert_eq!(avg, std::time::Duration::ZERO);
 }
 
@@ -473,12 +488,13 @@ fn test_generate_user_profile_text() {
     profile.interaction_counts.insert("dm_received".to_string(), 17);
     
     let timeline = vec![];
-    let profile_text = relationship::text_generators::generate_user_profile_text(&profile, &timeline);
+    let profile_text = relationship::text_generators::generate_user_profile_text(&profile, &timeline, relationship::Locale::EnUs, relationship::RenderStyle::Structured);
     
     assert!(profile_text.contains("USER RELATIONSHIP PROFILE"));
     assert!(profile_text.contains("test_user_id_123456"));
     assert!(profile_text.contains("Total Interactions: 42"));
     assert!(profile_text.contains("dm_messages: 25"));
+    assert!(profile_text.contains("Contact Type: Minimal"));
 }
 
 #[test]
@@ -501,6 +517,7 @@ fn test_generate_timeline_text() {
     assert!(timeline_text.contains("CHRONOLOGICAL INTERACTION LOG"));
     assert!(timeline_text.contains("Total Events: 1"));
     assert!(timeline_text.contains("2023-06"));
+    assert!(timeline_text.contains("Most Active Day: Thu (low confidence: only 1 events)"));
 }
 
 #[test]
@@ -510,7 +527,7 @@ fn test_generate_llm_analysis_prompts() {
     profile.total_interactions = 42;
     profiles.insert("user1".to_string(), profile);
     
-    let prompts = relationship::prompts_generator::generate_llm_analysis_prompts(&profiles);
+    let prompts = relationship::prompts_generator::generate_llm_analysis_prompts(&profiles, &[], relationship::PromptLength::Medium);
     
     assert!(prompts.contains("Which relationships need more attention"));
     assert!(prompts.contains("What communication patterns make conversations most engaging"));
@@ -533,7 +550,7 @@ fn test_tweet_creation() {
         retweeted: false,
         favorited: false,
         truncated: false,
-        lang: "en".to_string(),
+        lang: Some("en".to_string()),
         source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
         display_text_range: vec!["0".to_string(), "10".to_string()],
         in_reply_to_status_id: None,
@@ -543,12 +560,15 @@ fn test_tweet_creation() {
         in_reply_to_screen_name: None,
         edit_info: None,
         entities: TweetEntities {
+            poll_option_labels: None,
             hashtags: vec![],
             symbols: vec![],
             user_mentions: vec![],
             urls: vec![],
         },
         possibly_sensitive: None,
+        quoted_status_id: None,
+        conversation_id: None,
     };
     
     assert_eq!(tweet.full_text, "test tweet");
@@ -570,7 +590,7 @@ fn test_thread_creation() {
         retweeted: false,
         favorited: false,
         truncated: false,
-        lang: "en".to_string(),
+        lang: Some("en".to_string()),
         source: "<a href=\"http://twitter.com\" rel=\"nofollow\">Twitter Web App</a>".to_string(),
         display_text_range: vec!["0".to_string(), "10".to_string()],
         in_reply_to_status_id: None,
@@ -580,12 +600,15 @@ fn test_thread_creation() {
         in_reply_to_screen_name: None,
         edit_info: None,
         entities: TweetEntities {
+            poll_option_labels: None,
             hashtags: vec![],
             symbols: vec![],
             user_mentions: vec![],
             urls: vec![],
         },
         possibly_sensitive: None,
+        quoted_status_id: None,
+        conversation_id: None,
     };
     
     let thread = processing::data_structures::Thread {
@@ -594,6 +617,10 @@ fn test_thread_creation() {
         tweet_count: 1,
         favorite_count: 0,
         retweet_count: 0,
+        url: String::new(),
+        contains_poll: false,
+        first_tweet_url_entities: Vec::new(),
+        engagement_dropoff_rate: 0.0,
     };
     
     assert_eq!(thread.tweets.len(), 1);
@@ -612,7 +639,7 @@ fn test_format_duration() {
 fn test_format_timestamp() {
     use chrono::TimeZone;
     let timestamp = chrono::Utc.with_ymd_and_hms(2023, 6, 15, 14, 30, 0).unwrap();
-    let formatted = utils::format_timestamp(&timestamp);
+    let formatted = utils::format_timestamp(&timestamp, None);
     // format_timestamp returns relative time for recent dates, absolute format for old dates
     assert!(formatted.contains("ago") || formatted == "just now" || formatted.contains("2023") || formatted.contains("Jun"));
 }
\ No newline at end of file
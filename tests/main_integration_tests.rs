@@ -168,7 +168,7 @@ mod main_integration_tests {
         
         // Verify individual profile files were created
         for profile in &profiles {
-            let profile_filename = format!("user_{}_profile.txt", &profile.user_id[..8]);
+            let profile_filename = format!("user_{}_profile.txt", profile.user_id);
             assert!(std::path::Path::new(&expected_dir).join(profile_filename).exists());
         }
     }
@@ -198,9 +198,13 @@ mod main_integration_tests {
 
     #[tokio::test]
     async fn test_relationship_analysis_error_handling() {
-        // Test with invalid path
+        // Test with invalid path (nested under a regular file, so it fails even for root)
+        let temp_dir = tempdir().unwrap();
+        let blocker = temp_dir.path().join("not_a_dir");
+        std::fs::write(&blocker, "file").unwrap();
+        let invalid_path = blocker.join("nested");
         let result = analyze_relationships(
-            "/invalid/path/that/does/not/exist",
+            invalid_path.to_str().unwrap(),
             "testuser",
             1234567890,
             &create_sample_profiles(),
@@ -231,6 +235,8 @@ mod main_integration_tests {
         assert!(validate_output_directory(valid_path).is_ok());
         
         // Invalid directory should fail
-        assert!(validate_output_directory("/invalid/path").is_err());
+        let blocker = temp_dir.path().join("not_a_dir");
+        std::fs::write(&blocker, "file").unwrap();
+        assert!(validate_output_directory(blocker.join("nested").to_str().unwrap()).is_err());
     }
 }
\ No newline at end of file